@@ -2,15 +2,17 @@
 use crate::window::ViziaWindow;
 use crate::Renderer;
 use baseview::{WindowHandle, WindowScalePolicy};
-use femtovg::Canvas;
+use femtovg::{Canvas, ImageFlags};
 use raw_window_handle::HasRawWindowHandle;
+use std::path::PathBuf;
 use vizia_core::{apply_inline_inheritance, apply_shared_inheritance, TreeExt};
 use vizia_core::{MouseButton, MouseButtonState};
 //use vizia_core::WindowWidget;
 use vizia_core::{
-    apply_clipping, apply_hover, apply_styles, apply_text_constraints, apply_transform,
-    apply_visibility, apply_z_ordering, geometry_changed, Context, Display, Entity, EventManager,
-    FontOrId, Modifiers, Units, Visibility, WindowEvent, WindowSize,
+    apply_animations, apply_clipping, apply_hover, apply_styles, apply_text_constraints,
+    apply_timers, apply_transform, apply_visibility, apply_z_ordering, focus_next, focus_prev,
+    geometry_changed, persistence_path, update_computed_values, Context, Display, Entity,
+    EventManager, FontOrId, ImageOrId, Modifiers, Units, Visibility, WindowEvent, WindowSize,
 };
 use vizia_core::{BoundingBox, Event, Propagation, WindowDescription};
 
@@ -22,6 +24,8 @@ where
     app: F,
     window_description: WindowDescription,
     on_idle: Option<Box<dyn Fn(&mut Context) + Send>>,
+    // The file the state of persistent models is saved to
+    persistence_path: Option<PathBuf>,
 }
 
 impl<F> Application<F>
@@ -30,7 +34,7 @@ where
     F: 'static + Send,
 {
     pub fn new(window_description: WindowDescription, app: F) -> Self {
-        Self { app, window_description, on_idle: None }
+        Self { app, window_description, on_idle: None, persistence_path: None }
     }
 
     /// Open a new window that blocks the current thread until the window is destroyed.
@@ -40,7 +44,12 @@ where
     ///
     /// * `app` - The Tuix application builder.
     pub fn run(self) {
-        ViziaWindow::open_blocking(self.window_description, self.app, self.on_idle)
+        ViziaWindow::open_blocking(
+            self.window_description,
+            self.app,
+            self.on_idle,
+            self.persistence_path,
+        )
     }
 
     /// Open a new child window.
@@ -51,7 +60,13 @@ where
     /// * `parent` - The parent window.
     /// * `app` - The Tuix application builder.
     pub fn open_parented<P: HasRawWindowHandle>(self, parent: &P) -> WindowHandle {
-        ViziaWindow::open_parented(parent, self.window_description, self.app, self.on_idle)
+        ViziaWindow::open_parented(
+            parent,
+            self.window_description,
+            self.app,
+            self.on_idle,
+            self.persistence_path,
+        )
     }
 
     /// Open a new window as if it had a parent window.
@@ -61,7 +76,12 @@ where
     ///
     /// * `app` - The Tuix application builder.
     pub fn open_as_if_parented(self) -> WindowHandle {
-        ViziaWindow::open_as_if_parented(self.window_description, self.app, self.on_idle)
+        ViziaWindow::open_as_if_parented(
+            self.window_description,
+            self.app,
+            self.on_idle,
+            self.persistence_path,
+        )
     }
 
     /// Takes a closure which will be called at the end of every loop of the application.
@@ -87,6 +107,20 @@ where
 
         self
     }
+
+    /// Enables persistence for the application with the given name.
+    ///
+    /// The state of models built with `build_persistent()` is restored from a file in the configuration
+    /// directory of the user before the application is built, and is saved when the models change, at most
+    /// once per `PERSISTENT_SAVE_INTERVAL`, and when the window closes.
+    pub fn persist(mut self, app_name: &str) -> Self {
+        self.persistence_path = persistence_path(app_name);
+        if self.persistence_path.is_none() {
+            println!("Failed to find a directory to save the state of {} to", app_name);
+        }
+
+        self
+    }
 }
 
 pub(crate) struct ApplicationRunner {
@@ -99,7 +133,7 @@ pub(crate) struct ApplicationRunner {
 
     click_time: std::time::Instant,
     double_click_interval: std::time::Duration,
-    double_click: bool,
+    click_count: u32,
     click_pos: (f32, f32),
 }
 
@@ -171,7 +205,7 @@ impl ApplicationRunner {
 
             click_time: std::time::Instant::now(),
             double_click_interval: std::time::Duration::from_millis(500),
-            double_click: false,
+            click_count: 0,
             click_pos: (0.0, 0.0),
         }
     }
@@ -225,6 +259,11 @@ impl ApplicationRunner {
         //if let Some(mut window_view) = context.views.remove(&Entity::root()) {
         //if let Some(window) = window_view.downcast_mut::<Window>() {
 
+        // Reload any stylesheets which have been modified
+        if self.context.resource_manager.watch_stylesheets {
+            self.context.poll_stylesheets();
+        }
+
         // Load resources
         for (name, font) in self.context.resource_manager.fonts.iter_mut() {
             match font {
@@ -247,16 +286,59 @@ impl ApplicationRunner {
             }
         }
 
+        let mut failed = Vec::new();
+        for (name, image) in self.context.resource_manager.images.iter_mut() {
+            match image {
+                ImageOrId::Image(data) => {
+                    match self
+                        .canvas
+                        .load_image_mem(data, ImageFlags::REPEAT_X | ImageFlags::REPEAT_Y)
+                    {
+                        Ok(id) => *image = ImageOrId::Id(id),
+                        Err(error) => failed.push((name.clone(), error)),
+                    }
+                }
+
+                _ => {}
+            }
+        }
+
+        // Images which fail to load are removed so that they aren't retried every frame
+        for (name, error) in failed {
+            self.context.resource_manager.images.remove(&name);
+            self.context.report("images", format!("Failed to load image {}: {:?}", name, error));
+        }
+
         //}
 
         //context.views.insert(Entity::root(), window_view);
         //}
 
+        // Timers which have reached their deadline
+        apply_timers(&mut self.context, std::time::Instant::now());
+
+        // Results of finished background tasks
+        self.context.poll_tasks();
+
         // Events
-        while !self.context.event_queue.is_empty() {
-            self.event_manager.flush_events(&mut self.context);
+        if !self.context.event_queue.is_empty() {
+            while !self.context.event_queue.is_empty() {
+                self.event_manager.flush_events(&mut self.context);
+            }
+
+            // Persistent models may have been changed by the events
+            self.context.persistent_state.mark_dirty();
         }
 
+        // Save persistent models which have changed, at most once per save interval
+        self.context.autosave_persistent_state(std::time::Instant::now());
+
+        // Report problems found while handling the events, such as translations which failed to load
+        self.context.flush_diagnostics();
+
+        // Computed values are updated before the bindings which depend on them
+        update_computed_values(&mut self.context);
+
         // Data Updates
         let mut observers: Vec<Entity> = Vec::new();
         for model_store in self.context.data.dense.iter_mut().map(|entry| &mut entry.value) {
@@ -291,6 +373,7 @@ impl ApplicationRunner {
         // Styling
         apply_inline_inheritance(&mut self.context, &tree);
         apply_styles(&mut self.context, &tree);
+        apply_animations(&mut self.context, std::time::Instant::now());
         apply_shared_inheritance(&mut self.context, &tree);
 
         apply_z_ordering(&mut self.context, &tree);
@@ -457,25 +540,29 @@ impl ApplicationRunner {
                     if click_duration <= self.double_click_interval
                         && new_click_pos == self.click_pos
                     {
-                        if !self.double_click {
-                            let _target = if self.context.captured != Entity::null() {
-                                self.context.event_queue.push_back(
-                                    Event::new(WindowEvent::MouseDoubleClick(b))
-                                        .target(self.context.captured)
-                                        .propagate(Propagation::Direct),
-                                );
-                                self.context.captured
-                            } else {
-                                self.context.event_queue.push_back(
-                                    Event::new(WindowEvent::MouseDoubleClick(b))
-                                        .target(self.context.hovered),
-                                );
-                                self.context.hovered
-                            };
-                            self.double_click = true;
-                        }
+                        self.click_count += 1;
                     } else {
-                        self.double_click = false;
+                        self.click_count = 1;
+                    }
+
+                    let click_event = match self.click_count {
+                        2 => Some(WindowEvent::MouseDoubleClick(b)),
+                        3 => Some(WindowEvent::MouseTripleClick(b)),
+                        _ => None,
+                    };
+
+                    if let Some(click_event) = click_event {
+                        if self.context.captured != Entity::null() {
+                            self.context.event_queue.push_back(
+                                Event::new(click_event)
+                                    .target(self.context.captured)
+                                    .propagate(Propagation::Direct),
+                            );
+                        } else {
+                            self.context
+                                .event_queue
+                                .push_back(Event::new(click_event).target(self.context.hovered));
+                        }
                     }
 
                     self.click_time = new_click_time;
@@ -661,53 +748,13 @@ impl ApplicationRunner {
                     }
                 }
 
+                // Move keyboard focus with Tab and Shift+Tab
                 if event.code == Code::Tab && s == MouseButtonState::Pressed {
-                    // let next_focus = self
-                    //     .state
-                    //     .style
-                    //     .focus_order
-                    //     .get(self.context.focused)
-                    //     .cloned()
-                    //     .unwrap_or_default()
-                    //     .next;
-                    // let prev_focus = self
-                    //     .state
-                    //     .style
-                    //     .focus_order
-                    //     .get(self.context.focused)
-                    //     .cloned()
-                    //     .unwrap_or_default()
-                    //     .prev;
-
-                    // if self.context.modifiers.shift {
-                    //     if prev_focus != Entity::null() {
-                    //         self.context.focused.set_focus(&mut self.context, false);
-                    //         self.context.focused = prev_focus;
-                    //         self.context.focused.set_focus(&mut self.context, true);
-                    //     } else {
-                    //         // TODO impliment reverse iterator for tree
-                    //         // state.focused = match state.focused.into_iter(&state.tree).next() {
-                    //         //     Some(val) => val,
-                    //         //     None => Entity::root(),
-                    //         // };
-                    //     }
-                    // } else {
-                    //     if next_focus != Entity::null() {
-                    //         self.context.focused.set_focus(&mut self.context, false);
-                    //         self.context.focused = next_focus;
-                    //         self.context.focused.set_focus(&mut self.context, true);
-                    //     } else {
-                    //         self.context.focused.set_focus(&mut self.context, false);
-                    //         self.context.focused =
-                    //             match self.context.focused.tree_iter(&self.tree).next() {
-                    //                 Some(val) => val,
-                    //                 None => Entity::root(),
-                    //             };
-                    //         self.context.focused.set_focus(&mut self.context, true);
-                    //     }
-                    // }
-
-                    self.context.style.needs_restyle = true;
+                    if self.context.modifiers.contains(Modifiers::SHIFT) {
+                        focus_prev(&mut self.context);
+                    } else {
+                        focus_next(&mut self.context);
+                    }
                 }
 
                 match s {
@@ -805,6 +852,7 @@ impl ApplicationRunner {
                     self.context.style.needs_redraw = true;
                 }
                 baseview::WindowEvent::WillClose => {
+                    self.context.save_persistent_state();
                     self.context.event_queue.push_back(Event::new(WindowEvent::WindowClose));
                 }
                 _ => {}
@@ -3,6 +3,7 @@ use baseview::{
     Event, EventStatus, Window, WindowHandle, WindowHandler, WindowOpenOptions, WindowScalePolicy,
 };
 use raw_window_handle::HasRawWindowHandle;
+use std::path::PathBuf;
 
 use vizia_core::*;
 
@@ -42,6 +43,7 @@ impl ViziaWindow {
         win_desc: WindowDescription,
         app: F,
         on_idle: Option<Box<dyn Fn(&mut Context) + Send>>,
+        persistence_path: Option<PathBuf>,
     ) -> WindowHandle
     where
         P: HasRawWindowHandle,
@@ -68,6 +70,10 @@ impl ViziaWindow {
 
                 context.add_theme(DEFAULT_THEME);
 
+                if let Some(path) = persistence_path {
+                    context.load_persistent_state(path);
+                }
+
                 ViziaWindow::new(context, win_desc, window, Some(Box::new(app)), on_idle)
             },
         )
@@ -80,6 +86,7 @@ impl ViziaWindow {
         win_desc: WindowDescription,
         app: F,
         on_idle: Option<Box<dyn Fn(&mut Context) + Send>>,
+        persistence_path: Option<PathBuf>,
     ) -> WindowHandle
     where
         F: Fn(&mut Context),
@@ -104,6 +111,10 @@ impl ViziaWindow {
 
                 context.add_theme(DEFAULT_THEME);
 
+                if let Some(path) = persistence_path {
+                    context.load_persistent_state(path);
+                }
+
                 ViziaWindow::new(context, win_desc, window, Some(Box::new(app)), on_idle)
             },
         )
@@ -116,6 +127,7 @@ impl ViziaWindow {
         win_desc: WindowDescription,
         app: F,
         on_idle: Option<Box<dyn Fn(&mut Context) + Send>>,
+        persistence_path: Option<PathBuf>,
     ) where
         F: Fn(&mut Context),
         F: 'static + Send,
@@ -139,6 +151,10 @@ impl ViziaWindow {
 
                 context.add_theme(DEFAULT_THEME);
 
+                if let Some(path) = persistence_path {
+                    context.load_persistent_state(path);
+                }
+
                 ViziaWindow::new(context, win_desc, window, Some(Box::new(app)), on_idle)
             },
         )
@@ -1,92 +1,117 @@
-use crate::{Animation, AsEntity, State};
+use std::time::Duration;
+
+use crate::{Animation, AsEntity, Context, Entity};
 
 /// Trait which provides methods for entities to manipulate linked animations
+///
+/// As an animation plays, [AnimationEvent](crate::AnimationEvent)s are sent to the entity when it starts, finishes, and begins each new iteration.
 pub trait AnimExt: AsEntity + Sized {
-
     /// Play an animation on the entity.
     ///
     /// Internally this generates an active animation and links the entity to it for each animated property.
     ///
     /// # Example
-    /// Create an animation which animates the `left` property from 0 to 100 pixels in 5 seconds 
+    /// Create an animation which animates the `left` property from 0 to 100 pixels in 5 seconds
     /// and play the animation on an entity:
-    /// ```
-    /// let animation_id = state.create_animation(std::time::Duration::from_secs(5))
+    /// ```ignore
+    /// let animation_id = cx.create_animation(std::time::Duration::from_secs(5))
     ///     .add_keyframe(0.0, |keyframe| keyframe.set_left(Pixels(0.0)))
     ///     .add_keyframe(1.0, |keyframe| keyframe.set_left(Pixels(100.0)))
-    ///     .build(); 
+    ///     .build();
     ///
-    /// entity.play_animation(state, animation_id);
+    /// entity.play_animation(cx, animation_id);
     /// ```
-    fn play_animation(self, state: &mut State, animation: Animation) -> Self {
-
-        // Background
-        state.style.background_color.play_animation(self.entity(), animation);
-
-        // Space
-        state.style.left.play_animation(self.entity(), animation);
-        state.style.right.play_animation(self.entity(), animation);
-        state.style.top.play_animation(self.entity(), animation);
-        state.style.bottom.play_animation(self.entity(), animation);
-
-        // Min/Max Space
-        state.style.min_left.play_animation(self.entity(), animation);
-        state.style.min_right.play_animation(self.entity(), animation);
-        state.style.min_top.play_animation(self.entity(), animation);
-        state.style.min_bottom.play_animation(self.entity(), animation);
-        state.style.max_left.play_animation(self.entity(), animation);
-        state.style.max_right.play_animation(self.entity(), animation);
-        state.style.max_top.play_animation(self.entity(), animation);
-        state.style.max_bottom.play_animation(self.entity(), animation);
-
-        // Child Space
-        state.style.child_left.play_animation(self.entity(), animation);
-        state.style.child_right.play_animation(self.entity(), animation);
-        state.style.child_top.play_animation(self.entity(), animation);
-        state.style.child_bottom.play_animation(self.entity(), animation);
-
-        // Size
-        state.style.width.play_animation(self.entity(), animation);
-        state.style.height.play_animation(self.entity(), animation);
-
-        // Min/Max Size
-        state.style.min_width.play_animation(self.entity(), animation);
-        state.style.min_height.play_animation(self.entity(), animation);
-        state.style.max_width.play_animation(self.entity(), animation);
-        state.style.max_height.play_animation(self.entity(), animation);
-
-        // Border
-        state.style.border_color.play_animation(self.entity(), animation);
-        state.style.border_width.play_animation(self.entity(), animation);
-        state.style.border_radius_bottom_left.play_animation(self.entity(), animation);
-        state.style.border_radius_top_left.play_animation(self.entity(), animation);
-        state.style.border_radius_bottom_right.play_animation(self.entity(), animation);
-        state.style.border_radius_top_right.play_animation(self.entity(), animation);
-
-        // Transform
-        state.style.rotate.play_animation(self.entity(), animation);
-        // state.style.translate.play_animation(self.entity(), animation);
-        state.style.scale.play_animation(self.entity(), animation);
-
-        // Display
-        state.style.opacity.play_animation(self.entity(), animation);
-
-
-        self
+    fn play_animation(self, cx: &mut Context, animation: Animation) -> Entity {
+        cx.style.play_animation(self.entity(), animation);
+
+        cx.style.needs_relayout = true;
+        cx.style.needs_redraw = true;
+
+        self.entity()
     }
 
-    /// Returns true if there is an active animation with the given id.
-    /// 
+    /// Pauses an animation playing on the entity, holding its current value until it is resumed.
+    ///
     /// # Example
+    /// ```ignore
+    /// entity.pause_animation(cx, animation_id);
     /// ```
-    /// let test = entity.is_animation(animation_id);
+    fn pause_animation(self, cx: &mut Context, animation: Animation) -> Entity {
+        cx.style.pause_animation(self.entity(), animation);
+
+        self.entity()
+    }
+
+    /// Resumes a paused animation from where it was paused.
+    ///
+    /// # Example
+    /// ```ignore
+    /// entity.resume_animation(cx, animation_id);
     /// ```
-    fn is_animating(self, state: &mut State, animation: Animation) -> bool {
-        state.style.height.is_animating(self.entity(), animation) ||
-        state.style.width.is_animating(self.entity(), animation)
+    fn resume_animation(self, cx: &mut Context, animation: Animation) -> Entity {
+        cx.style.resume_animation(self.entity(), animation);
+
+        cx.style.needs_redraw = true;
+
+        self.entity()
+    }
+
+    /// Moves an animation playing on the entity to the given time, measured from the start of its first iteration.
+    ///
+    /// # Example
+    /// Jump to halfway through a one second animation:
+    /// ```ignore
+    /// entity.seek_animation(cx, animation_id, std::time::Duration::from_millis(500));
+    /// ```
+    fn seek_animation(self, cx: &mut Context, animation: Animation, time: Duration) -> Entity {
+        cx.style.seek_animation(self.entity(), animation, time);
+
+        cx.style.needs_relayout = true;
+        cx.style.needs_redraw = true;
+
+        self.entity()
     }
-}
 
-impl<T: AsEntity> AnimExt for T {
+    /// Reverses an animation playing on the entity so that it plays back to its starting value.
+    ///
+    /// # Example
+    /// ```ignore
+    /// entity.reverse_animation(cx, animation_id);
+    /// ```
+    fn reverse_animation(self, cx: &mut Context, animation: Animation) -> Entity {
+        cx.style.reverse_animation(self.entity(), animation);
+
+        cx.style.needs_redraw = true;
+
+        self.entity()
+    }
+
+    /// Cancels an animation playing on the entity. The animated properties return to their inline or shared values.
+    ///
+    /// Unlike an animation which finishes, a cancelled animation does not emit [AnimationEvent::AnimationEnded](crate::AnimationEvent::AnimationEnded).
+    ///
+    /// # Example
+    /// ```ignore
+    /// entity.cancel_animation(cx, animation_id);
+    /// ```
+    fn cancel_animation(self, cx: &mut Context, animation: Animation) -> Entity {
+        cx.style.stop_animation(self.entity(), animation);
+
+        cx.style.needs_relayout = true;
+        cx.style.needs_redraw = true;
+
+        self.entity()
+    }
+
+    /// Returns true if the animation with the given id is playing on the entity.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let test = entity.is_animating(cx, animation_id);
+    /// ```
+    fn is_animating(self, cx: &mut Context, animation: Animation) -> bool {
+        cx.style.is_animating(self.entity(), animation)
+    }
+}
 
-}
\ No newline at end of file
+impl<T: AsEntity> AnimExt for T {}
@@ -1,114 +1,132 @@
-use morphorm::Units;
+use std::time::Duration;
 
-use crate::{Animation, AnimationState, Color, Opacity, State};
+use morphorm::Units;
 
-pub(crate) struct AnimationDescription {
-    duration: std::time::Duration,
-    delay: std::time::Duration,
-    persistent: bool,
-}
+use crate::storage::animatable_set::AnimatableSet;
+use crate::style::Opacity;
+use crate::{
+    Animation, AnimationDescription, AnimationDirection, AnimationFillMode, Color, Context,
+    Interpolator, TimingFunction,
+};
 
 /// A builder for constructing animations.
 ///
-/// Returned from `state.create_animation(duration)`.
+/// Returned from `cx.create_animation(duration)`.
 ///
 /// # Example
-/// ```
-/// let animation_id = state.create_animation(std::time::Duration::from_secs(1))
-///     .add_keyframe(0.0, |keyframe| 
+/// ```no_run
+/// # use vizia_core::*;
+/// # let cx = &mut Context::new();
+/// let animation_id = cx.create_animation(std::time::Duration::from_secs(1))
+///     .with_timing_function(TimingFunction::EASE_IN_OUT)
+///     .add_keyframe(0.0, |keyframe|
 ///         keyframe
 ///             .set_background_color(Color::red())
-///             .set_border_color(Color::blue())
+///             .set_width(Pixels(100.0))
 ///     )
-///     .add_keyframe(1.0, |keyframe| 
+///     .add_keyframe(1.0, |keyframe|
 ///         keyframe
-///             .set_background_color(Color::blue()))
-///             .set_border_color(Color::red())
+///             .set_background_color(Color::blue())
+///             .set_width(Pixels(200.0))
+///     )
 ///     .build();
 /// ```
 pub struct AnimationBuilder<'a> {
     id: Animation,
-    state: &'a mut State,
+    cx: &'a mut Context,
     animation_description: AnimationDescription,
 }
 
 impl<'a> AnimationBuilder<'a> {
-    pub fn new(id: Animation, state: &'a mut State, duration: std::time::Duration) -> Self {
-        Self {
-            id, 
-            state,
-            animation_description: AnimationDescription {
-                duration,
-                delay: std::time::Duration::from_secs(0),
-                persistent: false,
-            }
-        }
-    }
+    pub(crate) fn new(id: Animation, cx: &'a mut Context, duration: Duration) -> Self {
+        let mut animation_description = AnimationDescription::new();
+        animation_description.duration = duration.as_secs_f32();
+        animation_description.timing_function = TimingFunction::Linear;
 
+        Self { id, cx, animation_description }
+    }
 
-    /// Sets the delay before the animation will play. 
+    /// Sets the delay before the animation will play.
     ///
     /// Needs to be called before setting keyframes.
-    pub fn with_delay(mut self, delay: std::time::Duration) -> Self {
-        self.animation_description.delay = delay;
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.animation_description.delay = delay.as_secs_f32();
 
         self
     }
 
     /// Sets the animation to persist after completion.
     ///
-    /// Normally, after an animation is finished, the animated property will return to the the previous value 
+    /// Normally, after an animation is finished, the animated property will return to the the previous value
     /// before the animation was played. Setting an animation to persistent causes the property to be set to the last
-    /// value of the animation.
-    pub fn persistent(mut self) -> Self {
-        self.animation_description.persistent = true;
+    /// value of the animation. This is the same as a fill mode of [AnimationFillMode::Forwards].
+    pub fn persistent(self) -> Self {
+        self.with_fill_mode(AnimationFillMode::Forwards)
+    }
+
+    /// Sets how the progress of each iteration changes over time. Animations are linear by default.
+    pub fn with_timing_function(mut self, timing_function: TimingFunction) -> Self {
+        self.animation_description.timing_function = timing_function;
+
+        self
+    }
+
+    /// Sets the number of times the animation plays, which may be fractional or `f32::INFINITY`.
+    pub fn with_iterations(mut self, iterations: f32) -> Self {
+        self.animation_description.iterations = iterations;
+
+        self
+    }
+
+    /// Sets the animation to repeat forever.
+    pub fn infinite(self) -> Self {
+        self.with_iterations(f32::INFINITY)
+    }
+
+    /// Sets the direction in which each iteration of the animation plays.
+    pub fn with_direction(mut self, direction: AnimationDirection) -> Self {
+        self.animation_description.direction = direction;
+
+        self
+    }
+
+    /// Sets whether the animation applies its values during the delay and after it finishes.
+    pub fn with_fill_mode(mut self, fill_mode: AnimationFillMode) -> Self {
+        self.animation_description.fill_mode = fill_mode;
 
         self
     }
 
     /// Adds a keyframe to the animation.
     ///
-    /// 
-    pub fn add_keyframe<F>(self, time: f32, keyframe: F) -> KeyframeBuilder<'a> 
-    where F: FnOnce(KeyframeBuilder<'a>) -> KeyframeBuilder<'a>
+    /// The time of the keyframe is a fraction of the duration between 0.0 and 1.0.
+    pub fn add_keyframe<F>(self, time: f32, keyframe: F) -> KeyframeBuilder<'a>
+    where
+        F: FnOnce(KeyframeBuilder<'a>) -> KeyframeBuilder<'a>,
     {
-        (keyframe)(KeyframeBuilder::new(self.id, self.state, time, self.animation_description))
+        (keyframe)(KeyframeBuilder::new(self.id, self.cx, time, self.animation_description))
     }
 }
 
-
 /// A builder for constructing keyframes.
-/// 
-/// # Example
-/// ```
-/// let animation_id = state.create_animation(std::time::Duration::from_secs(1))
-///     .add_keyframe(0.0, |keyframe| 
-///         keyframe
-///             .set_background_color(Color::red())
-///             .set_border_color(Color::blue())
-///     )
-///     .add_keyframe(1.0, |keyframe| 
-///         keyframe
-///             .set_background_color(Color::blue()))
-///             .set_border_color(Color::red())
-///     .build();
-/// ```
+///
+/// Returned from the `add_keyframe()` method of an [AnimationBuilder].
 pub struct KeyframeBuilder<'a> {
     id: Animation,
-    state: &'a mut State,
+    cx: &'a mut Context,
     time: f32,
     animation_description: AnimationDescription,
 }
 
 impl<'a> KeyframeBuilder<'a> {
-    pub(crate) fn new(id: Animation, state: &'a mut State, time: f32, animation_description: AnimationDescription) -> Self {
-        Self {
-            id,
-            state,
-            time,
-            animation_description,
-        }
-    } 
+    pub(crate) fn new(
+        id: Animation,
+        cx: &'a mut Context,
+        time: f32,
+        animation_description: AnimationDescription,
+    ) -> Self {
+        Self { id, cx, time, animation_description }
+    }
 
     /// Finish building the animation, returning an [Animation] id.
     pub fn build(self) -> Animation {
@@ -116,317 +134,201 @@ impl<'a> KeyframeBuilder<'a> {
     }
 
     /// Add another keyframe to the animation.
-    pub fn add_keyframe<F>(self, time: f32, keyframe: F) -> Self 
-    where F: FnOnce(KeyframeBuilder<'a>) -> KeyframeBuilder<'a>
+    pub fn add_keyframe<F>(self, time: f32, keyframe: F) -> Self
+    where
+        F: FnOnce(KeyframeBuilder<'a>) -> KeyframeBuilder<'a>,
     {
-        (keyframe)(KeyframeBuilder::new(self.id, self.state, time, self.animation_description))
+        (keyframe)(KeyframeBuilder::new(self.id, self.cx, time, self.animation_description))
     }
 
     /// Adds a background-color property to the keyframe.
-    /// 
+    ///
     /// # Example
-    /// ```
+    /// ```ignore
     /// .add_keyframe(0.0, |keyframe| keyframe.set_background_color(Color::red()))
     /// ```
-    pub fn set_background_color(self, color: Color) -> Self {
-
-        if let Some(anim_state) = self.state.style.background_color.get_animation_mut(self.id) {
-            anim_state.keyframes.push((self.time, color));
-        } else {
-            let anim_state = AnimationState::new(self.id)
-                .with_duration(self.animation_description.duration)
-                .with_delay(self.animation_description.delay)
-                .set_persistent(self.animation_description.persistent)
-                .with_keyframe((self.time, color));
-                
-            self.state.style.background_color.insert_animation(self.id, anim_state);
-
-        }
+    pub fn set_background_color(mut self, color: Color) -> Self {
+        insert_keyframe(
+            &mut self.cx.style.background_color,
+            self.id,
+            self.time,
+            &self.animation_description,
+            color,
+        );
 
         self
-        
     }
 
     /// Adds a left property to the keyframe.
-    /// 
-    /// # Example
-    /// ```
-    /// .add_keyframe(0.0, |keyframe| keyframe.set_left(Pixels(50.0)))
-    /// ```
-    pub fn set_left(self, value: Units) -> Self {
-
-        if let Some(anim_state) = self.state.style.left.get_animation_mut(self.id) {
-            anim_state.keyframes.push((self.time, value));
-        } else {
-            let anim_state = AnimationState::new(self.id)
-                .with_duration(self.animation_description.duration)
-                .with_delay(self.animation_description.delay)
-                .set_persistent(self.animation_description.persistent)
-                .with_keyframe((self.time, value));
-                
-            self.state.style.left.insert_animation(self.id, anim_state);
-
-        }
-
-        self   
+    pub fn set_left(mut self, value: Units) -> Self {
+        insert_keyframe(
+            &mut self.cx.style.left,
+            self.id,
+            self.time,
+            &self.animation_description,
+            value,
+        );
+
+        self
     }
 
     /// Adds a right property to the keyframe.
-    /// 
-    /// # Example
-    /// ```
-    /// .add_keyframe(0.0, |keyframe| keyframe.set_right(Pixels(50.0)))
-    /// ```
-    pub fn set_right(self, value: Units) -> Self {
-
-        if let Some(anim_state) = self.state.style.right.get_animation_mut(self.id) {
-            anim_state.keyframes.push((self.time, value));
-        } else {
-            let anim_state = AnimationState::new(self.id)
-                .with_duration(self.animation_description.duration)
-                .with_delay(self.animation_description.delay)
-                .set_persistent(self.animation_description.persistent)
-                .with_keyframe((self.time, value));
-                
-            self.state.style.right.insert_animation(self.id, anim_state);
-
-        }
-
-        self   
+    pub fn set_right(mut self, value: Units) -> Self {
+        insert_keyframe(
+            &mut self.cx.style.right,
+            self.id,
+            self.time,
+            &self.animation_description,
+            value,
+        );
+
+        self
     }
 
     /// Adds a top property to the keyframe.
-    /// 
-    /// # Example
-    /// ```
-    /// .add_keyframe(0.0, |keyframe| keyframe.set_top(Pixels(50.0)))
-    /// ```
-    pub fn set_top(self, value: Units) -> Self {
-
-        if let Some(anim_state) = self.state.style.top.get_animation_mut(self.id) {
-            anim_state.keyframes.push((self.time, value));
-        } else {
-            let anim_state = AnimationState::new(self.id)
-                .with_duration(self.animation_description.duration)
-                .with_delay(self.animation_description.delay)
-                .set_persistent(self.animation_description.persistent)
-                .with_keyframe((self.time, value));
-                
-            self.state.style.top.insert_animation(self.id, anim_state);
-
-        }
-
-        self   
+    pub fn set_top(mut self, value: Units) -> Self {
+        insert_keyframe(
+            &mut self.cx.style.top,
+            self.id,
+            self.time,
+            &self.animation_description,
+            value,
+        );
+
+        self
     }
 
     /// Adds a bottom property to the keyframe.
-    /// 
-    /// # Example
-    /// ```
-    /// .add_keyframe(0.0, |keyframe| keyframe.set_bottom(Pixels(50.0)))
-    /// ```
-    pub fn set_bottom(self, value: Units) -> Self {
-
-        if let Some(anim_state) = self.state.style.bottom.get_animation_mut(self.id) {
-            anim_state.keyframes.push((self.time, value));
-        } else {
-            let anim_state = AnimationState::new(self.id)
-                .with_duration(self.animation_description.duration)
-                .with_delay(self.animation_description.delay)
-                .set_persistent(self.animation_description.persistent)
-                .with_keyframe((self.time, value));
-                
-            self.state.style.bottom.insert_animation(self.id, anim_state);
-
-        }
-
-        self   
+    pub fn set_bottom(mut self, value: Units) -> Self {
+        insert_keyframe(
+            &mut self.cx.style.bottom,
+            self.id,
+            self.time,
+            &self.animation_description,
+            value,
+        );
+
+        self
     }
 
     /// Adds a width property to the keyframe.
-    /// 
-    /// # Example
-    /// ```
-    /// .add_keyframe(0.0, |keyframe| keyframe.set_width(Pixels(50.0)))
-    /// ```
-    pub fn set_width(self, value: Units) -> Self {
-
-        if let Some(anim_state) = self.state.style.width.get_animation_mut(self.id) {
-            anim_state.keyframes.push((self.time, value));
-        } else {
-            let anim_state = AnimationState::new(self.id)
-                .with_duration(self.animation_description.duration)
-                .with_delay(self.animation_description.delay)
-                .set_persistent(self.animation_description.persistent)
-                .with_keyframe((self.time, value));
-                
-            self.state.style.width.insert_animation(self.id, anim_state);
-
-        }
-
-        self   
+    pub fn set_width(mut self, value: Units) -> Self {
+        insert_keyframe(
+            &mut self.cx.style.width,
+            self.id,
+            self.time,
+            &self.animation_description,
+            value,
+        );
+
+        self
     }
 
     /// Adds a height property to the keyframe.
-    /// 
-    /// # Example
-    /// ```
-    /// .add_keyframe(0.0, |keyframe| keyframe.set_height(Pixels(50.0)))
-    /// ```
-    pub fn set_height(self, value: Units) -> Self {
-
-        if let Some(anim_state) = self.state.style.height.get_animation_mut(self.id) {
-            anim_state.keyframes.push((self.time, value));
-        } else {
-            let anim_state = AnimationState::new(self.id)
-                .with_duration(self.animation_description.duration)
-                .with_delay(self.animation_description.delay)
-                .set_persistent(self.animation_description.persistent)
-                .with_keyframe((self.time, value));
-                
-            self.state.style.height.insert_animation(self.id, anim_state);
-
-        }
-
-        self   
+    pub fn set_height(mut self, value: Units) -> Self {
+        insert_keyframe(
+            &mut self.cx.style.height,
+            self.id,
+            self.time,
+            &self.animation_description,
+            value,
+        );
+
+        self
     }
 
     /// Adds a child-left property to the keyframe.
-    /// 
-    /// # Example
-    /// ```
-    /// .add_keyframe(0.0, |keyframe| keyframe.set_child_left(Pixels(50.0)))
-    /// ```
-    pub fn set_child_left(self, value: Units) -> Self {
-
-        if let Some(anim_state) = self.state.style.child_left.get_animation_mut(self.id) {
-            anim_state.keyframes.push((self.time, value));
-        } else {
-            let anim_state = AnimationState::new(self.id)
-                .with_duration(self.animation_description.duration)
-                .with_delay(self.animation_description.delay)
-                .set_persistent(self.animation_description.persistent)
-                .with_keyframe((self.time, value));
-                
-            self.state.style.child_left.insert_animation(self.id, anim_state);
-
-        }
-
-        self   
+    pub fn set_child_left(mut self, value: Units) -> Self {
+        insert_keyframe(
+            &mut self.cx.style.child_left,
+            self.id,
+            self.time,
+            &self.animation_description,
+            value,
+        );
+
+        self
     }
 
     /// Adds a child-right property to the keyframe.
-    /// 
-    /// # Example
-    /// ```
-    /// .add_keyframe(0.0, |keyframe| keyframe.set_child_right(Pixels(50.0)))
-    /// ```
-    pub fn set_child_right(self, value: Units) -> Self {
-
-        if let Some(anim_state) = self.state.style.child_right.get_animation_mut(self.id) {
-            anim_state.keyframes.push((self.time, value));
-        } else {
-            let anim_state = AnimationState::new(self.id)
-                .with_duration(self.animation_description.duration)
-                .with_delay(self.animation_description.delay)
-                .set_persistent(self.animation_description.persistent)
-                .with_keyframe((self.time, value));
-                
-            self.state.style.child_right.insert_animation(self.id, anim_state);
-
-        }
-
-        self   
+    pub fn set_child_right(mut self, value: Units) -> Self {
+        insert_keyframe(
+            &mut self.cx.style.child_right,
+            self.id,
+            self.time,
+            &self.animation_description,
+            value,
+        );
+
+        self
     }
 
     /// Adds a child-top property to the keyframe.
-    /// 
-    /// # Example
-    /// ```
-    /// .add_keyframe(0.0, |keyframe| keyframe.set_child_top(Pixels(50.0)))
-    /// ```
-    pub fn set_child_top(self, value: Units) -> Self {
-
-        if let Some(anim_state) = self.state.style.child_top.get_animation_mut(self.id) {
-            anim_state.keyframes.push((self.time, value));
-        } else {
-            let anim_state = AnimationState::new(self.id)
-                .with_duration(self.animation_description.duration)
-                .with_delay(self.animation_description.delay)
-                .set_persistent(self.animation_description.persistent)
-                .with_keyframe((self.time, value));
-                
-            self.state.style.child_top.insert_animation(self.id, anim_state);
-
-        }
-
-        self   
+    pub fn set_child_top(mut self, value: Units) -> Self {
+        insert_keyframe(
+            &mut self.cx.style.child_top,
+            self.id,
+            self.time,
+            &self.animation_description,
+            value,
+        );
+
+        self
     }
 
     /// Adds a child-bottom property to the keyframe.
-    /// 
-    /// # Example
-    /// ```
-    /// .add_keyframe(0.0, |keyframe| keyframe.set_child_bottom(Pixels(50.0)))
-    /// ```
-    pub fn set_child_bottom(self, value: Units) -> Self {
-
-        if let Some(anim_state) = self.state.style.child_bottom.get_animation_mut(self.id) {
-            anim_state.keyframes.push((self.time, value));
-        } else {
-            let anim_state = AnimationState::new(self.id)
-                .with_duration(self.animation_description.duration)
-                .with_delay(self.animation_description.delay)
-                .set_persistent(self.animation_description.persistent)
-                .with_keyframe((self.time, value));
-                
-            self.state.style.child_bottom.insert_animation(self.id, anim_state);
-
-        }
-
-        self   
-    }
+    pub fn set_child_bottom(mut self, value: Units) -> Self {
+        insert_keyframe(
+            &mut self.cx.style.child_bottom,
+            self.id,
+            self.time,
+            &self.animation_description,
+            value,
+        );
 
-    /// Adds a rotate transform property to the keyframe.
-    /// 
-    /// # Example
-    /// ```
-    /// .add_keyframe(0.0, |keyframe| keyframe.set_rotate(Pixels(50.0)))
-    /// ```
-    pub fn set_rotate(self, value: f32) -> Self {
-        if let Some(anim_state) = self.state.style.rotate.get_animation_mut(self.id) {
-            anim_state.keyframes.push((self.time, value));
-        } else {
-            
-            let anim_state = AnimationState::new(self.id)
-                .with_duration(self.animation_description.duration)
-                .with_delay(self.animation_description.delay)
-                .set_persistent(self.animation_description.persistent)
-                .with_keyframe((self.time, value));
-
-            self.state.style.rotate.insert_animation(self.id, anim_state);
-
-        }
-
-        self   
+        self
     }
 
-    pub fn set_opacity(self, value: f32) -> Self {
-        if let Some(anim_state) = self.state.style.opacity.get_animation_mut(self.id) {
-            anim_state.keyframes.push((self.time, Opacity(value)));
-        } else {
-            let anim_state = AnimationState::new(self.id)
-                .with_duration(self.animation_description.duration)
-                .with_delay(self.animation_description.delay)
-                .set_persistent(self.animation_description.persistent)
-                .with_keyframe((self.time, Opacity(value)));
-                
-            self.state.style.opacity.insert_animation(self.id, anim_state);
-
-        }
-
-        self 
+    /// Adds a rotate property to the keyframe.
+    pub fn set_rotate(mut self, value: f32) -> Self {
+        insert_keyframe(
+            &mut self.cx.style.rotate,
+            self.id,
+            self.time,
+            &self.animation_description,
+            value,
+        );
+
+        self
     }
 
+    /// Adds an opacity property to the keyframe.
+    pub fn set_opacity(mut self, value: f32) -> Self {
+        insert_keyframe(
+            &mut self.cx.style.opacity,
+            self.id,
+            self.time,
+            &self.animation_description,
+            Opacity(value),
+        );
+
+        self
+    }
+}
 
-}
\ No newline at end of file
+// Adds a keyframe value to the animation of a property.
+fn insert_keyframe<T>(
+    set: &mut AnimatableSet<T>,
+    id: Animation,
+    time: f32,
+    animation_description: &AnimationDescription,
+    value: T,
+) where
+    T: 'static + Default + Clone + Interpolator + PartialEq + std::fmt::Debug,
+{
+    set.insert_keyframe(id, time, value);
+
+    if let Some(anim_state) = set.get_animation_mut(id) {
+        anim_state.set_description(animation_description);
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{AnimationDirection, AnimationFillMode, TimingFunction};
+
+/// Describes how a `@keyframes` animation is played, as set by the `animation` style property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationDescription {
+    // Name of the keyframes to play
+    pub name: String,
+    // Duration of each iteration in seconds
+    pub duration: f32,
+    // Delay before the animation starts in seconds
+    pub delay: f32,
+    // How the progress of each iteration changes over time
+    pub timing_function: TimingFunction,
+    // The number of times the animation plays, which may be infinite
+    pub iterations: f32,
+    // The direction in which each iteration plays
+    pub direction: AnimationDirection,
+    // Whether the animation applies its values before starting and after finishing
+    pub fill_mode: AnimationFillMode,
+}
+
+impl AnimationDescription {
+    pub fn new() -> Self {
+        AnimationDescription {
+            name: String::new(),
+            duration: 0.0,
+            delay: 0.0,
+            timing_function: TimingFunction::EASE,
+            iterations: 1.0,
+            direction: AnimationDirection::Normal,
+            fill_mode: AnimationFillMode::None,
+        }
+    }
+}
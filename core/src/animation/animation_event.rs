@@ -0,0 +1,17 @@
+use crate::Animation;
+
+/// Events sent to an entity as an animation playing on it progresses.
+///
+/// Events are only sent for animations played with [AnimExt](crate::AnimExt) or the `animation` style property,
+/// not for transitions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnimationEvent {
+    /// Emitted when an animation starts playing, after any delay has passed.
+    AnimationStarted(Animation),
+    /// Emitted when an animation finishes playing all of its iterations.
+    ///
+    /// This is not emitted for an animation which is cancelled.
+    AnimationEnded(Animation),
+    /// Emitted when an animation starts a new iteration, other than the first.
+    AnimationIteration(Animation),
+}
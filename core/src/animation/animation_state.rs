@@ -3,7 +3,57 @@ use std::{
     time::{Duration, Instant},
 };
 
-use crate::{Animation, Entity, Interpolator};
+use crate::{Animation, AnimationDescription, Entity, Interpolator, TimingFunction};
+
+/// The direction in which each iteration of an animation plays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationDirection {
+    /// Every iteration plays forwards.
+    Normal,
+    /// Every iteration plays backwards.
+    Reverse,
+    /// Iterations alternate between playing forwards and backwards, starting forwards.
+    Alternate,
+    /// Iterations alternate between playing backwards and forwards, starting backwards.
+    AlternateReverse,
+}
+
+impl AnimationDirection {
+    // Returns the direction which plays every iteration the opposite way.
+    fn reversed(self) -> Self {
+        match self {
+            AnimationDirection::Normal => AnimationDirection::Reverse,
+            AnimationDirection::Reverse => AnimationDirection::Normal,
+            AnimationDirection::Alternate => AnimationDirection::AlternateReverse,
+            AnimationDirection::AlternateReverse => AnimationDirection::Alternate,
+        }
+    }
+}
+
+impl Default for AnimationDirection {
+    fn default() -> Self {
+        AnimationDirection::Normal
+    }
+}
+
+/// Whether an animation applies its values before it starts and after it finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationFillMode {
+    /// The animation only applies its values while playing.
+    None,
+    /// The last value of the animation is kept after it finishes.
+    Forwards,
+    /// The first value of the animation is applied during the delay before it starts.
+    Backwards,
+    /// Both `Forwards` and `Backwards`.
+    Both,
+}
+
+impl Default for AnimationFillMode {
+    fn default() -> Self {
+        AnimationFillMode::None
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct AnimationState<Prop: Interpolator> {
@@ -17,13 +67,27 @@ pub struct AnimationState<Prop: Interpolator> {
     pub duration: Duration,
     //
     pub delay: f32,
-    // Animation keyframes (time, value)
+    // Animation keyframes (time, value), ordered by time
     pub keyframes: Vec<(f32, Prop)>,
+    // How the progress of each iteration changes over time
+    pub timing_function: TimingFunction,
+    // The number of times the animation plays, which may be infinite
+    pub iterations: f32,
+    // The direction in which each iteration plays
+    pub direction: AnimationDirection,
+    // Whether the first value is applied during the delay
+    pub fill_mode: AnimationFillMode,
     // The output of the animation
     pub output: Option<Prop>,
     // A flag used to check if the animation is finished
     pub persistent: bool,
     pub t0: f32,
+    // The number of iterations played when last updated, which is negative during the delay
+    pub progress: f32,
+    // A progress to continue the animation from on the next update
+    pub seek_to: Option<f32>,
+    // A flag used to hold the animation at its current progress
+    pub paused: bool,
     // How far through the animation between 0.0 and 1.0 (used for transitions)
     pub t: f32,
 
@@ -53,9 +117,16 @@ where
             duration: Duration::new(0, 0),
             delay: 0.0,
             keyframes: Vec::new(),
+            timing_function: TimingFunction::default(),
+            iterations: 1.0,
+            direction: AnimationDirection::default(),
+            fill_mode: AnimationFillMode::default(),
             output: None,
             persistent: false,
             t0: 0.0,
+            progress: std::f32::NEG_INFINITY,
+            seek_to: None,
+            paused: false,
             t: 0.0,
             active: false,
             entities: HashSet::new(),
@@ -72,40 +143,59 @@ where
     }
 
     pub fn with_delay(mut self, delay: Duration) -> Self {
-        self.delay = delay.as_secs_f32() / self.duration.as_secs_f32();
+        self.set_delay(delay);
 
         self
     }
 
     pub fn set_delay(&mut self, delay: Duration) -> &mut Self {
-        self.delay = delay.as_secs_f32() / self.duration.as_secs_f32();
+        self.set_delay_secs(delay.as_secs_f32());
 
         self
     }
 
+    // Sets the delay in seconds, which is stored as a number of iterations. A negative delay starts the
+    // animation part way through. An animation without a duration finishes immediately so has no delay.
+    fn set_delay_secs(&mut self, delay: f32) {
+        let duration = self.duration.as_secs_f32();
+        self.delay = if duration > 0.0 { delay / duration } else { 0.0 };
+    }
+
     pub fn with_keyframe(mut self, key: (f32, Prop)) -> Self {
-        self.keyframes.push(key);
+        self.add_keyframe(key);
 
         self
     }
 
-    pub fn interpolate(&mut self, current_time: Instant) -> bool {
-        if current_time > self.start_time + self.duration {
-            return false;
-        }
-        // println!("Animating");
+    /// Adds a keyframe, keeping the keyframes ordered by time.
+    pub fn add_keyframe(&mut self, key: (f32, Prop)) {
+        let index = self.keyframes.iter().position(|(time, _)| *time > key.0);
+        self.keyframes.insert(index.unwrap_or(self.keyframes.len()), key);
+    }
 
-        //let point = self.start_time.elapsed().as_secs_f32() / self.duration.as_secs_f32();
+    pub fn with_timing_function(mut self, timing_function: TimingFunction) -> Self {
+        self.timing_function = timing_function;
 
-        //let value = Prop::interpolate((0.0,1.0), (&self.keyframes[0].1, &self.keyframes[1].1), point);
-        // use the keyframes to interpolate the value and store the result in output.
-        //let mut pos = Positioning::default();
+        self
+    }
 
-        //let i = Prop::interpolate(0.0, Prop::default(), 1.0, Prop::default())
+    /// Sets the fill mode of the animation. A fill mode of `Forwards` or `Both` makes the animation persistent.
+    pub fn set_fill_mode(&mut self, fill_mode: AnimationFillMode) -> &mut Self {
+        self.fill_mode = fill_mode;
+        self.persistent =
+            matches!(fill_mode, AnimationFillMode::Forwards | AnimationFillMode::Both);
 
-        //let i = pos.interpolate();
+        self
+    }
 
-        true
+    /// Sets the duration, delay, timing function, iteration count, direction and fill mode from an animation description.
+    pub(crate) fn set_description(&mut self, description: &AnimationDescription) {
+        self.duration = Duration::from_secs_f32(description.duration.max(0.0));
+        self.set_delay_secs(description.delay);
+        self.timing_function = description.timing_function;
+        self.iterations = description.iterations;
+        self.direction = description.direction;
+        self.set_fill_mode(description.fill_mode);
     }
 
     pub fn set_persistent(mut self, flag: bool) -> Self {
@@ -123,8 +213,192 @@ where
         self.active = true;
         self.t = 0.0;
         self.start_time = std::time::Instant::now();
+        // Start from the beginning of the delay at the time of the next update
+        self.progress = std::f32::NEG_INFINITY;
+        self.seek_to = Some(-self.delay);
+        self.paused = false;
         self.entities.insert(entity);
     }
+
+    /// Holds the animation at its current progress until it is resumed.
+    pub(crate) fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Continues a paused animation from the progress it was paused at.
+    pub(crate) fn resume(&mut self) {
+        if self.paused {
+            self.paused = false;
+            if self.seek_to.is_none() {
+                self.seek_to = Some(self.progress.max(-self.delay));
+            }
+        }
+    }
+
+    /// Moves the animation to the given time, measured from the start of the first iteration.
+    ///
+    /// A finished persistent animation continues playing from the new time.
+    pub(crate) fn seek(&mut self, time: Duration) {
+        let duration = self.duration.as_secs_f32();
+        if duration > 0.0 {
+            self.seek_to = Some((time.as_secs_f32() / duration).min(self.iterations));
+            self.t0 = 0.0;
+            self.t = 0.0;
+            self.active = true;
+        }
+    }
+
+    /// Reverses the direction of the animation so that it plays back to where it started from its current value.
+    pub(crate) fn reverse(&mut self) {
+        let progress = self.seek_to.unwrap_or(self.progress).max(-self.delay);
+
+        if progress < 0.0 {
+            // Still waiting for the delay so just swap the direction
+            self.direction = self.direction.reversed();
+            return;
+        }
+
+        if self.iterations.is_finite() {
+            // Play the iterations which have already played in the opposite order. With an even number of
+            // iterations alternating directions already match when played in the opposite order.
+            let alternating = matches!(
+                self.direction,
+                AnimationDirection::Alternate | AnimationDirection::AlternateReverse
+            );
+            if !alternating || self.iterations.ceil() % 2.0 == 1.0 {
+                self.direction = self.direction.reversed();
+            }
+            self.seek_to = Some(self.iterations - progress.min(self.iterations));
+        } else {
+            // Play the current iteration in the opposite direction
+            self.direction = self.direction.reversed();
+            self.seek_to = Some(2.0 * progress.floor() + 1.0 - progress);
+        }
+
+        self.t0 = 0.0;
+        self.t = 0.0;
+        self.active = true;
+    }
+}
+
+impl<Prop> AnimationState<Prop>
+where
+    Prop: Interpolator + Clone + PartialEq,
+{
+    /// Updates the output of the animation for the given time.
+    ///
+    /// When the animation has played all of its iterations `t` is set to 1.0 and, unless the animation is
+    /// persistent, the animation is marked as inactive.
+    pub(crate) fn tick(&mut self, time: Instant) {
+        if self.keyframes.is_empty() {
+            self.t0 = 1.0;
+            self.t = 1.0;
+            self.progress = self.iterations;
+            return;
+        }
+
+        // An animation between identical values finishes immediately
+        if self.keyframes.windows(2).all(|pair| pair[0].1 == pair[1].1) {
+            self.t0 = 1.0;
+            self.t = 1.0;
+            self.progress = self.iterations;
+            self.output = self.keyframes.last().map(|(_, value)| value.clone());
+            return;
+        }
+
+        let elapsed_time = time.saturating_duration_since(self.start_time).as_secs_f32();
+        let duration = self.duration.as_secs_f32();
+
+        // Number of iterations played so far
+        let progress = if duration <= 0.0 {
+            self.iterations
+        } else if let Some(progress) = self.seek_to.take() {
+            // Move the start time so that the animation continues from the new progress
+            let offset = Duration::from_secs_f32(((progress + self.delay) * duration).max(0.0));
+            self.start_time = time.checked_sub(offset).unwrap_or(time);
+            progress
+        } else if self.paused {
+            self.progress
+        } else {
+            elapsed_time / duration - self.delay
+        };
+
+        self.progress = progress;
+
+        // Store previous time state
+        self.t0 = self.t;
+
+        if progress >= self.iterations {
+            // Animation is finished
+            self.t = 1.0;
+            self.output = Some(self.value_at(self.iterations));
+
+            if !self.persistent {
+                self.active = false;
+            }
+        } else if progress < 0.0 {
+            // Waiting for the delay to pass
+            self.t = 0.0;
+            self.output = self.initial_output();
+        } else {
+            self.t = progress / self.iterations;
+            self.output = Some(self.value_at(progress));
+        }
+    }
+
+    /// Returns the output of the animation before it starts, which depends on the fill mode.
+    pub(crate) fn initial_output(&self) -> Option<Prop> {
+        if self.delay > 0.0
+            && !matches!(self.fill_mode, AnimationFillMode::Backwards | AnimationFillMode::Both)
+        {
+            return None;
+        }
+
+        Some(self.value_at(0.0))
+    }
+
+    /// Returns the animated value after a given number of iterations.
+    pub(crate) fn value_at(&self, progress: f32) -> Prop {
+        // The current iteration and the fraction of it which has played
+        let (iteration, fraction) = if progress >= self.iterations {
+            let iteration = (self.iterations.ceil() - 1.0).max(0.0);
+            (iteration, (self.iterations - iteration).min(1.0))
+        } else {
+            (progress.floor(), progress.fract())
+        };
+
+        let reversed = match self.direction {
+            AnimationDirection::Normal => false,
+            AnimationDirection::Reverse => true,
+            AnimationDirection::Alternate => iteration % 2.0 == 1.0,
+            AnimationDirection::AlternateReverse => iteration % 2.0 == 0.0,
+        };
+
+        let fraction = if reversed { 1.0 - fraction } else { fraction };
+
+        self.keyframe_value(self.timing_function.value(fraction))
+    }
+
+    // Interpolates between the keyframes either side of the given time.
+    fn keyframe_value(&self, time: f32) -> Prop {
+        let (first_time, first_value) = &self.keyframes[0];
+        if time <= *first_time {
+            return first_value.clone();
+        }
+
+        for pair in self.keyframes.windows(2) {
+            let (start_time, start_value) = &pair[0];
+            let (end_time, end_value) = &pair[1];
+
+            if time <= *end_time {
+                let span = end_time - start_time;
+                let t = if span > 0.0 { (time - start_time) / span } else { 1.0 };
+                return Prop::interpolate(start_value, end_value, t);
+            }
+        }
+
+        self.keyframes[self.keyframes.len() - 1].1.clone()
+    }
 }
 
 impl<Prop> Default for AnimationState<Prop>
@@ -139,9 +413,16 @@ where
             duration: Duration::new(0, 0),
             delay: 0.0,
             keyframes: Vec::new(),
+            timing_function: TimingFunction::default(),
+            iterations: 1.0,
+            direction: AnimationDirection::default(),
+            fill_mode: AnimationFillMode::default(),
             output: None,
             persistent: true,
             t0: 0.0,
+            progress: std::f32::NEG_INFINITY,
+            seek_to: None,
+            paused: false,
             t: 0.0,
             active: false,
             entities: HashSet::new(),
@@ -151,3 +432,147 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StepPosition;
+
+    fn animation() -> AnimationState<f32> {
+        AnimationState::new(Animation::null())
+            .with_duration(Duration::from_secs(1))
+            .with_keyframe((0.0, 0.0))
+            .with_keyframe((1.0, 100.0))
+            .with_keyframe((0.5, 20.0))
+    }
+
+    #[test]
+    fn keyframes_are_interpolated_in_order() {
+        let animation = animation();
+
+        assert_eq!(animation.value_at(0.25), 10.0);
+        assert_eq!(animation.value_at(0.75), 60.0);
+    }
+
+    #[test]
+    fn alternate_direction_reverses_odd_iterations() {
+        let mut animation = animation();
+        animation.iterations = std::f32::INFINITY;
+        animation.direction = AnimationDirection::Alternate;
+
+        let start = animation.start_time;
+        animation.tick(start + Duration::from_millis(1750));
+        assert_eq!(animation.output, Some(10.0));
+        assert!(animation.t < 1.0);
+    }
+
+    #[test]
+    fn animation_finishes_after_iterations() {
+        let mut animation = animation();
+        animation.iterations = 2.0;
+
+        let start = animation.start_time;
+        animation.tick(start + Duration::from_millis(1500));
+        assert_eq!(animation.output, Some(20.0));
+
+        animation.tick(start + Duration::from_secs(3));
+        assert_eq!(animation.t, 1.0);
+        assert_eq!(animation.output, Some(100.0));
+        assert!(!animation.active);
+    }
+
+    #[test]
+    fn fill_mode_applies_first_value_during_delay() {
+        let mut animation = animation();
+        animation.set_delay(Duration::from_millis(500));
+
+        let start = animation.start_time;
+        animation.tick(start + Duration::from_millis(250));
+        assert_eq!(animation.output, None);
+
+        animation.set_fill_mode(AnimationFillMode::Backwards);
+        animation.tick(start + Duration::from_millis(250));
+        assert_eq!(animation.output, Some(0.0));
+        assert!(!animation.persistent);
+
+        animation.set_fill_mode(AnimationFillMode::Both);
+        assert!(animation.persistent);
+    }
+
+    #[test]
+    fn description_with_negative_delay_starts_part_way() {
+        let mut animation = animation();
+        let mut description = AnimationDescription::new();
+        description.duration = 1.0;
+        description.delay = -0.25;
+        description.timing_function = TimingFunction::Linear;
+        animation.set_description(&description);
+        animation.play(Entity::root());
+
+        let start = Instant::now();
+        animation.tick(start);
+        assert_eq!(animation.output, Some(10.0));
+
+        // A description without a duration has no delay and finishes immediately
+        description.duration = 0.0;
+        animation.set_description(&description);
+        assert_eq!(animation.delay, 0.0);
+        animation.tick(start);
+        assert_eq!(animation.output, Some(100.0));
+    }
+
+    #[test]
+    fn timing_function_is_applied_to_each_iteration() {
+        let mut animation = AnimationState::new(Animation::null())
+            .with_duration(Duration::from_secs(1))
+            .with_timing_function(TimingFunction::Steps(2, StepPosition::End))
+            .with_keyframe((0.0, 0.0))
+            .with_keyframe((1.0, 100.0));
+        animation.iterations = 2.0;
+
+        let start = animation.start_time;
+        animation.tick(start + Duration::from_millis(1400));
+        assert_eq!(animation.output, Some(0.0));
+        animation.tick(start + Duration::from_millis(1600));
+        assert_eq!(animation.output, Some(50.0));
+    }
+
+    #[test]
+    fn paused_animation_resumes_from_the_same_progress() {
+        let mut animation = animation();
+        animation.play(Entity::root());
+
+        let start = Instant::now();
+        animation.tick(start);
+        animation.tick(start + Duration::from_millis(250));
+        assert_eq!(animation.output, Some(10.0));
+
+        animation.pause();
+        animation.tick(start + Duration::from_millis(750));
+        assert_eq!(animation.output, Some(10.0));
+
+        animation.resume();
+        animation.tick(start + Duration::from_millis(1000));
+        assert_eq!(animation.output, Some(10.0));
+        animation.tick(start + Duration::from_millis(1250));
+        assert_eq!(animation.output, Some(20.0));
+    }
+
+    #[test]
+    fn reversed_animation_plays_back_to_the_start() {
+        let mut animation = animation();
+        animation.play(Entity::root());
+
+        let start = Instant::now();
+        animation.tick(start);
+        animation.tick(start + Duration::from_millis(250));
+
+        animation.reverse();
+        animation.tick(start + Duration::from_millis(250));
+        assert_eq!(animation.output, Some(10.0));
+
+        animation.tick(start + Duration::from_millis(500));
+        assert_eq!(animation.output, Some(0.0));
+        assert_eq!(animation.t, 1.0);
+    }
+}
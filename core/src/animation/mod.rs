@@ -3,13 +3,13 @@
 //! Nearly every style property can be animated.
 //!
 //! # Creating Anaimtions
-//! To create an animation, call `state.create_animation(duration)`, where the animation `duration` is a `std::time::Duration` type.
+//! To create an animation, call `cx.create_animation(duration)`, where the animation `duration` is a `std::time::Duration` type.
 //! This function returns an [AnimationBuilder], which can be used to construct the animation of several properties at once.
 // !
 //! ## Example
 //! The following code creates an animation which will animate the `background-color` property from red to blue over 1 second:
-//! ```compile_fail
-//! let animation = state.create_animation(std::time::Duration::from_secs(1))
+//! ```ignore
+//! let animation = cx.create_animation(std::time::Duration::from_secs(1))
 //!     .add_keyframe(0.0, |keyframe| keyframe.set_background_color(Color::red()))
 //!     .add_keyframe(1.0, |keyframe| keyframe.set_background_color(Color::blue()))
 //!     .build();
@@ -20,8 +20,8 @@
 //! For example, the above animation can be modified to animate both background color and border color simultaneously over the 1 second duration:
 //!
 //! ## Example
-//! ```compile_fail
-//! let animation_id = state.create_animation(std::time::Duration::from_secs(1))
+//! ```ignore
+//! let animation_id = cx.create_animation(std::time::Duration::from_secs(1))
 //!     .add_keyframe(0.0, |keyframe|
 //!         keyframe
 //!             .set_background_color(Color::red())
@@ -39,14 +39,36 @@
 //! Animations are linked to entities and controlled using methods from the [AnimExt] trait. For example,
 //! the following code links an entity to an animation. This causes the animation to become active and play until completion.
 //! An animation can be played on muliple entities by calling `.play_animation()` on each of the entities.
-//! ```compile_fail
-//! entity.play_animation(animation_id);
+//! ```ignore
+//! entity.play_animation(cx, animation_id);
+//! ```
+//! A playing animation can also be paused, resumed, moved to a particular time, reversed, or cancelled with the other methods of [AnimExt].
+//!
+//! # Animation Events
+//! As an animation plays, an [AnimationEvent] is sent to the entity when the animation starts, when each new iteration starts, and when the
+//! animation ends. For example, a view can be removed once its fade out animation has finished:
+//! ```ignore
+//! if let Some(AnimationEvent::AnimationEnded(animation)) = event.message.downcast() {
+//!     if *animation == self.fade_out {
+//!         cx.remove(cx.current);
+//!     }
+//! }
 //! ```
 mod animation;
 pub use animation::Animation;
 
 mod animation_state;
 pub(crate) use animation_state::AnimationState;
+pub use animation_state::{AnimationDirection, AnimationFillMode};
+
+mod animation_event;
+pub use animation_event::AnimationEvent;
+
+mod animation_description;
+pub(crate) use animation_description::AnimationDescription;
+
+mod timing_function;
+pub use timing_function::{StepPosition, TimingFunction};
 
 mod interpolator;
 pub(crate) use interpolator::Interpolator;
@@ -54,8 +76,8 @@ pub(crate) use interpolator::Interpolator;
 mod transition;
 pub(crate) use transition::Transition;
 
-// mod animation_builder;
-// pub use animation_builder::*;
+mod animation_builder;
+pub use animation_builder::*;
 
-// mod anim_ext;
-// pub use anim_ext::AnimExt;
+mod anim_ext;
+pub use anim_ext::AnimExt;
@@ -0,0 +1,243 @@
+/// Describes how the progress of an animation changes over time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimingFunction {
+    /// Progress changes at a constant rate.
+    Linear,
+    /// Progress follows a cubic bezier curve from (0, 0) to (1, 1) with the control points (x1, y1) and (x2, y2).
+    CubicBezier(f32, f32, f32, f32),
+    /// Progress jumps between a number of equal steps.
+    Steps(u32, StepPosition),
+    /// Progress follows a damped spring with the given mass, stiffness, damping and initial velocity.
+    ///
+    /// The spring is scaled so that it comes to rest at the end of the animation.
+    Spring { mass: f32, stiffness: f32, damping: f32, velocity: f32 },
+}
+
+/// Where the jumps of a [TimingFunction::Steps] timing function occur.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepPosition {
+    /// The first jump happens at the start of the animation.
+    Start,
+    /// The last jump happens at the end of the animation.
+    End,
+}
+
+impl Default for StepPosition {
+    fn default() -> Self {
+        StepPosition::End
+    }
+}
+
+impl Default for TimingFunction {
+    fn default() -> Self {
+        TimingFunction::Linear
+    }
+}
+
+impl TimingFunction {
+    /// Equivalent to the css `ease` timing function.
+    pub const EASE: TimingFunction = TimingFunction::CubicBezier(0.25, 0.1, 0.25, 1.0);
+    /// Equivalent to the css `ease-in` timing function.
+    pub const EASE_IN: TimingFunction = TimingFunction::CubicBezier(0.42, 0.0, 1.0, 1.0);
+    /// Equivalent to the css `ease-out` timing function.
+    pub const EASE_OUT: TimingFunction = TimingFunction::CubicBezier(0.0, 0.0, 0.58, 1.0);
+    /// Equivalent to the css `ease-in-out` timing function.
+    pub const EASE_IN_OUT: TimingFunction = TimingFunction::CubicBezier(0.42, 0.0, 0.58, 1.0);
+    /// Equivalent to the css `step-start` timing function.
+    pub const STEP_START: TimingFunction = TimingFunction::Steps(1, StepPosition::Start);
+    /// Equivalent to the css `step-end` timing function.
+    pub const STEP_END: TimingFunction = TimingFunction::Steps(1, StepPosition::End);
+    /// A spring which overshoots slightly before settling.
+    pub const SPRING: TimingFunction =
+        TimingFunction::Spring { mass: 1.0, stiffness: 100.0, damping: 10.0, velocity: 0.0 };
+
+    /// Returns the progress of the animation for a given fraction of its duration between 0.0 and 1.0.
+    pub fn value(&self, t: f32) -> f32 {
+        match *self {
+            TimingFunction::Linear => t,
+            TimingFunction::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
+            TimingFunction::Steps(count, position) => steps(count, position, t),
+            TimingFunction::Spring { mass, stiffness, damping, velocity } => {
+                spring(mass, stiffness, damping, velocity, t)
+            }
+        }
+    }
+}
+
+// Returns the progress of a stepped timing function.
+fn steps(steps: u32, position: StepPosition, t: f32) -> f32 {
+    if t >= 1.0 {
+        return 1.0;
+    }
+
+    if t < 0.0 {
+        return 0.0;
+    }
+
+    let steps = steps.max(1) as f32;
+    let step = match position {
+        StepPosition::Start => (t * steps).floor() + 1.0,
+        StepPosition::End => (t * steps).floor(),
+    };
+
+    (step / steps).min(1.0)
+}
+
+// Returns the progress of a damped spring which starts at 0 and comes to rest at 1.
+fn spring(mass: f32, stiffness: f32, damping: f32, velocity: f32, t: f32) -> f32 {
+    if t <= 0.0 {
+        return 0.0;
+    }
+
+    if t >= 1.0 || mass <= 0.0 || stiffness <= 0.0 {
+        return 1.0;
+    }
+
+    // Natural frequency and damping ratio
+    let w0 = (stiffness / mass).sqrt();
+    let zeta = (damping.max(0.0) / (2.0 * (stiffness * mass).sqrt())).max(0.01);
+
+    // The rate at which the spring settles, used to scale the spring to the duration of the animation
+    let decay = if zeta < 1.0 { zeta * w0 } else { w0 * (zeta - (zeta * zeta - 1.0).sqrt()) };
+
+    // Time for the distance to the rest position to fall below 0.1%
+    let time = t * 1000f32.ln() / decay;
+
+    // Distance from the rest position, starting at 1 and moving towards it with the initial velocity
+    let distance = if zeta < 1.0 {
+        let wd = w0 * (1.0 - zeta * zeta).sqrt();
+        (-zeta * w0 * time).exp()
+            * ((wd * time).cos() + ((zeta * w0 - velocity) / wd) * (wd * time).sin())
+    } else if zeta == 1.0 {
+        (-w0 * time).exp() * (1.0 + (w0 - velocity) * time)
+    } else {
+        let root = (zeta * zeta - 1.0).sqrt();
+        let r1 = -w0 * (zeta - root);
+        let r2 = -w0 * (zeta + root);
+        let c2 = (-velocity - r1) / (r2 - r1);
+        (1.0 - c2) * (r1 * time).exp() + c2 * (r2 * time).exp()
+    };
+
+    1.0 - distance
+}
+
+// Returns the y value of a cubic bezier curve for a given x value.
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    // Value of one component of the curve at the curve parameter t
+    let bezier = |p1: f32, p2: f32, t: f32| {
+        3.0 * p1 * (1.0 - t) * (1.0 - t) * t + 3.0 * p2 * (1.0 - t) * t * t + t * t * t
+    };
+
+    // Derivative of one component of the curve at the curve parameter t
+    let slope = |p1: f32, p2: f32, t: f32| {
+        3.0 * p1 * (1.0 - t) * (1.0 - t)
+            + 6.0 * (p2 - p1) * (1.0 - t) * t
+            + 3.0 * (1.0 - p2) * t * t
+    };
+
+    // Find the curve parameter for x using Newton's method
+    let mut t = x;
+    for _ in 0..8 {
+        let error = bezier(x1, x2, t) - x;
+        if error.abs() < 1e-6 {
+            return bezier(y1, y2, t);
+        }
+
+        let dx = slope(x1, x2, t);
+        if dx.abs() < 1e-6 {
+            break;
+        }
+
+        t -= error / dx;
+    }
+
+    // Fall back to bisection if Newton's method fails to converge
+    let mut low = 0.0;
+    let mut high = 1.0;
+    t = x;
+    for _ in 0..32 {
+        let value = bezier(x1, x2, t);
+        if (value - x).abs() < 1e-6 {
+            break;
+        }
+
+        if value < x {
+            low = t;
+        } else {
+            high = t;
+        }
+
+        t = (low + high) / 2.0;
+    }
+
+    bezier(y1, y2, t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear() {
+        assert_eq!(TimingFunction::Linear.value(0.25), 0.25);
+    }
+
+    #[test]
+    fn cubic_bezier_end_points() {
+        for timing_function in [
+            TimingFunction::EASE,
+            TimingFunction::EASE_IN,
+            TimingFunction::EASE_OUT,
+            TimingFunction::EASE_IN_OUT,
+        ] {
+            assert_eq!(timing_function.value(0.0), 0.0);
+            assert_eq!(timing_function.value(1.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn steps_jump_at_start_or_end() {
+        let step_end = TimingFunction::Steps(4, StepPosition::End);
+        assert_eq!(step_end.value(0.0), 0.0);
+        assert_eq!(step_end.value(0.3), 0.25);
+        assert_eq!(step_end.value(0.99), 0.75);
+        assert_eq!(step_end.value(1.0), 1.0);
+
+        let step_start = TimingFunction::Steps(4, StepPosition::Start);
+        assert_eq!(step_start.value(0.0), 0.25);
+        assert_eq!(step_start.value(0.8), 1.0);
+    }
+
+    #[test]
+    fn spring_settles_at_end() {
+        assert_eq!(TimingFunction::SPRING.value(0.0), 0.0);
+        assert_eq!(TimingFunction::SPRING.value(1.0), 1.0);
+        // An underdamped spring overshoots before settling close to the end value
+        assert!((0..100).any(|i| TimingFunction::SPRING.value(i as f32 / 100.0) > 1.0));
+        assert!((TimingFunction::SPRING.value(0.99) - 1.0).abs() < 0.01);
+
+        // A critically damped spring doesn't overshoot
+        let critical =
+            TimingFunction::Spring { mass: 1.0, stiffness: 100.0, damping: 20.0, velocity: 0.0 };
+        assert!((0..100).all(|i| critical.value(i as f32 / 100.0) <= 1.0));
+    }
+
+    #[test]
+    fn cubic_bezier_shape() {
+        assert!((TimingFunction::EASE_IN_OUT.value(0.5) - 0.5).abs() < 1e-4);
+        assert!(TimingFunction::EASE_IN.value(0.25) < 0.25);
+        assert!(TimingFunction::EASE_OUT.value(0.25) > 0.25);
+        // A bezier with control points on the diagonal is linear
+        assert!(
+            (TimingFunction::CubicBezier(0.25, 0.25, 0.75, 0.75).value(0.3) - 0.3).abs() < 1e-4
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use std::time::Instant;
+
+use crate::{Context, Event, Propagation};
+
+/// Advances any playing animations and transitions to the given time.
+///
+/// Lifecycle events, such as [AnimationEvent::AnimationEnded](crate::AnimationEvent::AnimationEnded), are sent
+/// directly to the animated entities.
+///
+/// Returns true if any animated values changed, in which case layout and drawing are marked as needing an update.
+pub fn apply_animations(cx: &mut Context, time: Instant) -> bool {
+    let mut events = Vec::new();
+    let animating = cx.style.tick_animations(time, &mut events);
+
+    if animating {
+        cx.style.needs_relayout = true;
+        cx.style.needs_redraw = true;
+    }
+
+    for (entity, event) in events {
+        cx.event_queue.push_back(
+            Event::new(event).target(entity).origin(entity).propagate(Propagation::Direct),
+        );
+    }
+
+    animating
+}
@@ -1,15 +1,22 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "clipboard")]
 use copypasta::ClipboardContext;
 use femtovg::TextContext;
-// use fluent_bundle::{FluentBundle, FluentResource};
-// use unic_langid::LanguageIdentifier;
 
+use morphorm::{PositionType, Units};
+
+use crate::resource::StylesheetSource;
+use crate::style::{parse_stylesheet, ParsedStylesheet};
 use crate::{
-    storage::sparse_set::SparseSet, CachedData, Entity, Enviroment, Event, FontOrId, IdManager,
-    Message, ModelDataStore, Modifiers, MouseState, Propagation, ResourceManager, Style, Tree,
-    TreeExt, View, ViewHandler,
+    storage::sparse_set::SparseSet, Abilities, AnimationBuilder, CachedData, Diagnostic, Display,
+    Entity, Enviroment, Event, FontOrId, History, IdManager, ImageOrId, Message, ModelDataStore,
+    Modifiers, MouseState, PersistentState, PropSet, Propagation, ResourceManager, StoreId, Style,
+    StyleDiagnostic, StyleErrorMode, TaskHandle, TaskPool, Timer, Timers, Tree, TreeExt, View,
+    ViewHandler, WindowDescription, WindowEvent,
 };
 
 static DEFAULT_THEME: &str = include_str!("default_theme.css");
@@ -35,10 +42,30 @@ pub struct Context {
     pub hovered: Entity,
     pub focused: Entity,
 
+    /// Descriptions of the windows opened with `open_window`, keyed by the entity at the root of each window.
+    pub windows: HashMap<Entity, WindowDescription>,
+    // The entities at the root of opened windows, until their subtrees are removed
+    window_roots: HashSet<Entity>,
+    // The hidden entities which keep the place of windows opened by builders, with the window each one opened
+    window_slots: HashMap<Entity, Entity>,
+    /// The window which most recently received mouse input. The mouse cursor position is relative to this window.
+    pub active_window: Entity,
+
     pub resource_manager: ResourceManager,
+    /// The saved state of models built with `build_persistent()`.
+    pub persistent_state: PersistentState,
+    /// The changes recorded by models which can be undone and redone.
+    pub history: History,
+    /// The background tasks spawned with `spawn()` and `spawn_blocking()`.
+    pub tasks: TaskPool,
+    /// The timers added with `add_timer()`.
+    pub timers: Timers,
 
     pub text_context: TextContext,
 
+    // The problems kept when the style error mode is `StyleErrorMode::Collect`
+    diagnostics: Vec<Diagnostic>,
+
     #[cfg(feature = "clipboard")]
     pub clipboard: ClipboardContext,
 }
@@ -65,8 +92,17 @@ impl Context {
             captured: Entity::null(),
             hovered: Entity::root(),
             focused: Entity::root(),
+            windows: HashMap::new(),
+            window_roots: HashSet::new(),
+            window_slots: HashMap::new(),
+            active_window: Entity::root(),
             resource_manager: ResourceManager::new(),
+            persistent_state: PersistentState::new(),
+            history: History::new(),
+            tasks: TaskPool::new(),
+            timers: Timers::new(),
             text_context: TextContext::default(),
+            diagnostics: Vec::new(),
 
             #[cfg(feature = "clipboard")]
             clipboard: ClipboardContext::new().expect("Failed to init clipboard"),
@@ -89,6 +125,44 @@ impl Context {
             self.style.needs_redraw = true;
         }
 
+        // Focus the root if the focused entity is removed
+        if delete_list.contains(&self.focused) {
+            self.focused = Entity::root();
+        }
+
+        if delete_list.contains(&self.active_window) {
+            self.active_window = Entity::root();
+        }
+
+        self.windows.retain(|window, _| !delete_list.contains(window));
+        self.window_roots.retain(|window| !delete_list.contains(window));
+
+        // Windows are closed along with the entity which keeps their place
+        for slot in delete_list.iter() {
+            if let Some(window) = self.window_slots.remove(slot) {
+                self.windows.remove(&window);
+            }
+        }
+
+        let deleted = delete_list.iter().copied().collect::<HashSet<_>>();
+
+        // End transactions begun by removed entities, such as a slider removed while being dragged
+        self.history.remove_entities(&deleted);
+
+        // Drop the stores of removed bindings, and shared stores which no longer have any observers
+        for entry in self.data.dense.iter_mut() {
+            entry.value.lenses.retain(|store_id, store| {
+                if let StoreId::Binding(binding) = store_id {
+                    if deleted.contains(binding) {
+                        return false;
+                    }
+                }
+
+                store.remove_observers(&deleted);
+                !store.observers().is_empty()
+            });
+        }
+
         for entity in delete_list.iter().rev() {
             // Remove from observers
             for entry in self.data.dense.iter_mut() {
@@ -105,9 +179,149 @@ impl Context {
             self.data.remove(*entity);
             self.entity_manager.destroy(*entity);
             self.views.remove(entity);
+            self.tasks.cancel(*entity);
+            self.timers.remove_entity(*entity);
+        }
+    }
+
+    /// Moves keyboard focus to the given entity.
+    ///
+    /// Sends a `FocusOut` event to the previously focused entity and a `FocusIn` event to the newly
+    /// focused entity, and updates the `:focus` pseudo-class of both.
+    pub fn focus(&mut self, entity: Entity) {
+        if entity == self.focused {
+            return;
+        }
+
+        let prev_focused = self.focused;
+
+        if prev_focused != Entity::null() {
+            prev_focused.set_focus(self, false);
+            self.event_queue.push_back(
+                Event::new(WindowEvent::FocusOut)
+                    .target(prev_focused)
+                    .propagate(Propagation::Direct),
+            );
+        }
+
+        self.focused = entity;
+
+        if entity != Entity::null() {
+            entity.set_focus(self, true);
+            self.event_queue.push_back(
+                Event::new(WindowEvent::FocusIn).target(entity).propagate(Propagation::Direct),
+            );
+        }
+    }
+
+    /// Opens a new window and builds its contents with the given builder.
+    ///
+    /// The window is a subtree added to the root, so models built above the root are shared with the main window.
+    /// Returns the entity at the root of the window, which can be passed to `close_window`. The OS window is created
+    /// by the backend at the start of the next frame.
+    ///
+    /// A window opened while building a view keeps its place among the children of the view, so building the view
+    /// again returns the window it already opened rather than opening another, and removing the view closes the window.
+    pub fn open_window<F>(&mut self, window_description: WindowDescription, builder: F) -> Entity
+    where
+        F: 'static + FnOnce(&mut Context),
+    {
+        let slot = match self.built_child(self.current, self.count) {
+            Some(slot) => match self.window_slots.get(&slot) {
+                Some(window) => {
+                    self.count += 1;
+                    return *window;
+                }
+
+                // Another entity is in the place of the window, such as when opening it from an event handler
+                None => None,
+            },
+
+            None => {
+                let slot = self.entity_manager.create();
+                self.tree.add(slot, self.current).expect("Failed to add to tree");
+                self.cache.add(slot).expect("Failed to add to cache");
+                self.style.add(slot);
+                slot.set_display(self, Display::None);
+                self.count += 1;
+                Some(slot)
+            }
+        };
+
+        let entity = self.entity_manager.create();
+        self.tree.add(entity, Entity::root()).expect("Failed to add to tree");
+        self.cache.add(entity).expect("Failed to add to cache");
+        self.style.add(entity);
+
+        // Windows are laid out independently of the main window, starting from their own origin
+        entity.set_position_type(self, PositionType::SelfDirected);
+        entity.set_left(self, Units::Pixels(0.0));
+        entity.set_top(self, Units::Pixels(0.0));
+        entity.set_width(self, Units::Pixels(window_description.inner_size.width as f32));
+        entity.set_height(self, Units::Pixels(window_description.inner_size.height as f32));
+
+        // Keep keyboard focus traversal within the window
+        if let Some(abilities) = self.style.abilities.get_mut(entity) {
+            abilities.set(Abilities::FOCUS_SCOPE, true);
+        }
+
+        self.windows.insert(entity, window_description);
+        self.window_roots.insert(entity);
+        if let Some(slot) = slot {
+            self.window_slots.insert(slot, entity);
+        }
+
+        let prev = self.current;
+        let prev_count = self.count;
+        self.current = entity;
+        self.count = 0;
+
+        (builder)(self);
+
+        self.current = prev;
+        self.count = prev_count;
+
+        entity
+    }
+
+    /// Returns the child of an entity at the given position among the children added by building it.
+    ///
+    /// Views are matched to the entities they built previously by their position, which leaves out the windows
+    /// added to the root.
+    pub fn built_child(&self, entity: Entity, position: usize) -> Option<Entity> {
+        if entity == Entity::root() && !self.window_roots.is_empty() {
+            entity
+                .child_iter(&self.tree)
+                .filter(|child| !self.window_roots.contains(child))
+                .nth(position)
+        } else {
+            self.tree.get_child(entity, position)
         }
     }
 
+    /// Closes a window opened with `open_window`. The subtree of the window is removed by the backend at the start
+    /// of the next frame.
+    pub fn close_window(&mut self, window: Entity) {
+        self.windows.remove(&window);
+    }
+
+    /// Returns the root entities of the windows which have been closed but whose subtrees haven't been removed yet.
+    pub fn closed_windows(&self) -> Vec<Entity> {
+        self.window_roots
+            .iter()
+            .filter(|window| !self.windows.contains_key(window))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the root entity of the window containing the given entity, which is the root entity for the main window.
+    pub fn window_of(&self, entity: Entity) -> Entity {
+        entity
+            .parent_iter(&self.tree)
+            .find(|ancestor| self.windows.contains_key(ancestor))
+            .unwrap_or(Entity::root())
+    }
+
     /// Get stored data from the context.
     pub fn data<T: 'static>(&self) -> Option<&T> {
         // return data for the static model
@@ -144,6 +358,76 @@ impl Context {
         );
     }
 
+    /// Records a change made by the current model so that it can be undone.
+    ///
+    /// The `undo` event reverses the change and the `redo` event makes the change again. Both are sent directly
+    /// to the current entity, which should be the entity of the model, when the change is undone or redone.
+    /// Changes made while handling these events aren't recorded.
+    pub fn record_undo<M: Message + Clone>(&mut self, undo: M, redo: M) {
+        self.history.record(
+            self.current,
+            Box::new(move || Event::new(undo.clone())),
+            Box::new(move || Event::new(redo.clone())),
+        );
+    }
+
+    /// Adds a timer which sends a `TimerEvent::Tick` directly to the current entity after the given duration.
+    ///
+    /// A repeating timer ticks once per duration, of at least a millisecond, until it is removed with
+    /// `remove_timer()`. Timers are removed automatically when the current entity is removed.
+    pub fn add_timer(&mut self, duration: Duration, repeat: bool) -> Timer {
+        self.timers.add(self.current, duration, repeat)
+    }
+
+    /// Removes a timer so that it doesn't tick again.
+    pub fn remove_timer(&mut self, timer: Timer) {
+        self.timers.remove(timer);
+    }
+
+    /// Runs a future on a background thread and emits its output as an event from the current entity.
+    ///
+    /// The event is sent to the current entity and propagates up the tree, in the same way as `emit()`, so a model
+    /// or view can handle the result of the work it spawned:
+    /// ```ignore
+    /// cx.spawn(async move {
+    ///     let preset = load_preset(path).await;
+    ///     AppEvent::PresetLoaded(preset)
+    /// });
+    /// ```
+    /// The task is cancelled if the current entity is removed before it finishes, in which case the future is dropped
+    /// and no event is sent.
+    pub fn spawn<F, M>(&mut self, future: F) -> TaskHandle
+    where
+        F: 'static + Future<Output = M> + Send,
+        M: Message,
+    {
+        let entity = self.current;
+        self.tasks.spawn_future(entity, async move { Some(task_event(entity, future.await)) })
+    }
+
+    /// Runs a blocking closure on a background thread and emits its return value as an event from the current entity.
+    ///
+    /// The event is sent in the same way as `spawn()`. If the current entity is removed before the closure returns, the
+    /// closure still runs to completion but its result is dropped.
+    pub fn spawn_blocking<F, M>(&mut self, task: F) -> TaskHandle
+    where
+        F: 'static + FnOnce() -> M + Send,
+        M: Message,
+    {
+        let entity = self.current;
+        self.tasks.spawn_blocking(entity, move || Some(task_event(entity, (task)())))
+    }
+
+    /// Adds the results of any finished background tasks to the event queue.
+    pub fn poll_tasks(&mut self) {
+        self.tasks.poll(&mut self.event_queue, false);
+    }
+
+    /// Blocks until all spawned background tasks have finished and adds their results to the event queue.
+    pub fn wait_for_tasks(&mut self) {
+        self.tasks.poll(&mut self.event_queue, true);
+    }
+
     pub fn add_listener<F, W>(&mut self, listener: F)
     where
         W: View,
@@ -180,11 +464,43 @@ impl Context {
         self.resource_manager.fonts.insert(name.to_owned(), FontOrId::Font(data.to_vec()));
     }
 
+    /// Add an image from memory to the application
+    ///
+    /// The data can be any image format supported by the renderer, e.g. png or jpeg. The image is registered
+    /// with the canvas before the next draw and can then be used by name with an [Image](crate::Image) view or
+    /// the `background-image` style property.
+    pub fn load_image(&mut self, name: &str, data: &[u8]) {
+        if self.resource_manager.images.contains_key(name) {
+            self.report("images", format!("Image already exists: {}", name));
+            return;
+        }
+
+        self.resource_manager.images.insert(name.to_owned(), ImageOrId::Image(data.to_vec()));
+    }
+
     /// Sets the global default font for the application
     pub fn set_default_font(&mut self, name: &str) {
         self.style.default_font = name.to_string();
     }
 
+    /// Adds a fluent (.ftl) translation for the given locale.
+    pub fn add_translation(&mut self, locale: &str, ftl: String) {
+        self.enviroment.add_translation(locale, ftl);
+    }
+
+    /// Loads the fluent (.ftl) translations from a directory containing a sub-directory per locale.
+    pub fn add_translation_dir(&mut self, path: &str) -> Result<(), std::io::Error> {
+        self.enviroment.add_translation_dir(path)
+    }
+
+    /// Creates a new animation with the given duration, returning an [AnimationBuilder] used to add keyframes.
+    ///
+    /// The animation can then be played on an entity with the [AnimExt](crate::AnimExt) trait.
+    pub fn create_animation(&mut self, duration: std::time::Duration) -> AnimationBuilder {
+        let id = self.style.animation_manager.create();
+        AnimationBuilder::new(id, self, duration)
+    }
+
     pub fn add_theme(&mut self, theme: &str) {
         self.resource_manager.themes.push(theme.to_owned());
 
@@ -197,14 +513,184 @@ impl Context {
         self.add_theme(DEFAULT_THEME);
     }
 
+    /// Adds a stylesheet from a file path.
+    ///
+    /// Problems in the stylesheet are reported according to the style error mode, along with the path
+    /// of the file and the location of the problem.
     pub fn add_stylesheet(&mut self, path: &str) -> Result<(), std::io::Error> {
-        let style_string = std::fs::read_to_string(path.clone())?;
+        let parsed = self.load_stylesheet(path)?;
         self.resource_manager.stylesheets.push(path.to_owned());
-        self.style.parse_theme(&style_string);
+        self.style.add_rules(parsed);
 
         Ok(())
     }
 
+    /// Sets how problems found while parsing themes and stylesheets are reported.
+    ///
+    /// By default problems are printed and the offending rule or declaration is ignored. Use
+    /// `StyleErrorMode::Panic` during development so that broken themes fail loudly.
+    pub fn set_style_error_mode(&mut self, mode: StyleErrorMode) {
+        self.resource_manager.style_error_mode = mode;
+    }
+
+    /// Reports a problem according to the style error mode.
+    ///
+    /// The source describes where the problem was found, such as the path of a file.
+    pub fn report(&mut self, source: &str, message: impl Into<String>) {
+        let diagnostic = Diagnostic { source: source.to_owned(), message: message.into() };
+
+        match self.resource_manager.style_error_mode {
+            StyleErrorMode::Log => println!("Warning: {}", diagnostic),
+            StyleErrorMode::Panic => panic!("{}", diagnostic),
+            StyleErrorMode::Collect => self.diagnostics.push(diagnostic),
+            StyleErrorMode::Ignore => {}
+        }
+    }
+
+    /// Reports the problems found by parts of the application which can't report them directly, such
+    /// as the translations of the enviroment and the saved state of persistent models.
+    ///
+    /// This is called by the backends after handling events.
+    pub fn flush_diagnostics(&mut self) {
+        let mut diagnostics = self.enviroment.take_diagnostics();
+        diagnostics.append(&mut self.persistent_state.take_diagnostics());
+
+        for diagnostic in diagnostics {
+            self.report(&diagnostic.source, diagnostic.message);
+        }
+    }
+
+    /// Returns the problems which have been kept since the last call when the style error mode is
+    /// `StyleErrorMode::Collect`.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        self.flush_diagnostics();
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    /// Sets whether stylesheets added with `add_stylesheet` are reloaded when their files are modified.
+    ///
+    /// This is intended for use during development. When enabled, the application polls the
+    /// modification times of the stylesheets and reloads the styles when any of them change.
+    pub fn watch_stylesheets(&mut self, watch: bool) {
+        self.resource_manager.watch_stylesheets = watch;
+    }
+
+    /// Reloads the styles if any of the stylesheets added with `add_stylesheet` have been modified
+    /// since they were last loaded. Returns true if the styles were reloaded.
+    pub fn poll_stylesheets(&mut self) -> bool {
+        let modified = self.resource_manager.stylesheets.iter().any(|path| {
+            let last_modified = self
+                .resource_manager
+                .stylesheet_sources
+                .get(path)
+                .and_then(|source| source.modified);
+
+            modified_time(path) != last_modified
+        });
+
+        if modified {
+            self.reload_styles().expect("Failed to reload styles");
+            self.style.needs_relayout = true;
+            self.style.needs_redraw = true;
+        }
+
+        modified
+    }
+
+    /// Enables persistence, loading the state of persistent models saved to the given file by a previous run.
+    ///
+    /// Models built with `build_persistent()` after this call are restored from the loaded state, so this
+    /// should be called before the application is built. Backends provide a `persist()` method on the
+    /// application which does this for a file in the configuration directory of the user.
+    pub fn load_persistent_state(&mut self, path: impl Into<PathBuf>) {
+        self.persistent_state.load(path.into());
+    }
+
+    /// Saves the state of persistent models if persistence is enabled and any of them have changed.
+    ///
+    /// This is called by the backends when the application exits.
+    pub fn save_persistent_state(&mut self) -> bool {
+        let saved = self.persistent_state.save(&self.data);
+        self.flush_diagnostics();
+        saved
+    }
+
+    /// Saves the state of persistent models like `save_persistent_state()`, unless the state was last
+    /// saved less than `PERSISTENT_SAVE_INTERVAL` before the given time.
+    ///
+    /// This is called by the backends after handling events, so that models which change continuously,
+    /// such as while dragging a slider, aren't written to disk on every event.
+    pub fn autosave_persistent_state(&mut self, time: Instant) -> bool {
+        self.persistent_state.autosave(&self.data, time)
+    }
+
+    // Reports the problems found while parsing a theme, stylesheet or rich text markup according to the style error
+    // mode.
+    pub(crate) fn report_style_diagnostics(
+        &mut self,
+        source: &str,
+        diagnostics: &[StyleDiagnostic],
+    ) {
+        for diagnostic in diagnostics.iter() {
+            self.report(source, diagnostic.to_string());
+        }
+    }
+
+    // Reads and parses a stylesheet, returning the parsed rules.
+    //
+    // If the file cannot be read, or contains errors, then the rules from the last version of the file
+    // which parsed without errors are returned instead. A file which has never parsed without errors
+    // returns whichever of its rules could be parsed.
+    fn load_stylesheet(&mut self, path: &str) -> Result<ParsedStylesheet, std::io::Error> {
+        let modified = modified_time(path);
+
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                let last_contents =
+                    self.resource_manager.stylesheet_sources.get_mut(path).and_then(|source| {
+                        source.modified = modified;
+                        source.contents.clone()
+                    });
+
+                if let Some(last_contents) = last_contents {
+                    self.report(
+                        path,
+                        format!("Failed to read stylesheet: {}. Keeping previous styles.", error),
+                    );
+                    return Ok(parse_stylesheet(&last_contents).0);
+                }
+
+                return Err(error);
+            }
+        };
+
+        let (parsed, diagnostics) = parse_stylesheet(&contents);
+
+        self.report_style_diagnostics(path, &diagnostics);
+
+        let source = self
+            .resource_manager
+            .stylesheet_sources
+            .entry(path.to_owned())
+            .or_insert(StylesheetSource { modified, contents: None });
+
+        source.modified = modified;
+
+        if diagnostics.is_empty() {
+            source.contents = Some(contents.clone());
+        } else if let Some(last_contents) = source.contents.clone() {
+            self.report(path, "Keeping previous styles");
+            return Ok(parse_stylesheet(&last_contents).0);
+        }
+
+        Ok(parsed)
+    }
+
+    /// Reparses all of the themes and stylesheets and restyles the application.
+    ///
+    /// Stylesheets which can no longer be read, or which contain errors, keep the styles from the
+    /// last version of the file which parsed without errors.
     pub fn reload_styles(&mut self) -> Result<(), std::io::Error> {
         if self.resource_manager.themes.is_empty() && self.resource_manager.stylesheets.is_empty() {
             return Ok(());
@@ -213,28 +699,34 @@ impl Context {
         self.style.remove_rules();
 
         self.style.rules.clear();
+        self.style.keyframes.clear();
 
         self.style.remove_all();
 
-        let mut overall_theme = String::new();
+        let mut parsed = ParsedStylesheet::default();
 
         // Reload the stored themes
-        for (index, theme) in self.resource_manager.themes.iter().enumerate() {
+        for (index, theme) in self.resource_manager.themes.clone().iter().enumerate() {
             if !self.enviroment.include_default_theme && index == 0 {
                 continue;
             }
 
-            //self.style.parse_theme(theme);
-            overall_theme += theme;
+            let (mut theme_parsed, diagnostics) = parse_stylesheet(theme);
+            self.report_style_diagnostics("theme", &diagnostics);
+            parsed.append(&mut theme_parsed);
         }
 
         // Reload the stored stylesheets
-        for stylesheet in self.resource_manager.stylesheets.iter() {
-            let theme = std::fs::read_to_string(stylesheet)?;
-            overall_theme += &theme;
+        for stylesheet in self.resource_manager.stylesheets.clone().iter() {
+            match self.load_stylesheet(stylesheet) {
+                Ok(mut stylesheet_parsed) => parsed.append(&mut stylesheet_parsed),
+                Err(error) => {
+                    self.report(stylesheet, format!("Failed to read stylesheet: {}", error))
+                }
+            }
         }
 
-        self.style.parse_theme(&overall_theme);
+        self.style.add_rules(parsed);
 
         // self.enviroment.needs_rebuild = true;
 
@@ -247,3 +739,13 @@ impl Context {
         Ok(())
     }
 }
+
+// Returns the modification time of a file, or None if it cannot be read.
+fn modified_time(path: &str) -> Option<std::time::SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+// Creates the event which sends the result of a task to the entity which spawned it.
+fn task_event<M: Message>(entity: Entity, message: M) -> Event {
+    Event::new(message).target(entity).origin(entity).propagate(Propagation::Up)
+}
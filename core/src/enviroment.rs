@@ -1,7 +1,24 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use unic_langid::LanguageIdentifier;
+
+use crate::Diagnostic;
+
 pub struct Enviroment {
     // Signifies whether the app should be rebuilt.
     pub needs_rebuild: bool,
     pub include_default_theme: bool,
+    // The requested locale of the application.
+    pub locale: LanguageIdentifier,
+    // Fluent (.ftl) sources for each available locale.
+    translations: HashMap<LanguageIdentifier, Vec<String>>,
+    // Bundles for the negotiated locales, in order of preference.
+    bundles: Vec<FluentBundle<FluentResource>>,
+    // Problems found while loading and formatting translations, until they are reported by the context.
+    diagnostics: RefCell<Vec<Diagnostic>>,
 }
 
 impl Default for Enviroment {
@@ -12,41 +29,208 @@ impl Default for Enviroment {
 
 impl Enviroment {
     pub fn new() -> Self {
-        // let lang =  "en-US".parse::<LanguageIdentifier>().expect("Failed to parse locale");
-        // let resolved_locales = vec![&lang];
-        // let mut bundle = FluentBundle::new(resolved_locales.into_iter().cloned().collect());
-        // let mut file = std::fs::File::open("examples/resources/en-US/hello.ftl").expect("No File Found");
-        // let mut source: String = String::new();
-        // file.read_to_string(&mut source).expect("Failed to read ftl file");
-        // let resource = FluentResource::try_new(source).expect("Could not parse an FTL string.");
-        // bundle
-        //     .add_resource(resource)
-        //     .expect("Failed to add FTL resources to the bundle.");
         Self {
             needs_rebuild: true,
-            //bundle,
             include_default_theme: true,
+            locale: "en-US".parse().expect("Failed to parse locale"),
+            translations: HashMap::new(),
+            bundles: Vec::new(),
+            diagnostics: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Sets the locale of the application and rebuilds the application so that localized text is updated.
+    ///
+    /// If there are no translations for the requested locale then the closest available locale is used,
+    /// falling back to `en-US`.
+    pub fn set_locale(&mut self, locale: &str) {
+        let lang = match locale.parse::<LanguageIdentifier>() {
+            Ok(lang) => lang,
+            Err(_) => {
+                self.report(format!("Failed to parse locale: {}", locale));
+                return;
+            }
+        };
+
+        self.locale = lang;
+        self.update_bundles();
+        self.needs_rebuild = true;
+    }
+
+    /// Adds a fluent (.ftl) source to the translations for the given locale.
+    ///
+    /// Adding the same source more than once has no effect.
+    pub fn add_translation(&mut self, locale: &str, ftl: String) {
+        let lang = match locale.parse::<LanguageIdentifier>() {
+            Ok(lang) => lang,
+            Err(_) => {
+                self.report(format!("Failed to parse locale: {}", locale));
+                return;
+            }
+        };
+
+        let sources = self.translations.entry(lang).or_default();
+
+        if sources.contains(&ftl) {
+            return;
+        }
+
+        sources.push(ftl);
+
+        self.update_bundles();
+    }
+
+    /// Loads the fluent (.ftl) files from a directory containing a sub-directory per locale.
+    ///
+    /// For example, a directory `resources` containing `en-US/hello.ftl` and `fr/hello.ftl`.
+    pub fn add_translation_dir(&mut self, path: &str) -> Result<(), std::io::Error> {
+        for locale_dir in std::fs::read_dir(path)? {
+            let locale_dir = locale_dir?.path();
+
+            if !locale_dir.is_dir() {
+                continue;
+            }
+
+            let locale = match locale_dir.file_name().and_then(|name| name.to_str()) {
+                Some(locale) => locale.to_owned(),
+                None => continue,
+            };
+
+            for file in std::fs::read_dir(&locale_dir)? {
+                let file = file?.path();
+                if file.extension().and_then(|ext| ext.to_str()) == Some("ftl") {
+                    let ftl = std::fs::read_to_string(&file)?;
+                    self.add_translation(&locale, ftl);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the localized text for a message key, formatted with the given arguments.
+    ///
+    /// Bundles are searched in order of preference. If no bundle contains the message then the key itself is returned.
+    pub fn localize(&self, key: &str, args: Option<&FluentArgs>) -> String {
+        for bundle in self.bundles.iter() {
+            if let Some(message) = bundle.get_message(key) {
+                if let Some(pattern) = message.value() {
+                    let mut errors = Vec::new();
+                    let value = bundle.format_pattern(pattern, args, &mut errors);
+                    for error in errors {
+                        self.report(format!("Failed to format message '{}': {:?}", key, error));
+                    }
+
+                    return value.to_string();
+                }
+            }
         }
+
+        key.to_owned()
+    }
+
+    /// Returns the problems found while loading and formatting translations since the last call.
+    ///
+    /// These are reported according to the style error mode by `cx.flush_diagnostics()`.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(self.diagnostics.get_mut())
     }
 
-    // TODO
-    pub fn set_locale(&mut self, _locale: &str) {
-        // TODO
-        // let lang =  locale.parse::<LanguageIdentifier>().expect("Failed to parse locale");
-        // let resolved_locales = vec![&lang];
-        // let mut bundle = FluentBundle::new(resolved_locales.into_iter().cloned().collect());
-        // let mut file = std::fs::File::open(&format!("examples/resources/{}/hello.ftl", locale)).expect("No File Found");
-        // let mut source: String = String::new();
-        // file.read_to_string(&mut source).expect("Failed to read ftl file");
-        // let resource = FluentResource::try_new(source).expect("Could not parse an FTL string.");
-        // bundle
-        //     .add_resource(resource)
-        //     .expect("Failed to add FTL resources to the bundle.");
-        // self.bundle = bundle;
-        // self.needs_rebuild = true;
+    // Keeps a problem with the translations until it is reported by the context.
+    fn report(&self, message: String) {
+        self.diagnostics
+            .borrow_mut()
+            .push(Diagnostic { source: "translations".to_owned(), message });
+    }
+
+    // Negotiates the available locales against the requested locale and rebuilds the fluent bundles.
+    fn update_bundles(&mut self) {
+        let available = self.translations.keys().cloned().collect::<Vec<_>>();
+        let default: LanguageIdentifier = "en-US".parse().expect("Failed to parse locale");
+        let requested = vec![self.locale.clone()];
+
+        let negotiated = negotiate_languages(
+            &requested,
+            &available,
+            Some(&default),
+            NegotiationStrategy::Filtering,
+        );
+
+        self.bundles.clear();
+
+        for lang in negotiated {
+            let mut bundle = FluentBundle::new(vec![lang.clone()]);
+            // Unicode isolation marks around arguments are not rendered correctly by the text renderer
+            bundle.set_use_isolating(false);
+
+            if let Some(sources) = self.translations.get(lang) {
+                for source in sources.iter() {
+                    let resource = match FluentResource::try_new(source.clone()) {
+                        Ok(resource) => resource,
+                        Err((resource, errors)) => {
+                            self.report(format!(
+                                "Failed to parse translation for {}: {:?}",
+                                lang, errors
+                            ));
+                            resource
+                        }
+                    };
+
+                    if let Err(errors) = bundle.add_resource(resource) {
+                        self.report(format!(
+                            "Failed to add translation for {}: {:?}",
+                            lang, errors
+                        ));
+                    }
+                }
+            }
+
+            self.bundles.push(bundle);
+        }
     }
 }
 
 pub trait Env {
     fn ignore_default_styles(self) -> Self;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn localize_with_fallback() {
+        let mut env = Enviroment::new();
+        env.add_translation("en-US", "hello = Hello\ngreeting = Hello, { $name }!".to_owned());
+        env.add_translation("fr", "hello = Bonjour".to_owned());
+
+        assert_eq!(env.localize("hello", None), "Hello");
+        assert_eq!(env.localize("missing-key", None), "missing-key");
+
+        let mut args = FluentArgs::new();
+        args.set("name", "Ferris");
+        assert_eq!(env.localize("greeting", Some(&args)), "Hello, Ferris!");
+
+        // A regional locale negotiates to the base language and falls back to the default locale
+        env.set_locale("fr-FR");
+        assert_eq!(env.localize("hello", None), "Bonjour");
+        assert_eq!(env.localize("greeting", Some(&args)), "Hello, Ferris!");
+
+        assert!(env.take_diagnostics().is_empty());
+    }
+
+    #[test]
+    fn translation_errors_are_kept() {
+        let mut env = Enviroment::new();
+        env.set_locale("not a locale");
+        env.add_translation("en-US", "greeting = Hello, { $name }!".to_owned());
+        env.localize("greeting", None);
+
+        let diagnostics = env.take_diagnostics();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].message, "Failed to parse locale: not a locale");
+        assert!(diagnostics[1].message.starts_with("Failed to format message 'greeting'"));
+
+        assert!(env.take_diagnostics().is_empty());
+    }
+}
@@ -73,6 +73,9 @@ pub struct Event {
 
     pub trace: bool,
 
+    // Whether the event was sent by an undo or redo of the history
+    pub(crate) from_history: bool,
+
     // The event message
     pub message: Box<dyn Message>,
 }
@@ -100,6 +103,7 @@ impl Event {
             consumed: false,
             order: 0,
             trace: false,
+            from_history: false,
             message: Box::new(message),
         }
     }
@@ -109,6 +113,12 @@ impl Event {
         self
     }
 
+    // Marks the event as sent by the history so that the changes it makes aren't recorded again
+    pub(crate) fn from_history(mut self) -> Self {
+        self.from_history = true;
+        self
+    }
+
     /// Sets the target of the event
     pub fn target(mut self, entity: Entity) -> Self {
         self.target = entity;
@@ -11,6 +11,8 @@ pub trait ViewHandler: Any {
 
     fn event(&mut self, cx: &mut Context, event: &mut Event);
 
+    fn hit_test(&self, cx: &Context, x: f32, y: f32) -> bool;
+
     fn draw(&self, cx: &mut Context, canvas: &mut Canvas);
 }
 
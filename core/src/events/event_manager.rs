@@ -1,4 +1,6 @@
-use crate::{Context, Entity, Event, Propagation, Tree, TreeExt};
+use crate::{
+    Code, Context, Entity, Event, HistoryEvent, Modifiers, Propagation, Tree, TreeExt, WindowEvent,
+};
 
 /// Dispatches events to views.
 ///
@@ -38,6 +40,9 @@ impl EventManager {
                 println!("Event: {:?}", event);
             }
 
+            // Changes made by events sent by the history aren't recorded again
+            context.history.replaying = event.from_history;
+
             // Send events to any listeners
             let listeners =
                 context.listeners.iter().map(|(entity, _)| *entity).collect::<Vec<Entity>>();
@@ -125,6 +130,43 @@ impl EventManager {
                     }
                 }
             }
+
+            apply_history(context, event);
+        }
+
+        context.history.replaying = false;
+    }
+}
+
+// Applies history events and the undo and redo shortcuts once they have been sent to their target without being consumed.
+fn apply_history(context: &mut Context, event: &mut Event) {
+    let mut history_event = event.message.downcast::<HistoryEvent>().copied();
+
+    if let Some(WindowEvent::KeyDown(Code::KeyZ, _)) = event.message.downcast() {
+        if context.modifiers.contains(Modifiers::CTRL) {
+            if context.modifiers.contains(Modifiers::SHIFT) {
+                history_event = Some(HistoryEvent::Redo);
+            } else {
+                history_event = Some(HistoryEvent::Undo);
+            }
         }
     }
+
+    match history_event {
+        Some(HistoryEvent::Undo) => {
+            let events = context.history.undo();
+            context.event_queue.extend(events);
+        }
+
+        Some(HistoryEvent::Redo) => {
+            let events = context.history.redo();
+            context.event_queue.extend(events);
+        }
+
+        Some(HistoryEvent::BeginTransaction) => context.history.begin_transaction(event.origin),
+
+        Some(HistoryEvent::EndTransaction) => context.history.end_transaction(event.origin),
+
+        None => {}
+    }
 }
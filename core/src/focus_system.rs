@@ -0,0 +1,113 @@
+use crate::{Abilities, Context, Display, Entity, TreeExt, Visibility};
+
+/// Moves keyboard focus to the next focusable entity.
+///
+/// If the focused entity has a `next` entity set in its `FocusOrder` then focus
+/// moves to that entity, otherwise focus moves to the next focusable entity in tree order.
+/// Traversal is limited to the nearest focus scope containing the focused entity and wraps around
+/// at the end of the scope.
+pub fn focus_next(cx: &mut Context) {
+    let scope = focus_scope(cx);
+
+    let next = cx.style.focus_order.get(cx.focused).map(|focus_order| focus_order.next);
+    if let Some(next) = next {
+        if is_focusable(cx, next) && in_scope(cx, next, scope) {
+            cx.focus(next);
+            return;
+        }
+    }
+
+    let entities = scope.branch_iter(&cx.tree).collect::<Vec<_>>();
+
+    if let Some(next) = find_focusable(cx, scope, &entities) {
+        cx.focus(next);
+    }
+}
+
+/// Moves keyboard focus to the previous focusable entity.
+///
+/// If the focused entity has a `prev` entity set in its `FocusOrder` then focus
+/// moves to that entity, otherwise focus moves to the previous focusable entity in tree order.
+/// Traversal is limited to the nearest focus scope containing the focused entity and wraps around
+/// at the start of the scope.
+pub fn focus_prev(cx: &mut Context) {
+    let scope = focus_scope(cx);
+
+    let prev = cx.style.focus_order.get(cx.focused).map(|focus_order| focus_order.prev);
+    if let Some(prev) = prev {
+        if is_focusable(cx, prev) && in_scope(cx, prev, scope) {
+            cx.focus(prev);
+            return;
+        }
+    }
+
+    let entities =
+        scope.branch_iter(&cx.tree).collect::<Vec<_>>().into_iter().rev().collect::<Vec<_>>();
+
+    if let Some(prev) = find_focusable(cx, scope, &entities) {
+        cx.focus(prev);
+    }
+}
+
+// Returns the nearest displayed focus scope containing the focused entity, or the root if there isn't one.
+fn focus_scope(cx: &Context) -> Entity {
+    for entity in cx.focused.parent_iter(&cx.tree) {
+        if let Some(abilities) = cx.style.abilities.get(entity) {
+            if abilities.contains(Abilities::FOCUS_SCOPE) && is_displayed(cx, entity) {
+                return entity;
+            }
+        }
+    }
+
+    Entity::root()
+}
+
+// Returns the first focusable entity after the focused entity, wrapping around to the start of the list.
+// If the focused entity is not in the list then the first focusable entity is returned.
+fn find_focusable(cx: &Context, scope: Entity, entities: &[Entity]) -> Option<Entity> {
+    let start = entities
+        .iter()
+        .position(|entity| *entity == cx.focused)
+        .map(|index| index + 1)
+        .unwrap_or(0);
+
+    entities[start..]
+        .iter()
+        .chain(entities[..start].iter())
+        .find(|entity| {
+            **entity != cx.focused && is_focusable(cx, **entity) && in_scope(cx, **entity, scope)
+        })
+        .cloned()
+}
+
+// Returns true if the entity is within the scope. Entities in a different window to the scope are not.
+fn in_scope(cx: &Context, entity: Entity, scope: Entity) -> bool {
+    (entity == scope || entity.is_descendant_of(&cx.tree, scope))
+        && cx.window_of(entity) == cx.window_of(scope)
+}
+
+fn is_displayed(cx: &Context, entity: Entity) -> bool {
+    cx.cache.get_display(entity) != Display::None
+        && cx.cache.get_visibility(entity) != Visibility::Invisible
+}
+
+fn is_focusable(cx: &Context, entity: Entity) -> bool {
+    if entity == Entity::null() || cx.tree.get_parent(entity).is_none() {
+        return false;
+    }
+
+    if !cx
+        .style
+        .abilities
+        .get(entity)
+        .map_or(false, |abilities| abilities.contains(Abilities::FOCUSABLE))
+    {
+        return false;
+    }
+
+    if cx.style.disabled.get(entity).cloned().unwrap_or_default() {
+        return false;
+    }
+
+    is_displayed(cx, entity)
+}
@@ -4,13 +4,13 @@ use morphorm::{LayoutType, PositionType, Units};
 
 use crate::{
     style::Overflow, Abilities, BorderCornerShape, Color, Context, CursorIcon, Display, Entity,
-    PseudoClass, Res, Visibility,
+    ImageFit, PseudoClass, Res, TextOverflow, TextWrap, Visibility,
 };
 
 macro_rules! set_style {
     ($name:ident, $t:ty) => {
         pub fn $name(self, value: impl Res<$t>) -> Self {
-            self.cx.style.$name.insert(self.entity, value.get(self.cx).clone().into());
+            self.cx.style.$name.insert(self.entity, value.get(self.cx).into());
 
             // TODO - Split this out
             self.cx.style.needs_relayout = true;
@@ -58,8 +58,24 @@ impl<'a, T> Handle<'a, T> {
         self
     }
 
+    pub fn background_image(self, image_name: &str) -> Self {
+        self.cx.style.background_image.insert(self.entity, Some(image_name.to_owned()));
+
+        self.cx.style.needs_redraw = true;
+
+        self
+    }
+
+    pub fn background_fit(self, image_fit: ImageFit) -> Self {
+        self.cx.style.background_fit.insert(self.entity, image_fit);
+
+        self.cx.style.needs_redraw = true;
+
+        self
+    }
+
     pub fn checked(self, state: impl Res<bool>) -> Self {
-        let state = state.get(self.cx).clone();
+        let state = state.get(self.cx);
         if let Some(pseudo_classes) = self.cx.style.pseudo_classes.get_mut(self.entity) {
             pseudo_classes.set(PseudoClass::CHECKED, state);
         } else {
@@ -111,7 +127,7 @@ impl<'a, T> Handle<'a, T> {
     }
 
     pub fn display<U: Clone + Into<Display>>(self, value: impl Res<U>) -> Self {
-        self.cx.style.display.insert(self.entity, value.get(self.cx).clone().into());
+        self.cx.style.display.insert(self.entity, value.get(self.cx).into());
 
         self.cx.style.needs_relayout = true;
         self.cx.style.needs_redraw = true;
@@ -120,7 +136,7 @@ impl<'a, T> Handle<'a, T> {
     }
 
     pub fn visibility<U: Clone + Into<Visibility>>(self, value: impl Res<U>) -> Self {
-        self.cx.style.visibility.insert(self.entity, value.get(self.cx).clone().into());
+        self.cx.style.visibility.insert(self.entity, value.get(self.cx).into());
 
         self.cx.style.needs_redraw = true;
 
@@ -138,6 +154,25 @@ impl<'a, T> Handle<'a, T> {
         self
     }
 
+    /// Sets whether the view can receive keyboard focus with Tab and Shift+Tab.
+    pub fn focusable(self, state: bool) -> Self {
+        if let Some(abilities) = self.cx.style.abilities.get_mut(self.entity) {
+            abilities.set(Abilities::FOCUSABLE, state);
+        }
+
+        self
+    }
+
+    /// Sets whether the view is a focus scope. While a descendant of a focus scope is focused,
+    /// Tab and Shift+Tab only move focus between the focusable descendants of the scope.
+    pub fn focus_scope(self, state: bool) -> Self {
+        if let Some(abilities) = self.cx.style.abilities.get_mut(self.entity) {
+            abilities.set(Abilities::FOCUS_SCOPE, state);
+        }
+
+        self
+    }
+
     pub fn child_space(self, value: Units) -> Self {
         self.cx.style.child_left.insert(self.entity, value);
         self.cx.style.child_right.insert(self.entity, value);
@@ -252,12 +287,16 @@ impl<'a, T> Handle<'a, T> {
     set_style!(border_color, Color);
 
     set_style!(font_size, f32);
+    set_style!(text_wrap, TextWrap);
+    set_style!(text_overflow, TextOverflow);
+    set_style!(line_height, Units);
 
     //set_style!(display, Display);
     //set_style!(visibility, Visibility);
 
     set_style!(rotate, f32);
     set_style!(translate, (f32, f32));
+    set_style!(scale, f32);
 
     set_style!(border_shape_top_left, BorderCornerShape);
     set_style!(border_shape_top_right, BorderCornerShape);
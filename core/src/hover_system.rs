@@ -1,5 +1,6 @@
 use crate::{
-    Abilities, Context, Display, Entity, Event, Propagation, PseudoClass, Visibility, WindowEvent,
+    Abilities, BorderCornerShape, Context, Display, Entity, Event, Propagation, PseudoClass, Units,
+    Visibility, WindowEvent,
 };
 
 /// Determines the hovered entity based on the mouse cursor position.
@@ -11,7 +12,10 @@ pub fn apply_hover(cx: &mut Context) {
     let cursorx = cx.mouse.cursorx;
     let cursory = cx.mouse.cursory;
 
-    let mut hovered_widget = Entity::root();
+    // The cursor position is relative to the active window
+    let mut hovered_widget = cx.active_window;
+
+    let prev_current = cx.current;
 
     for entity in draw_tree.into_iter() {
         // Skip invisible widgets
@@ -29,6 +33,11 @@ pub fn apply_hover(cx: &mut Context) {
             continue;
         }
 
+        // Skip widgets in other windows
+        if cx.window_of(entity) != cx.active_window {
+            continue;
+        }
+
         // Skip non-hoverable widgets
         // if cx.cache.get_hoverable(entity) != true {
         //     continue;
@@ -40,27 +49,27 @@ pub fn apply_hover(cx: &mut Context) {
             }
         }
 
+        // The clip region is in window coordinates
+        let clip_region = cx.cache.get_clip_region(entity);
+        let in_clip_region = cursorx >= clip_region.x
+            && cursorx < (clip_region.x + clip_region.w)
+            && cursory >= clip_region.y
+            && cursory < (clip_region.y + clip_region.h);
+
+        // Map the cursor into the untransformed space of the entity
         let mut transform = cx.cache.get_transform(entity);
         transform.inverse();
 
         let (tx, ty) = transform.transform_point(cursorx, cursory);
 
-        let posx = cx.cache.get_posx(entity);
-        let posy = cx.cache.get_posy(entity);
-        let width = cx.cache.get_width(entity);
-        let height = cx.cache.get_height(entity);
+        cx.current = entity;
+        let hit = in_clip_region
+            && match cx.views.get(&entity) {
+                Some(view) => view.hit_test(cx, tx, ty),
+                None => hit_test_bounds(cx, entity, tx, ty),
+            };
 
-        let clip_region = cx.cache.get_clip_region(entity);
-
-        if tx >= posx
-            && tx >= clip_region.x
-            && tx < (posx + width)
-            && tx < (clip_region.x + clip_region.w)
-            && ty >= posy
-            && ty >= clip_region.y
-            && ty < (posy + height)
-            && ty < (clip_region.y + clip_region.h)
-        {
+        if hit {
             hovered_widget = entity;
             if cx
                 .style
@@ -102,6 +111,8 @@ pub fn apply_hover(cx: &mut Context) {
         }
     }
 
+    cx.current = prev_current;
+
     if hovered_widget != cx.hovered {
         // Useful for debugging
 
@@ -116,9 +127,14 @@ pub fn apply_hover(cx: &mut Context) {
             cx.cache.get_height(hovered_widget),
         );
 
+        // Sent from the hovered widget so that it reaches the window containing it
         let cursor = cx.style.cursor.get(hovered_widget).cloned().unwrap_or_default();
         if cx.captured == Entity::null() {
-            cx.emit(WindowEvent::SetCursor(cursor));
+            cx.event_queue.push_back(
+                Event::new(WindowEvent::SetCursor(cursor))
+                    .target(hovered_widget)
+                    .propagate(Propagation::Up),
+            );
         }
 
         // Set current hovered pseudoclass to true
@@ -139,3 +155,76 @@ pub fn apply_hover(cx: &mut Context) {
         cx.style.needs_restyle = true;
     }
 }
+
+/// Returns true if the point, in the untransformed space of the entity, is within the bounds of the entity.
+///
+/// Points within the bounds but outside of a rounded or bevelled corner are not considered to be within the entity.
+/// This is the default hit test for views.
+pub fn hit_test_bounds(cx: &Context, entity: Entity, x: f32, y: f32) -> bool {
+    let bounds = cx.cache.get_bounds(entity);
+
+    if x < bounds.x || x >= bounds.x + bounds.w || y < bounds.y || y >= bounds.y + bounds.h {
+        return false;
+    }
+
+    let max_radius = bounds.w.min(bounds.h) / 2.0;
+
+    let radius = |radius: Option<&Units>| match radius.cloned().unwrap_or_default() {
+        Units::Pixels(val) => val.min(max_radius),
+        Units::Percentage(val) => (bounds.w.min(bounds.h) * (val / 100.0)).min(max_radius),
+        _ => 0.0,
+    };
+
+    // Distances of the point from the centre of each corner, along with the corner radius and shape
+    let corners = [
+        (
+            bounds.x - x,
+            bounds.y - y,
+            radius(cx.style.border_radius_top_left.get(entity)),
+            cx.style.border_shape_top_left.get(entity).cloned().unwrap_or_default(),
+        ),
+        (
+            x - (bounds.x + bounds.w),
+            bounds.y - y,
+            radius(cx.style.border_radius_top_right.get(entity)),
+            cx.style.border_shape_top_right.get(entity).cloned().unwrap_or_default(),
+        ),
+        (
+            bounds.x - x,
+            y - (bounds.y + bounds.h),
+            radius(cx.style.border_radius_bottom_left.get(entity)),
+            cx.style.border_shape_bottom_left.get(entity).cloned().unwrap_or_default(),
+        ),
+        (
+            x - (bounds.x + bounds.w),
+            y - (bounds.y + bounds.h),
+            radius(cx.style.border_radius_bottom_right.get(entity)),
+            cx.style.border_shape_bottom_right.get(entity).cloned().unwrap_or_default(),
+        ),
+    ];
+
+    for (dx, dy, radius, shape) in corners {
+        if radius <= 0.0 {
+            continue;
+        }
+
+        let dx = dx + radius;
+        let dy = dy + radius;
+
+        // The point is only affected by the corner if it lies within the square covered by the corner
+        if dx <= 0.0 || dy <= 0.0 {
+            continue;
+        }
+
+        let outside = match shape {
+            BorderCornerShape::Round => dx * dx + dy * dy > radius * radius,
+            BorderCornerShape::Bevel => dx + dy > radius,
+        };
+
+        if outside {
+            return false;
+        }
+    }
+
+    true
+}
@@ -37,8 +37,8 @@ mod storage;
 
 mod style;
 pub use style::{
-    apply_transform, BorderCornerShape, Display, Overflow, PropSet, PseudoClass, Rule, Style,
-    Visibility,
+    apply_transform, BorderCornerShape, Diagnostic, Display, ImageFit, Overflow, PropSet,
+    PseudoClass, Rule, Style, StyleDiagnostic, StyleErrorMode, TextOverflow, TextWrap, Visibility,
 };
 
 mod animation;
@@ -56,18 +56,30 @@ pub use resource::*;
 mod window;
 pub use window::*;
 
+mod timer;
+pub use timer::*;
+
+mod tasks;
+pub use tasks::{TaskHandle, TaskPool};
+
 mod state;
 pub use state::*;
 
 mod hover_system;
-pub use hover_system::apply_hover;
+pub use hover_system::{apply_hover, hit_test_bounds};
+
+mod focus_system;
+pub use focus_system::{focus_next, focus_prev};
 
 mod style_system;
 pub use style_system::*;
 
+mod animation_system;
+pub use animation_system::apply_animations;
+
 pub use morphorm::Units::*;
 
-pub use vizia_derive::{Data, Lens};
+pub use vizia_derive::{Data, Lens, Persistent};
 
 mod view;
 pub use view::{Canvas, View};
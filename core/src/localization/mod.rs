@@ -1,15 +1,61 @@
-pub trait LocalizedStringKey<'a> {
-    fn key(&self) -> &'a str;
+use fluent_bundle::FluentArgs;
+pub use fluent_bundle::FluentValue;
+
+use crate::Enviroment;
+
+/// Text which can be resolved through the localization bundles of the [Enviroment].
+///
+/// A plain string is treated as a message key. If no translation is found for the key
+/// then the key itself is used as the text.
+pub trait LocalizedStringKey {
+    fn localize(&self, env: &Enviroment) -> String;
+}
+
+impl LocalizedStringKey for &str {
+    fn localize(&self, env: &Enviroment) -> String {
+        env.localize(self, None)
+    }
+}
+
+impl LocalizedStringKey for &String {
+    fn localize(&self, env: &Enviroment) -> String {
+        env.localize(self.as_str(), None)
+    }
 }
 
-impl<'a> LocalizedStringKey<'a> for &'a str {
-    fn key(&self) -> &'a str {
+impl LocalizedStringKey for String {
+    fn localize(&self, env: &Enviroment) -> String {
+        env.localize(self.as_str(), None)
+    }
+}
+
+/// A message key with arguments which are interpolated into the translated text.
+///
+/// # Example
+/// Given the fluent message `greeting = Hello, { $name }!`:
+/// ```compile_fail
+/// Label::new(cx, Localized::new("greeting").arg("name", "Ferris"));
+/// ```
+pub struct Localized {
+    key: String,
+    args: FluentArgs<'static>,
+}
+
+impl Localized {
+    pub fn new(key: &str) -> Self {
+        Self { key: key.to_owned(), args: FluentArgs::new() }
+    }
+
+    /// Sets the value of an argument of the message.
+    pub fn arg(mut self, name: &str, value: impl Into<FluentValue<'static>>) -> Self {
+        self.args.set(name.to_owned(), value.into());
+
         self
     }
 }
 
-impl<'a> LocalizedStringKey<'a> for &'a String {
-    fn key(&self) -> &'a str {
-        self.as_str()
+impl LocalizedStringKey for Localized {
+    fn localize(&self, env: &Enviroment) -> String {
+        env.localize(&self.key, Some(&self.args))
     }
 }
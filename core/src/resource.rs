@@ -1,6 +1,9 @@
 #![allow(dead_code)]
 
 use std::collections::HashMap;
+use std::time::SystemTime;
+
+use crate::StyleErrorMode;
 
 // pub struct Image {
 //     name: String,
@@ -9,10 +12,10 @@ use std::collections::HashMap;
 //     pub data: Vec<u8>,
 // }
 
-// pub enum ImageOrId {
-//     Image(image::DynamicImage),
-//     Id(femtovg::ImageId),
-// }
+pub enum ImageOrId {
+    Image(Vec<u8>),
+    Id(femtovg::ImageId),
+}
 
 pub enum FontOrId {
     Font(Vec<u8>),
@@ -22,13 +25,27 @@ pub enum FontOrId {
 // #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 // pub struct Resource(u32);
 
+/// The last successfully parsed contents of an external stylesheet.
+pub(crate) struct StylesheetSource {
+    // The modification time of the file when it was last read.
+    pub modified: Option<SystemTime>,
+    // The contents of the file when it was last parsed without errors.
+    pub contents: Option<String>,
+}
+
 #[derive(Default)]
 pub struct ResourceManager {
     //pub images: HashMap<String, Image>,
     pub stylesheets: Vec<String>, // Stylesheets refer to a fiel path
     pub themes: Vec<String>,      // Themes are the string content stylesheets
+    pub(crate) stylesheet_sources: HashMap<String, StylesheetSource>,
+    // Whether stylesheets should be reloaded when they are modified.
+    pub watch_stylesheets: bool,
+    // How problems found while parsing themes and stylesheets are reported.
+    pub style_error_mode: StyleErrorMode,
     //pub images: Vec<Image>,
     pub fonts: HashMap<String, FontOrId>,
+    pub images: HashMap<String, ImageOrId>,
 
     //pub image_ids: HashMap<Rc<()>, ImageOrId>,
     count: u32,
@@ -40,10 +57,14 @@ impl ResourceManager {
             //images: HashMap::new(),
             stylesheets: Vec::new(),
             themes: Vec::new(),
+            stylesheet_sources: HashMap::new(),
+            watch_stylesheets: false,
+            style_error_mode: StyleErrorMode::default(),
             //images: Vec::new(),
             //image_ids: HashMap::new(),
             count: 0,
             fonts: HashMap::new(),
+            images: HashMap::new(),
         }
     }
 
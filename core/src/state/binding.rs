@@ -4,7 +4,7 @@ use std::collections::HashSet;
 use morphorm::{LayoutType, PositionType};
 
 use crate::{
-    Color, Context, Display, Entity, Handle, StateStore, TreeExt, Units, View, Visibility,
+    Color, Context, Display, Entity, Handle, StateStore, StoreId, TreeExt, Units, View, Visibility,
 };
 
 use crate::{Data, Lens, Model};
@@ -34,7 +34,7 @@ where
 
         let binding = Self { lens, parent, count: cx.count + 1, builder: Some(Box::new(builder)) };
 
-        let id = if let Some(id) = cx.tree.get_child(cx.current, cx.count) {
+        let id = if let Some(id) = cx.built_child(cx.current, cx.count) {
             id
         } else {
             let id = cx.entity_manager.create();
@@ -46,10 +46,12 @@ where
 
         let ancestors = parent.parent_iter(&cx.tree).collect::<HashSet<_>>();
 
+        let store_id = StoreId::new(&lens, id);
+
         for entity in id.parent_iter(&cx.tree) {
             if let Some(model_data_store) = cx.data.get_mut(entity) {
                 if let Some(model_data) = model_data_store.data.get(&TypeId::of::<L::Source>()) {
-                    if let Some(lens_wrap) = model_data_store.lenses.get_mut(&TypeId::of::<L>()) {
+                    if let Some(lens_wrap) = model_data_store.lenses.get_mut(&store_id) {
                         let observers = lens_wrap.observers();
 
                         if ancestors.intersection(observers).next().is_none() {
@@ -61,11 +63,11 @@ where
 
                         let model = model_data.downcast_ref::<L::Source>().unwrap();
 
-                        let old = lens.view(model);
+                        let old = lens.view(model, |data| data.cloned());
 
                         model_data_store.lenses.insert(
-                            TypeId::of::<L>(),
-                            Box::new(StateStore { entity: id, lens, old: old.clone(), observers }),
+                            store_id,
+                            Box::new(StateStore { entity: id, lens, old, observers }),
                         );
                     }
 
@@ -116,26 +118,54 @@ impl<L: Lens> Field<L>
 where
     <L as Lens>::Source: 'static,
 {
-    pub fn get<'a>(&self, cx: &'a Context) -> &'a L::Target {
-        self.lens.view(cx.data().expect(&format!(
-            "Failed to get {:?} for entity: {:?}. Is the data in the tree?",
-            self.lens, cx.current
-        )))
+    /// Returns a copy of the bound data.
+    ///
+    /// Panics if the lens doesn't point to a value, for example an index past the end of a `Vec`.
+    /// Use `try_get()` for lenses which may not point to a value.
+    pub fn get(&self, cx: &Context) -> L::Target
+    where
+        L::Target: Clone,
+    {
+        self.try_get(cx).unwrap_or_else(|| {
+            panic!(
+                "Failed to get {:?} for entity: {:?}. The lens has no value",
+                self.lens, cx.current
+            )
+        })
+    }
+
+    /// Returns a copy of the bound data, or None if the lens doesn't point to a value.
+    pub fn try_get(&self, cx: &Context) -> Option<L::Target>
+    where
+        L::Target: Clone,
+    {
+        self.view(cx, |data| data.cloned())
+    }
+
+    /// Calls a closure with a reference to the bound data, which avoids copying it.
+    pub fn view<O>(&self, cx: &Context, map: impl FnOnce(Option<&L::Target>) -> O) -> O {
+        self.lens.view(
+            cx.data().expect(&format!(
+                "Failed to get {:?} for entity: {:?}. Is the data in the tree?",
+                self.lens, cx.current
+            )),
+            map,
+        )
     }
 }
 
 macro_rules! impl_res_simple {
     ($t:ty) => {
         impl Res<$t> for $t {
-            fn get<'a>(&'a self, _: &'a Context) -> &'a $t {
-                self
+            fn get(&self, _: &Context) -> $t {
+                *self
             }
         }
     };
 }
 
 pub trait Res<T> {
-    fn get<'a>(&'a self, cx: &'a Context) -> &'a T;
+    fn get(&self, cx: &Context) -> T;
 }
 
 impl_res_simple!(i8);
@@ -158,50 +188,51 @@ impl_res_simple!(f64);
 impl<T, L> Res<T> for Field<L>
 where
     L: Lens<Target = T>,
+    T: Clone,
 {
-    fn get<'a>(&'a self, cx: &'a Context) -> &'a T {
+    fn get(&self, cx: &Context) -> T {
         self.get(cx)
     }
 }
 
 impl Res<Color> for Color {
-    fn get<'a>(&'a self, _: &'a Context) -> &'a Color {
-        self
+    fn get(&self, _: &Context) -> Color {
+        *self
     }
 }
 
 impl Res<Units> for Units {
-    fn get<'a>(&'a self, _: &'a Context) -> &'a Units {
-        self
+    fn get(&self, _: &Context) -> Units {
+        *self
     }
 }
 
 impl Res<Visibility> for Visibility {
-    fn get<'a>(&'a self, _: &'a Context) -> &'a Visibility {
-        self
+    fn get(&self, _: &Context) -> Visibility {
+        *self
     }
 }
 
 impl Res<Display> for Display {
-    fn get<'a>(&'a self, _: &'a Context) -> &'a Display {
-        self
+    fn get(&self, _: &Context) -> Display {
+        *self
     }
 }
 
 impl Res<LayoutType> for LayoutType {
-    fn get<'a>(&'a self, _: &'a Context) -> &'a LayoutType {
-        self
+    fn get(&self, _: &Context) -> LayoutType {
+        *self
     }
 }
 
 impl Res<PositionType> for PositionType {
-    fn get<'a>(&'a self, _: &'a Context) -> &'a PositionType {
-        self
+    fn get(&self, _: &Context) -> PositionType {
+        *self
     }
 }
 
-impl<T> Res<(T, T)> for (T, T) {
-    fn get<'a>(&'a self, _: &'a Context) -> &'a (T, T) {
-        self
+impl<T: Clone> Res<(T, T)> for (T, T) {
+    fn get(&self, _: &Context) -> (T, T) {
+        self.clone()
     }
 }
@@ -0,0 +1,271 @@
+use std::any::{Any, TypeId};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use crate::{Context, Data, Display, Entity, Lens, Model, ModelDataStore, PropSet};
+
+/// The lenses a computed value depends on, which can be a single lens or a tuple of lenses on different models.
+pub trait ComputedInputs: 'static {
+    /// The values of the lensed data, which are passed to the function computing the value.
+    type Values: Data;
+
+    /// Returns the values of the lensed data, or None if any of the data can't be found from the current entity.
+    fn values(&self, cx: &Context) -> Option<Self::Values>;
+}
+
+impl<L> ComputedInputs for L
+where
+    L: Lens,
+    L::Target: Data,
+{
+    type Values = L::Target;
+
+    fn values(&self, cx: &Context) -> Option<Self::Values> {
+        cx.data::<L::Source>().and_then(|source| self.view(source, |value| value.cloned()))
+    }
+}
+
+macro_rules! impl_computed_inputs_tuple {
+    ($($input:ident: $index:tt),+) => {
+        impl<$($input: ComputedInputs),+> ComputedInputs for ($($input,)+) {
+            type Values = ($($input::Values,)+);
+
+            fn values(&self, cx: &Context) -> Option<Self::Values> {
+                Some(($(self.$index.values(cx)?,)+))
+            }
+        }
+    };
+}
+
+impl_computed_inputs_tuple!(A: 0, B: 1);
+impl_computed_inputs_tuple!(A: 0, B: 1, C: 2);
+impl_computed_inputs_tuple!(A: 0, B: 1, C: 2, D: 3);
+
+// A computed value with its inputs, the function which computes it, and the inputs it was last computed from.
+struct ComputedValue<I: ComputedInputs, T, F> {
+    // The entity made for the value within the entity which declared it, from which the inputs are found
+    entity: Entity,
+    inputs: I,
+    compute: F,
+    last_inputs: Option<I::Values>,
+    value: Option<T>,
+}
+
+trait AnyComputedValue {
+    fn entity(&self) -> Entity;
+    // Returns the new inputs and value, boxed together, if the inputs have changed since the value was last computed.
+    fn evaluate(&self, cx: &Context) -> Option<Box<dyn Any>>;
+    fn apply(&mut self, evaluated: Box<dyn Any>);
+    fn value(&self) -> Option<&dyn Any>;
+}
+
+impl<I, T, F> AnyComputedValue for ComputedValue<I, T, F>
+where
+    I: ComputedInputs,
+    T: Data,
+    F: 'static + Fn(&I::Values) -> T,
+{
+    fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    fn evaluate(&self, cx: &Context) -> Option<Box<dyn Any>> {
+        let inputs = self.inputs.values(cx)?;
+
+        if let Some(last_inputs) = &self.last_inputs {
+            if inputs.same(last_inputs) {
+                return None;
+            }
+        }
+
+        let value = (self.compute)(&inputs);
+        Some(Box::new((inputs, value)))
+    }
+
+    fn apply(&mut self, evaluated: Box<dyn Any>) {
+        if let Ok(evaluated) = evaluated.downcast::<(I::Values, T)>() {
+            let (inputs, value) = *evaluated;
+            self.last_inputs = Some(inputs);
+            self.value = Some(value);
+        }
+    }
+
+    fn value(&self) -> Option<&dyn Any> {
+        self.value.as_ref().map(|value| value as &dyn Any)
+    }
+}
+
+/// The model which stores the computed values of an application, built on the root entity.
+#[derive(Default)]
+pub struct ComputedValues {
+    // Stored in the order they were declared so that values computed from other computed values are updated after them
+    values: Vec<(u64, Box<dyn AnyComputedValue>)>,
+}
+
+impl Model for ComputedValues {}
+
+impl ComputedValues {
+    fn get(&self, id: u64) -> Option<&dyn AnyComputedValue> {
+        self.values.iter().find(|(value_id, _)| *value_id == id).map(|(_, value)| &**value)
+    }
+}
+
+/// A value computed from the data of one or more models, which is memoized and only recomputed when its inputs change.
+///
+/// A computed value is declared with a lens, or a tuple of lenses on different models, and a function which computes the
+/// value from the lensed data. The returned `Computed` is a lens which can be bound to like any other lens, and bindings
+/// to it are only rebuilt when the computed value changes:
+/// ```ignore
+/// let volume_text = Computed::new(cx, (AppData::volume, Settings::units), |(volume, units)| {
+///     format!("{} {}", volume, units)
+/// });
+///
+/// Binding::new(cx, volume_text, |cx, text| {
+///     Label::new(cx, &text.get(cx));
+/// });
+/// ```
+/// The inputs are found from the entity which declares the value. Like a `Binding`, each value is kept by a hidden
+/// entity in the place it is declared, so every declaration is a separate value, and declaring it again when the
+/// entity is rebuilt replaces the function computing it.
+pub struct Computed<T> {
+    id: u64,
+    p: PhantomData<T>,
+}
+
+impl<T: Data> Computed<T> {
+    pub fn new<I, F>(cx: &mut Context, inputs: I, compute: F) -> Self
+    where
+        I: ComputedInputs,
+        F: 'static + Fn(&I::Values) -> T,
+    {
+        let entity = if let Some(id) = cx.built_child(cx.current, cx.count) {
+            id
+        } else {
+            let id = cx.entity_manager.create();
+            cx.tree.add(id, cx.current).expect("Failed to add to tree");
+            cx.cache.add(id).expect("Failed to add to cache");
+            cx.style.add(id);
+            id
+        };
+
+        cx.count += 1;
+        entity.set_display(cx, Display::None);
+
+        let mut hasher = DefaultHasher::new();
+        entity.hash(&mut hasher);
+        let id = hasher.finish();
+
+        let mut value = ComputedValue { entity, inputs, compute, last_inputs: None, value: None };
+
+        if let Some(evaluated) = value.evaluate(cx) {
+            value.apply(evaluated);
+        }
+
+        let computed_values = computed_values(cx);
+        computed_values.values.retain(|(value_id, _)| *value_id != id);
+        computed_values.values.push((id, Box::new(value)));
+
+        Self { id, p: PhantomData::default() }
+    }
+}
+
+// Returns the computed values model on the root entity, building it if it doesn't exist.
+fn computed_values(cx: &mut Context) -> &mut ComputedValues {
+    if cx.data.get(Entity::root()).is_none() {
+        cx.data
+            .insert(Entity::root(), ModelDataStore::default())
+            .expect("Failed to add computed values");
+    }
+
+    cx.data
+        .get_mut(Entity::root())
+        .expect("Failed to get computed values")
+        .data
+        .entry(TypeId::of::<ComputedValues>())
+        .or_insert_with(|| Box::new(ComputedValues::default()))
+        .downcast::<ComputedValues>()
+        .expect("Failed to get computed values")
+}
+
+// Returns the computed values model on the root entity, if any values have been declared.
+fn root_computed_values(cx: &Context) -> Option<&ComputedValues> {
+    cx.data
+        .get(Entity::root())
+        .and_then(|store| store.data.get(&TypeId::of::<ComputedValues>()))
+        .and_then(|model| model.downcast_ref::<ComputedValues>())
+}
+
+/// Recomputes the computed values whose inputs have changed, and removes the values declared by removed entities.
+///
+/// This is called after events have been handled, before the bindings are updated.
+pub fn update_computed_values(cx: &mut Context) {
+    let ids = match root_computed_values(cx) {
+        Some(computed_values) => {
+            computed_values.values.iter().map(|(id, _)| *id).collect::<Vec<_>>()
+        }
+        None => return,
+    };
+
+    let prev = cx.current;
+
+    for id in ids {
+        let entity = match root_computed_values(cx).and_then(|values| values.get(id)) {
+            Some(value) => value.entity(),
+            None => continue,
+        };
+
+        if !cx.entity_manager.is_alive(entity) {
+            computed_values(cx).values.retain(|(value_id, _)| *value_id != id);
+            continue;
+        }
+
+        // The inputs are found from the entity which declared the value
+        cx.current = entity;
+
+        let evaluated = root_computed_values(cx)
+            .and_then(|values| values.get(id))
+            .and_then(|value| value.evaluate(cx));
+
+        if let Some(evaluated) = evaluated {
+            if let Some((_, value)) =
+                computed_values(cx).values.iter_mut().find(|(value_id, _)| *value_id == id)
+            {
+                value.apply(evaluated);
+            }
+        }
+    }
+
+    cx.current = prev;
+}
+
+impl<T> Clone for Computed<T> {
+    fn clone(&self) -> Self {
+        Self { id: self.id, p: PhantomData::default() }
+    }
+}
+
+impl<T> Copy for Computed<T> {}
+
+impl<T> Debug for Computed<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Computed({})", self.id)
+    }
+}
+
+impl<T: 'static> Lens for Computed<T> {
+    type Source = ComputedValues;
+    type Target = T;
+
+    fn view<O, F: FnOnce(Option<&Self::Target>) -> O>(&self, source: &Self::Source, map: F) -> O {
+        map(source
+            .get(self.id)
+            .and_then(|value| value.value())
+            .and_then(|value| value.downcast_ref()))
+    }
+
+    fn cache_key(&self) -> Option<u64> {
+        Some(self.id)
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+
+use crate::{Entity, Event, Propagation};
+
+/// Events which control the undo history of an application.
+///
+/// These events are handled by the event manager after they have been sent to their target, so they can be
+/// emitted from anywhere in the tree. Pressing Ctrl+Z and Ctrl+Shift+Z also sends `Undo` and `Redo` when the
+/// key press isn't consumed by the focused view, which a textbox does while it is being edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryEvent {
+    /// Undo the most recent transaction.
+    Undo,
+    /// Redo the most recently undone transaction.
+    Redo,
+    /// Start grouping recorded changes into a single transaction.
+    ///
+    /// Transactions can be nested, in which case the changes are grouped until the outermost transaction ends.
+    BeginTransaction,
+    /// Finish the current transaction.
+    EndTransaction,
+}
+
+// A change to a model, recorded as the events which undo and redo it.
+struct Change {
+    // The entity of the model which recorded the change
+    target: Entity,
+    undo: Box<dyn Fn() -> Event>,
+    redo: Box<dyn Fn() -> Event>,
+}
+
+impl Change {
+    fn undo_event(&self) -> Event {
+        (self.undo)().target(self.target).propagate(Propagation::Direct).from_history()
+    }
+
+    fn redo_event(&self) -> Event {
+        (self.redo)().target(self.target).propagate(Propagation::Direct).from_history()
+    }
+}
+
+// A group of changes which are undone and redone together.
+type Transaction = Vec<Change>;
+
+/// Records the changes made to models so that they can be undone and redone.
+///
+/// Models opt in to undo by recording a pair of events for each change they make with `cx.record_undo()`,
+/// one which reverses the change and one which makes it again:
+/// ```ignore
+/// impl Model for AppData {
+///     fn event(&mut self, cx: &mut Context, event: &mut Event) {
+///         if let Some(AppEvent::SetVolume(volume)) = event.message.downcast() {
+///             cx.record_undo(AppEvent::SetVolume(self.volume), AppEvent::SetVolume(*volume));
+///             self.volume = *volume;
+///         }
+///     }
+/// }
+/// ```
+/// Each recorded change is a separate step in the history unless it is made during a transaction, which is
+/// started and finished with [HistoryEvent::BeginTransaction] and [HistoryEvent::EndTransaction].
+#[derive(Default)]
+pub struct History {
+    undo_stack: Vec<Transaction>,
+    redo_stack: Vec<Transaction>,
+    // Changes recorded during the current transaction
+    transaction: Transaction,
+    // The entities which began the transactions which haven't ended, in the order they began
+    open: Vec<Entity>,
+    // Whether the event being handled was sent by an undo or redo, in which case changes aren't recorded
+    pub(crate) replaying: bool,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if there is a transaction which can be undone.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty() || !self.transaction.is_empty()
+    }
+
+    /// Returns true if there is a transaction which can be redone.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Removes all recorded changes.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.transaction.clear();
+        self.open.clear();
+    }
+
+    // Records a change, adding it to the current transaction if there is one.
+    pub(crate) fn record(
+        &mut self,
+        target: Entity,
+        undo: Box<dyn Fn() -> Event>,
+        redo: Box<dyn Fn() -> Event>,
+    ) {
+        if self.replaying {
+            return;
+        }
+
+        self.transaction.push(Change { target, undo, redo });
+        self.redo_stack.clear();
+
+        if self.open.is_empty() {
+            self.commit();
+        }
+    }
+
+    // Begins a transaction for the entity which sent the event.
+    pub(crate) fn begin_transaction(&mut self, origin: Entity) {
+        self.open.push(origin);
+    }
+
+    // Ends the most recent transaction begun by the entity which sent the event, or by any entity if it has
+    // none.
+    pub(crate) fn end_transaction(&mut self, origin: Entity) {
+        match self.open.iter().rposition(|entity| *entity == origin) {
+            Some(index) => {
+                self.open.remove(index);
+            }
+            None => {
+                self.open.pop();
+            }
+        }
+
+        if self.open.is_empty() {
+            self.commit();
+        }
+    }
+
+    // Ends the transactions begun by removed entities so that later changes aren't grouped into a
+    // transaction which never ends.
+    pub(crate) fn remove_entities(&mut self, entities: &HashSet<Entity>) {
+        if self.open.is_empty() {
+            return;
+        }
+
+        self.open.retain(|entity| !entities.contains(entity));
+
+        if self.open.is_empty() {
+            self.commit();
+        }
+    }
+
+    // Moves the changes of the current transaction onto the undo stack.
+    fn commit(&mut self) {
+        if !self.transaction.is_empty() {
+            self.undo_stack.push(std::mem::take(&mut self.transaction));
+        }
+    }
+
+    /// Returns the events which undo the most recent transaction, which is moved to the redo stack.
+    ///
+    /// Any unfinished transaction is finished first.
+    pub(crate) fn undo(&mut self) -> Vec<Event> {
+        self.open.clear();
+        self.commit();
+
+        if let Some(transaction) = self.undo_stack.pop() {
+            let events = transaction.iter().rev().map(Change::undo_event).collect();
+            self.redo_stack.push(transaction);
+            events
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Returns the events which redo the most recently undone transaction, which is moved back to the undo stack.
+    pub(crate) fn redo(&mut self) -> Vec<Event> {
+        if let Some(transaction) = self.redo_stack.pop() {
+            let events = transaction.iter().map(Change::redo_event).collect();
+            self.undo_stack.push(transaction);
+            events
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(history: &mut History, value: i32) {
+        history.record(
+            Entity::root(),
+            Box::new(move || Event::new(value - 1)),
+            Box::new(move || Event::new(value)),
+        );
+    }
+
+    fn values(events: Vec<Event>) -> Vec<i32> {
+        events.into_iter().map(|mut event| *event.message.downcast::<i32>().unwrap()).collect()
+    }
+
+    #[test]
+    fn transactions_are_undone_together() {
+        let mut history = History::new();
+        record(&mut history, 1);
+
+        history.begin_transaction(Entity::root());
+        record(&mut history, 2);
+        record(&mut history, 3);
+        history.end_transaction(Entity::root());
+
+        assert_eq!(values(history.undo()), vec![2, 1]);
+        assert_eq!(values(history.undo()), vec![0]);
+        assert!(!history.can_undo());
+
+        assert_eq!(values(history.redo()), vec![1]);
+        assert_eq!(values(history.redo()), vec![2, 3]);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn removed_entities_end_their_transactions() {
+        let mut history = History::new();
+        let slider = Entity::new(1, 0);
+
+        history.begin_transaction(slider);
+        record(&mut history, 1);
+        record(&mut history, 2);
+        history.remove_entities(&[slider].into_iter().collect());
+
+        // Later changes are recorded as separate steps
+        record(&mut history, 3);
+        assert_eq!(values(history.undo()), vec![2]);
+        assert_eq!(values(history.undo()), vec![1, 0]);
+    }
+
+    #[test]
+    fn recording_a_change_clears_redo() {
+        let mut history = History::new();
+        record(&mut history, 1);
+        history.undo();
+        assert!(history.can_redo());
+
+        record(&mut history, 5);
+        assert!(!history.can_redo());
+    }
+}
@@ -1,17 +1,37 @@
-use crate::Model;
 use std::any::TypeId;
+use std::collections::hash_map::DefaultHasher;
 use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
 
 /// A Lens allows the construction of a reference to a field of a struct.
 ///
 /// When deriving the `Lens` trait on a struct, the derive macro constructs a static type which implements the `Lens` trait for each field.
-/// The `view()` method takes a reference to the struct type as input and outputs a reference to the field.
+/// The `view()` method takes a reference to the struct type as input and passes a reference to the field to a closure.
 /// This provides a way to specify a binding to a specific field of some application data.
+///
+/// Some lenses don't always point to a value, such as an index past the end of a `Vec`, in which case the closure receives `None`.
 pub trait Lens: 'static + Clone + Copy + std::fmt::Debug {
-    type Source: Model;
+    type Source: 'static;
     type Target;
 
-    fn view<'a>(&self, source: &'a Self::Source) -> &'a Self::Target;
+    fn view<O, F: FnOnce(Option<&Self::Target>) -> O>(&self, source: &Self::Source, map: F) -> O;
+
+    /// Returns a key which, together with the type of the lens, identifies the data the lens points to.
+    ///
+    /// Bindings to lenses with the same type and key share a single store for change detection. By default a lens has
+    /// no key, so that each binding to it has its own store. Lenses which hold no state of their own, such as the ones
+    /// generated by `#[derive(Lens)]`, can return `Some(0)` to share a store between all of their bindings.
+    fn cache_key(&self) -> Option<u64> {
+        None
+    }
+}
+
+// Combines the cache keys of two lenses into one.
+fn combine_keys(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    (a?, b?).hash(&mut hasher);
+    Some(hasher.finish())
 }
 
 /// Helpers for constructing more complex `Lens`es.
@@ -33,24 +53,71 @@ pub trait LensExt: Lens {
         Then::new(self, other)
     }
 
-    // fn and<Other>(self, other: Other) -> And<Self, Other>
-    // where
-    //     Other: Lens + Sized,
-    //     Self: Sized,
-    // {
-    //     And::new(self, other)
-    // }
+    /// Used to construct a lens to a pair of values from the same model.
+    ///
+    /// # Example
+    /// ```compile_fail
+    /// Binding::new(cx, AppData::first_name.and(AppData::last_name), |cx, name|{
+    ///     let (first, last) = name.get(cx);
+    /// });
+    /// ```
+    fn and<Other>(self, other: Other) -> And<Self, Other>
+    where
+        Other: Lens<Source = Self::Source> + Sized,
+        Self: Sized,
+    {
+        And::new(self, other)
+    }
 
-    // TODO
-    // fn index<I: 'static>(self, index: I) -> Then<Self, Index<Self::Target, I>>
-    // where
-    //     Self: Sized,
-    //     I: Clone,
-    //     Self::Target: std::ops::Index<I> + Sized,
-    //     <<Self as Lens>::Target as std::ops::Index<I>>::Output: Sized + Clone,
-    // {
-    //     Then::new(self, Index::new(index))
-    // }
+    /// Used to construct a lens to the element of a `Vec` at an index.
+    ///
+    /// # Example
+    /// ```compile_fail
+    /// Binding::new(cx, AppData::list.index(0), |cx, item|{
+    ///     Label::new(cx, &item.get(cx).to_string());
+    /// });
+    /// ```
+    fn index<T>(self, index: usize) -> Then<Self, Index<T>>
+    where
+        Self: Lens<Target = Vec<T>> + Sized,
+        T: 'static,
+    {
+        Then::new(self, Index::new(index))
+    }
+
+    /// Used to construct a lens to a value derived from the lensed data.
+    ///
+    /// Bindings to the lens are only rebuilt when the derived value changes.
+    ///
+    /// # Example
+    /// ```compile_fail
+    /// Binding::new(cx, AppData::list.map(|list| list.len()), |cx, len|{
+    ///     Label::new(cx, &len.get(cx).to_string());
+    /// });
+    /// ```
+    fn map<G, B>(self, get: G) -> Map<Self, G>
+    where
+        G: 'static + Clone + Copy + Fn(&Self::Target) -> B,
+        Self: Sized,
+    {
+        Map::new(self, get)
+    }
+
+    /// Used to construct a lens to the value contained in an `Option`, which points to nothing when the option is `None`.
+    fn some<T>(self) -> UnwrapSome<Self>
+    where
+        Self: Lens<Target = Option<T>> + Sized,
+    {
+        UnwrapSome::new(self)
+    }
+
+    /// Used to construct a lens to the success value of a `Result`, which points to nothing when the result is an error.
+    fn ok<T, E>(self) -> UnwrapOk<Self>
+    where
+        Self: Lens<Target = Result<T, E>> + Sized,
+    {
+        UnwrapOk::new(self)
+    }
 }
 
 // Implement LensExt for all types which implement Lens
@@ -81,8 +148,15 @@ where
     type Source = A::Source;
     type Target = B::Target;
 
-    fn view<'a>(&self, data: &'a Self::Source) -> &'a Self::Target {
-        &self.b.view(&self.a.view(data))
+    fn view<O, F: FnOnce(Option<&Self::Target>) -> O>(&self, source: &Self::Source, map: F) -> O {
+        self.a.view(source, |data| match data {
+            Some(data) => self.b.view(data, map),
+            None => map(None),
+        })
+    }
+
+    fn cache_key(&self) -> Option<u64> {
+        combine_keys(self.a.cache_key(), self.b.cache_key())
     }
 }
 
@@ -92,34 +166,195 @@ impl<T: Clone, U: Clone> Clone for Then<T, U> {
     }
 }
 
-// pub struct Index<T,I> {
-//     index: I,
-//     output: PhantomData<T>,
-// }
+/// `Lens` to a pair of values from two lenses with the same source
+#[derive(Debug, Clone, Copy)]
+pub struct And<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A, B> And<A, B> {
+    pub fn new(a: A, b: B) -> Self
+    where
+        A: Lens,
+        B: Lens<Source = A::Source>,
+    {
+        Self { a, b }
+    }
+}
+
+impl<A, B> Lens for And<A, B>
+where
+    A: Lens,
+    B: Lens<Source = A::Source>,
+    A::Target: Clone,
+    B::Target: Clone,
+{
+    type Source = A::Source;
+    type Target = (A::Target, B::Target);
+
+    fn view<O, F: FnOnce(Option<&Self::Target>) -> O>(&self, source: &Self::Source, map: F) -> O {
+        self.a.view(source, |a| {
+            self.b.view(source, |b| match (a, b) {
+                (Some(a), Some(b)) => map(Some(&(a.clone(), b.clone()))),
+                _ => map(None),
+            })
+        })
+    }
+
+    fn cache_key(&self) -> Option<u64> {
+        combine_keys(self.a.cache_key(), self.b.cache_key())
+    }
+}
+
+/// `Lens` to the element of a `Vec` at an index
+pub struct Index<T> {
+    index: usize,
+    p: PhantomData<T>,
+}
+
+impl<T> Index<T> {
+    pub fn new(index: usize) -> Self {
+        Self { index, p: PhantomData::default() }
+    }
+
+    /// Returns the index of the element the lens points to.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+// Manual implementations of Clone, Copy and Debug or else the compiler requires them to be implemented on T
+impl<T> Clone for Index<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.index)
+    }
+}
+
+impl<T> Copy for Index<T> {}
+
+impl<T> Debug for Index<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Index({})", self.index)
+    }
+}
+
+impl<T: 'static> Lens for Index<T> {
+    type Source = Vec<T>;
+    type Target = T;
 
-// impl<T,I> Index<T,I> {
-//     pub fn new(index: I) -> Self {
-//         Self {
-//             index,
-//             output: PhantomData::default(),
-//         }
-//     }
-// }
+    fn view<O, F: FnOnce(Option<&Self::Target>) -> O>(&self, source: &Self::Source, map: F) -> O {
+        map(source.get(self.index))
+    }
 
-// impl<T,I> Lens for Index<T,I>
-// where
+    fn cache_key(&self) -> Option<u64> {
+        Some(self.index as u64)
+    }
+}
 
-//     T: 'static + std::ops::Index<I> + Sized,
-//     I: 'static + Clone,
-//     <T as std::ops::Index<I>>::Output: Sized + Clone,
-// {
-//     type Source = T;
-//     type Target = <T as std::ops::Index<I>>::Output;
+/// `Lens` to a value derived from some other lensed data with a closure
+pub struct Map<L, G> {
+    lens: L,
+    get: G,
+}
+
+impl<L, G> Map<L, G> {
+    pub fn new<B>(lens: L, get: G) -> Self
+    where
+        L: Lens,
+        G: Fn(&L::Target) -> B,
+    {
+        Self { lens, get }
+    }
+}
 
-//     fn view<'a>(&self, data: &'a Self::Source) -> &'a Self::Target {
-//         &data[self.index.clone()]
-//     }
-// }
+impl<L: Clone, G: Clone> Clone for Map<L, G> {
+    fn clone(&self) -> Self {
+        Self { lens: self.lens.clone(), get: self.get.clone() }
+    }
+}
+
+impl<L: Copy, G: Copy> Copy for Map<L, G> {}
+
+impl<L: Debug, G> Debug for Map<L, G> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}.map(..)", self.lens)
+    }
+}
+
+impl<L, G, B> Lens for Map<L, G>
+where
+    L: Lens,
+    G: 'static + Clone + Copy + Fn(&L::Target) -> B,
+{
+    type Source = L::Source;
+    type Target = B;
+
+    fn view<O, F: FnOnce(Option<&Self::Target>) -> O>(&self, source: &Self::Source, map: F) -> O {
+        self.lens.view(source, |data| map(data.map(|data| (self.get)(data)).as_ref()))
+    }
+
+    // The closure can capture values which aren't part of the type, so maps can't be compared
+    fn cache_key(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// `Lens` to the value contained in an `Option`
+#[derive(Debug, Clone, Copy)]
+pub struct UnwrapSome<L> {
+    lens: L,
+}
+
+impl<L> UnwrapSome<L> {
+    pub fn new(lens: L) -> Self {
+        Self { lens }
+    }
+}
+
+impl<L, T> Lens for UnwrapSome<L>
+where
+    L: Lens<Target = Option<T>>,
+{
+    type Source = L::Source;
+    type Target = T;
+
+    fn view<O, F: FnOnce(Option<&Self::Target>) -> O>(&self, source: &Self::Source, map: F) -> O {
+        self.lens.view(source, |data| map(data.and_then(|data| data.as_ref())))
+    }
+
+    fn cache_key(&self) -> Option<u64> {
+        self.lens.cache_key()
+    }
+}
+
+/// `Lens` to the success value of a `Result`
+#[derive(Debug, Clone, Copy)]
+pub struct UnwrapOk<L> {
+    lens: L,
+}
+
+impl<L> UnwrapOk<L> {
+    pub fn new(lens: L) -> Self {
+        Self { lens }
+    }
+}
+
+impl<L, T, E> Lens for UnwrapOk<L>
+where
+    L: Lens<Target = Result<T, E>>,
+{
+    type Source = L::Source;
+    type Target = T;
+
+    fn view<O, F: FnOnce(Option<&Self::Target>) -> O>(&self, source: &Self::Source, map: F) -> O {
+        self.lens.view(source, |data| map(data.and_then(|data| data.as_ref().ok())))
+    }
+
+    fn cache_key(&self) -> Option<u64> {
+        self.lens.cache_key()
+    }
+}
 
 pub struct StaticLens<T: 'static> {
     data: &'static T,
@@ -145,8 +380,12 @@ impl<T> Lens for StaticLens<T> {
     type Source = ();
     type Target = T;
 
-    fn view<'a>(&self, _source: &'a Self::Source) -> &'a Self::Target {
-        self.data
+    fn view<O, F: FnOnce(Option<&Self::Target>) -> O>(&self, _source: &Self::Source, map: F) -> O {
+        map(Some(self.data))
+    }
+
+    fn cache_key(&self) -> Option<u64> {
+        Some(self.data as *const T as usize as u64)
     }
 }
 
@@ -155,3 +394,99 @@ impl<T> StaticLens<T> {
         StaticLens { data }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Entity, StoreId};
+
+    #[derive(Debug, Clone, Copy)]
+    struct Items;
+
+    impl Lens for Items {
+        type Source = (Vec<i32>, Option<i32>);
+        type Target = Vec<i32>;
+
+        fn view<O, F: FnOnce(Option<&Self::Target>) -> O>(
+            &self,
+            source: &Self::Source,
+            map: F,
+        ) -> O {
+            map(Some(&source.0))
+        }
+
+        fn cache_key(&self) -> Option<u64> {
+            Some(0)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    struct Selected;
+
+    impl Lens for Selected {
+        type Source = (Vec<i32>, Option<i32>);
+        type Target = Option<i32>;
+
+        fn view<O, F: FnOnce(Option<&Self::Target>) -> O>(
+            &self,
+            source: &Self::Source,
+            map: F,
+        ) -> O {
+            map(Some(&source.1))
+        }
+
+        fn cache_key(&self) -> Option<u64> {
+            Some(0)
+        }
+    }
+
+    // A lens with state which isn't part of its type and which doesn't provide a cache key
+    #[derive(Debug, Clone, Copy)]
+    struct Offset(usize);
+
+    impl Lens for Offset {
+        type Source = (Vec<i32>, Option<i32>);
+        type Target = i32;
+
+        fn view<O, F: FnOnce(Option<&Self::Target>) -> O>(
+            &self,
+            source: &Self::Source,
+            map: F,
+        ) -> O {
+            map(source.0.get(self.0))
+        }
+    }
+
+    #[test]
+    fn composed_lenses_view_the_source() {
+        let source = (vec![1, 2, 3], None);
+
+        assert_eq!(Items.index(1).view(&source, |item| item.cloned()), Some(2));
+        assert_eq!(Items.index(3).view(&source, |item| item.cloned()), None);
+        assert_eq!(Items.map(|items| items.len()).view(&source, |len| len.cloned()), Some(3));
+        assert_eq!(
+            Items.index(0).and(Selected).view(&source, |pair| pair.cloned()),
+            Some((1, None))
+        );
+        assert_eq!(Selected.some().view(&source, |selected| selected.cloned()), None);
+        assert_eq!(Selected.some().view(&(vec![], Some(4)), |selected| selected.cloned()), Some(4));
+    }
+
+    #[test]
+    fn cache_keys_distinguish_indices() {
+        assert_eq!(Items.index(1).cache_key(), Items.index(1).cache_key());
+        assert_ne!(Items.index(1).cache_key(), Items.index(2).cache_key());
+        assert_eq!(Items.map(|items| items.len()).cache_key(), None);
+        assert_eq!(Items.map(|items| items.clone()).index(0).cache_key(), None);
+    }
+
+    #[test]
+    fn lenses_without_cache_keys_are_not_shared() {
+        assert_eq!(Offset(1).cache_key(), None);
+        assert_eq!(Offset(1).and(Selected).cache_key(), None);
+
+        let binding = Entity::new(1, 0);
+        assert_eq!(StoreId::new(&Offset(1), binding), StoreId::Binding(binding));
+        assert_ne!(StoreId::new(&Offset(1), binding), StoreId::new(&Offset(2), Entity::new(2, 0)));
+    }
+}
@@ -94,3 +94,15 @@ pub use data::*;
 
 mod binding;
 pub use binding::*;
+
+mod reconcile;
+pub use reconcile::*;
+
+mod computed;
+pub use computed::*;
+
+mod persistence;
+pub use persistence::*;
+
+mod history;
+pub use history::*;
@@ -3,7 +3,7 @@ use std::{
     collections::HashMap,
 };
 
-use crate::{Context, Entity, Event, LensWrap};
+use crate::{Context, Entity, Event, LensWrap, StoreId};
 
 pub trait Model: 'static + Sized {
     fn build(self, cx: &mut Context) {
@@ -107,7 +107,7 @@ impl<T: Model> ModelData for T {
 #[derive(Default)]
 pub struct ModelDataStore {
     pub data: HashMap<TypeId, Box<dyn ModelData>>,
-    pub lenses: HashMap<TypeId, Box<dyn LensWrap>>,
+    pub lenses: HashMap<StoreId, Box<dyn LensWrap>>,
 }
 
 impl Model for () {}
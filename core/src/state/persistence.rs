@@ -0,0 +1,299 @@
+use std::{
+    any::TypeId,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    storage::sparse_set::SparseSet, Context, Diagnostic, Entity, Model, ModelData, ModelDataStore,
+};
+
+/// The minimum time between saves of persistent state while the application is running.
+pub const PERSISTENT_SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A model which can be saved to disk and restored when the application restarts.
+///
+/// This trait is usually derived alongside `Serialize` and `Deserialize`. The derive uses the name of the type as the key
+/// the model is saved under, which can be changed, along with the schema version, with the `persistent` attribute:
+/// ```ignore
+/// #[derive(Lens, Data, Clone, Serialize, Deserialize, Persistent)]
+/// #[persistent(key = "preferences", version = 2)]
+/// pub struct Preferences {
+///     dark_mode: bool,
+/// }
+/// ```
+/// Persistence is enabled for an application by giving it a name, for example with `Application::persist("my-app")`.
+/// Persistent models are then built with `build_persistent()` in place of `build()`.
+pub trait Persistent: Model + Serialize + DeserializeOwned {
+    /// The key the model is saved under. Each persistent model in an application should have a different key.
+    const KEY: &'static str;
+    /// The schema version of the saved model. Saved state with a different version is dropped when it is loaded.
+    const VERSION: u32 = 0;
+
+    /// Builds the model into the tree, replacing it with any state saved by a previous run of the application.
+    fn build_persistent(self, cx: &mut Context) {
+        let model = cx.persistent_state.restore(Self::KEY, Self::VERSION).unwrap_or(self);
+
+        cx.persistent_state.register(PersistentModel {
+            entity: cx.current,
+            type_id: TypeId::of::<Self>(),
+            key: Self::KEY,
+            version: Self::VERSION,
+            save: |model| {
+                model.downcast_ref::<Self>().and_then(|model| serde_json::to_value(model).ok())
+            },
+        });
+
+        model.build(cx);
+    }
+}
+
+// A model which has been saved under a key along with its schema version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SavedModel {
+    version: u32,
+    data: serde_json::Value,
+}
+
+// A persistent model which has been built into the tree.
+pub(crate) struct PersistentModel {
+    entity: Entity,
+    type_id: TypeId,
+    key: &'static str,
+    version: u32,
+    // Serializes the model, which is passed as a `ModelData` trait object.
+    save: fn(&dyn ModelData) -> Option<serde_json::Value>,
+}
+
+/// Stores the saved state of persistent models and the file it is loaded from and saved to.
+#[derive(Default)]
+pub struct PersistentState {
+    // The file the state is saved to, or None if persistence is disabled.
+    path: Option<PathBuf>,
+    // The last saved state of each model, keyed by the model key.
+    saved: BTreeMap<String, SavedModel>,
+    // The persistent models which have been built.
+    models: Vec<PersistentModel>,
+    // Whether the models may have changed since they were last saved.
+    dirty: bool,
+    // Whether the saved state has changes which failed to be written to the file.
+    unwritten: bool,
+    // The time the models were last saved while the application was running.
+    last_save: Option<Instant>,
+    // Problems found while loading and saving the state, until they are reported by the context.
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl PersistentState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the file persistent state is saved to, if persistence is enabled.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    // Reads the saved state from a file. A missing file leaves no saved state and an unreadable file is ignored.
+    pub(crate) fn load(&mut self, path: PathBuf) {
+        self.saved.clear();
+
+        if let Ok(contents) = std::fs::read_to_string(&path) {
+            match serde_json::from_str(&contents) {
+                Ok(saved) => self.saved = saved,
+                Err(err) => {
+                    self.report(format!("Ignoring saved state in {}: {}", path.display(), err));
+                }
+            }
+        }
+
+        self.path = Some(path);
+    }
+
+    // Returns the saved state for a model if it has the same schema version and can be deserialized.
+    // Incompatible state is dropped so that it isn't restored again.
+    fn restore<T: DeserializeOwned>(&mut self, key: &str, version: u32) -> Option<T> {
+        let saved = self.saved.get(key)?;
+
+        let reason = if saved.version == version {
+            match serde_json::from_value(saved.data.clone()) {
+                Ok(model) => return Some(model),
+                Err(err) => format!("{}", err),
+            }
+        } else {
+            format!("saved with version {} but expected version {}", saved.version, version)
+        };
+
+        self.report(format!("Dropping saved state for {}: {}", key, reason));
+        self.saved.remove(key);
+
+        None
+    }
+
+    // Adds a persistent model to be saved, replacing any model previously registered with the same key.
+    fn register(&mut self, model: PersistentModel) {
+        self.models.retain(|registered| registered.key != model.key);
+        self.models.push(model);
+        self.dirty = true;
+    }
+
+    /// Marks the persistent models as possibly changed, so that they are checked on the next save.
+    ///
+    /// This is called by the backends after handling events.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Returns the problems found while loading and saving the state since the last call.
+    ///
+    /// These are reported according to the style error mode by `cx.flush_diagnostics()`.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    // Keeps a problem with the saved state until it is reported by the context.
+    fn report(&mut self, message: String) {
+        self.diagnostics.push(Diagnostic { source: "persistence".to_owned(), message });
+    }
+
+    /// Saves the state of the persistent models if they may have changed and were last saved at least
+    /// `PERSISTENT_SAVE_INTERVAL` before the given time.
+    ///
+    /// Returns true if the file was written.
+    pub(crate) fn autosave(&mut self, data: &SparseSet<ModelDataStore>, time: Instant) -> bool {
+        if !self.dirty {
+            return false;
+        }
+
+        if let Some(last_save) = self.last_save {
+            if time.saturating_duration_since(last_save) < PERSISTENT_SAVE_INTERVAL {
+                return false;
+            }
+        }
+
+        self.last_save = Some(time);
+        self.save(data)
+    }
+
+    /// Saves the state of the persistent models if they may have changed, writing the file if any of the
+    /// models have changed.
+    ///
+    /// Returns true if the file was written.
+    pub(crate) fn save(&mut self, data: &SparseSet<ModelDataStore>) -> bool {
+        if self.path.is_none() || !self.dirty {
+            return false;
+        }
+
+        self.dirty = false;
+
+        let mut changed = self.unwritten;
+
+        for persistent_model in self.models.iter() {
+            let data = data
+                .get(persistent_model.entity)
+                .and_then(|store| store.data.get(&persistent_model.type_id))
+                .and_then(|model| (persistent_model.save)(&**model));
+
+            if let Some(data) = data {
+                let saved = SavedModel { version: persistent_model.version, data };
+                if self.saved.get(persistent_model.key) != Some(&saved) {
+                    self.saved.insert(persistent_model.key.to_owned(), saved);
+                    changed = true;
+                }
+            }
+        }
+
+        if !changed {
+            return false;
+        }
+
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => return false,
+        };
+
+        let contents =
+            serde_json::to_string_pretty(&self.saved).expect("Failed to serialize state");
+
+        if let Err(err) = write_atomic(&path, &contents) {
+            self.report(format!("Failed to save state to {}: {}", path.display(), err));
+            // Try again on the next save
+            self.dirty = true;
+            self.unwritten = true;
+            return false;
+        }
+
+        self.unwritten = false;
+
+        true
+    }
+}
+
+// Writes a file by writing a temporary file next to it and renaming it over the file, so that the file is
+// never left partially written.
+fn write_atomic(path: &Path, contents: &str) -> Result<(), std::io::Error> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut temp_name = path.file_name().unwrap_or_default().to_owned();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    std::fs::write(&temp_path, contents)?;
+
+    if let Err(err) = std::fs::rename(&temp_path, path) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Returns the file used to save the persistent state of an application with the given name.
+///
+/// The file is placed in the configuration directory of the current user, or None is returned if it can't be found.
+pub fn persistence_path(app_name: &str) -> Option<PathBuf> {
+    let config_dir = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+
+    config_dir.map(|dir| dir.join(app_name).join("state.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(version: u32, data: serde_json::Value) -> PersistentState {
+        let mut state = PersistentState::new();
+        state.saved.insert("settings".to_owned(), SavedModel { version, data });
+        state
+    }
+
+    #[test]
+    fn matching_version_is_restored() {
+        let mut state = saved(1, serde_json::json!([1, 2]));
+        assert_eq!(state.restore::<(u32, u32)>("settings", 1), Some((1, 2)));
+    }
+
+    #[test]
+    fn incompatible_state_is_dropped() {
+        let mut state = saved(1, serde_json::json!([1, 2]));
+        assert_eq!(state.restore::<(u32, u32)>("settings", 2), None);
+        assert!(state.saved.is_empty());
+
+        let mut state = saved(1, serde_json::json!("not a tuple"));
+        assert_eq!(state.restore::<(u32, u32)>("settings", 1), None);
+        assert!(state.saved.is_empty());
+    }
+}
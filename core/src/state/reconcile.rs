@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{Context, Data, Entity};
+
+// The entity built for an item of a collection, along with the index and value of the item it was built for.
+struct BuiltItem<T> {
+    entity: Entity,
+    index: usize,
+    data: T,
+}
+
+/// Builds a child for each item of a collection, reusing the entities built for the items the last time.
+///
+/// Each item has a key which is used to match it to the entity built for an item with the same key the last time the
+/// children were built. Matched entities are moved into place and keep their style, cache and view. New entities are
+/// only built for new items and only removed for items which no longer exist. Using the index of an item as its key
+/// matches the children by position.
+///
+/// The views of an item are built with its index, e.g. with a lens to that index, so a matched entity is rebuilt in
+/// place if its item has changed or has moved to a different index. Removing an item near the start of a collection
+/// therefore rebuilds the views of the items after it, although their entities are kept.
+///
+/// A `Reconciler` is used by `List` and `Table`, and can be stored in a view to reconcile the children built by a
+/// `Binding` to a collection:
+/// ```ignore
+/// Binding::new(cx, AppData::todos, |cx, todos| {
+///     let entity = cx.current;
+///     if let Some(mut view) = cx.views.remove(&entity) {
+///         if let Some(todo_list) = view.downcast_mut::<TodoList>() {
+///             let items = todos.get(cx).into_iter().map(|todo| (todo.id, todo));
+///             todo_list.reconciler.reconcile(cx, items, |cx, index| {
+///                 TodoItem::new(cx, AppData::todos.index(index));
+///             });
+///         }
+///         cx.views.insert(entity, view);
+///     }
+/// });
+/// ```
+pub struct Reconciler<K, T> {
+    // The items built the last time the children were reconciled, in order
+    items: Vec<(K, BuiltItem<T>)>,
+}
+
+impl<K, T> Default for Reconciler<K, T> {
+    fn default() -> Self {
+        Self { items: Vec::new() }
+    }
+}
+
+impl<K, T> Reconciler<K, T>
+where
+    K: Hash + Eq,
+    T: Data,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the entities built for the items, in the order of the items.
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.items.iter().map(|(_, item)| item.entity)
+    }
+
+    /// Builds the children of the current entity for the given keys and items, starting at the current child count.
+    ///
+    /// The `build` closure is called with the index of an item to build a single view for it. It is only called for new
+    /// items and for items which have changed or moved to a different index.
+    pub fn reconcile<I, F>(&mut self, cx: &mut Context, items: I, mut build: F)
+    where
+        I: IntoIterator<Item = (K, T)>,
+        F: FnMut(&mut Context, usize),
+    {
+        let parent = cx.current;
+        let start = cx.count;
+
+        // Entities built for items with a duplicate key can't be matched, so they are removed
+        let mut previous = HashMap::new();
+        let mut stale = Vec::new();
+        for (key, item) in self.items.drain(..) {
+            if let Some(duplicate) = previous.insert(key, item) {
+                stale.push(duplicate.entity);
+            }
+        }
+
+        // The child at the current position, the last child and the number of children are kept up to date as
+        // children are moved and built, so that the list of children is only walked once
+        let mut children = Children {
+            next: cx.tree.get_child(parent, start),
+            last: cx.tree.get_last_child(parent),
+            len: cx.tree.get_num_children(parent).unwrap_or_default() as usize,
+        };
+
+        let mut position = start;
+
+        for (index, (key, data)) in items.into_iter().enumerate() {
+            let entity = if let Some(item) = previous.remove(&key) {
+                children.move_to_next(cx, item.entity);
+
+                if item.index != index || !item.data.same(&data) {
+                    cx.count = position;
+                    (build)(cx, index);
+                }
+
+                Some(item.entity)
+            } else {
+                // Build the view for a new item after the existing children and then move it into place
+                cx.count = children.len;
+                (build)(cx, index);
+
+                let built = match children.last {
+                    Some(last) => cx.tree.get_next_sibling(last),
+                    None => cx.tree.get_first_child(parent),
+                };
+
+                if let Some(entity) = built {
+                    children.len += 1;
+                    children.last = Some(entity);
+                    children.move_to_next(cx, entity);
+                }
+
+                built
+            };
+
+            if let Some(entity) = entity {
+                self.items.push((key, BuiltItem { entity, index, data }));
+                position += 1;
+            }
+        }
+
+        for entity in previous.into_values().map(|item| item.entity).chain(stale) {
+            cx.remove(entity);
+        }
+
+        cx.count = position;
+    }
+}
+
+// The children of the parent being reconciled.
+struct Children {
+    // The child at the position of the next item, or None if the item is at the end
+    next: Option<Entity>,
+    last: Option<Entity>,
+    len: usize,
+}
+
+impl Children {
+    // Moves a child which is at or after the position of the next item to that position.
+    fn move_to_next(&mut self, cx: &mut Context, entity: Entity) {
+        match self.next {
+            Some(next) if next == entity => {
+                self.next = cx.tree.get_next_sibling(entity);
+            }
+
+            Some(next) => {
+                if self.last == Some(entity) {
+                    self.last = cx.tree.get_prev_sibling(entity);
+                }
+
+                cx.tree.set_prev_sibling(next, entity).expect("Failed to move item");
+
+                cx.style.needs_restyle = true;
+                cx.style.needs_relayout = true;
+                cx.style.needs_redraw = true;
+            }
+
+            None => {}
+        }
+    }
+}
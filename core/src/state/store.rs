@@ -1,11 +1,32 @@
+use std::any::TypeId;
 use std::collections::HashSet;
 
 use crate::{Data, Entity, Lens, ModelData};
 
+/// Identifies the store used by a binding to detect changes to the data it is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StoreId {
+    /// A store shared by the bindings to lenses with the same type and cache key.
+    Lens(TypeId, u64),
+    /// A store used only by the binding with the given entity, for lenses without a cache key.
+    Binding(Entity),
+}
+
+impl StoreId {
+    /// Returns the id of the store for a binding with the given entity to a lens.
+    pub fn new<L: Lens>(lens: &L, binding: Entity) -> Self {
+        match lens.cache_key() {
+            Some(key) => StoreId::Lens(TypeId::of::<L>(), key),
+            None => StoreId::Binding(binding),
+        }
+    }
+}
+
 pub trait LensWrap {
     fn update(&mut self, model: &Box<dyn ModelData>) -> bool;
     fn observers(&self) -> &HashSet<Entity>;
     fn add_observer(&mut self, observer: Entity);
+    fn remove_observers(&mut self, observers: &HashSet<Entity>);
     fn entity(&self) -> Entity;
 }
 
@@ -13,7 +34,8 @@ pub struct StateStore<L: Lens, T> {
     // The entity which declared the binding
     pub entity: Entity,
     pub lens: L,
-    pub old: T,
+    // The last value of the lensed data, or None if the lens didn't point to a value
+    pub old: Option<T>,
     pub observers: HashSet<Entity>,
 }
 
@@ -28,11 +50,20 @@ where
 
     fn update(&mut self, model: &Box<dyn ModelData>) -> bool {
         if let Some(data) = model.downcast_ref::<L::Source>() {
-            let state = self.lens.view(data);
-            if !state.same(&self.old) {
-                self.old = state.clone();
-                return true;
-            }
+            let old = &mut self.old;
+            return self.lens.view(data, |state| {
+                let changed = match (state, old.as_ref()) {
+                    (Some(state), Some(old)) => !state.same(old),
+                    (None, None) => false,
+                    _ => true,
+                };
+
+                if changed {
+                    *old = state.cloned();
+                }
+
+                changed
+            });
         }
 
         false
@@ -45,4 +76,8 @@ where
     fn add_observer(&mut self, observer: Entity) {
         self.observers.insert(observer);
     }
+
+    fn remove_observers(&mut self, observers: &HashSet<Entity>) {
+        self.observers.retain(|observer| !observers.contains(observer));
+    }
 }
@@ -1,4 +1,6 @@
-use crate::{Animation, AnimationState, Entity, GenerationalId, Interpolator, Rule};
+use crate::{
+    Animation, AnimationEvent, AnimationState, Entity, GenerationalId, Interpolator, Rule,
+};
 
 use super::sparse_set::{DenseIndex, SparseSet, SparseSetGeneric};
 
@@ -160,6 +162,9 @@ where
     pub fn remove(&mut self, entity: Entity) -> Option<T> {
         let entity_index = entity.index();
 
+        // Stop any animation playing on the entity so that it isn't applied to a new entity with the same index
+        self.detach_animation(entity);
+
         if entity_index < self.inline_data.sparse.len() {
             let data_index = self.inline_data.sparse[entity_index].data_index;
             if data_index.is_inline() {
@@ -268,7 +273,18 @@ where
         self.animations.insert(animation, animation_description).unwrap();
     }
 
+    /// Removes an animation, stopping it on any entities it is playing on.
     pub fn remove_animation(&mut self, animation: Animation) -> Option<AnimationState<T>> {
+        for state in self.active_animations.iter_mut() {
+            if state.id == animation {
+                for entity in state.entities.drain() {
+                    self.inline_data.sparse[entity.index()].anim_index = std::u32::MAX;
+                }
+            }
+        }
+
+        self.remove_innactive_animations();
+
         self.animations.remove(animation)
     }
 
@@ -292,121 +308,149 @@ where
         }
     }
 
+    /// Adds a keyframe to an animation, creating the animation if it doesn't exist.
+    pub fn insert_keyframe(&mut self, animation: Animation, time: f32, value: T) {
+        if let Some(anim_state) = self.animations.get_mut(animation) {
+            anim_state.add_keyframe((time, value));
+        } else {
+            self.insert_animation(
+                animation,
+                AnimationState::new(animation).with_keyframe((time, value)),
+            );
+        }
+    }
+
+    /// Plays an animation on an entity, replacing any animation already playing on the entity.
     pub fn play_animation(&mut self, entity: Entity, animation: Animation) {
         let entity_index = entity.index();
 
-        if !self.animations.contains(animation) {
-            return;
-        }
+        // Safe to unwrap keyframes once checked that the animation exists with keyframes
+        let mut anim_state = match self.animations.get(animation) {
+            Some(anim_state) if !anim_state.keyframes.is_empty() => anim_state.clone(),
+            _ => return,
+        };
 
         // If there is no inline or shared data for the entity then add the entity as animation only
         if entity_index >= self.inline_data.sparse.len() {
             self.inline_data.sparse.resize(entity_index + 1, InlineIndex::null());
         }
 
+        self.detach_animation(entity);
+
+        anim_state.output = anim_state.initial_output();
+        anim_state.play(entity);
+        self.inline_data.sparse[entity_index].anim_index = self.active_animations.len() as u32;
+        self.active_animations.push(anim_state);
+    }
+
+    /// Stops an animation playing on an entity. The animated property returns to its inline or shared value.
+    pub fn stop_animation(&mut self, entity: Entity, animation: Animation) {
+        let entity_index = entity.index();
         if entity_index < self.inline_data.sparse.len() {
-            let active_anim_index = self.inline_data.sparse[entity_index].anim_index as usize;
-            if active_anim_index < self.active_animations.len() {
-                let anim_state = &mut self.active_animations[active_anim_index];
-                if anim_state.id == animation {
-                    anim_state.t0 = 0.0;
-                    anim_state.active = true;
-                    anim_state.t = 0.0;
-                    anim_state.start_time = std::time::Instant::now();
-                    anim_state.output = Some(
-                        self.animations
-                            .get(animation)
-                            .cloned()
-                            .unwrap()
-                            .keyframes
-                            .first()
-                            .unwrap()
-                            .1
-                            .clone(),
-                    );
-                } else {
-                    anim_state.output = Some(
-                        self.animations
-                            .get(animation)
-                            .cloned()
-                            .unwrap()
-                            .keyframes
-                            .first()
-                            .unwrap()
-                            .1
-                            .clone(),
-                    );
-                    anim_state.entities.remove(&entity);
+            let anim_index = self.inline_data.sparse[entity_index].anim_index as usize;
+            if anim_index < self.active_animations.len()
+                && self.active_animations[anim_index].id == animation
+            {
+                self.detach_animation(entity);
+            }
+        }
+    }
+
+    // Unlinks an entity from any animation playing on it. Active animations without any entities
+    // are removed on the next tick.
+    fn detach_animation(&mut self, entity: Entity) {
+        let entity_index = entity.index();
+        if entity_index < self.inline_data.sparse.len() {
+            let anim_index = self.inline_data.sparse[entity_index].anim_index as usize;
+            if anim_index < self.active_animations.len() {
+                self.active_animations[anim_index].entities.remove(&entity);
+            }
+
+            self.inline_data.sparse[entity_index].anim_index = std::u32::MAX;
+        }
+    }
+
+    /// Returns the active animation with the given id playing on an entity.
+    pub fn get_active_animation_mut(
+        &mut self,
+        entity: Entity,
+        animation: Animation,
+    ) -> Option<&mut AnimationState<T>> {
+        let entity_index = entity.index();
+        if entity_index < self.inline_data.sparse.len() {
+            let anim_index = self.inline_data.sparse[entity_index].anim_index as usize;
+            if let Some(state) = self.active_animations.get_mut(anim_index) {
+                if state.id == animation {
+                    return Some(state);
                 }
-                //println!("Already playing: {:?}", anim_state);
-                //anim_state.play(entity);
             }
-            //else {
-            // Safe to unwrap because already checked that the animation exists
-            let mut anim_state = self.animations.get(animation).cloned().unwrap();
-            //println!("Start playing: {} {:?}", animation, anim_state);
-            anim_state.output = Some(
-                self.animations
-                    .get(animation)
-                    .cloned()
-                    .unwrap()
-                    .keyframes
-                    .first()
-                    .unwrap()
-                    .1
-                    .clone(),
-            );
-            anim_state.play(entity);
-            self.inline_data.sparse[entity_index].anim_index = self.active_animations.len() as u32;
-            self.active_animations.push(anim_state);
-            //}
         }
+
+        None
     }
 
-    pub fn tick(&mut self, time: std::time::Instant) {
+    /// Updates the output of the active animations for the given time and removes any which have finished.
+    ///
+    /// Any lifecycle events caused by the update are added to `events` along with the animated entity,
+    /// skipping events which are already in the list.
+    ///
+    /// Returns true if any animated values changed.
+    pub fn tick(
+        &mut self,
+        time: std::time::Instant,
+        events: &mut Vec<(Entity, AnimationEvent)>,
+    ) -> bool {
+        let mut changed = false;
+
         for state in self.active_animations.iter_mut() {
-            // If the animation is already finished then return false
-            if state.t0 == 1.0 {
+            // If the animation is already finished or is paused then skip it
+            if state.t0 == 1.0 || (state.paused && state.seek_to.is_none()) {
                 continue;
             }
 
-            let start = state.keyframes.first().unwrap();
-            let end = state.keyframes.last().unwrap();
+            let previous = state.progress;
 
-            if start.1 == end.1 {
-                state.t0 = 1.0;
-                state.output = Some(end.1.clone());
+            state.tick(time);
+
+            changed = true;
+
+            // Transitions don't send events
+            if state.from_rule != std::usize::MAX {
                 continue;
             }
 
-            let elapsed_time = time.duration_since(state.start_time);
+            let progress = state.progress;
+            let mut state_events = Vec::new();
 
-            // Store previous time state
-            state.t0 = state.t;
+            if previous < 0.0 && progress >= 0.0 {
+                state_events.push(AnimationEvent::AnimationStarted(state.id));
+            }
 
-            // Update time state
-            state.t = (elapsed_time.as_secs_f32() / state.duration.as_secs_f32()) - state.delay;
+            if previous >= 0.0 && progress < state.iterations && progress.floor() > previous.floor()
+            {
+                state_events.push(AnimationEvent::AnimationIteration(state.id));
+            }
 
-            if state.t >= 1.0 {
-                //Animation is finished
-                state.output = Some(T::interpolate(&start.1, &end.1, 1.0));
+            if previous < state.iterations && progress >= state.iterations {
+                state_events.push(AnimationEvent::AnimationEnded(state.id));
+            }
 
-                if !state.persistent {
-                    state.t = 1.0;
-                    state.active = false;
-                } else {
-                    state.t = 1.0;
+            for event in state_events {
+                for entity in state.entities.iter() {
+                    if !events.contains(&(*entity, event)) {
+                        events.push((*entity, event));
+                    }
                 }
-            } else if state.t <= 0.0 {
-                state.output = Some(start.1.clone());
-            } else {
-                state.output = Some(T::interpolate(&start.1, &end.1, state.t));
             }
+        }
 
-            //println!("Tick: {:?}", state.get_output());
+        if self.active_animations.iter().any(|state| state.entities.is_empty()) {
+            changed = true;
         }
 
         self.remove_innactive_animations();
+
+        changed
     }
 
     pub fn is_animating(&mut self, entity: Entity, animation: Animation) -> bool {
@@ -415,7 +459,7 @@ where
             if entity_index < self.inline_data.sparse.len() {
                 let anim_index = self.inline_data.sparse[entity_index].anim_index as usize;
                 if anim_index < self.active_animations.len() {
-                    return true;
+                    return self.active_animations[anim_index].id == animation;
                 }
             }
         }
@@ -433,8 +477,8 @@ where
             .collect();
 
         // Remove inactive animation states from active animations list
-        // Retains persistent animations
-        self.active_animations.retain(|e| e.t0 < 1.0 || e.persistent);
+        // Retains persistent animations which are still linked to an entity
+        self.active_animations.retain(|e| (e.t0 < 1.0 || e.persistent) && !e.entities.is_empty());
 
         for state in inactive.into_iter() {
             for entity in state.entities.iter() {
@@ -511,7 +555,9 @@ where
             let animation_index = self.inline_data.sparse[entity_index].anim_index as usize;
 
             if animation_index < self.active_animations.len() {
-                return self.active_animations[animation_index].get_output();
+                if let Some(output) = self.active_animations[animation_index].get_output() {
+                    return Some(output);
+                }
             }
 
             let data_index = self.inline_data.sparse[entity_index].data_index;
@@ -568,7 +614,7 @@ where
                         *current_anim_state.keyframes.last_mut().unwrap() =
                             (1.0, self.shared_data.dense[current_anim_state.to_rule].value.clone());
                         current_anim_state.delay = current_anim_state.t - 1.0;
-                        current_anim_state.start_time = std::time::Instant::now();
+                        current_anim_state.seek_to = Some(-current_anim_state.delay);
                     }
                 } else {
                     if rule_animation.index() < self.animations.dense.len() {
@@ -90,6 +90,40 @@ impl Default for Overflow {
     }
 }
 
+/// Determines where the text of an element is broken into lines when it is wider than the element.
+///
+/// Text is always broken at newlines.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TextWrap {
+    /// The text is not wrapped.
+    None,
+    /// The text is wrapped between words, and words which don't fit on a line of their own are broken between characters.
+    Word,
+    /// The text is wrapped between any characters.
+    Char,
+}
+
+impl Default for TextWrap {
+    fn default() -> Self {
+        TextWrap::None
+    }
+}
+
+/// Determines how text which doesn't fit within an element is shown.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TextOverflow {
+    /// The text is clipped by the bounds of the element, if overflow is hidden.
+    Clip,
+    /// The text is cut short to fit and ended with an ellipsis.
+    Ellipsis,
+}
+
+impl Default for TextOverflow {
+    fn default() -> Self {
+        TextOverflow::Clip
+    }
+}
+
 /// Next and previous widgets which receive focus.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct FocusOrder {
@@ -0,0 +1,18 @@
+/// Determines how a background image is sized to fit the bounds of an entity.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ImageFit {
+    /// The image is stretched to fill the bounds, ignoring its aspect ratio.
+    Fill,
+    /// The image is scaled, preserving its aspect ratio, to fit entirely within the bounds.
+    Contain,
+    /// The image is scaled, preserving its aspect ratio, to cover the bounds. Parts of the image may be clipped.
+    Cover,
+    /// The image is repeated at its natural size, starting from the top-left corner of the bounds.
+    Tile,
+}
+
+impl Default for ImageFit {
+    fn default() -> Self {
+        ImageFit::Fill
+    }
+}
@@ -1,12 +1,11 @@
-use std::{collections::HashSet, rc::Rc};
+use std::collections::{HashMap, HashSet};
 
 use morphorm::{LayoutType, PositionType, Units};
 
-use cssparser::{Parser, ParserInput};
-
 use crate::{
     storage::{animatable_set::AnimatableSet, sparse_set::SparseSet, style_set::StyleSet},
-    Animation, AnimationState, CursorIcon, Entity, IdManager, Interpolator, Transition,
+    Animation, AnimationDescription, AnimationEvent, AnimationFillMode, AnimationState, CursorIcon,
+    Entity, IdManager, Interpolator, TextSpan, Transition,
 };
 
 mod color;
@@ -25,10 +24,11 @@ mod transform;
 pub use transform::*;
 
 mod theme;
+pub(crate) use theme::parse_stylesheet;
 pub use theme::*;
 
 mod style_rule;
-use style_rule::StyleRule;
+pub(crate) use style_rule::{KeyframesRule, ParsedStylesheet, StyleRule};
 
 mod selector;
 pub use selector::*;
@@ -42,6 +42,9 @@ pub use property::*;
 mod gradient;
 pub use gradient::*;
 
+mod image;
+pub use image::*;
+
 mod shadow;
 use shadow::*;
 
@@ -50,6 +53,196 @@ pub use prop::*;
 
 use morphorm::Units::*;
 
+// Runs a statement for each of the animatable properties of the style.
+macro_rules! for_each_animatable {
+    ($style:ident, $set:ident => $body:block) => {{
+        {
+            let $set = &mut $style.display;
+            $body
+        }
+        {
+            let $set = &mut $style.visibility;
+            $body
+        }
+        {
+            let $set = &mut $style.opacity;
+            $body
+        }
+        {
+            let $set = &mut $style.rotate;
+            $body
+        }
+        {
+            let $set = &mut $style.scale;
+            $body
+        }
+        {
+            let $set = &mut $style.border_width;
+            $body
+        }
+        {
+            let $set = &mut $style.border_color;
+            $body
+        }
+        {
+            let $set = &mut $style.border_radius_top_left;
+            $body
+        }
+        {
+            let $set = &mut $style.border_radius_top_right;
+            $body
+        }
+        {
+            let $set = &mut $style.border_radius_bottom_left;
+            $body
+        }
+        {
+            let $set = &mut $style.border_radius_bottom_right;
+            $body
+        }
+        {
+            let $set = &mut $style.background_color;
+            $body
+        }
+        {
+            let $set = &mut $style.outer_shadow_h_offset;
+            $body
+        }
+        {
+            let $set = &mut $style.outer_shadow_v_offset;
+            $body
+        }
+        {
+            let $set = &mut $style.outer_shadow_blur;
+            $body
+        }
+        {
+            let $set = &mut $style.outer_shadow_color;
+            $body
+        }
+        {
+            let $set = &mut $style.inner_shadow_h_offset;
+            $body
+        }
+        {
+            let $set = &mut $style.inner_shadow_v_offset;
+            $body
+        }
+        {
+            let $set = &mut $style.inner_shadow_blur;
+            $body
+        }
+        {
+            let $set = &mut $style.inner_shadow_color;
+            $body
+        }
+        {
+            let $set = &mut $style.font_color;
+            $body
+        }
+        {
+            let $set = &mut $style.font_size;
+            $body
+        }
+        {
+            let $set = &mut $style.left;
+            $body
+        }
+        {
+            let $set = &mut $style.right;
+            $body
+        }
+        {
+            let $set = &mut $style.top;
+            $body
+        }
+        {
+            let $set = &mut $style.bottom;
+            $body
+        }
+        {
+            let $set = &mut $style.width;
+            $body
+        }
+        {
+            let $set = &mut $style.height;
+            $body
+        }
+        {
+            let $set = &mut $style.max_width;
+            $body
+        }
+        {
+            let $set = &mut $style.max_height;
+            $body
+        }
+        {
+            let $set = &mut $style.min_width;
+            $body
+        }
+        {
+            let $set = &mut $style.min_height;
+            $body
+        }
+        {
+            let $set = &mut $style.min_left;
+            $body
+        }
+        {
+            let $set = &mut $style.max_left;
+            $body
+        }
+        {
+            let $set = &mut $style.min_right;
+            $body
+        }
+        {
+            let $set = &mut $style.max_right;
+            $body
+        }
+        {
+            let $set = &mut $style.min_top;
+            $body
+        }
+        {
+            let $set = &mut $style.max_top;
+            $body
+        }
+        {
+            let $set = &mut $style.min_bottom;
+            $body
+        }
+        {
+            let $set = &mut $style.max_bottom;
+            $body
+        }
+        {
+            let $set = &mut $style.row_between;
+            $body
+        }
+        {
+            let $set = &mut $style.col_between;
+            $body
+        }
+        {
+            let $set = &mut $style.child_left;
+            $body
+        }
+        {
+            let $set = &mut $style.child_right;
+            $body
+        }
+        {
+            let $set = &mut $style.child_top;
+            $body
+        }
+        {
+            let $set = &mut $style.child_bottom;
+            $body
+        }
+    }};
+}
+
 // #[derive(Default)]
 // pub struct Style {
 
@@ -249,12 +442,13 @@ bitflags! {
         const FOCUSABLE = 1 << 1;
         const CHECKABLE = 1 << 2;
         const SELECTABLE = 1 << 3;
+        const FOCUS_SCOPE = 1 << 4;
     }
 }
 
 impl Default for Abilities {
     fn default() -> Abilities {
-        Abilities::all()
+        Abilities::HOVERABLE | Abilities::CHECKABLE | Abilities::SELECTABLE
     }
 }
 
@@ -268,6 +462,12 @@ pub struct Style {
 
     pub(crate) rules: Vec<StyleRule>,
 
+    /// Keyframes parsed from `@keyframes` rules
+    pub(crate) keyframes: Vec<KeyframesRule>,
+
+    /// Maps the name of a `@keyframes` rule to its animation
+    pub(crate) keyframe_animations: HashMap<String, Animation>,
+
     pub default_font: String,
 
     pub elements: SparseSet<String>,
@@ -296,8 +496,7 @@ pub struct Style {
     pub translate: StyleSet<(f32, f32)>,
     pub scale: AnimatableSet<f32>,
 
-    pub overflow: StyleSet<Overflow>, // TODO
-    //pub scroll: DenseStorage<Scroll>,     // TODO
+    pub overflow: StyleSet<Overflow>,
 
     // Border
     pub border_width: AnimatableSet<Units>,
@@ -320,7 +519,9 @@ pub struct Style {
 
     // Background
     pub background_color: AnimatableSet<Color>,
-    pub background_image: StyleSet<Rc<()>>,
+    // The name of the image resource, or None for `background-image: none`
+    pub background_image: StyleSet<Option<String>>,
+    pub background_fit: StyleSet<ImageFit>,
     pub background_gradient: StyleSet<LinearGradient>,
 
     // Outer Shadow
@@ -337,9 +538,15 @@ pub struct Style {
 
     //Text & Font
     pub text: StyleSet<String>,
+    pub rich_text: SparseSet<Vec<TextSpan>>,
+    // How far single-line text is scrolled to the left, such as to keep the caret of a textbox in view
+    pub text_scroll: SparseSet<f32>,
     pub font: StyleSet<String>,
     pub font_color: AnimatableSet<Color>,
     pub font_size: AnimatableSet<f32>,
+    pub text_wrap: StyleSet<TextWrap>,
+    pub text_overflow: StyleSet<TextOverflow>,
+    pub line_height: StyleSet<Units>,
 
     pub tooltip: SparseSet<String>,
 
@@ -398,6 +605,9 @@ pub struct Style {
 
     pub cursor: StyleSet<CursorIcon>,
 
+    // Keyframe animations played by an entity
+    pub(crate) animations: StyleSet<Vec<AnimationDescription>>,
+
     pub needs_restyle: bool,
     pub needs_relayout: bool,
     pub needs_redraw: bool,
@@ -420,36 +630,25 @@ impl Style {
         }
     }
 
-    pub fn parse_theme(&mut self, stylesheet: &str) {
-        let mut input = ParserInput::new(stylesheet);
-        let mut parser = Parser::new(&mut input);
-        let rule_parser = theme::RuleParser::new();
+    /// Parses a theme and adds its rules, returning any problems found while parsing.
+    ///
+    /// Rules with invalid selectors, and invalid declarations within rules, are ignored.
+    pub fn parse_theme(&mut self, stylesheet: &str) -> Vec<StyleDiagnostic> {
+        let (parsed, diagnostics) = parse_stylesheet(stylesheet);
 
-        let rules = {
-            let rule_list_parser =
-                cssparser::RuleListParser::new_for_stylesheet(&mut parser, rule_parser);
-            rule_list_parser.collect::<Vec<_>>()
-        };
+        self.add_rules(parsed);
 
-        let mut rule_list: Vec<StyleRule> = rules
-            .into_iter()
-            .filter_map(|rule| {
-                match rule {
-                    Ok(mut style_rule) => {
-                        style_rule.id = self.rule_manager.create();
-                        Some(style_rule)
-                    }
-                    Err(parse_error) => {
-                        let style_parse_error = StyleParseError(parse_error.0);
-                        println!("{}", style_parse_error);
-                        None
-                    }
-                }
-                //rule.ok()
-            })
-            .collect();
+        diagnostics
+    }
+
+    /// Adds parsed style rules and keyframes and reapplies the style properties of all rules.
+    pub(crate) fn add_rules(&mut self, mut parsed: ParsedStylesheet) {
+        for style_rule in parsed.rules.iter_mut() {
+            style_rule.id = self.rule_manager.create();
+        }
 
-        self.rules.append(&mut rule_list);
+        self.rules.append(&mut parsed.rules);
+        self.keyframes.append(&mut parsed.keyframes);
 
         self.rules.sort_by_key(|rule| rule.specificity());
         self.rules.reverse();
@@ -460,6 +659,241 @@ impl Style {
 
         self.remove_all();
         self.set_style_properties();
+        self.set_keyframe_animations();
+    }
+
+    // Recreates the animations for the stored keyframes, replacing any previous keyframe animations.
+    fn set_keyframe_animations(&mut self) {
+        for (_, animation) in std::mem::take(&mut self.keyframe_animations) {
+            self.remove_keyframe_animation(animation);
+        }
+
+        for keyframes_rule in self.keyframes.clone() {
+            let animation = self.animation_manager.create();
+
+            for (time, properties) in keyframes_rule.keyframes.iter() {
+                for property in properties.iter() {
+                    self.insert_keyframe(animation, *time, property);
+                }
+            }
+
+            // Later keyframes with the same name replace earlier ones
+            if let Some(previous) = self.keyframe_animations.insert(keyframes_rule.name, animation)
+            {
+                self.remove_keyframe_animation(previous);
+            }
+        }
+    }
+
+    fn remove_keyframe_animation(&mut self, animation: Animation) {
+        for_each_animatable!(self, set => {
+            set.remove_animation(animation);
+        });
+
+        self.animation_manager.destroy(animation);
+    }
+
+    // Adds the value of a property to a keyframe animation.
+    fn insert_keyframe(&mut self, animation: Animation, time: f32, property: &Property) {
+        match property.clone() {
+            Property::Opacity(value) => {
+                self.opacity.insert_keyframe(animation, time, Opacity(value))
+            }
+
+            Property::BackgroundColor(value) => {
+                self.background_color.insert_keyframe(animation, time, value)
+            }
+
+            Property::FontColor(value) => self.font_color.insert_keyframe(animation, time, value),
+            Property::FontSize(value) => self.font_size.insert_keyframe(animation, time, value),
+
+            // Spacing
+            Property::Space(value) => {
+                self.left.insert_keyframe(animation, time, value);
+                self.right.insert_keyframe(animation, time, value);
+                self.top.insert_keyframe(animation, time, value);
+                self.bottom.insert_keyframe(animation, time, value);
+            }
+            Property::Left(value) => self.left.insert_keyframe(animation, time, value),
+            Property::Right(value) => self.right.insert_keyframe(animation, time, value),
+            Property::Top(value) => self.top.insert_keyframe(animation, time, value),
+            Property::Bottom(value) => self.bottom.insert_keyframe(animation, time, value),
+
+            // Spacing Constraints
+            Property::MinLeft(value) => self.min_left.insert_keyframe(animation, time, value),
+            Property::MaxLeft(value) => self.max_left.insert_keyframe(animation, time, value),
+            Property::MinRight(value) => self.min_right.insert_keyframe(animation, time, value),
+            Property::MaxRight(value) => self.max_right.insert_keyframe(animation, time, value),
+            Property::MinTop(value) => self.min_top.insert_keyframe(animation, time, value),
+            Property::MaxTop(value) => self.max_top.insert_keyframe(animation, time, value),
+            Property::MinBottom(value) => self.min_bottom.insert_keyframe(animation, time, value),
+            Property::MaxBottom(value) => self.max_bottom.insert_keyframe(animation, time, value),
+
+            // Size
+            Property::Width(value) => self.width.insert_keyframe(animation, time, value),
+            Property::Height(value) => self.height.insert_keyframe(animation, time, value),
+
+            // Size Constraints
+            Property::MinWidth(value) => self.min_width.insert_keyframe(animation, time, value),
+            Property::MaxWidth(value) => self.max_width.insert_keyframe(animation, time, value),
+            Property::MinHeight(value) => self.min_height.insert_keyframe(animation, time, value),
+            Property::MaxHeight(value) => self.max_height.insert_keyframe(animation, time, value),
+
+            // Child Spacing
+            Property::ChildSpace(value) => {
+                self.child_left.insert_keyframe(animation, time, value);
+                self.child_right.insert_keyframe(animation, time, value);
+                self.child_top.insert_keyframe(animation, time, value);
+                self.child_bottom.insert_keyframe(animation, time, value);
+            }
+            Property::ChildLeft(value) => self.child_left.insert_keyframe(animation, time, value),
+            Property::ChildRight(value) => self.child_right.insert_keyframe(animation, time, value),
+            Property::ChildTop(value) => self.child_top.insert_keyframe(animation, time, value),
+            Property::ChildBottom(value) => {
+                self.child_bottom.insert_keyframe(animation, time, value)
+            }
+            Property::RowBetween(value) => self.row_between.insert_keyframe(animation, time, value),
+            Property::ColBetween(value) => self.col_between.insert_keyframe(animation, time, value),
+
+            // Border
+            Property::BorderWidth(value) => {
+                self.border_width.insert_keyframe(animation, time, value)
+            }
+            Property::BorderColor(value) => {
+                self.border_color.insert_keyframe(animation, time, value)
+            }
+            Property::BorderRadius(value) => {
+                self.border_radius_top_left.insert_keyframe(animation, time, value);
+                self.border_radius_top_right.insert_keyframe(animation, time, value);
+                self.border_radius_bottom_left.insert_keyframe(animation, time, value);
+                self.border_radius_bottom_right.insert_keyframe(animation, time, value);
+            }
+            Property::BorderTopLeftRadius(value) => {
+                self.border_radius_top_left.insert_keyframe(animation, time, value)
+            }
+            Property::BorderTopRightRadius(value) => {
+                self.border_radius_top_right.insert_keyframe(animation, time, value)
+            }
+            Property::BorderBottomLeftRadius(value) => {
+                self.border_radius_bottom_left.insert_keyframe(animation, time, value)
+            }
+            Property::BorderBottomRightRadius(value) => {
+                self.border_radius_bottom_right.insert_keyframe(animation, time, value)
+            }
+
+            // Shadows
+            Property::OuterShadowColor(value) => {
+                self.outer_shadow_color.insert_keyframe(animation, time, value)
+            }
+            Property::InnerShadowColor(value) => {
+                self.inner_shadow_color.insert_keyframe(animation, time, value)
+            }
+
+            // Properties which can't be animated are reported when the keyframes are parsed
+            _ => {}
+        }
+    }
+
+    /// Plays the keyframes named by an animation description on an entity.
+    pub(crate) fn play_keyframes(&mut self, entity: Entity, description: &AnimationDescription) {
+        if let Some(animation) = self.keyframe_animations.get(&description.name).copied() {
+            for_each_animatable!(self, set => {
+                if let Some(anim_state) = set.get_animation_mut(animation) {
+                    anim_state.set_description(description);
+                    set.play_animation(entity, animation);
+                }
+            });
+        }
+    }
+
+    /// Plays an animation on an entity for each property which the animation has keyframes for.
+    pub(crate) fn play_animation(&mut self, entity: Entity, animation: Animation) {
+        for_each_animatable!(self, set => {
+            set.play_animation(entity, animation);
+        });
+    }
+
+    /// Returns true if the animation is playing on an entity.
+    pub(crate) fn is_animating(&mut self, entity: Entity, animation: Animation) -> bool {
+        let mut animating = false;
+
+        for_each_animatable!(self, set => {
+            animating |= set.is_animating(entity, animation);
+        });
+
+        animating
+    }
+
+    /// Stops the keyframes with the given name if they are playing on an entity.
+    pub(crate) fn stop_keyframes(&mut self, entity: Entity, name: &str) {
+        if let Some(animation) = self.keyframe_animations.get(name).copied() {
+            self.stop_animation(entity, animation);
+        }
+    }
+
+    /// Stops an animation playing on an entity without finishing it.
+    pub(crate) fn stop_animation(&mut self, entity: Entity, animation: Animation) {
+        for_each_animatable!(self, set => {
+            set.stop_animation(entity, animation);
+        });
+    }
+
+    /// Pauses an animation playing on an entity.
+    pub(crate) fn pause_animation(&mut self, entity: Entity, animation: Animation) {
+        for_each_animatable!(self, set => {
+            if let Some(anim_state) = set.get_active_animation_mut(entity, animation) {
+                anim_state.pause();
+            }
+        });
+    }
+
+    /// Resumes a paused animation playing on an entity.
+    pub(crate) fn resume_animation(&mut self, entity: Entity, animation: Animation) {
+        for_each_animatable!(self, set => {
+            if let Some(anim_state) = set.get_active_animation_mut(entity, animation) {
+                anim_state.resume();
+            }
+        });
+    }
+
+    /// Moves an animation playing on an entity to the given time.
+    pub(crate) fn seek_animation(
+        &mut self,
+        entity: Entity,
+        animation: Animation,
+        time: std::time::Duration,
+    ) {
+        for_each_animatable!(self, set => {
+            if let Some(anim_state) = set.get_active_animation_mut(entity, animation) {
+                anim_state.seek(time);
+            }
+        });
+    }
+
+    /// Reverses the direction of an animation playing on an entity.
+    pub(crate) fn reverse_animation(&mut self, entity: Entity, animation: Animation) {
+        for_each_animatable!(self, set => {
+            if let Some(anim_state) = set.get_active_animation_mut(entity, animation) {
+                anim_state.reverse();
+            }
+        });
+    }
+
+    /// Updates all active animations for the given time, adding any lifecycle events to `events`.
+    ///
+    /// Returns true if any animated values changed.
+    pub(crate) fn tick_animations(
+        &mut self,
+        time: std::time::Instant,
+        events: &mut Vec<(Entity, AnimationEvent)>,
+    ) -> bool {
+        let mut changed = false;
+
+        for_each_animatable!(self, set => {
+            changed |= set.tick(time, events);
+        });
+
+        changed
     }
 
     fn set_style_properties(&mut self) {
@@ -484,8 +918,12 @@ impl Style {
                         self.overflow.insert_rule(rule_id, value);
                     }
 
-                    Property::BackgroundImage(_value) => {
-                        todo!();
+                    Property::BackgroundImage(value) => {
+                        self.background_image.insert_rule(rule_id, value);
+                    }
+
+                    Property::BackgroundFit(value) => {
+                        self.background_fit.insert_rule(rule_id, value);
                     }
 
                     // Property::BackgroundGradient(value) => {
@@ -653,15 +1091,23 @@ impl Style {
                         self.font.insert_rule(rule_id, value);
                     }
 
+                    Property::TextWrap(value) => {
+                        self.text_wrap.insert_rule(rule_id, value);
+                    }
+
+                    Property::TextOverflow(value) => {
+                        self.text_overflow.insert_rule(rule_id, value);
+                    }
+
+                    Property::LineHeight(value) => {
+                        self.line_height.insert_rule(rule_id, value);
+                    }
+
                     // Background
                     Property::BackgroundColor(value) => {
                         self.background_color.insert_rule(rule_id, value);
                     }
 
-                    // Property::BackgroundImage(value) => {
-                    //     self.background_image.insert_rule(rule_id, value);
-                    // }
-
                     // Layout
                     Property::LayoutType(value) => {
                         self.layout_type.insert_rule(rule_id, value);
@@ -729,6 +1175,10 @@ impl Style {
                         self.cursor.insert_rule(rule_id, cursor);
                     }
 
+                    Property::Animation(animations) => {
+                        self.animations.insert_rule(rule_id, animations);
+                    }
+
                     // TODO
                     // Property::Translate(value) => {
                     //     self.translate.insert_rule(rule_id, value);
@@ -977,10 +1427,6 @@ impl Style {
                         }
                     }
 
-                    Property::Unknown(s, _) => {
-                        println!("Unknown style property: {}", s)
-                    }
-
                     _ => {}
                 }
             }
@@ -993,11 +1439,17 @@ impl Style {
         &self,
         transition: Transition,
     ) -> AnimationState<T> {
-        AnimationState::new(Animation::null())
+        let mut state = AnimationState::new(Animation::null())
             .with_duration(std::time::Duration::from_secs_f32(transition.duration))
             .with_delay(std::time::Duration::from_secs_f32(transition.delay))
+            .with_timing_function(transition.timing_function)
             .with_keyframe((0.0, Default::default()))
-            .with_keyframe((1.0, Default::default()))
+            .with_keyframe((1.0, Default::default()));
+
+        // The starting value is shown during the delay
+        state.set_fill_mode(AnimationFillMode::Backwards);
+
+        state
     }
 
     /*
@@ -1160,10 +1612,6 @@ impl Style {
                         self.background_color.insert_rule(rule_id, value);
                     }
 
-                    // Property::BackgroundImage(value) => {
-                    //     self.background_image.insert_rule(rule_id, value);
-                    // }
-
                     // Flex Container
                     Property::FlexDirection(value) => {
                         self.flex_direction.insert_rule(rule_id, value);
@@ -1535,6 +1983,7 @@ impl Style {
         self.background_color.remove(entity);
         self.background_gradient.remove(entity);
         self.background_image.remove(entity);
+        self.background_fit.remove(entity);
 
         // Border
         self.border_color.remove(entity);
@@ -1599,9 +2048,16 @@ impl Style {
 
         // Text and Font
         self.text.remove(entity);
+        self.rich_text.remove(entity);
+        self.text_scroll.remove(entity);
         self.font.remove(entity);
         self.font_color.remove(entity);
         self.font_size.remove(entity);
+        self.text_wrap.remove(entity);
+        self.text_overflow.remove(entity);
+        self.line_height.remove(entity);
+
+        self.animations.remove(entity);
     }
 
     pub fn remove_all(&mut self) {
@@ -1614,11 +2070,15 @@ impl Style {
         // Remove all non-inline style data
         self.background_color.clear_rules();
         self.background_image.clear_rules();
+        self.background_fit.clear_rules();
         self.background_gradient.clear_rules();
 
         self.font_color.clear_rules();
         self.font.clear_rules();
         self.font_size.clear_rules();
+        self.text_wrap.clear_rules();
+        self.text_overflow.clear_rules();
+        self.line_height.clear_rules();
 
         self.position_type.clear_rules();
         self.layout_type.clear_rules();
@@ -1696,5 +2156,7 @@ impl Style {
         self.col_index.clear_rules();
         self.row_span.clear_rules();
         self.col_span.clear_rules();
+
+        self.animations.clear_rules();
     }
 }
@@ -8,8 +8,6 @@ use crate::tree::TreeExt;
 
 use morphorm::{LayoutType, PositionType, Units};
 
-use std::rc::Rc;
-
 /// To be replaced by [PropSet2]
 pub trait PropSet: AsEntity + Sized {
     /// Helper method for sending an event to self with upward propagation
@@ -846,6 +844,68 @@ pub trait PropSet: AsEntity + Sized {
         self.entity()
     }
 
+    /// Set where the text displayed within the entity is broken into lines when it is wider than the entity.
+    ///
+    /// # Example
+    /// ```compile_fail
+    /// entity.set_text_wrap(cx, TextWrap::Word);
+    /// ```
+    ///
+    /// # CSS
+    /// ```css
+    /// text-wrap: none | word | char
+    /// ```
+    fn set_text_wrap(self, cx: &mut Context, value: TextWrap) -> Entity {
+        cx.style.text_wrap.insert(self.entity(), value);
+
+        cx.style.needs_relayout = true;
+        cx.style.needs_redraw = true;
+
+        self.entity()
+    }
+
+    /// Set how the text displayed within the entity is shown when it doesn't fit.
+    ///
+    /// # Example
+    /// ```compile_fail
+    /// entity.set_text_overflow(cx, TextOverflow::Ellipsis);
+    /// ```
+    ///
+    /// # CSS
+    /// ```css
+    /// text-overflow: clip | ellipsis
+    /// ```
+    fn set_text_overflow(self, cx: &mut Context, value: TextOverflow) -> Entity {
+        cx.style.text_overflow.insert(self.entity(), value);
+
+        cx.style.needs_relayout = true;
+        cx.style.needs_redraw = true;
+
+        self.entity()
+    }
+
+    /// Set the height of each line of the text displayed within the entity.
+    ///
+    /// A percentage is relative to the height of the font, which is also the height of a line when it is `Auto`.
+    ///
+    /// # Example
+    /// ```compile_fail
+    /// entity.set_line_height(cx, Units::Pixels(24.0));
+    /// ```
+    ///
+    /// # CSS
+    /// ```css
+    /// line-height: auto | {}px | {}%
+    /// ```
+    fn set_line_height(self, cx: &mut Context, value: Units) -> Entity {
+        cx.style.line_height.insert(self.entity(), value);
+
+        cx.style.needs_relayout = true;
+        cx.style.needs_redraw = true;
+
+        self.entity()
+    }
+
     // Tooltip
     fn set_tooltip(self, cx: &mut Context, text: &str) -> Entity {
         cx.style.tooltip.insert(self.entity(), text.to_owned()).expect("Failed to set tooltip");
@@ -894,9 +954,30 @@ pub trait PropSet: AsEntity + Sized {
         self.entity()
     }
 
-    // TODO
-    fn set_background_image(self, cx: &mut Context, value: Rc<()>) -> Entity {
-        cx.style.background_image.insert(self.entity(), value);
+    /// Set the background image of the entity to the image resource with the given name.
+    ///
+    /// Images are added to the application with `cx.load_image(name, bytes)`.
+    ///
+    /// # CSS
+    /// ```css
+    /// background-image: url("name")
+    /// ```
+    fn set_background_image(self, cx: &mut Context, value: &str) -> Entity {
+        cx.style.background_image.insert(self.entity(), Some(value.to_owned()));
+
+        cx.style.needs_redraw = true;
+
+        self.entity()
+    }
+
+    /// Set how the background image of the entity is sized to fit its bounds.
+    ///
+    /// # CSS
+    /// ```css
+    /// background-fit: fill | contain | cover | tile
+    /// ```
+    fn set_background_fit(self, cx: &mut Context, value: ImageFit) -> Entity {
+        cx.style.background_fit.insert(self.entity(), value);
 
         cx.style.needs_redraw = true;
 
@@ -1379,6 +1460,9 @@ pub trait PropGet: Sized + AsEntity {
     // Text
     fn get_text(&self, cx: &Context) -> String;
     fn get_font(&self, cx: &Context) -> String;
+    fn get_text_wrap(&self, cx: &Context) -> TextWrap;
+    fn get_text_overflow(&self, cx: &Context) -> TextOverflow;
+    fn get_line_height(&self, cx: &Context) -> Units;
 }
 
 impl PropGet for Entity {
@@ -1496,4 +1580,16 @@ impl PropGet for Entity {
     fn get_font(&self, cx: &Context) -> String {
         cx.style.font.get(*self).cloned().unwrap_or_default()
     }
+
+    fn get_text_wrap(&self, cx: &Context) -> TextWrap {
+        cx.style.text_wrap.get(*self).cloned().unwrap_or_default()
+    }
+
+    fn get_text_overflow(&self, cx: &Context) -> TextOverflow {
+        cx.style.text_overflow.get(*self).cloned().unwrap_or_default()
+    }
+
+    fn get_line_height(&self, cx: &Context) -> Units {
+        cx.style.line_height.get(*self).cloned().unwrap_or_default()
+    }
 }
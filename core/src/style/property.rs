@@ -6,20 +6,12 @@ use crate::Color;
 
 use crate::style::*;
 
-use crate::Transition;
+use crate::{AnimationDescription, Transition};
 
 use morphorm::{LayoutType, PositionType, Units};
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum PropType {
-    Units(Units),
-    String(String),
-}
-
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) enum Property {
-    Unknown(String, PropType),
-
     // General
     Display(Display),
     Visibility(Visibility),
@@ -85,7 +77,8 @@ pub(crate) enum Property {
 
     // Background
     BackgroundColor(Color),
-    BackgroundImage(String),
+    BackgroundImage(Option<String>),
+    BackgroundFit(ImageFit),
     // TODO
     //BackgroundGradient(LinearGradient),
 
@@ -93,6 +86,9 @@ pub(crate) enum Property {
     FontSize(f32),
     FontColor(Color),
     Font(String),
+    TextWrap(TextWrap),
+    TextOverflow(TextOverflow),
+    LineHeight(Units),
 
     // Shadow
     OuterShadow(BoxShadow),
@@ -108,6 +104,7 @@ pub(crate) enum Property {
     InnerShadowColor(Color),
 
     Transition(Vec<Transition>),
+    Animation(Vec<AnimationDescription>),
 
     ZIndex(i32),
 
@@ -118,6 +115,54 @@ pub(crate) enum Property {
     Cursor(CursorIcon),
 }
 
+impl Property {
+    // Returns true if the property can be set by the keyframes of a `@keyframes` rule.
+    pub(crate) fn is_animatable(&self) -> bool {
+        matches!(
+            self,
+            Property::Opacity(_)
+                | Property::BackgroundColor(_)
+                | Property::FontColor(_)
+                | Property::FontSize(_)
+                | Property::Space(_)
+                | Property::Left(_)
+                | Property::Right(_)
+                | Property::Top(_)
+                | Property::Bottom(_)
+                | Property::MinLeft(_)
+                | Property::MaxLeft(_)
+                | Property::MinRight(_)
+                | Property::MaxRight(_)
+                | Property::MinTop(_)
+                | Property::MaxTop(_)
+                | Property::MinBottom(_)
+                | Property::MaxBottom(_)
+                | Property::Width(_)
+                | Property::Height(_)
+                | Property::MinWidth(_)
+                | Property::MaxWidth(_)
+                | Property::MinHeight(_)
+                | Property::MaxHeight(_)
+                | Property::ChildSpace(_)
+                | Property::ChildLeft(_)
+                | Property::ChildRight(_)
+                | Property::ChildTop(_)
+                | Property::ChildBottom(_)
+                | Property::RowBetween(_)
+                | Property::ColBetween(_)
+                | Property::BorderWidth(_)
+                | Property::BorderColor(_)
+                | Property::BorderRadius(_)
+                | Property::BorderTopLeftRadius(_)
+                | Property::BorderTopRightRadius(_)
+                | Property::BorderBottomLeftRadius(_)
+                | Property::BorderBottomRightRadius(_)
+                | Property::OuterShadowColor(_)
+                | Property::InnerShadowColor(_)
+        )
+    }
+}
+
 /*
 impl std::fmt::Display for Property {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -210,16 +255,22 @@ impl std::fmt::Display for Property {
 
             // Background
             Property::BackgroundColor(val) => write!(f, "background-color: {:?};", val),
-            Property::BackgroundImage(val) => write!(f, "background-image: {};", val),
+            Property::BackgroundImage(val) => {
+                write!(f, "background-image: {};", val.as_deref().unwrap_or("none"))
+            }
             Property::BackgroundGradient(val) => write!(f, "background-gradient: {};", 4),
 
             Property::FontSize(val) => write!(f, "font-size: {};", val),
             Property::FontColor(val) => write!(f, "color: {:?};", val),
+            Property::TextWrap(val) => write!(f, "text-wrap: {:?};", val),
+            Property::TextOverflow(val) => write!(f, "text-overflow: {:?};", val),
+            Property::LineHeight(val) => write!(f, "line-height: {};", val),
 
             Property::OuterShadow(val) => write!(f, "outer-shadow: {};", 5),
             Property::InnerShadow(val) => write!(f, "inner-shadow: {};", 6),
 
             Property::Transition(val) => write!(f, "transition: {:?};", val),
+            Property::Animation(val) => write!(f, "animation: {:?};", val),
 
             Property::ZIndex(val) => write!(f, "z-index: {};", val),
 
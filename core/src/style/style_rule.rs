@@ -38,6 +38,28 @@ impl StyleRule {
     }
 }
 
+/// A named list of keyframes parsed from a `@keyframes` rule.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct KeyframesRule {
+    pub(crate) name: String,
+    // The properties of each keyframe, with the time of the keyframe between 0.0 and 1.0
+    pub(crate) keyframes: Vec<(f32, Vec<Property>)>,
+}
+
+/// The style rules and keyframes parsed from a theme or stylesheet.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct ParsedStylesheet {
+    pub(crate) rules: Vec<StyleRule>,
+    pub(crate) keyframes: Vec<KeyframesRule>,
+}
+
+impl ParsedStylesheet {
+    pub(crate) fn append(&mut self, other: &mut ParsedStylesheet) {
+        self.rules.append(&mut other.rules);
+        self.keyframes.append(&mut other.keyframes);
+    }
+}
+
 /*
 impl StyleRule {
     pub fn new(id: Rule) -> Self {
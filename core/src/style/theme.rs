@@ -2,14 +2,17 @@ use std::fmt::Debug;
 
 use cssparser::{
     self, AtRuleType, BasicParseError, BasicParseErrorKind, CowRcStr, DeclarationListParser,
-    ParseError, ParseErrorKind, Parser, ParserInput, SourceLocation, Token,
+    ParseError, ParseErrorKind, Parser, ParserInput, RuleListParser, SourceLocation, Token,
 };
 
 use crate::style::property::Property;
 use crate::style::selector::{Selector, SelectorRelation};
 
-use crate::style::StyleRule;
-use crate::{CursorIcon, Transition};
+use crate::style::{KeyframesRule, ParsedStylesheet, StyleRule};
+use crate::{
+    AnimationDescription, AnimationDirection, AnimationFillMode, CursorIcon, StepPosition,
+    TimingFunction, Transition,
+};
 
 use crate::style::*;
 
@@ -23,6 +26,8 @@ pub enum CustomParseError {
     InvalidColorHex(String),
     InvalidStringName(String),
     UnrecognisedPseudoclass(String),
+    UnrecognisedProperty(String),
+    UnanimatableProperty(String),
 }
 
 impl<'t> From<CustomParseError> for ParseError<'t, CustomParseError> {
@@ -34,22 +39,107 @@ impl<'t> From<CustomParseError> for ParseError<'t, CustomParseError> {
     }
 }
 
-/// Type which describes errors produced by the css style parser.
-pub struct StyleParseError<'t>(pub ParseError<'t, CustomParseError>);
+/// A problem found while parsing a theme or stylesheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleDiagnostic {
+    /// The line of the problem, starting at 1.
+    pub line: u32,
+    /// The column of the problem, starting at 1.
+    pub column: u32,
+    /// The name of the property for problems in a declaration.
+    pub property: Option<String>,
+    /// Why the rule or declaration was ignored, e.g. an unrecognised property, a bad unit or an unknown color name.
+    pub reason: String,
+}
+
+impl StyleDiagnostic {
+    // Creates a diagnostic from a parse error. Errors without a location are given the location of the start
+    // of the source of the rule or declaration which produced them.
+    fn new(
+        stylesheet: &str,
+        error: &ParseError<'_, CustomParseError>,
+        source: &str,
+        property: Option<String>,
+    ) -> Self {
+        let location = if error.location.line == 0 && error.location.column == 0 {
+            source_location(stylesheet, source)
+        } else {
+            error.location
+        };
 
-impl<'t> std::fmt::Display for StyleParseError<'t> {
+        Self {
+            line: location.line + 1,
+            column: location.column,
+            property,
+            reason: error_reason(&error.kind),
+        }
+    }
+}
+
+impl std::fmt::Display for StyleDiagnostic {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let error_message = match &self.0.kind {
-            ParseErrorKind::Custom(custom_error) => {
-                format!("{:?}", custom_error)
+        match &self.property {
+            Some(property) => {
+                write!(f, "{}:{}: {}: {}", self.line, self.column, property, self.reason)
             }
+            None => write!(f, "{}:{}: {}", self.line, self.column, self.reason),
+        }
+    }
+}
 
-            ParseErrorKind::Basic(basic_error) => {
-                format!("{:?}", basic_error)
-            }
-        };
+/// A problem reported by the application, such as a stylesheet which failed to parse or a translation
+/// which failed to load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Where the problem was found, such as the path of a stylesheet.
+    pub source: String,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.source, self.message)
+    }
+}
+
+/// Determines how problems found while parsing themes and stylesheets, and loading other resources,
+/// are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleErrorMode {
+    /// Print each problem and ignore the rule or declaration which caused it.
+    Log,
+    /// Panic on the first problem. Useful during development so that broken themes fail loudly.
+    Panic,
+    /// Keep each problem without printing it. The problems can be read with `cx.take_diagnostics()`.
+    Collect,
+    /// Ignore problems silently.
+    Ignore,
+}
+
+impl Default for StyleErrorMode {
+    fn default() -> Self {
+        StyleErrorMode::Log
+    }
+}
+
+fn error_reason(kind: &ParseErrorKind<'_, CustomParseError>) -> String {
+    match kind {
+        ParseErrorKind::Custom(custom_error) => format!("{:?}", custom_error),
+
+        ParseErrorKind::Basic(BasicParseErrorKind::UnexpectedToken(token)) => {
+            format!("Unexpected token: {:?}", token)
+        }
+
+        ParseErrorKind::Basic(BasicParseErrorKind::EndOfInput) => {
+            "Unexpected end of input".to_owned()
+        }
 
-        write!(f, "Warning: {}", error_message)
+        ParseErrorKind::Basic(BasicParseErrorKind::AtRuleInvalid(name)) => {
+            format!("Unknown at-rule: @{}", name)
+        }
+
+        ParseErrorKind::Basic(basic_error) => format!("{:?}", basic_error),
     }
 }
 
@@ -64,6 +154,14 @@ impl Debug for CustomParseError {
                 write!(f, "Unrecognised pseudoclass: {}", error_string)
             }
 
+            CustomParseError::UnrecognisedProperty(error_string) => {
+                write!(f, "Unrecognised property: {}", error_string)
+            }
+
+            CustomParseError::UnanimatableProperty(error_string) => {
+                write!(f, "Property cannot be animated: {}", error_string)
+            }
+
             CustomParseError::InvalidLengthUnits(error_string) => {
                 write!(f, "Invalid length units: {}", error_string)
             }
@@ -83,17 +181,59 @@ impl Debug for CustomParseError {
     }
 }
 
-pub(crate) struct RuleParser;
+pub(crate) struct RuleParser<'s> {
+    // The stylesheet being parsed, used to find the location of declarations.
+    stylesheet: &'s str,
+    // Problems found in the declarations of otherwise valid rules.
+    diagnostics: Vec<StyleDiagnostic>,
+}
+
+impl<'s> RuleParser<'s> {
+    pub fn new(stylesheet: &'s str) -> Self {
+        RuleParser { stylesheet, diagnostics: Vec::new() }
+    }
+}
+
+impl RuleParser<'_> {
+    // Parses a list of declarations, recording a diagnostic for each invalid declaration. The declarations of a
+    // keyframe may only contain properties which can be animated.
+    fn parse_declarations<'i, 't>(
+        &mut self,
+        input: &mut Parser<'i, 't>,
+        keyframe: bool,
+    ) -> Vec<Property> {
+        let decl_parser = DeclarationParser { keyframe };
+
+        let mut properties = Vec::new();
+
+        for declaration in DeclarationListParser::new(input, decl_parser) {
+            match declaration {
+                Ok(property) => properties.push(property),
+                Err((error, source)) => {
+                    let property = source.split(':').next().map(|name| name.trim().to_owned());
+                    self.diagnostics.push(StyleDiagnostic::new(
+                        self.stylesheet,
+                        &error,
+                        source,
+                        property,
+                    ));
+                }
+            }
+        }
 
-impl RuleParser {
-    pub fn new() -> Self {
-        RuleParser {}
+        properties
     }
 }
 
-impl<'i> cssparser::QualifiedRuleParser<'i> for RuleParser {
+// A rule parsed from the top level of a stylesheet.
+enum CssRule {
+    Style(StyleRule),
+    Keyframes(KeyframesRule),
+}
+
+impl<'i> cssparser::QualifiedRuleParser<'i> for RuleParser<'_> {
     type Prelude = Vec<Selector>;
-    type QualifiedRule = StyleRule;
+    type QualifiedRule = CssRule;
     type Error = CustomParseError;
 
     fn parse_prelude<'t>(
@@ -110,20 +250,16 @@ impl<'i> cssparser::QualifiedRuleParser<'i> for RuleParser {
         _location: SourceLocation,
         input: &mut Parser<'i, 't>,
     ) -> Result<Self::QualifiedRule, ParseError<'i, Self::Error>> {
-        let decl_parser = DeclarationParser {};
-
-        let properties = DeclarationListParser::new(input, decl_parser)
-            .filter_map(|property| property.ok())
-            .collect::<Vec<_>>();
+        let properties = self.parse_declarations(input, false);
 
-        Ok(StyleRule { id: Rule::null(), selectors, properties })
+        Ok(CssRule::Style(StyleRule { id: Rule::null(), selectors, properties }))
     }
 }
 
-impl<'i> cssparser::AtRuleParser<'i> for RuleParser {
-    type PreludeBlock = ();
+impl<'i> cssparser::AtRuleParser<'i> for RuleParser<'_> {
+    type PreludeBlock = String;
     type PreludeNoBlock = ();
-    type AtRule = StyleRule;
+    type AtRule = CssRule;
     type Error = CustomParseError;
 
     fn parse_prelude<'t>(
@@ -134,47 +270,103 @@ impl<'i> cssparser::AtRuleParser<'i> for RuleParser {
     {
         match &*name {
             "keyframes" => {
-                while let Ok(t) = input.next() {
-                    match t {
-                        Token::Ident(_animation_name) => {}
-
-                        t => {
-                            let basic_error = BasicParseError {
-                                kind: BasicParseErrorKind::UnexpectedToken(t.clone()),
-                                location: input.current_source_location(),
-                            };
-                            return Err(basic_error.into());
-                        }
+                let animation_name = match input.next()? {
+                    Token::Ident(animation_name) | Token::QuotedString(animation_name) => {
+                        animation_name.to_string()
                     }
-                }
+
+                    t => {
+                        let token = t.clone();
+                        return Err(input.new_basic_unexpected_token_error(token).into());
+                    }
+                };
+
+                input.expect_exhausted()?;
+
+                Ok(AtRuleType::WithBlock(animation_name))
             }
 
             _ => {
-                let token = input.next()?.to_owned();
-                return Err(input.new_basic_unexpected_token_error(token).into());
+                Err(input.new_basic_error(BasicParseErrorKind::AtRuleInvalid(name.clone())).into())
             }
         }
-
-        Ok(AtRuleType::WithBlock(()))
     }
 
-    // TODO
-    /*
     fn parse_block<'t>(
         &mut self,
-        prelude: Self::PreludeBlock,
-        location: SourceLocation,
-        input: &mut Parser<'i, 't>
+        name: Self::PreludeBlock,
+        _location: SourceLocation,
+        input: &mut Parser<'i, 't>,
     ) -> Result<Self::AtRule, ParseError<'i, Self::Error>> {
-        let rule_parser = RuleParser::new();
-        let rule_list_parser = RuleListParser::new_for_nested_rule(input, rule_parser).collect::<Vec<_>>();
-
-        // Keyframe rules
-        for rule in &rule_list_parser {
+        let stylesheet = self.stylesheet;
+        let mut keyframes = Vec::new();
+
+        let keyframe_list =
+            RuleListParser::new_for_nested_rule(input, KeyframeParser { rule_parser: self })
+                .collect::<Vec<_>>();
+
+        for keyframe in keyframe_list {
+            match keyframe {
+                Ok((times, properties)) => {
+                    for time in times {
+                        keyframes.push((time, properties.clone()));
+                    }
+                }
 
+                Err((error, source)) => {
+                    self.diagnostics.push(StyleDiagnostic::new(stylesheet, &error, source, None));
+                }
+            }
         }
+
+        Ok(CssRule::Keyframes(KeyframesRule { name, keyframes }))
+    }
+}
+
+// Parses the keyframes within a `@keyframes` rule.
+struct KeyframeParser<'a, 's> {
+    rule_parser: &'a mut RuleParser<'s>,
+}
+
+impl<'i> cssparser::QualifiedRuleParser<'i> for KeyframeParser<'_, '_> {
+    // The times of the keyframe between 0.0 and 1.0
+    type Prelude = Vec<f32>;
+    type QualifiedRule = (Vec<f32>, Vec<Property>);
+    type Error = CustomParseError;
+
+    fn parse_prelude<'t>(
+        &mut self,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self::Prelude, ParseError<'i, Self::Error>> {
+        input.parse_comma_separated(|input| {
+            let location = input.current_source_location();
+            match input.next()? {
+                Token::Ident(name) if name.eq_ignore_ascii_case("from") => Ok(0.0),
+                Token::Ident(name) if name.eq_ignore_ascii_case("to") => Ok(1.0),
+                Token::Percentage { unit_value, .. } if (0.0..=1.0).contains(unit_value) => {
+                    Ok(*unit_value)
+                }
+
+                t => Err(location.new_unexpected_token_error(t.clone())),
+            }
+        })
+    }
+
+    fn parse_block<'t>(
+        &mut self,
+        times: Self::Prelude,
+        _location: SourceLocation,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Self::QualifiedRule, ParseError<'i, Self::Error>> {
+        Ok((times, self.rule_parser.parse_declarations(input, true)))
     }
-    */
+}
+
+impl<'i> cssparser::AtRuleParser<'i> for KeyframeParser<'_, '_> {
+    type PreludeBlock = ();
+    type PreludeNoBlock = ();
+    type AtRule = (Vec<f32>, Vec<Property>);
+    type Error = CustomParseError;
 }
 
 fn parse_selectors<'i, 't>(
@@ -339,7 +531,10 @@ fn parse_selectors<'i, 't>(
 //     fn parse_block
 // }
 
-struct DeclarationParser;
+struct DeclarationParser {
+    // Whether the declarations are in a keyframe
+    keyframe: bool,
+}
 
 impl<'i> cssparser::DeclarationParser<'i> for DeclarationParser {
     type Declaration = Property;
@@ -350,11 +545,12 @@ impl<'i> cssparser::DeclarationParser<'i> for DeclarationParser {
         name: CowRcStr<'i>,
         input: &mut Parser<'i, 't>,
     ) -> Result<Self::Declaration, ParseError<'i, Self::Error>> {
-        Ok(match &*name {
+        let property = match &*name {
             // Colors
             "background-color" => Property::BackgroundColor(parse_color(input)?),
             "color" => Property::FontColor(parse_color(input)?),
-            "background-image" => Property::BackgroundImage(parse_string(input)?),
+            "background-image" => Property::BackgroundImage(parse_background_image(input)?),
+            "background-fit" => Property::BackgroundFit(parse_image_fit(input)?),
 
             // Position
             "position" | "position-type" => Property::PositionType(parse_position_type(input)?),
@@ -396,6 +592,9 @@ impl<'i> cssparser::DeclarationParser<'i> for DeclarationParser {
             "col-between" => Property::ColBetween(parse_units(input)?),
             "font-size" => Property::FontSize(parse_font_size(input)?),
             "font" => Property::Font(parse_string(input)?),
+            "text-wrap" => Property::TextWrap(parse_text_wrap(input)?),
+            "text-overflow" => Property::TextOverflow(parse_text_overflow(input)?),
+            "line-height" => Property::LineHeight(parse_units(input)?),
 
             // Border
             "border-width" => Property::BorderWidth(parse_units(input)?),
@@ -444,19 +643,22 @@ impl<'i> cssparser::DeclarationParser<'i> for DeclarationParser {
                 input.parse_comma_separated(|parser| parse_transition2(parser))?,
             ),
 
+            "animation" => Property::Animation(input.parse_comma_separated(parse_animation)?),
+
             "z-index" => Property::ZIndex(parse_z_index(input)?),
 
             "cursor" => Property::Cursor(parse_cursor(input)?),
 
-            ident => Property::Unknown(ident.to_owned(), parse_unknown(input)?),
-            // _ => {
-            //     let basic_error = BasicParseError {
-            //         kind: BasicParseErrorKind::UnexpectedToken(input.next()?.to_owned()),
-            //         location: SourceLocation { line: 0, column: 0 },
-            //     };
-            //     return Err(basic_error.into());
-            // }
-        })
+            ident => {
+                return Err(CustomParseError::UnrecognisedProperty(ident.to_owned()).into());
+            }
+        };
+
+        if self.keyframe && !property.is_animatable() {
+            return Err(CustomParseError::UnanimatableProperty(name.to_string()).into());
+        }
+
+        Ok(property)
     }
 }
 
@@ -495,41 +697,6 @@ fn css_string(name: &str) -> Option<String> {
     Some(String::from(name))
 }
 
-fn parse_unknown<'i, 't>(
-    input: &mut Parser<'i, 't>,
-) -> Result<PropType, ParseError<'i, CustomParseError>> {
-    Ok(match input.next()? {
-        Token::QuotedString(s) => match css_string(&s) {
-            Some(string) => PropType::String(string),
-            None => {
-                return Err(CustomParseError::InvalidStringName(s.to_owned().to_string()).into())
-            }
-        },
-
-        Token::Number { value: x, .. } => PropType::Units(Units::Pixels(*x as f32)),
-        Token::Percentage { unit_value: x, .. } => PropType::Units(Units::Percentage(*x as f32)),
-
-        Token::Dimension { has_sign: _, value: v, int_value: _, unit: u } if u == &"px" => {
-            PropType::Units(Units::Pixels(*v as f32))
-        }
-
-        Token::Dimension { has_sign: _, value: v, int_value: _, unit: u } if u == &"s" => {
-            PropType::Units(Units::Stretch(*v as f32))
-        }
-
-        Token::Ident(name) if name == &"auto" => PropType::Units(Units::Auto),
-
-        t => {
-            let basic_error = BasicParseErrorKind::UnexpectedToken(t.to_owned());
-            let parse_error = ParseError {
-                kind: ParseErrorKind::Basic(basic_error),
-                location: SourceLocation { line: 0, column: 0 },
-            };
-            return Err(parse_error);
-        }
-    })
-}
-
 fn parse_string<'i, 't>(
     input: &mut Parser<'i, 't>,
 ) -> Result<String, ParseError<'i, CustomParseError>> {
@@ -742,52 +909,249 @@ fn parse_transition2<'i, 't>(
     input: &mut Parser<'i, 't>,
 ) -> Result<Transition, ParseError<'i, CustomParseError>> {
     let mut transition = Transition::new();
+    let mut has_duration = false;
 
-    Ok(match input.next()? {
-        Token::Ident(s) => {
-            transition.property = s.to_string();
+    while !input.is_exhausted() {
+        // Errors are reported at the start of the next token rather than the whitespace before it
+        input.skip_whitespace();
+        let location = input.current_source_location();
 
-            match input.next()? {
-                Token::Number { value: x, .. } => {
-                    transition.duration = *x;
+        match input.next()? {
+            // The first time is the duration and the second is the delay. Plain numbers are in seconds.
+            token @ Token::Dimension { .. } | token @ Token::Number { .. } => {
+                let seconds = parse_time(token, location)?;
 
-                    match input.next()? {
-                        Token::Number { value: x, .. } => {
-                            transition.delay = *x;
-                        }
-
-                        _t => {
-                            // let basic_error = BasicParseError {
-                            //     kind: BasicParseErrorKind::UnexpectedToken(t.to_owned()),
-                            //     location: SourceLocation { line: 0, column: 0 },
-                            // };
-                            // return Err(basic_error.into());
-                        }
-                    }
+                if has_duration {
+                    transition.delay = seconds;
+                } else {
+                    transition.duration = seconds;
+                    has_duration = true;
                 }
+            }
 
-                t => {
-                    let basic_error = BasicParseError {
-                        kind: BasicParseErrorKind::UnexpectedToken(t.to_owned()),
-                        location: SourceLocation { line: 0, column: 0 },
-                    };
-                    return Err(basic_error.into());
-                }
+            Token::Ident(name) => match timing_function_keyword(name) {
+                Some(timing_function) => transition.timing_function = timing_function,
+                None if transition.property.is_empty() => transition.property = name.to_string(),
+                None => return Err(CustomParseError::InvalidValue(name.to_string()).into()),
+            },
+
+            Token::Function(name) => {
+                let name = name.clone();
+                transition.timing_function =
+                    input.parse_nested_block(|input| parse_timing_function(&name, input))?;
             }
 
-            transition
+            t => {
+                let basic_error = BasicParseError {
+                    kind: BasicParseErrorKind::UnexpectedToken(t.to_owned()),
+                    location,
+                };
+                return Err(basic_error.into());
+            }
         }
+    }
 
-        t => {
-            let basic_error = BasicParseError {
-                kind: BasicParseErrorKind::UnexpectedToken(t.to_owned()),
-                location: SourceLocation { line: 0, column: 0 },
-            };
-            return Err(basic_error.into());
+    if transition.property.is_empty() {
+        return Err(CustomParseError::InvalidValue("missing transition property".to_owned()).into());
+    }
+
+    Ok(transition)
+}
+
+// Converts a time in seconds or milliseconds to seconds. A number without units is in seconds.
+// Parses a time in seconds or milliseconds, reporting errors at the location of the token.
+fn parse_time<'i>(
+    token: &Token<'i>,
+    location: SourceLocation,
+) -> Result<f32, ParseError<'i, CustomParseError>> {
+    let seconds = match token {
+        Token::Number { value, .. } => *value,
+        Token::Dimension { value, unit, .. } if unit.eq_ignore_ascii_case("s") => *value,
+        Token::Dimension { value, unit, .. } if unit.eq_ignore_ascii_case("ms") => *value / 1000.0,
+        Token::Dimension { unit, .. } => {
+            return Err(location.new_custom_error(CustomParseError::InvalidValue(format!(
+                "invalid time units: {}",
+                unit
+            ))));
         }
+        t => return Err(location.new_unexpected_token_error(t.to_owned())),
+    };
+
+    // Durations and delays can't be negative
+    if seconds < 0.0 || !seconds.is_finite() {
+        return Err(location.new_custom_error(CustomParseError::InvalidValue(format!(
+            "time out of range: {}s",
+            seconds
+        ))));
+    }
+
+    Ok(seconds)
+}
+
+// Returns the timing function for a keyword such as `ease-in`.
+fn timing_function_keyword(name: &str) -> Option<TimingFunction> {
+    Some(match name {
+        "linear" => TimingFunction::Linear,
+        "ease" => TimingFunction::EASE,
+        "ease-in" => TimingFunction::EASE_IN,
+        "ease-out" => TimingFunction::EASE_OUT,
+        "ease-in-out" => TimingFunction::EASE_IN_OUT,
+        "step-start" => TimingFunction::STEP_START,
+        "step-end" => TimingFunction::STEP_END,
+        "spring" => TimingFunction::SPRING,
+        _ => return None,
     })
 }
 
+// Parses the arguments of a `cubic-bezier()`, `steps()` or `spring()` timing function.
+fn parse_timing_function<'i, 't>(
+    name: &str,
+    input: &mut Parser<'i, 't>,
+) -> Result<TimingFunction, ParseError<'i, CustomParseError>> {
+    match name {
+        "cubic-bezier" => {
+            let x1 = input.expect_number()?;
+            input.expect_comma()?;
+            let y1 = input.expect_number()?;
+            input.expect_comma()?;
+            let x2 = input.expect_number()?;
+            input.expect_comma()?;
+            let y2 = input.expect_number()?;
+
+            if !(0.0..=1.0).contains(&x1) || !(0.0..=1.0).contains(&x2) {
+                return Err(CustomParseError::InvalidValue(
+                    "cubic-bezier x values must be between 0 and 1".to_owned(),
+                )
+                .into());
+            }
+
+            Ok(TimingFunction::CubicBezier(x1, y1, x2, y2))
+        }
+
+        "steps" => {
+            let steps = input.expect_integer()?;
+            if steps < 1 {
+                return Err(CustomParseError::InvalidValue(
+                    "steps must be a positive integer".to_owned(),
+                )
+                .into());
+            }
+
+            let mut position = StepPosition::End;
+            if !input.is_exhausted() {
+                input.expect_comma()?;
+                position = match input.expect_ident()?.as_ref() {
+                    "start" | "jump-start" => StepPosition::Start,
+                    "end" | "jump-end" => StepPosition::End,
+                    ident => return Err(CustomParseError::InvalidValue(ident.to_owned()).into()),
+                };
+            }
+
+            Ok(TimingFunction::Steps(steps as u32, position))
+        }
+
+        "spring" => {
+            let mass = input.expect_number()?;
+            input.expect_comma()?;
+            let stiffness = input.expect_number()?;
+            input.expect_comma()?;
+            let damping = input.expect_number()?;
+
+            let mut velocity = 0.0;
+            if !input.is_exhausted() {
+                input.expect_comma()?;
+                velocity = input.expect_number()?;
+            }
+
+            if mass <= 0.0 || stiffness <= 0.0 || damping < 0.0 {
+                return Err(CustomParseError::InvalidValue(
+                    "spring mass and stiffness must be positive".to_owned(),
+                )
+                .into());
+            }
+
+            Ok(TimingFunction::Spring { mass, stiffness, damping, velocity })
+        }
+
+        _ => {
+            Err(CustomParseError::InvalidValue(format!("unknown timing function: {}", name)).into())
+        }
+    }
+}
+
+fn parse_animation<'i, 't>(
+    input: &mut Parser<'i, 't>,
+) -> Result<AnimationDescription, ParseError<'i, CustomParseError>> {
+    let mut animation = AnimationDescription::new();
+    let mut has_duration = false;
+
+    while !input.is_exhausted() {
+        // Errors are reported at the start of the next token rather than the whitespace before it
+        input.skip_whitespace();
+        let location = input.current_source_location();
+
+        match input.next()? {
+            // The first time is the duration and the second is the delay
+            token @ Token::Dimension { .. } => {
+                let seconds = parse_time(token, location)?;
+
+                if has_duration {
+                    animation.delay = seconds;
+                } else {
+                    animation.duration = seconds;
+                    has_duration = true;
+                }
+            }
+
+            Token::Number { value, .. } if *value >= 0.0 => animation.iterations = *value,
+
+            Token::Ident(name) => {
+                if let Some(timing_function) = timing_function_keyword(name) {
+                    animation.timing_function = timing_function;
+                    continue;
+                }
+
+                match name.as_ref() {
+                    "infinite" => animation.iterations = f32::INFINITY,
+                    "normal" => animation.direction = AnimationDirection::Normal,
+                    "reverse" => animation.direction = AnimationDirection::Reverse,
+                    "alternate" => animation.direction = AnimationDirection::Alternate,
+                    "alternate-reverse" => {
+                        animation.direction = AnimationDirection::AlternateReverse
+                    }
+                    "none" => animation.fill_mode = AnimationFillMode::None,
+                    "forwards" => animation.fill_mode = AnimationFillMode::Forwards,
+                    "backwards" => animation.fill_mode = AnimationFillMode::Backwards,
+                    "both" => animation.fill_mode = AnimationFillMode::Both,
+                    _ => animation.name = name.to_string(),
+                }
+            }
+
+            Token::Function(name) => {
+                let name = name.clone();
+                animation.timing_function =
+                    input.parse_nested_block(|input| parse_timing_function(&name, input))?;
+            }
+
+            Token::QuotedString(name) => animation.name = name.to_string(),
+
+            t => {
+                let basic_error = BasicParseError {
+                    kind: BasicParseErrorKind::UnexpectedToken(t.to_owned()),
+                    location,
+                };
+                return Err(basic_error.into());
+            }
+        }
+    }
+
+    if animation.name.is_empty() {
+        return Err(CustomParseError::InvalidValue("missing animation name".to_owned()).into());
+    }
+
+    Ok(animation)
+}
+
 fn parse_units<'i, 't>(
     input: &mut Parser<'i, 't>,
 ) -> Result<Units, ParseError<'i, CustomParseError>> {
@@ -805,6 +1169,10 @@ fn parse_units<'i, 't>(
 
         Token::Ident(name) if name == &"auto" => Units::Auto,
 
+        Token::Dimension { unit: u, .. } => {
+            return Err(CustomParseError::InvalidLengthUnits(u.to_string()).into());
+        }
+
         t => {
             let basic_error = BasicParseError {
                 kind: BasicParseErrorKind::UnexpectedToken(t.to_owned()),
@@ -974,6 +1342,110 @@ fn parse_overflow<'i, 't>(
     })
 }
 
+fn parse_text_wrap<'i, 't>(
+    input: &mut Parser<'i, 't>,
+) -> Result<TextWrap, ParseError<'i, CustomParseError>> {
+    let location = input.current_source_location();
+
+    Ok(match input.next()? {
+        Token::Ident(name) => match name.as_ref() {
+            "none" => TextWrap::None,
+            "word" => TextWrap::Word,
+            "char" => TextWrap::Char,
+
+            _ => {
+                return Err(CustomParseError::InvalidStringName(name.to_owned().to_string()).into());
+            }
+        },
+
+        t => {
+            let basic_error = BasicParseError {
+                kind: BasicParseErrorKind::UnexpectedToken(t.to_owned()),
+                location,
+            };
+            return Err(basic_error.into());
+        }
+    })
+}
+
+fn parse_text_overflow<'i, 't>(
+    input: &mut Parser<'i, 't>,
+) -> Result<TextOverflow, ParseError<'i, CustomParseError>> {
+    let location = input.current_source_location();
+
+    Ok(match input.next()? {
+        Token::Ident(name) => match name.as_ref() {
+            "clip" => TextOverflow::Clip,
+            "ellipsis" => TextOverflow::Ellipsis,
+
+            _ => {
+                return Err(CustomParseError::InvalidStringName(name.to_owned().to_string()).into());
+            }
+        },
+
+        t => {
+            let basic_error = BasicParseError {
+                kind: BasicParseErrorKind::UnexpectedToken(t.to_owned()),
+                location,
+            };
+            return Err(basic_error.into());
+        }
+    })
+}
+
+// Parses the name of an image resource given as `url(name)`, `url("name")`, `"name"` or `name`, or `none` for no
+// image.
+fn parse_background_image<'i, 't>(
+    input: &mut Parser<'i, 't>,
+) -> Result<Option<String>, ParseError<'i, CustomParseError>> {
+    let location = input.current_source_location();
+
+    Ok(Some(match input.next()?.clone() {
+        Token::UnquotedUrl(url) => url.to_string(),
+
+        Token::Function(ref name) if name.eq_ignore_ascii_case("url") => {
+            input.parse_nested_block(|input| parse_string(input))?
+        }
+
+        Token::Ident(ref name) if name.eq_ignore_ascii_case("none") => return Ok(None),
+
+        Token::QuotedString(name) | Token::Ident(name) => name.to_string(),
+
+        t => {
+            let basic_error =
+                BasicParseError { kind: BasicParseErrorKind::UnexpectedToken(t), location };
+            return Err(basic_error.into());
+        }
+    }))
+}
+
+fn parse_image_fit<'i, 't>(
+    input: &mut Parser<'i, 't>,
+) -> Result<ImageFit, ParseError<'i, CustomParseError>> {
+    let location = input.current_source_location();
+
+    Ok(match input.next()? {
+        Token::Ident(name) => match name.as_ref() {
+            "fill" => ImageFit::Fill,
+            "contain" => ImageFit::Contain,
+            "cover" => ImageFit::Cover,
+            "tile" => ImageFit::Tile,
+
+            _ => {
+                return Err(CustomParseError::InvalidStringName(name.to_owned().to_string()).into());
+            }
+        },
+
+        t => {
+            let basic_error = BasicParseError {
+                kind: BasicParseErrorKind::UnexpectedToken(t.to_owned()),
+                location,
+            };
+            return Err(basic_error.into());
+        }
+    })
+}
+
 fn parse_border_corner_shape<'i, 't>(
     input: &mut Parser<'i, 't>,
 ) -> Result<BorderCornerShape, ParseError<'i, CustomParseError>> {
@@ -1128,16 +1600,326 @@ fn parse_font_size<'i, 't>(
     })
 }
 
-pub(crate) fn _parse(s: &str) -> Vec<StyleRule> {
-    let mut input = ParserInput::new(s);
+/// Parses a stylesheet into its style rules and keyframes along with any problems found while parsing.
+///
+/// Rules with invalid selectors are skipped, as are invalid or unknown declarations within a rule.
+/// The diagnostics are ordered by their location in the stylesheet.
+pub(crate) fn parse_stylesheet(stylesheet: &str) -> (ParsedStylesheet, Vec<StyleDiagnostic>) {
+    let mut input = ParserInput::new(stylesheet);
     let mut parser = Parser::new(&mut input);
-    let rule_parser = RuleParser::new();
 
-    let rules = {
-        let rule_list_parser =
-            cssparser::RuleListParser::new_for_stylesheet(&mut parser, rule_parser);
-        rule_list_parser.collect::<Vec<_>>()
-    };
+    let mut rule_list_parser =
+        RuleListParser::new_for_stylesheet(&mut parser, RuleParser::new(stylesheet));
+
+    let mut parsed = ParsedStylesheet::default();
+    let mut diagnostics = Vec::new();
+
+    while let Some(rule) = rule_list_parser.next() {
+        match rule {
+            Ok(CssRule::Style(style_rule)) => parsed.rules.push(style_rule),
+            Ok(CssRule::Keyframes(keyframes_rule)) => parsed.keyframes.push(keyframes_rule),
+            Err((error, source)) => {
+                diagnostics.push(StyleDiagnostic::new(stylesheet, &error, source, None))
+            }
+        }
+    }
+
+    diagnostics.append(&mut rule_list_parser.parser.diagnostics);
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+
+    (parsed, diagnostics)
+}
+
+// Returns the location of the start of a slice of the stylesheet.
+fn source_location(stylesheet: &str, source: &str) -> SourceLocation {
+    let offset = (source.as_ptr() as usize)
+        .checked_sub(stylesheet.as_ptr() as usize)
+        .filter(|offset| *offset <= stylesheet.len())
+        .unwrap_or(0);
+
+    let preceding = &stylesheet[..offset];
+    let line = preceding.matches('\n').count() as u32;
+    let line_start = preceding.rfind('\n').map(|index| index + 1).unwrap_or(0);
+    let column = preceding[line_start..].chars().count() as u32 + 1;
+
+    SourceLocation { line, column }
+}
+
+pub(crate) fn _parse(s: &str) -> Vec<StyleRule> {
+    parse_stylesheet(s).0.rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_background_image() {
+        let rules = _parse(
+            r#"
+            .a { background-image: url(icon); }
+            .b { background-image: url("icon"); background-fit: contain; }
+            .c { background-image: "icon"; background-fit: tile; }
+            .d { background-image: none; }
+            .e { background-image: "none"; }
+            "#,
+        );
+
+        let icon = Property::BackgroundImage(Some("icon".to_owned()));
+        assert_eq!(rules[0].properties, vec![icon.clone()]);
+        assert_eq!(
+            rules[1].properties,
+            vec![icon.clone(), Property::BackgroundFit(ImageFit::Contain)]
+        );
+        assert_eq!(rules[2].properties, vec![icon, Property::BackgroundFit(ImageFit::Tile)]);
+
+        // Only the keyword means no image, a quoted name is always the name of an image
+        assert_eq!(rules[3].properties, vec![Property::BackgroundImage(None)]);
+        assert_eq!(rules[4].properties, vec![Property::BackgroundImage(Some("none".to_owned()))]);
+    }
+
+    #[test]
+    fn test_text_layout_properties() {
+        let rules = _parse(
+            r#"
+            .a { text-wrap: word; text-overflow: ellipsis; line-height: 20px; }
+            .b { text-wrap: char; text-overflow: clip; line-height: 150%; }
+            "#,
+        );
+
+        assert_eq!(
+            rules[0].properties,
+            vec![
+                Property::TextWrap(TextWrap::Word),
+                Property::TextOverflow(TextOverflow::Ellipsis),
+                Property::LineHeight(Units::Pixels(20.0))
+            ]
+        );
+        assert_eq!(
+            rules[1].properties,
+            vec![
+                Property::TextWrap(TextWrap::Char),
+                Property::TextOverflow(TextOverflow::Clip),
+                Property::LineHeight(Units::Percentage(150.0))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let (parsed, errors) =
+            parse_stylesheet(".a { width: 10px; }\n\n  .b! { width: 10px; }\n.c { width: 10px; }");
+
+        assert_eq!(parsed.rules.len(), 2);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 3);
+        assert_eq!(errors[0].column, 3);
+        assert_eq!(errors[0].property, None);
+    }
+
+    #[test]
+    fn test_declaration_diagnostics() {
+        let (parsed, diagnostics) = parse_stylesheet(
+            ".a {\n    width: 10px;\n    height: 10pz;\n    colour: red;\n    color: reddish;\n}",
+        );
+
+        // The rule is kept with its valid declarations
+        assert_eq!(parsed.rules.len(), 1);
+        assert_eq!(parsed.rules[0].properties, vec![Property::Width(Units::Pixels(10.0))]);
+
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].line, 3);
+        assert_eq!(diagnostics[0].property.as_deref(), Some("height"));
+        assert_eq!(diagnostics[0].reason, "Invalid length units: pz");
+        assert_eq!(
+            diagnostics[1],
+            StyleDiagnostic {
+                line: 4,
+                column: 5,
+                property: Some("colour".to_owned()),
+                reason: "Unrecognised property: colour".to_owned(),
+            }
+        );
+        assert_eq!(diagnostics[2].line, 5);
+        assert_eq!(diagnostics[2].property.as_deref(), Some("color"));
+        assert_eq!(diagnostics[2].reason, "Unrecognised color name: reddish");
+    }
+
+    #[test]
+    fn test_keyframes() {
+        let (parsed, diagnostics) = parse_stylesheet(
+            r#"
+            @keyframes grow {
+                from { width: 100px; }
+                50%, 75% { width: 150px; opacity: 0.5; }
+                to { width: 200px; }
+            }
+            .a { animation: grow 300ms ease-in infinite alternate; }
+            "#,
+        );
+
+        assert!(diagnostics.is_empty());
+        assert_eq!(parsed.rules.len(), 1);
+        assert_eq!(parsed.keyframes.len(), 1);
+
+        let keyframes = &parsed.keyframes[0];
+        assert_eq!(keyframes.name, "grow");
+        assert_eq!(
+            keyframes.keyframes.iter().map(|(time, _)| *time).collect::<Vec<_>>(),
+            vec![0.0, 0.5, 0.75, 1.0]
+        );
+        assert_eq!(
+            keyframes.keyframes[1].1,
+            vec![Property::Width(Units::Pixels(150.0)), Property::Opacity(0.5)]
+        );
+
+        assert_eq!(
+            parsed.rules[0].properties,
+            vec![Property::Animation(vec![AnimationDescription {
+                name: "grow".to_owned(),
+                duration: 0.3,
+                delay: 0.0,
+                timing_function: TimingFunction::EASE_IN,
+                iterations: f32::INFINITY,
+                direction: AnimationDirection::Alternate,
+                fill_mode: AnimationFillMode::None,
+            }])]
+        );
+    }
+
+    #[test]
+    fn test_keyframes_diagnostics() {
+        let (parsed, diagnostics) = parse_stylesheet(
+            "@keyframes fade {\n    from { opacity: 0; }\n    half { opacity: 0.5; }\n    to { opacity: 1; }\n}\n.a { animation: 1s; }",
+        );
+
+        // The invalid keyframe is skipped
+        assert_eq!(parsed.keyframes[0].keyframes.len(), 2);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].line, 3);
+        assert_eq!(diagnostics[1].line, 6);
+        assert_eq!(diagnostics[1].property.as_deref(), Some("animation"));
+    }
+
+    #[test]
+    fn test_keyframes_unanimatable_property() {
+        let (parsed, diagnostics) =
+            parse_stylesheet("@keyframes a {\n    to { width: 10px; display: none; }\n}");
+
+        // The property which can't be animated is skipped
+        assert_eq!(parsed.keyframes[0].keyframes[0].1.len(), 1);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 2);
+        assert_eq!(diagnostics[0].property.as_deref(), Some("display"));
+        assert_eq!(diagnostics[0].reason, "Property cannot be animated: display");
+    }
+
+    #[test]
+    fn test_timing_functions() {
+        let rules = _parse(
+            r#"
+            .a { transition: width 200ms ease-in-out 0.1s; }
+            .b { transition: height 1 cubic-bezier(0.1, 0.7, 1.0, 0.1), left 2s steps(4, start); }
+            .c { animation: bounce 1s spring(1, 200, 12) 2.5 both; }
+            .d { animation: fade 1s steps(3) reverse backwards; }
+            "#,
+        );
+
+        let mut transition = Transition::new();
+        transition.property = "width".to_owned();
+        transition.duration = 0.2;
+        transition.delay = 0.1;
+        transition.timing_function = TimingFunction::EASE_IN_OUT;
+        assert_eq!(rules[0].properties, vec![Property::Transition(vec![transition])]);
+
+        match &rules[1].properties[0] {
+            Property::Transition(transitions) => {
+                assert_eq!(transitions.len(), 2);
+                assert_eq!(transitions[0].duration, 1.0);
+                assert_eq!(
+                    transitions[0].timing_function,
+                    TimingFunction::CubicBezier(0.1, 0.7, 1.0, 0.1)
+                );
+                assert_eq!(transitions[1].property, "left");
+                assert_eq!(
+                    transitions[1].timing_function,
+                    TimingFunction::Steps(4, StepPosition::Start)
+                );
+            }
 
-    rules.into_iter().filter_map(|rule| rule.ok()).collect()
+            property => panic!("Unexpected property: {:?}", property),
+        }
+
+        match &rules[2].properties[0] {
+            Property::Animation(animations) => {
+                assert_eq!(
+                    animations[0].timing_function,
+                    TimingFunction::Spring {
+                        mass: 1.0,
+                        stiffness: 200.0,
+                        damping: 12.0,
+                        velocity: 0.0
+                    }
+                );
+                assert_eq!(animations[0].iterations, 2.5);
+                assert_eq!(animations[0].fill_mode, AnimationFillMode::Both);
+            }
+
+            property => panic!("Unexpected property: {:?}", property),
+        }
+
+        match &rules[3].properties[0] {
+            Property::Animation(animations) => {
+                assert_eq!(animations[0].name, "fade");
+                assert_eq!(
+                    animations[0].timing_function,
+                    TimingFunction::Steps(3, StepPosition::End)
+                );
+                assert_eq!(animations[0].direction, AnimationDirection::Reverse);
+                assert_eq!(animations[0].fill_mode, AnimationFillMode::Backwards);
+            }
+
+            property => panic!("Unexpected property: {:?}", property),
+        }
+    }
+
+    #[test]
+    fn test_invalid_timing_functions() {
+        let (parsed, diagnostics) = parse_stylesheet(
+            ".a { transition: width 1s cubic-bezier(2, 0, 1, 1); animation: fade 1s steps(0); }",
+        );
+
+        assert!(parsed.rules[0].properties.is_empty());
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].property.as_deref(), Some("transition"));
+        assert_eq!(diagnostics[1].property.as_deref(), Some("animation"));
+    }
+
+    #[test]
+    fn test_negative_animation_times() {
+        let (parsed, diagnostics) =
+            parse_stylesheet(".a { animation: fade 1s -0.5s; }\n.b { animation: fade -1s; }");
+
+        assert!(parsed.rules.iter().all(|rule| rule.properties.is_empty()));
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].reason, "Invalid value: time out of range: -0.5s");
+        assert_eq!(diagnostics[1].reason, "Invalid value: time out of range: -1s");
+    }
+
+    #[test]
+    fn test_negative_transition_times() {
+        let (parsed, diagnostics) = parse_stylesheet(
+            ".a {\n    transition: width -1s;\n}\n.b { transition: width 1s -0.5s; }",
+        );
+
+        assert!(parsed.rules.iter().all(|rule| rule.properties.is_empty()));
+        assert_eq!(diagnostics.len(), 2);
+
+        // The location is of the time rather than the declaration
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (2, 23));
+        assert_eq!(diagnostics[0].reason, "Invalid value: time out of range: -1s");
+        assert_eq!((diagnostics[1].line, diagnostics[1].column), (4, 27));
+    }
 }
@@ -2,8 +2,11 @@ use femtovg::{Align, Baseline, Paint};
 use morphorm::Units;
 
 use crate::{
+    line_height,
     style::{Overflow, PropGet, Selector, SelectorRelation},
-    BoundingBox, Context, Display, Entity, FontOrId, PseudoClass, Rule, Tree, TreeExt, Visibility,
+    text::RichTextLayout,
+    text_lines, text_width, BoundingBox, Context, Display, Entity, FontOrId, PseudoClass, Rule,
+    TextOverflow, Tree, TreeExt, Visibility,
 };
 
 pub fn apply_z_ordering(cx: &mut Context, tree: &Tree) {
@@ -30,6 +33,13 @@ pub fn apply_clipping(cx: &mut Context, tree: &Tree) {
             continue;
         }
 
+        // Windows are clipped to their own bounds rather than the bounds of the main window
+        if cx.windows.contains_key(&entity) {
+            let bounds = cx.cache.get_bounds(entity);
+            cx.cache.set_clip_region(entity, bounds);
+            continue;
+        }
+
         let parent = tree.get_parent(entity).unwrap();
 
         let parent_clip_region = cx.cache.get_clip_region(parent);
@@ -41,7 +51,7 @@ pub fn apply_clipping(cx: &mut Context, tree: &Tree) {
         // parent_clip_region.w;
         // parent_clip_region.h;
 
-        let root_clip_region = cx.cache.get_clip_region(Entity::root());
+        let window_clip_region = cx.cache.get_clip_region(cx.window_of(entity));
 
         let overflow = cx.style.overflow.get(entity).cloned().unwrap_or_default();
 
@@ -75,7 +85,7 @@ pub fn apply_clipping(cx: &mut Context, tree: &Tree) {
             //    cx.cache.set_clip_region(entity, parent_clip_region);
             //}
         } else {
-            cx.cache.set_clip_region(entity, root_clip_region);
+            cx.cache.set_clip_region(entity, window_clip_region);
         }
 
         //let clip_region = cx.cache.get_clip_region(entity);
@@ -135,6 +145,15 @@ pub fn apply_text_constraints(cx: &mut Context, tree: &Tree) {
         let desired_width = cx.style.width.get(entity).cloned().unwrap_or_default();
         let desired_height = cx.style.height.get(entity).cloned().unwrap_or_default();
 
+        if let Some(spans) = cx.style.rich_text.get(entity) {
+            if desired_width == Units::Auto || desired_height == Units::Auto {
+                let layout = RichTextLayout::new(cx, entity, spans);
+                set_text_size(cx, entity, layout.width, layout.height);
+            }
+
+            continue;
+        }
+
         if cx.style.text.get(entity).is_some()
             && (desired_width == Units::Auto || desired_height == Units::Auto)
         {
@@ -250,33 +269,103 @@ pub fn apply_text_constraints(cx: &mut Context, tree: &Tree) {
             paint.set_text_baseline(baseline);
 
             if let Some(text) = cx.style.text.get(entity) {
-                if let Ok(text_metrics) = cx.text_context.measure_text(x, y, text, paint) {
-                    // Add an extra pixel to account to AA
-                    let text_width = text_metrics.width().round() + 1.0;
-                    let text_height = text_metrics.height().round() + 1.0;
-
-                    if cx.style.width.get(entity) == Some(&Units::Auto) {
-                        //let previous_min_width = entity.get_min_width(cx).value_or(0.0, 0.0);
-                        if entity.get_min_width(cx) != Units::Pixels(text_width) {
-                            cx.style.min_width.insert(entity, Units::Pixels(text_width));
-                            cx.style.needs_relayout = true;
-                            cx.style.needs_redraw = true;
-                        }
-                    }
-
-                    if cx.style.height.get(entity) == Some(&Units::Auto) {
-                        if entity.get_min_height(cx) != Units::Pixels(text_height) {
-                            cx.style.min_height.insert(entity, Units::Pixels(text_height));
-                            cx.style.needs_relayout = true;
-                            cx.style.needs_redraw = true;
-                        }
-                    }
+                let text_wrap = cx.style.text_wrap.get(entity).cloned().unwrap_or_default();
+                let text_overflow = cx.style.text_overflow.get(entity).cloned().unwrap_or_default();
+                let line_height_units =
+                    cx.style.line_height.get(entity).cloned().unwrap_or_default();
+
+                // Text is broken against the width of the parent when the entity is sized by its text. A parent
+                // which is sized by its children, or which hasn't been laid out yet, has no width to break
+                // against, so the text is measured unwrapped.
+                let parent_desired_width = cx.style.width.get(parent).cloned().unwrap_or_default();
+
+                let max_width = if desired_width == Units::Auto
+                    && (parent_desired_width == Units::Auto || parent_width <= 0.0)
+                {
+                    f32::INFINITY
+                } else if desired_width == Units::Auto {
+                    let parent_border_width = cx
+                        .style
+                        .border_width
+                        .get(parent)
+                        .cloned()
+                        .unwrap_or_default()
+                        .value_or(parent_width, 0.0);
+
+                    let parent_child_left =
+                        cx.style.child_left.get(parent).cloned().unwrap_or_default();
+                    let parent_child_right =
+                        cx.style.child_right.get(parent).cloned().unwrap_or_default();
+
+                    parent_width
+                        - 2.0 * parent_border_width
+                        - parent_child_left.value_or(parent_width, 0.0)
+                        - parent_child_right.value_or(parent_width, 0.0)
+                        - entity.get_left(cx).value_or(parent_width, 0.0)
+                        - entity.get_right(cx).value_or(parent_width, 0.0)
+                } else {
+                    width
+                        - 2.0 * border_width
+                        - child_left.value_or(width, 0.0)
+                        - child_right.value_or(width, 0.0)
+                };
+
+                let lines = text_lines(&cx.text_context, text, paint, text_wrap, max_width);
+
+                let text_size = if lines.len() == 1 && line_height_units == Units::Auto {
+                    cx.text_context
+                        .measure_text(x, y, text, paint)
+                        .map(|text_metrics| (text_metrics.width(), text_metrics.height()))
+                        .ok()
+                } else {
+                    let max_line_width = lines
+                        .iter()
+                        .map(|line| text_width(&cx.text_context, &text[line.clone()], paint))
+                        .fold(0.0, f32::max);
+                    let line_height = line_height(&cx.text_context, paint, line_height_units);
+
+                    Some((max_line_width, lines.len() as f32 * line_height))
+                };
+
+                if let Some((text_width, text_height)) = text_size {
+                    // An ellipsized text is no wider than the space it is broken against
+                    let text_width = if text_overflow == TextOverflow::Ellipsis {
+                        text_width.min(max_width.max(0.0))
+                    } else {
+                        text_width
+                    };
+
+                    set_text_size(cx, entity, text_width, text_height);
                 }
             }
         }
     }
 }
 
+// Sets the minimum size of an entity which is sized by its text to the size of the text.
+fn set_text_size(cx: &mut Context, entity: Entity, text_width: f32, text_height: f32) {
+    // Add an extra pixel to account to AA
+    let text_width = text_width.round() + 1.0;
+    let text_height = text_height.round() + 1.0;
+
+    if cx.style.width.get(entity) == Some(&Units::Auto) {
+        //let previous_min_width = entity.get_min_width(cx).value_or(0.0, 0.0);
+        if entity.get_min_width(cx) != Units::Pixels(text_width) {
+            cx.style.min_width.insert(entity, Units::Pixels(text_width));
+            cx.style.needs_relayout = true;
+            cx.style.needs_redraw = true;
+        }
+    }
+
+    if cx.style.height.get(entity) == Some(&Units::Auto) {
+        if entity.get_min_height(cx) != Units::Pixels(text_height) {
+            cx.style.min_height.insert(entity, Units::Pixels(text_height));
+            cx.style.needs_relayout = true;
+            cx.style.needs_redraw = true;
+        }
+    }
+}
+
 pub fn apply_inline_inheritance(cx: &mut Context, tree: &Tree) {
     for entity in tree.into_iter() {
         if let Some(parent) = entity.parent(tree) {
@@ -631,6 +720,10 @@ pub fn apply_styles(cx: &mut Context, tree: &Tree) {
             should_redraw = true;
         }
 
+        if cx.style.background_fit.link(entity, &matched_rules) {
+            should_redraw = true;
+        }
+
         // Font
         if cx.style.font_color.link(entity, &matched_rules) {
             //println!("43");
@@ -647,6 +740,22 @@ pub fn apply_styles(cx: &mut Context, tree: &Tree) {
             should_redraw = true;
         }
 
+        // Text layout
+        if cx.style.text_wrap.link(entity, &matched_rules) {
+            should_relayout = true;
+            should_redraw = true;
+        }
+
+        if cx.style.text_overflow.link(entity, &matched_rules) {
+            should_relayout = true;
+            should_redraw = true;
+        }
+
+        if cx.style.line_height.link(entity, &matched_rules) {
+            should_relayout = true;
+            should_redraw = true;
+        }
+
         // Outer Shadow
         if cx.style.outer_shadow_h_offset.link(entity, &matched_rules) {
             //println!("45");
@@ -723,6 +832,23 @@ pub fn apply_styles(cx: &mut Context, tree: &Tree) {
             should_redraw = true;
         }
 
+        // Keyframe Animations
+        let previous_animations = cx.style.animations.get(entity).cloned().unwrap_or_default();
+        if cx.style.animations.link(entity, &matched_rules) {
+            for animation in previous_animations.iter() {
+                cx.style.stop_keyframes(entity, &animation.name);
+            }
+
+            if let Some(animations) = cx.style.animations.get(entity).cloned() {
+                for animation in animations.iter() {
+                    cx.style.play_keyframes(entity, animation);
+                }
+            }
+
+            should_relayout = true;
+            should_redraw = true;
+        }
+
         if should_relayout {
             cx.style.needs_relayout = true;
         }
@@ -0,0 +1,345 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Wake, Waker};
+use std::thread;
+
+use crate::{Entity, Event};
+
+// The number of worker threads which run the tasks of an application.
+const NUM_WORKERS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+// The shared state of a task, used to cancel it.
+#[derive(Default)]
+struct TaskState {
+    cancelled: AtomicBool,
+    // The waker of the future of the task, if it is an unfinished future
+    waker: Mutex<Option<Waker>>,
+}
+
+impl TaskState {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+
+        // Queue the future so that a worker drops it
+        if let Some(waker) = self.waker.lock().expect("Failed to lock task").as_ref() {
+            waker.wake_by_ref();
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+// The result of a finished task, which is None if the task was cancelled or panicked.
+struct TaskResult {
+    state: Arc<TaskState>,
+    event: Option<Event>,
+}
+
+// Sends the results of finished tasks to the application and wakes the event loop.
+#[derive(Clone)]
+struct ResultSender {
+    results: Arc<Mutex<Sender<TaskResult>>>,
+    waker: Arc<Mutex<Option<Box<dyn Fn() + Send>>>>,
+}
+
+impl ResultSender {
+    fn send(&self, state: Arc<TaskState>, event: Option<Event>) {
+        let _ = self
+            .results
+            .lock()
+            .expect("Failed to lock task results")
+            .send(TaskResult { state, event });
+
+        if let Some(waker) = self.waker.lock().expect("Failed to lock task waker").as_ref() {
+            (waker)();
+        }
+    }
+}
+
+// A spawned future, which is queued to be polled by a worker thread each time it is woken.
+struct FutureTask {
+    // The future is None once it has finished or been cancelled
+    future: Mutex<Option<Pin<Box<dyn Future<Output = Option<Event>> + Send>>>>,
+    state: Arc<TaskState>,
+    jobs: Mutex<Sender<Job>>,
+    results: ResultSender,
+}
+
+impl FutureTask {
+    // Adds the task to the queue of jobs run by the worker threads.
+    fn schedule(self: &Arc<Self>) {
+        let task = self.clone();
+        // Fails only if the pool and its workers have been dropped
+        let _ = self
+            .jobs
+            .lock()
+            .expect("Failed to lock task queue")
+            .send(Box::new(move || task.poll()));
+    }
+
+    // Polls the future once, sending the result of the task if it has finished or been cancelled.
+    fn poll(self: Arc<Self>) {
+        let mut slot = self.future.lock().expect("Failed to lock task");
+
+        // The task was woken after it finished
+        let future = match slot.as_mut() {
+            Some(future) => future,
+            None => return,
+        };
+
+        let poll = if self.state.is_cancelled() {
+            Poll::Ready(None)
+        } else {
+            let waker = Waker::from(self.clone());
+            let mut context = std::task::Context::from_waker(&waker);
+
+            // A panicking future still sends a result so that it isn't counted as pending forever
+            catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(&mut context)))
+                .unwrap_or(Poll::Ready(None))
+        };
+
+        if let Poll::Ready(event) = poll {
+            *slot = None;
+            drop(slot);
+
+            *self.state.waker.lock().expect("Failed to lock task") = None;
+            self.results.send(self.state.clone(), event);
+        }
+    }
+}
+
+impl Wake for FutureTask {
+    fn wake(self: Arc<Self>) {
+        self.schedule();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.schedule();
+    }
+}
+
+/// A handle to a spawned task, which can be used to cancel it.
+///
+/// Tasks are cancelled automatically when the entity which spawned them is removed.
+#[derive(Clone)]
+pub struct TaskHandle {
+    state: Arc<TaskState>,
+}
+
+impl TaskHandle {
+    /// Cancels the task so that its result is never sent.
+    ///
+    /// A future is dropped without being polled again, but a blocking task runs until it returns.
+    pub fn cancel(&self) {
+        self.state.cancel();
+    }
+
+    /// Returns true if the task has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.is_cancelled()
+    }
+}
+
+/// Runs the tasks spawned with `cx.spawn()` and `cx.spawn_blocking()` on a pool of worker threads.
+///
+/// A future is only run on a worker while it is being polled. When it returns `Poll::Pending` it is queued again
+/// by its waker, so futures which wait on I/O or timers don't hold on to a worker.
+///
+/// The result of each task is sent back to the application, which adds it to the event queue when it polls
+/// the pool at the start of each frame. Backends which wait for events should call `set_waker()` with a
+/// function which wakes the event loop, which is called from a worker thread whenever a task finishes.
+pub struct TaskPool {
+    // Sends jobs to the worker threads, which are started when the first task is spawned
+    jobs: Option<Sender<Job>>,
+    results: Receiver<TaskResult>,
+    result_sender: ResultSender,
+    // The running tasks of each entity, which are cancelled when the entity is removed
+    tasks: HashMap<Entity, Vec<Arc<TaskState>>>,
+    // The number of spawned tasks whose results haven't been received
+    pending: usize,
+}
+
+impl Default for TaskPool {
+    fn default() -> Self {
+        let (result_sender, results) = channel();
+
+        Self {
+            jobs: None,
+            results,
+            result_sender: ResultSender {
+                results: Arc::new(Mutex::new(result_sender)),
+                waker: Arc::new(Mutex::new(None)),
+            },
+            tasks: HashMap::new(),
+            pending: 0,
+        }
+    }
+}
+
+impl Drop for TaskPool {
+    fn drop(&mut self) {
+        // Unfinished futures are queued to be dropped so that the workers stop once the queue is empty
+        for tasks in self.tasks.values() {
+            for task in tasks {
+                task.cancel();
+            }
+        }
+    }
+}
+
+impl TaskPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the function which is called from a worker thread when a task finishes, to wake the event loop.
+    pub fn set_waker<F: 'static + Fn() + Send>(&mut self, waker: F) {
+        *self.result_sender.waker.lock().expect("Failed to lock task waker") =
+            Some(Box::new(waker));
+    }
+
+    /// Returns the number of spawned tasks whose results haven't been received yet.
+    pub fn num_pending(&self) -> usize {
+        self.pending
+    }
+
+    /// Cancels the tasks spawned by the given entity.
+    pub fn cancel(&mut self, entity: Entity) {
+        if let Some(tasks) = self.tasks.remove(&entity) {
+            for task in tasks {
+                task.cancel();
+            }
+        }
+    }
+
+    // Adds a task spawned by the given entity to the pending tasks.
+    fn add_task(&mut self, entity: Entity) -> Arc<TaskState> {
+        let state = Arc::new(TaskState::default());
+        self.tasks.entry(entity).or_default().push(state.clone());
+        self.pending += 1;
+
+        state
+    }
+
+    // Runs a blocking task on a worker thread, sending the event it returns to the application.
+    pub(crate) fn spawn_blocking<F>(&mut self, entity: Entity, task: F) -> TaskHandle
+    where
+        F: 'static + FnOnce() -> Option<Event> + Send,
+    {
+        let state = self.add_task(entity);
+
+        let task_state = state.clone();
+        let result_sender = self.result_sender.clone();
+
+        let job = Box::new(move || {
+            let event = if task_state.is_cancelled() {
+                None
+            } else {
+                // A panicking task still sends a result so that it isn't counted as pending forever
+                catch_unwind(AssertUnwindSafe(task)).unwrap_or(None)
+            };
+
+            result_sender.send(task_state, event);
+        });
+
+        self.jobs().send(job).expect("Failed to spawn task");
+
+        TaskHandle { state }
+    }
+
+    // Polls a future on the worker threads whenever it is woken, sending the event it returns to the application.
+    pub(crate) fn spawn_future<F>(&mut self, entity: Entity, future: F) -> TaskHandle
+    where
+        F: 'static + Future<Output = Option<Event>> + Send,
+    {
+        let state = self.add_task(entity);
+
+        let task = Arc::new(FutureTask {
+            future: Mutex::new(Some(Box::pin(future))),
+            state: state.clone(),
+            jobs: Mutex::new(self.jobs().clone()),
+            results: self.result_sender.clone(),
+        });
+
+        *state.waker.lock().expect("Failed to lock task") = Some(Waker::from(task.clone()));
+        task.schedule();
+
+        TaskHandle { state }
+    }
+
+    // Returns the sender of jobs to the worker threads, starting the workers if they haven't been started.
+    fn jobs(&mut self) -> &Sender<Job> {
+        self.jobs.get_or_insert_with(|| {
+            let (sender, receiver) = channel::<Job>();
+            let receiver = Arc::new(Mutex::new(receiver));
+
+            for index in 0..NUM_WORKERS {
+                let receiver = receiver.clone();
+                thread::Builder::new()
+                    .name(format!("vizia-worker-{}", index))
+                    .spawn(move || loop {
+                        // The lock is released before the job is run
+                        let job = receiver.lock().expect("Failed to lock task queue").recv();
+                        match job {
+                            Ok(job) => job(),
+                            // The pool has been dropped
+                            Err(_) => break,
+                        }
+                    })
+                    .expect("Failed to start worker thread");
+            }
+
+            sender
+        })
+    }
+
+    // Adds the events of the finished tasks to the queue. If `wait` is true, blocks until there are no pending tasks.
+    pub(crate) fn poll(&mut self, event_queue: &mut VecDeque<Event>, wait: bool) {
+        loop {
+            let result = if wait && self.num_pending() > 0 {
+                self.results.recv().ok()
+            } else {
+                self.results.try_recv().ok()
+            };
+
+            let result = match result {
+                Some(result) => result,
+                None => return,
+            };
+
+            self.pending -= 1;
+
+            let mut finished_entity = None;
+            for (entity, tasks) in self.tasks.iter_mut() {
+                if let Some(index) = tasks.iter().position(|task| Arc::ptr_eq(task, &result.state))
+                {
+                    tasks.remove(index);
+                    finished_entity = Some(*entity);
+                    break;
+                }
+            }
+
+            // Results of tasks spawned by removed entities are dropped
+            if let Some(entity) = finished_entity {
+                if self.tasks.get(&entity).map_or(false, |tasks| tasks.is_empty()) {
+                    self.tasks.remove(&entity);
+                }
+
+                if !result.state.is_cancelled() {
+                    if let Some(event) = result.event {
+                        event_queue.push_back(event);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -12,9 +12,12 @@ pub trait EditableText {
     fn prev_grapheme_offset(&self, current: usize) -> Option<usize>;
     /// Get the next grapheme offset from the current offset if it exists
     fn next_grapheme_offset(&self, current: usize) -> Option<usize>;
-    /// Get the prev word offset from the current offset if it exists
+    /// Get the offset of the start of the word before the current offset if it exists
     fn prev_word_offset(&self, current: usize) -> Option<usize>;
+    /// Get the offset of the end of the word after the current offset if it exists
     fn next_word_offset(&self, current: usize) -> Option<usize>;
+    /// Get the range of the word at the current offset, or of the whitespace or punctuation between words
+    fn word_range(&self, current: usize) -> Range<usize>;
 
     // fn prev_codepoint_offset(&self, from: usize) -> Option<usize>;
     // fn next_codepoint_offset(&self, from: usize) -> Option<usize>;
@@ -57,38 +60,48 @@ impl EditableText for String {
     //     }
     // }
 
+    // Words are the segments between unicode word boundaries which aren't whitespace
     fn prev_word_offset(&self, from: usize) -> Option<usize> {
-        let mut offset = from;
-        let mut passed_alphanumeric = false;
-        for prev_grapheme in self.get(0..from)?.graphemes(true).rev() {
-            let is_alphanumeric = prev_grapheme.chars().next()?.is_alphanumeric();
-            if is_alphanumeric {
-                passed_alphanumeric = true;
-            } else if passed_alphanumeric {
-                return Some(offset);
-            }
-            offset -= prev_grapheme.len();
-
-            if offset == 0 {
-                return Some(0);
-            }
+        if from == 0 {
+            return None;
         }
-        None
+
+        let from = from.min(self.len());
+        self.split_word_bound_indices()
+            .filter(|(index, word)| *index < from && !word.trim().is_empty())
+            .last()
+            .map(|(index, _)| index)
+            .or(Some(0))
     }
 
     fn next_word_offset(&self, from: usize) -> Option<usize> {
-        let mut offset = from;
-        let mut passed_alphanumeric = false;
-        for next_grapheme in self.get(from..)?.graphemes(true) {
-            let is_alphanumeric = next_grapheme.chars().next()?.is_alphanumeric();
-            if is_alphanumeric {
-                passed_alphanumeric = true;
-            } else if passed_alphanumeric {
-                return Some(offset);
+        if from >= self.len() {
+            return None;
+        }
+
+        self.split_word_bound_indices()
+            .map(|(index, word)| (index + word.len(), word))
+            .find(|(end, word)| *end > from && !word.trim().is_empty())
+            .map(|(end, _)| end)
+            .or(Some(self.len()))
+    }
+
+    fn word_range(&self, from: usize) -> Range<usize> {
+        let from = from.min(self.len());
+        let mut last = from..from;
+
+        for (index, word) in self.split_word_bound_indices() {
+            let range = index..index + word.len();
+
+            // A caret at the end of a word is in the word rather than what follows it
+            if from < range.end || (from == range.end && word.chars().any(char::is_alphanumeric)) {
+                return range;
             }
-            offset += next_grapheme.len();
+
+            last = range;
         }
-        Some(self.len())
+
+        last
     }
 }
 
@@ -100,5 +113,28 @@ mod tests {
     fn prev_word_offset() {
         let a = String::from("This is some text");
         assert_eq!(Some(0), a.prev_word_offset(5));
+        assert_eq!(Some(8), a.prev_word_offset(12));
+        assert_eq!(Some(5), a.prev_word_offset(8));
+        assert_eq!(None, a.prev_word_offset(0));
+    }
+
+    #[test]
+    fn next_word_offset() {
+        let a = String::from("This is some text");
+        assert_eq!(Some(4), a.next_word_offset(0));
+        assert_eq!(Some(7), a.next_word_offset(4));
+        assert_eq!(Some(17), a.next_word_offset(13));
+        assert_eq!(None, a.next_word_offset(17));
+    }
+
+    #[test]
+    fn word_range() {
+        let a = String::from("hello, wide world");
+        assert_eq!(0..5, a.word_range(2));
+        assert_eq!(0..5, a.word_range(5));
+        assert_eq!(6..7, a.word_range(6));
+        assert_eq!(7..11, a.word_range(7));
+        assert_eq!(12..17, a.word_range(17));
+        assert_eq!(0..0, String::new().word_range(0));
     }
 }
@@ -0,0 +1,345 @@
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::TextWrap;
+
+/// A single line of a [TextLayout].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLine {
+    /// The byte range of the line within the text, not including the line break which ends it.
+    pub range: Range<usize>,
+    /// The byte indices at which the caret can be placed on the line, with their offsets from the start of the line.
+    ///
+    /// The end of a line which is soft-wrapped is the start of the next line, so it is left out.
+    pub carets: Vec<(usize, f32)>,
+    /// The width of the text on the line.
+    pub width: f32,
+}
+
+impl TextLine {
+    /// Returns the offset of the caret at the given byte index from the start of the line.
+    pub fn caret_x(&self, index: usize) -> f32 {
+        self.carets
+            .iter()
+            .take_while(|(caret, _)| *caret <= index)
+            .last()
+            .or_else(|| self.carets.first())
+            .map(|(_, x)| *x)
+            .unwrap_or_default()
+    }
+
+    /// Returns the byte index of the caret position closest to the given offset from the start of the line.
+    pub fn hit(&self, x: f32) -> usize {
+        self.carets
+            .iter()
+            .min_by(|(_, a), (_, b)| {
+                (a - x).abs().partial_cmp(&(b - x).abs()).unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|(index, _)| *index)
+            .unwrap_or(self.range.start)
+    }
+
+    /// Returns the byte index of the last caret position on the line.
+    pub fn end(&self) -> usize {
+        self.carets.last().map(|(index, _)| *index).unwrap_or(self.range.end)
+    }
+}
+
+// A grapheme of a paragraph with its byte range in the text and its horizontal extent.
+struct Grapheme {
+    range: Range<usize>,
+    left: f32,
+    right: f32,
+}
+
+/// The lines of a text, which is broken at newlines and optionally wrapped to a maximum width.
+///
+/// The layout is used to break the text of views into lines, to position the caret and selection of a multi-line
+/// `Textbox` and to move the caret between lines. Line `n` starts `n * line_height` from the top of the text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextLayout {
+    pub lines: Vec<TextLine>,
+    pub line_height: f32,
+}
+
+impl TextLayout {
+    /// Lays out a text, wrapping it between words to fit `max_width` if it is given.
+    ///
+    /// The `measure` closure returns the byte index, x position and width of each glyph of a single line of text.
+    /// Words which are wider than `max_width` on their own are broken between graphemes.
+    pub fn new<F>(text: &str, max_width: Option<f32>, line_height: f32, mut measure: F) -> Self
+    where
+        F: FnMut(&str) -> Vec<(usize, f32, f32)>,
+    {
+        Self::with_wrap(text, TextWrap::Word, max_width, line_height, |range| measure(&text[range]))
+    }
+
+    /// Lays out a text, breaking lines which are wider than `max_width` as given by `wrap`.
+    ///
+    /// The `measure` closure is given the byte range of a single line of the text, and returns the byte index
+    /// relative to the start of the line, x position and width of each of its glyphs. This allows the line to be
+    /// measured in pieces, such as the spans of a rich text.
+    pub fn with_wrap<F>(
+        text: &str,
+        wrap: TextWrap,
+        max_width: Option<f32>,
+        line_height: f32,
+        mut measure: F,
+    ) -> Self
+    where
+        F: FnMut(Range<usize>) -> Vec<(usize, f32, f32)>,
+    {
+        let max_width = if wrap == TextWrap::None { None } else { max_width };
+
+        let mut lines = Vec::new();
+        let mut start = 0;
+
+        for paragraph in text.split('\n') {
+            let end = start + paragraph.len();
+            let paragraph = paragraph.strip_suffix('\r').unwrap_or(paragraph);
+
+            layout_paragraph(paragraph, start, wrap, max_width, &mut measure, &mut lines);
+
+            start = end + 1;
+        }
+
+        Self { lines, line_height }
+    }
+
+    /// Returns the index of the line containing the caret at the given byte index.
+    pub fn line_of(&self, index: usize) -> usize {
+        self.lines.iter().rposition(|line| line.range.start <= index).unwrap_or(0)
+    }
+
+    /// Returns the position of the caret at the given byte index, relative to the top left of the text.
+    ///
+    /// The returned y position is the top of the line containing the caret.
+    pub fn caret_position(&self, index: usize) -> (f32, f32) {
+        let line = self.line_of(index);
+        let x = self.lines.get(line).map(|text_line| text_line.caret_x(index)).unwrap_or_default();
+
+        (x, line as f32 * self.line_height)
+    }
+
+    /// Returns the byte index of the caret position closest to a point relative to the top left of the text.
+    pub fn hit(&self, x: f32, y: f32) -> usize {
+        let line = if y <= 0.0 || self.line_height <= 0.0 {
+            0
+        } else {
+            ((y / self.line_height) as usize).min(self.lines.len().saturating_sub(1))
+        };
+
+        self.lines.get(line).map(|text_line| text_line.hit(x)).unwrap_or_default()
+    }
+
+    /// Returns the rectangles covering the selected text on each line as `(x, y, width)`, each one line high.
+    pub fn selection_rects(&self, range: Range<usize>) -> Vec<(f32, f32, f32)> {
+        let mut rects = Vec::new();
+
+        if range.is_empty() {
+            return rects;
+        }
+
+        for (index, line) in self.lines.iter().enumerate() {
+            let next_start = self.lines.get(index + 1).map(|next| next.range.start);
+
+            // Skip lines which end before the selection starts or start after it ends
+            if next_start.map_or(false, |next_start| next_start <= range.start)
+                || line.range.start >= range.end
+            {
+                continue;
+            }
+
+            let left = line.caret_x(range.start.max(line.range.start));
+            let right = if next_start.map_or(false, |next_start| range.end >= next_start) {
+                line.width
+            } else {
+                line.caret_x(range.end)
+            };
+
+            rects.push((left, index as f32 * self.line_height, (right - left).max(0.0)));
+        }
+
+        rects
+    }
+}
+
+// Lays out a paragraph of text without newlines, adding its lines to `lines`.
+fn layout_paragraph<F>(
+    paragraph: &str,
+    offset: usize,
+    wrap: TextWrap,
+    max_width: Option<f32>,
+    measure: &mut F,
+    lines: &mut Vec<TextLine>,
+) where
+    F: FnMut(Range<usize>) -> Vec<(usize, f32, f32)>,
+{
+    let mut glyphs =
+        if paragraph.is_empty() { Vec::new() } else { measure(offset..offset + paragraph.len()) };
+
+    // The graphemes and glyphs are walked together in order of byte index
+    glyphs.sort_by_key(|(byte_index, _, _)| *byte_index);
+    let mut glyphs = glyphs.into_iter().peekable();
+
+    // The extent of each grapheme is the extent of the glyphs which start within it
+    let mut graphemes: Vec<Grapheme> = Vec::new();
+    for (index, grapheme) in paragraph.grapheme_indices(true) {
+        let range = index..index + grapheme.len();
+        let mut extent: Option<(f32, f32)> = None;
+        while let Some((_, x, width)) = glyphs.next_if(|(byte_index, _, _)| *byte_index < range.end)
+        {
+            extent = Some(match extent {
+                Some((left, right)) => (left.min(x), right.max(x + width)),
+                None => (x, x + width),
+            });
+        }
+
+        // Graphemes without glyphs take no space
+        let previous = graphemes.last().map(|grapheme| grapheme.right).unwrap_or_default();
+        let (left, right) = extent.unwrap_or((previous, previous));
+
+        graphemes.push(Grapheme { range: offset + range.start..offset + range.end, left, right });
+    }
+
+    let fits = |from: usize, to: usize| match max_width {
+        Some(max_width) => graphemes[to - 1].right - graphemes[from].left <= max_width,
+        None => true,
+    };
+
+    // The grapheme ranges of the lines of the paragraph
+    let mut breaks = Vec::new();
+    let mut line_start = 0;
+    let mut word_start = 0;
+
+    // Each grapheme is treated as a word when wrapping between characters
+    let words: Box<dyn Iterator<Item = &str>> = match wrap {
+        TextWrap::Char => Box::new(paragraph.graphemes(true)),
+        _ => Box::new(paragraph.split_word_bounds()),
+    };
+
+    for word in words {
+        let word_end = word_start + word.graphemes(true).count().min(graphemes.len() - word_start);
+        let is_whitespace = word.chars().all(char::is_whitespace);
+
+        // Whitespace is allowed to hang past the end of a line
+        if !is_whitespace && word_end > word_start && !fits(line_start, word_end) {
+            if word_start > line_start {
+                breaks.push(line_start..word_start);
+                line_start = word_start;
+            }
+
+            // Break a word which doesn't fit on a line of its own between graphemes, leaving its last grapheme to
+            // start the next line even if that grapheme is wider than the line on its own
+            while line_start + 1 < word_end && !fits(line_start, word_end) {
+                let mut end = line_start + 1;
+                while end < word_end && fits(line_start, end + 1) {
+                    end += 1;
+                }
+
+                breaks.push(line_start..end);
+                line_start = end;
+            }
+        }
+
+        word_start = word_end;
+    }
+
+    let num_breaks = breaks.len();
+    breaks.push(line_start..graphemes.len());
+
+    for (index, range) in breaks.into_iter().enumerate() {
+        let wrapped = index < num_breaks;
+
+        let line = if range.is_empty() {
+            let start =
+                graphemes.get(range.start).map_or(offset + paragraph.len(), |g| g.range.start);
+            TextLine { range: start..start, carets: vec![(start, 0.0)], width: 0.0 }
+        } else {
+            let base = graphemes[range.start].left;
+            let last = &graphemes[range.end - 1];
+
+            let mut carets = graphemes[range.clone()]
+                .iter()
+                .map(|grapheme| (grapheme.range.start, grapheme.left - base))
+                .collect::<Vec<_>>();
+
+            if !wrapped {
+                carets.push((last.range.end, last.right - base));
+            }
+
+            TextLine {
+                range: graphemes[range.start].range.start..last.range.end,
+                carets,
+                width: last.right - base,
+            }
+        };
+
+        lines.push(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Measures each character as 10 pixels wide.
+    fn measure(text: &str) -> Vec<(usize, f32, f32)> {
+        text.char_indices()
+            .enumerate()
+            .map(|(n, (index, _))| (index, n as f32 * 10.0, 10.0))
+            .collect()
+    }
+
+    fn line_ranges(layout: &TextLayout) -> Vec<Range<usize>> {
+        layout.lines.iter().map(|line| line.range.clone()).collect()
+    }
+
+    #[test]
+    fn text_is_broken_at_newlines() {
+        let layout = TextLayout::new("one\n\ntwo", None, 20.0, measure);
+        assert_eq!(line_ranges(&layout), vec![0..3, 4..4, 5..8]);
+        assert_eq!(layout.caret_position(3), (30.0, 0.0));
+        assert_eq!(layout.caret_position(4), (0.0, 20.0));
+        assert_eq!(layout.caret_position(6), (10.0, 40.0));
+    }
+
+    #[test]
+    fn text_is_wrapped_between_words() {
+        let layout = TextLayout::new("the quick brown fox", Some(100.0), 20.0, measure);
+        assert_eq!(line_ranges(&layout), vec![0..10, 10..19]);
+
+        // The end of a wrapped line is the start of the next line
+        assert_eq!(layout.line_of(10), 1);
+        assert_eq!(layout.lines[0].end(), 9);
+        assert_eq!(layout.hit(95.0, 5.0), 9);
+        assert_eq!(layout.hit(22.0, 45.0), 12);
+    }
+
+    #[test]
+    fn long_words_are_broken_between_graphemes() {
+        let layout = TextLayout::new("abcdefgh ij", Some(30.0), 20.0, measure);
+        assert_eq!(line_ranges(&layout), vec![0..3, 3..6, 6..9, 9..11]);
+    }
+
+    #[test]
+    fn graphemes_wider_than_the_wrap_width_are_put_on_their_own_lines() {
+        let layout = TextLayout::new("ab", Some(5.0), 20.0, measure);
+        assert_eq!(line_ranges(&layout), vec![0..1, 1..2]);
+    }
+
+    #[test]
+    fn glyphs_are_grouped_by_grapheme() {
+        // An e with a combining acute accent is a single grapheme of two glyphs
+        let layout = TextLayout::new("e\u{301}x", None, 20.0, measure);
+        assert_eq!(layout.lines[0].carets, vec![(0, 0.0), (3, 20.0), (4, 30.0)]);
+    }
+
+    #[test]
+    fn selections_span_lines() {
+        let layout = TextLayout::new("abc\ndefg", None, 20.0, measure);
+        assert_eq!(layout.selection_rects(1..6), vec![(10.0, 0.0, 20.0), (0.0, 20.0, 20.0)]);
+        assert!(layout.selection_rects(2..2).is_empty());
+    }
+}
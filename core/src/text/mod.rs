@@ -1,8 +1,18 @@
 mod edit;
 pub use edit::*;
 
+mod layout;
+pub use layout::*;
+
 mod movement;
 pub use movement::*;
 
 mod selection;
 pub use selection::*;
+
+mod wrap;
+pub use wrap::*;
+
+mod rich;
+pub(crate) use rich::RichTextLayout;
+pub use rich::*;
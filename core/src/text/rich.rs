@@ -0,0 +1,491 @@
+use std::ops::Range;
+
+use femtovg::{Align, Baseline, FontId, Paint};
+
+use crate::{Color, Context, Entity, FontOrId, StyleDiagnostic, TextLayout, TextWrap};
+
+/// A span of rich text with its own font, font size, color and underline.
+///
+/// Properties which aren't set on a span are taken from the text style of the entity displaying it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextSpan {
+    pub text: String,
+    pub font: Option<String>,
+    pub font_size: Option<f32>,
+    pub color: Option<Color>,
+    pub underline: bool,
+}
+
+impl TextSpan {
+    pub fn new(text: &str) -> Self {
+        Self { text: text.to_owned(), ..Default::default() }
+    }
+
+    /// Sets the name of the font of the span, such as `roboto-bold` or `icons`.
+    pub fn font(mut self, font: &str) -> Self {
+        self.font = Some(font.to_owned());
+
+        self
+    }
+
+    pub fn font_size(mut self, font_size: f32) -> Self {
+        self.font_size = Some(font_size);
+
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+
+        self
+    }
+
+    pub fn underline(mut self, underline: bool) -> Self {
+        self.underline = underline;
+
+        self
+    }
+
+    // Returns true if the span is drawn the same as another span.
+    fn same_style(&self, other: &TextSpan) -> bool {
+        self.font == other.font
+            && self.font_size == other.font_size
+            && self.color == other.color
+            && self.underline == other.underline
+    }
+}
+
+/// Parses rich text markup into spans.
+///
+/// The markup is text with a small subset of HTML tags, which can be nested:
+///  - `<b>bold</b>` - Uses the `roboto-bold` font.
+///  - `<u>underlined</u>`
+///  - `<font face="icons" size="20" color="#ff0000">...</font>` - Sets any of the font, font size and color.
+///  - `<br>` - A line break.
+///
+/// The entities `&lt;`, `&gt;`, `&amp;`, `&quot;` and `&#...;` can be used for characters which would otherwise be
+/// read as markup or which are hard to type, such as the code points of icons.
+///
+/// Problems with the markup, such as unknown tags, are returned along with the spans. The text is kept and the
+/// tag or attribute which caused the problem is ignored.
+pub fn parse_markup(markup: &str) -> (Vec<TextSpan>, Vec<StyleDiagnostic>) {
+    let mut spans: Vec<TextSpan> = Vec::new();
+    let mut diagnostics = Vec::new();
+    // The open tags, each with the style of the text inside it
+    let mut stack: Vec<(String, TextSpan)> = Vec::new();
+
+    let mut rest = markup;
+    while !rest.is_empty() {
+        let style = stack.last().map(|(_, style)| style.clone()).unwrap_or_default();
+
+        if let Some(tag) = rest.strip_prefix('<') {
+            // Problems are reported at the start of the tag
+            let location = markup_location(markup, rest);
+            let mut report = |reason: String| {
+                diagnostics.push(StyleDiagnostic {
+                    line: location.0,
+                    column: location.1,
+                    property: None,
+                    reason,
+                })
+            };
+
+            let end = match tag.find('>') {
+                Some(end) => end,
+                None => {
+                    report(format!("Unclosed tag: <{}", tag));
+                    break;
+                }
+            };
+
+            let tag_text = tag[..end].trim().trim_end_matches('/').trim_end();
+            rest = &tag[end + 1..];
+
+            if let Some(name) = tag_text.strip_prefix('/') {
+                // Close the innermost open tag with the same name
+                let name = name.trim().to_lowercase();
+                if let Some(index) = stack.iter().rposition(|(open, _)| *open == name) {
+                    stack.truncate(index);
+                } else {
+                    report(format!("Unmatched closing tag: </{}>", name));
+                }
+
+                continue;
+            }
+
+            let (name, attributes) = match tag_text.find(char::is_whitespace) {
+                Some(index) => (&tag_text[..index], &tag_text[index..]),
+                None => (tag_text, ""),
+            };
+
+            let name = name.to_lowercase();
+            let mut style = style;
+            match name.as_str() {
+                "br" => {
+                    push_text(&mut spans, &style, "\n");
+                    continue;
+                }
+
+                "b" => style.font = Some("roboto-bold".to_owned()),
+
+                "u" => style.underline = true,
+
+                "font" => {
+                    for (key, value) in parse_attributes(attributes) {
+                        match key.as_str() {
+                            "face" => style.font = Some(value),
+                            "size" => match value.trim_end_matches("px").parse::<f32>() {
+                                Ok(font_size) => style.font_size = Some(font_size),
+                                Err(_) => report(format!("Invalid font size: {}", value)),
+                            },
+                            "color" => match parse_hex_color(&value) {
+                                Some(color) => style.color = Some(color),
+                                None => report(format!("Invalid color: {}", value)),
+                            },
+                            _ => report(format!("Unknown font attribute: {}", key)),
+                        }
+                    }
+                }
+
+                _ => report(format!("Unknown tag: <{}>", name)),
+            }
+
+            stack.push((name, style));
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            push_text(&mut spans, &style, &decode_entities(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+
+    (spans, diagnostics)
+}
+
+// Returns the line and column, both starting at 1, of the start of the rest of a piece of markup.
+fn markup_location(markup: &str, rest: &str) -> (u32, u32) {
+    let preceding = &markup[..markup.len() - rest.len()];
+    let line = preceding.matches('\n').count() as u32 + 1;
+    let line_start = preceding.rfind('\n').map(|index| index + 1).unwrap_or(0);
+    let column = preceding[line_start..].chars().count() as u32 + 1;
+
+    (line, column)
+}
+
+// Adds text to the last span if it has the given style, or as a new span if it doesn't.
+fn push_text(spans: &mut Vec<TextSpan>, style: &TextSpan, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+
+    match spans.last_mut() {
+        Some(span) if span.same_style(style) => span.text.push_str(text),
+        _ => spans.push(TextSpan { text: text.to_owned(), ..style.clone() }),
+    }
+}
+
+// Parses the attributes of a tag, given as `key="value"`, `key='value'` or `key=value`.
+fn parse_attributes(mut attributes: &str) -> Vec<(String, String)> {
+    let mut parsed = Vec::new();
+
+    loop {
+        attributes = attributes.trim_start();
+        let equals = match attributes.find('=') {
+            Some(equals) => equals,
+            None => break,
+        };
+
+        let key = attributes[..equals].trim().to_lowercase();
+        let value = attributes[equals + 1..].trim_start();
+
+        let (value, rest) = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => match value[1..].find(quote) {
+                Some(end) => (&value[1..end + 1], &value[end + 2..]),
+                None => (&value[1..], ""),
+            },
+            _ => {
+                let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                (&value[..end], &value[end..])
+            }
+        };
+
+        parsed.push((key, decode_entities(value)));
+        attributes = rest;
+    }
+
+    parsed
+}
+
+// Parses a color given as `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`.
+fn parse_hex_color(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#')?;
+
+    if matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(Color::from(value))
+    } else {
+        None
+    }
+}
+
+// Replaces the character entities in a piece of markup with the characters they stand for.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest.find(';').map(|end| (&rest[1..end], end));
+        let character = entity.and_then(|(name, _)| match name {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => {
+                let code = name.strip_prefix('#')?;
+                let code = match code.strip_prefix('x').or_else(|| code.strip_prefix('X')) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => code.parse::<u32>().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+
+        match (character, entity) {
+            (Some(character), Some((_, end))) => {
+                decoded.push(character);
+                rest = &rest[end + 1..];
+            }
+
+            // An ampersand which doesn't start an entity is kept as it is
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+
+    decoded
+}
+
+// A piece of a span which is laid out on a single line.
+pub(crate) struct TextRun {
+    pub(crate) span: usize,
+    // The byte range of the run within the text of its span
+    pub(crate) range: Range<usize>,
+    // The position of the start of the run on its baseline, relative to the top left of the text
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) width: f32,
+    pub(crate) font_size: f32,
+    pub(crate) paint: Paint,
+    pub(crate) color: Color,
+    pub(crate) underline: bool,
+}
+
+/// The spans of a rich text laid out in lines, which are broken at newlines.
+pub(crate) struct RichTextLayout {
+    pub(crate) runs: Vec<TextRun>,
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+}
+
+impl RichTextLayout {
+    pub(crate) fn new(cx: &Context, entity: Entity, spans: &[TextSpan]) -> Self {
+        let default_font = cx.style.font.get(entity).cloned().unwrap_or_default();
+        let default_font_size = cx.style.font_size.get(entity).cloned().unwrap_or(16.0);
+        let default_color = cx.style.font_color.get(entity).cloned().unwrap_or_default();
+
+        let default_paint = text_paint(cx, &default_font, default_font_size);
+
+        // The text of all of the spans, with the byte range of each span within it
+        let mut text = String::new();
+        let mut span_ranges = Vec::with_capacity(spans.len());
+        let mut paints = Vec::with_capacity(spans.len());
+
+        for span in spans.iter() {
+            let start = text.len();
+            text.push_str(&span.text);
+            span_ranges.push(start..text.len());
+
+            let font = span.font.as_ref().unwrap_or(&default_font);
+            paints.push(text_paint(cx, font, span.font_size.unwrap_or(default_font_size)));
+        }
+
+        // The pieces of the spans which are within a range of the text
+        let pieces = |range: Range<usize>| {
+            span_ranges.iter().enumerate().filter_map(move |(index, span_range)| {
+                let piece = span_range.start.max(range.start)..span_range.end.min(range.end);
+                if piece.is_empty() {
+                    None
+                } else {
+                    Some((index, piece))
+                }
+            })
+        };
+
+        // Each line is measured one span at a time, with each span following on from the last
+        let layout = TextLayout::with_wrap(&text, TextWrap::None, None, 0.0, |line| {
+            let mut glyphs = Vec::new();
+            let mut x = 0.0;
+
+            for (index, piece) in pieces(line.clone()) {
+                if let Ok(metrics) =
+                    cx.text_context.measure_text(0.0, 0.0, &text[piece.clone()], paints[index])
+                {
+                    glyphs.extend(metrics.glyphs.iter().map(|glyph| {
+                        (piece.start - line.start + glyph.byte_index, x + glyph.x, glyph.width)
+                    }));
+                    x += metrics.width();
+                }
+            }
+
+            glyphs
+        });
+
+        let mut runs = Vec::new();
+        let mut width: f32 = 0.0;
+        let mut height = 0.0;
+
+        for line in layout.lines.iter() {
+            let line_start = runs.len();
+            let mut ascender: f32 = 0.0;
+            let mut descender: f32 = 0.0;
+
+            for (index, piece) in pieces(line.range.clone()) {
+                let span = &spans[index];
+                let paint = paints[index];
+
+                if let Ok(font_metrics) = cx.text_context.measure_font(paint) {
+                    ascender = ascender.max(font_metrics.ascender());
+                    descender = descender.min(font_metrics.descender());
+                }
+
+                let x = line.caret_x(piece.start);
+
+                runs.push(TextRun {
+                    span: index,
+                    range: piece.start - span_ranges[index].start
+                        ..piece.end - span_ranges[index].start,
+                    x,
+                    y: 0.0,
+                    width: line.caret_x(piece.end) - x,
+                    font_size: span.font_size.unwrap_or(default_font_size),
+                    paint,
+                    color: span.color.unwrap_or(default_color),
+                    underline: span.underline,
+                });
+            }
+
+            // An empty line is as high as the default font
+            if runs.len() == line_start {
+                if let Ok(font_metrics) = cx.text_context.measure_font(default_paint) {
+                    ascender = font_metrics.ascender();
+                    descender = font_metrics.descender();
+                }
+            }
+
+            height = end_line(&mut runs[line_start..], height, ascender, descender);
+            width = width.max(line.width);
+        }
+
+        Self { runs, width, height }
+    }
+}
+
+// Places the runs of a line on its baseline, returning the top of the next line.
+fn end_line(runs: &mut [TextRun], top: f32, ascender: f32, descender: f32) -> f32 {
+    for run in runs.iter_mut() {
+        run.y = top + ascender;
+    }
+
+    top + ascender - descender
+}
+
+// Returns a paint for drawing text on its alphabetic baseline with the given font and font size.
+fn text_paint(cx: &Context, font: &str, font_size: f32) -> Paint {
+    let mut paint = Paint::default();
+    if let Some(font_id) = font_id(cx, font) {
+        paint.set_font(&[font_id]);
+    }
+    paint.set_font_size(font_size);
+    paint.set_text_align(Align::Left);
+    paint.set_text_baseline(Baseline::Alphabetic);
+
+    paint
+}
+
+// Returns the id of the font with the given name, or of the default font if there isn't one. Text with neither font
+// isn't measured or drawn.
+fn font_id(cx: &Context, font: &str) -> Option<FontId> {
+    let find_font = |name: &str| match cx.resource_manager.fonts.get(name) {
+        Some(FontOrId::Id(id)) => Some(*id),
+        _ => None,
+    };
+
+    find_font(font).or_else(|| find_font(cx.style.default_font.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markup_is_parsed_into_spans() {
+        let (spans, diagnostics) = parse_markup(
+            "Volume: <b>-6 <u>dB</u></b><br><font color=\"#ff0000\" size=20>clip</font>",
+        );
+
+        assert!(diagnostics.is_empty());
+
+        assert_eq!(
+            spans,
+            vec![
+                TextSpan::new("Volume: "),
+                TextSpan::new("-6 ").font("roboto-bold"),
+                TextSpan::new("dB").font("roboto-bold").underline(true),
+                TextSpan::new("\n"),
+                TextSpan::new("clip").font_size(20.0).color(Color::rgb(255, 0, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn entities_are_decoded() {
+        let (spans, _) = parse_markup("a &lt;b&gt; &amp; <font face='icons'>&#x2713;</font> & c");
+
+        assert_eq!(
+            spans,
+            vec![
+                TextSpan::new("a <b> & "),
+                TextSpan::new("\u{2713}").font("icons"),
+                TextSpan::new(" & c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_and_unmatched_tags_keep_the_enclosing_style() {
+        let (spans, diagnostics) = parse_markup("<u>a<i>b</i></b>c</u>d");
+
+        assert_eq!(spans, vec![TextSpan::new("abc").underline(true), TextSpan::new("d")]);
+        assert_eq!(
+            diagnostics.iter().map(|d| d.reason.as_str()).collect::<Vec<_>>(),
+            vec!["Unknown tag: <i>", "Unmatched closing tag: </b>"]
+        );
+    }
+
+    #[test]
+    fn markup_problems_are_located() {
+        let (spans, diagnostics) = parse_markup("a\nb <font size=big>c</font>\n<font color=red");
+
+        assert_eq!(spans, vec![TextSpan::new("a\nb c\n")]);
+        assert_eq!(
+            diagnostics.iter().map(|d| (d.line, d.column, d.reason.as_str())).collect::<Vec<_>>(),
+            vec![(2, 3, "Invalid font size: big"), (3, 1, "Unclosed tag: <font color=red")]
+        );
+    }
+}
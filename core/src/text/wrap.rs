@@ -0,0 +1,155 @@
+use std::ops::Range;
+
+use femtovg::{Paint, TextContext};
+use morphorm::Units;
+
+use crate::{TextLayout, TextWrap};
+
+const ELLIPSIS: &str = "\u{2026}";
+
+/// Breaks a text into lines at newlines and, unless `wrap` is `TextWrap::None`, wherever a line is wider than
+/// `max_width`, returning the byte range of each line.
+///
+/// Whitespace at the point where a line is wrapped is left out of the lines on either side of it.
+pub fn text_lines(
+    text_context: &TextContext,
+    text: &str,
+    paint: Paint,
+    wrap: TextWrap,
+    max_width: f32,
+) -> Vec<Range<usize>> {
+    wrap_lines(text, wrap, max_width, |line| measure_glyphs(text_context, line, paint))
+}
+
+/// Returns the byte index, x position and width of each glyph of a line of text.
+pub fn measure_glyphs(
+    text_context: &TextContext,
+    text: &str,
+    paint: Paint,
+) -> Vec<(usize, f32, f32)> {
+    text_context
+        .measure_text(0.0, 0.0, text, paint)
+        .map(|metrics| {
+            metrics.glyphs.iter().map(|glyph| (glyph.byte_index, glyph.x, glyph.width)).collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the width of a line of text.
+pub fn text_width(text_context: &TextContext, text: &str, paint: Paint) -> f32 {
+    text_context
+        .measure_text(0.0, 0.0, text, paint)
+        .map(|metrics| metrics.width())
+        .unwrap_or_default()
+}
+
+/// Returns the height of a line of text given its `line-height`, which is the height of the font when it is `Auto`
+/// and a percentage of the height of the font when it is a percentage.
+pub fn line_height(text_context: &TextContext, paint: Paint, line_height: Units) -> f32 {
+    let font_height = text_context
+        .measure_font(paint)
+        .map(|font_metrics| font_metrics.height())
+        .expect("Failed to read font metrics");
+
+    match line_height {
+        Units::Pixels(val) => val,
+        Units::Percentage(val) => font_height * val / 100.0,
+        _ => font_height,
+    }
+}
+
+/// Cuts a line of text short so that it fits within `max_width` when followed by an ellipsis, and adds the ellipsis.
+pub fn ellipsize(text_context: &TextContext, text: &str, paint: Paint, max_width: f32) -> String {
+    let ellipsis_width = text_width(text_context, ELLIPSIS, paint);
+    let fit = fit_chars(text_context, text, paint, max_width - ellipsis_width);
+
+    format!("{}{}", text[..fit].trim_end(), ELLIPSIS)
+}
+
+// Returns the length in bytes of the longest start of a line whose glyphs fit within `max_width`.
+fn fit_chars(text_context: &TextContext, line: &str, paint: Paint, max_width: f32) -> usize {
+    let metrics = match text_context.measure_text(0.0, 0.0, line, paint) {
+        Ok(metrics) => metrics,
+        Err(_) => return line.len(),
+    };
+
+    let left = metrics.glyphs.first().map(|glyph| glyph.x).unwrap_or_default();
+
+    metrics
+        .glyphs
+        .iter()
+        .find(|glyph| glyph.x + glyph.width - left > max_width)
+        .map(|glyph| glyph.byte_index)
+        .unwrap_or(line.len())
+}
+
+// Breaks a text into lines with a `TextLayout`, given a function which measures the glyphs of a line.
+fn wrap_lines<F>(text: &str, wrap: TextWrap, max_width: f32, mut measure: F) -> Vec<Range<usize>>
+where
+    F: FnMut(&str) -> Vec<(usize, f32, f32)>,
+{
+    let layout =
+        TextLayout::with_wrap(text, wrap, Some(max_width), 0.0, |range| measure(&text[range]));
+
+    layout
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            // Whitespace hangs past the end of a line which is wrapped, rather than one ended by a newline
+            let wrapped = layout
+                .lines
+                .get(index + 1)
+                .map_or(false, |next| next.range.start == line.range.end);
+
+            if wrapped {
+                line.range.start..line.range.start + text[line.range.clone()].trim_end().len()
+            } else {
+                line.range.clone()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Breaks lines of text where each character is 10 pixels wide.
+    fn lines(text: &str, wrap: TextWrap, max_width: f32) -> Vec<&str> {
+        let measure = |line: &str| {
+            line.char_indices()
+                .enumerate()
+                .map(|(n, (index, _))| (index, n as f32 * 10.0, 10.0))
+                .collect()
+        };
+
+        wrap_lines(text, wrap, max_width, measure).into_iter().map(|range| &text[range]).collect()
+    }
+
+    #[test]
+    fn unwrapped_text_is_broken_at_newlines() {
+        assert_eq!(
+            lines("the quick\r\n\nbrown fox", TextWrap::None, 50.0),
+            vec!["the quick", "", "brown fox"]
+        );
+    }
+
+    #[test]
+    fn text_is_wrapped_between_words() {
+        assert_eq!(
+            lines("the quick brown fox", TextWrap::Word, 100.0),
+            vec!["the quick", "brown fox"]
+        );
+        assert_eq!(
+            lines("a verylongword b", TextWrap::Word, 50.0),
+            vec!["a", "veryl", "ongwo", "rd b"]
+        );
+    }
+
+    #[test]
+    fn text_is_wrapped_between_characters() {
+        assert_eq!(lines("the quick brown", TextWrap::Char, 60.0), vec!["the qu", "ick br", "own"]);
+        assert_eq!(lines("abc", TextWrap::Char, 0.0), vec!["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,114 @@
+use std::time::{Duration, Instant};
+
+use crate::{Context, Entity, Event, Propagation};
+
+// The shortest interval of a repeating timer, so that a zero interval doesn't tick on every frame.
+const MIN_REPEAT_INTERVAL: Duration = Duration::from_millis(1);
+
+/// An identifier for a timer added with `cx.add_timer()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timer(u64);
+
+/// Events sent by timers to the entity which added them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerEvent {
+    /// The timer has reached its deadline. A repeating timer sends this event once per interval.
+    Tick(Timer),
+}
+
+// A timer along with the entity which owns it and when it next ticks.
+struct TimerState {
+    timer: Timer,
+    entity: Entity,
+    interval: Duration,
+    repeat: bool,
+    deadline: Instant,
+}
+
+/// The timers of an application, which are ticked by the backend at the start of each frame.
+#[derive(Default)]
+pub struct Timers {
+    timers: Vec<TimerState>,
+    next_id: u64,
+    // A fixed clock from which new timers are started instead of the current time
+    clock: Option<Instant>,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the earliest time at which a timer ticks, if there are any timers.
+    ///
+    /// Backends which wait for events should wake up at this time.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.iter().map(|timer| timer.deadline).min()
+    }
+
+    /// Sets a fixed time from which new timers are started, for backends which don't use the current time.
+    pub fn set_clock(&mut self, time: Instant) {
+        self.clock = Some(time);
+    }
+
+    /// Returns true if the given timer hasn't been removed.
+    pub fn contains(&self, timer: Timer) -> bool {
+        self.timers.iter().any(|state| state.timer == timer)
+    }
+
+    pub(crate) fn add(&mut self, entity: Entity, interval: Duration, repeat: bool) -> Timer {
+        let timer = Timer(self.next_id);
+        self.next_id += 1;
+
+        let interval = if repeat { interval.max(MIN_REPEAT_INTERVAL) } else { interval };
+
+        let start = self.clock.unwrap_or_else(Instant::now);
+        self.timers.push(TimerState {
+            timer,
+            entity,
+            interval,
+            repeat,
+            deadline: start + interval,
+        });
+
+        timer
+    }
+
+    pub(crate) fn remove(&mut self, timer: Timer) {
+        self.timers.retain(|state| state.timer != timer);
+    }
+
+    // Removes the timers owned by the given entity.
+    pub(crate) fn remove_entity(&mut self, entity: Entity) {
+        self.timers.retain(|state| state.entity != entity);
+    }
+}
+
+/// Sends a `TimerEvent::Tick` for each timer which has reached its deadline by the given time.
+///
+/// Repeating timers are scheduled for their next interval, and any intervals which were missed are skipped rather
+/// than sent all at once. Other timers are removed after they tick.
+pub fn apply_timers(cx: &mut Context, time: Instant) {
+    for state in cx.timers.timers.iter_mut() {
+        if state.deadline > time {
+            continue;
+        }
+
+        cx.event_queue.push_back(
+            Event::new(TimerEvent::Tick(state.timer))
+                .target(state.entity)
+                .origin(state.entity)
+                .propagate(Propagation::Direct),
+        );
+
+        if state.repeat {
+            state.deadline += state.interval;
+            if state.deadline <= time {
+                state.deadline = time + state.interval;
+            }
+        }
+    }
+
+    // Timers which don't repeat are removed once they have ticked
+    cx.timers.timers.retain(|state| state.repeat || state.deadline > time);
+}
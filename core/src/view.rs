@@ -1,6 +1,8 @@
 use crate::{
-    style::{BorderCornerShape, GradientDirection},
-    Context, Event, FontOrId, Handle, ViewHandler,
+    ellipsize, hit_test_bounds, line_height,
+    style::{BorderCornerShape, GradientDirection, ImageFit},
+    text::RichTextLayout,
+    text_lines, text_width, Context, Event, FontOrId, Handle, ImageOrId, TextOverflow, ViewHandler,
 };
 
 use femtovg::{
@@ -21,7 +23,7 @@ pub trait View: 'static + Sized {
         F: 'static + FnOnce(&mut Context),
     {
         // Add the instance to context unless it already exists
-        let id = if let Some(id) = cx.tree.get_child(cx.current, cx.count) {
+        let id = if let Some(id) = cx.built_child(cx.current, cx.count) {
             id
         } else {
             let id = cx.entity_manager.create();
@@ -56,7 +58,7 @@ pub trait View: 'static + Sized {
         F: 'static + FnOnce(&mut Context),
     {
         // Add the instance to context unless it already exists
-        let id = if let Some(id) = cx.tree.get_child(cx.current, cx.count) {
+        let id = if let Some(id) = cx.built_child(cx.current, cx.count) {
             cx.views.insert(id, Box::new(self));
             id
         } else {
@@ -88,7 +90,7 @@ pub trait View: 'static + Sized {
     }
 
     fn build(mut self, cx: &mut Context) -> Handle<Self> {
-        let id = if let Some(id) = cx.tree.get_child(cx.current, cx.count) {
+        let id = if let Some(id) = cx.built_child(cx.current, cx.count) {
             let prev = cx.current;
             cx.current = id;
             let prev_count = cx.count;
@@ -128,6 +130,16 @@ pub trait View: 'static + Sized {
     #[allow(unused_variables)]
     fn event(&mut self, cx: &mut Context, event: &mut Event) {}
 
+    /// Returns true if the given point is within the view, used to determine the hovered view.
+    ///
+    /// The point is in the untransformed space of the view, so the inverse of any transform has already been
+    /// applied, and points outside of the clip region are discarded before this is called. By default the point
+    /// is tested against the bounds of the view, excluding any rounded corners. Override this for views with
+    /// non-rectangular shapes.
+    fn hit_test(&self, cx: &Context, x: f32, y: f32) -> bool {
+        hit_test_bounds(cx, cx.current, x, y)
+    }
+
     fn draw(&self, cx: &mut Context, canvas: &mut Canvas) {
         //println!("{}", debug(&mut context, entity));
         let entity = cx.current;
@@ -528,18 +540,6 @@ pub trait View: 'static + Sized {
         // Fill with background color
         let mut paint = Paint::color(background_color);
 
-        // if let Some(background_image) = cx.style.background_image.get(entity) {
-        //     if let Some(image_id) = cx.resource_manager.image_ids.get(background_image) {
-        //         match image_id {
-        //             crate::ImageOrId::Id(id) => {
-        //                 paint = Paint::image(*id, 0.0, 0.0, 100.0, 100.0, 0.0, 1.0);
-        //             }
-
-        //             _ => {}
-        //         }
-        //     }
-        // }
-
         // Gradient overrides background color
         if let Some(background_gradient) = cx.style.background_gradient.get(entity) {
             let (_, _, end_x, end_y, parent_length) = match background_gradient.direction {
@@ -570,6 +570,57 @@ pub trait View: 'static + Sized {
         // Fill the quad
         canvas.fill_path(&mut path, paint);
 
+        // Draw background image over the background color
+        let background_image = cx.style.background_image.get(entity).and_then(|name| {
+            match name.as_ref().and_then(|name| cx.resource_manager.images.get(name)) {
+                Some(ImageOrId::Id(id)) => Some(*id),
+                _ => None,
+            }
+        });
+
+        if let Some(image_id) = background_image {
+            if let Ok((image_width, image_height)) = canvas.image_size(image_id) {
+                let image_width = image_width as f32;
+                let image_height = image_height as f32;
+
+                let background_fit =
+                    cx.style.background_fit.get(entity).cloned().unwrap_or_default();
+
+                let (x, y, w, h) = match background_fit {
+                    ImageFit::Fill => (bounds.x, bounds.y, bounds.w, bounds.h),
+
+                    ImageFit::Contain | ImageFit::Cover => {
+                        let scale_x = bounds.w / image_width;
+                        let scale_y = bounds.h / image_height;
+                        let scale = if background_fit == ImageFit::Contain {
+                            scale_x.min(scale_y)
+                        } else {
+                            scale_x.max(scale_y)
+                        };
+
+                        let w = image_width * scale;
+                        let h = image_height * scale;
+
+                        (bounds.x + (bounds.w - w) / 2.0, bounds.y + (bounds.h - h) / 2.0, w, h)
+                    }
+
+                    ImageFit::Tile => (bounds.x, bounds.y, image_width, image_height),
+                };
+
+                let paint = Paint::image(image_id, x, y, w, h, 0.0, opacity);
+
+                if background_fit == ImageFit::Contain {
+                    // The image doesn't cover the whole shape so only fill the part of the shape it occupies
+                    canvas.save();
+                    canvas.intersect_scissor(x, y, w, h);
+                    canvas.fill_path(&mut path, paint);
+                    canvas.restore();
+                } else {
+                    canvas.fill_path(&mut path, paint);
+                }
+            }
+        }
+
         //println!("{:.2?} seconds for whatever you did.", start.elapsed());
 
         // Draw border
@@ -671,6 +722,8 @@ pub trait View: 'static + Sized {
                 _ => Align::Left,
             };
 
+            x -= cx.style.text_scroll.get(entity).cloned().unwrap_or_default();
+
             let baseline = match child_top {
                 Units::Pixels(val) => match child_bottom {
                     Units::Stretch(_) | Units::Auto => {
@@ -710,7 +763,115 @@ pub trait View: 'static + Sized {
             paint.set_text_baseline(baseline);
             paint.set_anti_alias(false);
 
-            canvas.fill_text(x, y, &text_string, paint).unwrap();
+            let text_wrap = cx.style.text_wrap.get(entity).cloned().unwrap_or_default();
+            let text_overflow = cx.style.text_overflow.get(entity).cloned().unwrap_or_default();
+            let line_height_units = cx.style.line_height.get(entity).cloned().unwrap_or_default();
+
+            // The space within the entity which the text is broken and ellipsized against
+            let max_width = bounds.w
+                - 2.0 * border_width
+                - child_left.value_or(bounds.w, 0.0)
+                - child_right.value_or(bounds.w, 0.0);
+            let max_height = bounds.h
+                - 2.0 * border_width
+                - child_top.value_or(bounds.h, 0.0)
+                - child_bottom.value_or(bounds.h, 0.0);
+
+            let lines = text_lines(&cx.text_context, &text_string, paint, text_wrap, max_width);
+
+            if lines.len() == 1
+                && line_height_units == Units::Auto
+                && text_overflow == TextOverflow::Clip
+            {
+                canvas.fill_text(x, y, &text_string, paint).unwrap();
+            } else {
+                let line_height = line_height(&cx.text_context, paint, line_height_units);
+
+                // Lines which don't fit are left out of an ellipsized text
+                let num_lines = if text_overflow == TextOverflow::Ellipsis && line_height > 0.0 {
+                    lines.len().min(((max_height / line_height).floor() as usize).max(1))
+                } else {
+                    lines.len()
+                };
+
+                let text_height = num_lines as f32 * line_height;
+                let top = match baseline {
+                    Baseline::Middle => y - 0.5 * text_height,
+                    Baseline::Bottom => y - text_height,
+                    _ => y,
+                };
+
+                // Each line is centered vertically within its line height
+                paint.set_text_baseline(Baseline::Middle);
+
+                for (index, line) in lines.iter().take(num_lines).enumerate() {
+                    let line_text = &text_string[line.clone()];
+                    let line_y = top + (index as f32 + 0.5) * line_height;
+
+                    if text_overflow == TextOverflow::Ellipsis
+                        && ((index + 1 == num_lines && num_lines < lines.len())
+                            || text_width(&cx.text_context, line_text, paint) > max_width)
+                    {
+                        let line_text = ellipsize(&cx.text_context, line_text, paint, max_width);
+                        canvas.fill_text(x, line_y, &line_text, paint).unwrap();
+                    } else {
+                        canvas.fill_text(x, line_y, line_text, paint).unwrap();
+                    }
+                }
+            }
+        }
+
+        // Draw rich text
+        if let Some(spans) = cx.style.rich_text.get(entity) {
+            let layout = RichTextLayout::new(cx, entity, spans);
+
+            let child_left = cx.style.child_left.get(entity).cloned().unwrap_or_default();
+            let child_right = cx.style.child_right.get(entity).cloned().unwrap_or_default();
+            let child_top = cx.style.child_top.get(entity).cloned().unwrap_or_default();
+            let child_bottom = cx.style.child_bottom.get(entity).cloned().unwrap_or_default();
+
+            // The text is positioned by the child spacing of the entity, like the text of a label
+            let x = match (child_left, child_right) {
+                (Units::Pixels(val), _) => bounds.x + border_width + val,
+                (Units::Stretch(_), Units::Pixels(val)) => {
+                    bounds.x + bounds.w - border_width - val - layout.width
+                }
+                (Units::Stretch(_), Units::Stretch(_)) => {
+                    bounds.x + 0.5 * (bounds.w - layout.width)
+                }
+                _ => bounds.x + border_width,
+            };
+
+            let y = match (child_top, child_bottom) {
+                (Units::Pixels(val), _) => bounds.y + border_width + val,
+                (Units::Stretch(_), Units::Pixels(val)) => {
+                    bounds.y + bounds.h - border_width - val - layout.height
+                }
+                (Units::Stretch(_), Units::Stretch(_)) => {
+                    bounds.y + 0.5 * (bounds.h - layout.height)
+                }
+                _ => bounds.y + border_width,
+            };
+
+            for run in layout.runs.iter() {
+                let mut color: femtovg::Color = run.color.into();
+                color.set_alphaf(color.a * opacity);
+
+                let mut paint = run.paint;
+                paint.set_color(color);
+                paint.set_anti_alias(false);
+
+                let text = &spans[run.span].text[run.range.clone()];
+                canvas.fill_text(x + run.x, y + run.y, text, paint).unwrap();
+
+                if run.underline {
+                    let thickness = (run.font_size / 16.0).round().max(1.0);
+
+                    let mut path = Path::new();
+                    path.rect(x + run.x, y + run.y + thickness, run.width, thickness);
+                    canvas.fill_path(&mut path, Paint::color(color));
+                }
+            }
         }
 
         // let bounds = cx.cache.get_bounds(entity);
@@ -746,6 +907,10 @@ where
         <T as View>::event(self, cx, event);
     }
 
+    fn hit_test(&self, cx: &Context, x: f32, y: f32) -> bool {
+        <T as View>::hit_test(self, cx, x, y)
+    }
+
     fn draw(&self, cx: &mut Context, canvas: &mut Canvas) {
         <T as View>::draw(self, cx, canvas);
     }
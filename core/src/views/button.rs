@@ -15,9 +15,11 @@ impl Button {
         L: 'static + Fn(&mut Context) -> Handle<Label>,
         Label: 'static + View,
     {
-        Self { action: Some(Box::new(action)) }.build2(cx, move |cx| {
-            (label)(cx);
-        })
+        Self { action: Some(Box::new(action)) }
+            .build2(cx, move |cx| {
+                (label)(cx);
+            })
+            .focusable(true)
     }
 }
 
@@ -27,7 +27,7 @@ const ICON_CHECK: &str = "\u{2713}";
 /// To use the checkbox, bind its value to some app data and use the `on_toggle` callback to mutate the data:
 /// ```compile_fail
 /// Binding::new(cx, AppData::value, |cx, value|{
-///     Checkbox::new(cx, *value.get(cx))
+///     Checkbox::new(cx, value.get(cx))
 ///         .on_toggle(|cx| cx.emit(AppEvent::ToggleValue));
 /// })
 /// ```
@@ -40,6 +40,7 @@ impl Checkbox {
     pub fn new(cx: &mut Context, checked: bool) -> Handle<Self> {
         Self { on_toggle: None }
             .build2(cx, |_| {})
+            .focusable(true)
             .width(Pixels(20.0))
             .height(Pixels(20.0))
             .text(if checked { ICON_CHECK } else { "" })
@@ -0,0 +1,26 @@
+use crate::{Context, Handle, View};
+
+/// A view which displays an image resource.
+///
+/// Images are added to the application with `cx.load_image(name, bytes)` and referred to by name.
+/// By default the image is scaled to fit within the bounds of the view while preserving its aspect ratio.
+/// This can be changed with the `background-fit` style property.
+///
+/// # Example
+/// ```compile_fail
+/// cx.load_image("logo", include_bytes!("resources/logo.png"));
+/// Image::new(cx, "logo").width(Pixels(64.0)).height(Pixels(64.0));
+/// ```
+pub struct Image;
+
+impl Image {
+    pub fn new(cx: &mut Context, name: &str) -> Handle<Self> {
+        Self {}.build2(cx, |_| {}).background_image(name)
+    }
+}
+
+impl View for Image {
+    fn element(&self) -> Option<String> {
+        Some("image".to_string())
+    }
+}
@@ -2,8 +2,8 @@ use femtovg::{LineCap, Paint, Path, Solidity};
 use morphorm::{Hierarchy, Units};
 
 use crate::{
-    Binding, Context, Entity, Handle, Model, Modifiers, MouseButton, SliderData, SliderEvent,
-    Units::*, View, WindowEvent, ZStack,
+    Binding, Context, Entity, Handle, HistoryEvent, Model, Modifiers, MouseButton, SliderData,
+    SliderEvent, Units::*, View, WindowEvent, ZStack,
 };
 
 static DEFAULT_DRAG_SCALAR: f32 = 0.0042;
@@ -59,7 +59,7 @@ impl Knob {
                     let radius = height.min(width) / 2.;
                     ArcTrack::new(
                         cx,
-                        *value.get(cx),
+                        value.get(cx),
                         centered,
                         Pixels(radius),
                         Percentage(15.),
@@ -80,6 +80,7 @@ impl Knob {
                 //     .rotate(30.0);
             });
         })
+        .focusable(true)
     }
     pub fn custom<'a, F, T>(
         cx: &'a mut Context,
@@ -109,12 +110,11 @@ impl Knob {
 
             ZStack::new(cx, move |cx| {
                 Binding::new(cx, SliderData::value, move |cx, value| {
-                    (content)(cx, *value.get(cx))
-                        .width(Percentage(100.0))
-                        .height(Percentage(100.0));
+                    (content)(cx, value.get(cx)).width(Percentage(100.0)).height(Percentage(100.0));
                 });
             });
         })
+        .focusable(true)
     }
 }
 
@@ -167,11 +167,16 @@ impl View for Knob {
         if let Some(window_event) = event.message.downcast::<WindowEvent>() {
             match window_event {
                 WindowEvent::MouseDown(button) if *button == MouseButton::Left => {
+                    // Changes made while dragging are undone in a single step
+                    if !self.is_dragging {
+                        cx.emit(HistoryEvent::BeginTransaction);
+                    }
+
                     self.is_dragging = true;
                     self.prev_drag_y = cx.mouse.left.pos_down.1;
 
                     cx.captured = cx.current;
-                    cx.focused = cx.current;
+                    cx.focus(cx.current);
 
                     if let Some(slider_data) = cx.data::<SliderData>() {
                         self.continuous_normal = slider_data.value;
@@ -184,6 +189,10 @@ impl View for Knob {
                 }
 
                 WindowEvent::MouseUp(button) if *button == MouseButton::Left => {
+                    if self.is_dragging {
+                        cx.emit(HistoryEvent::EndTransaction);
+                    }
+
                     self.is_dragging = false;
                     //self.continuous_normal = self.normalized_value;
 
@@ -228,6 +237,10 @@ impl View for Knob {
                 }
 
                 WindowEvent::MouseDoubleClick(button) if *button == MouseButton::Left => {
+                    if self.is_dragging {
+                        cx.emit(HistoryEvent::EndTransaction);
+                    }
+
                     self.is_dragging = false;
 
                     move_virtual_slider(self, cx, self.default_normal);
@@ -3,8 +3,9 @@ use crate::{Context, Handle, LocalizedStringKey, View};
 pub struct Label;
 
 impl Label {
-    pub fn new<'a>(cx: &mut Context, text: impl LocalizedStringKey<'a>) -> Handle<Self> {
-        Self {}.build2(cx, |_| {}).text(text.key())
+    pub fn new(cx: &mut Context, text: impl LocalizedStringKey) -> Handle<Self> {
+        let text = text.localize(&cx.enviroment);
+        Self {}.build2(cx, |_| {}).text(&text)
     }
 }
 
@@ -1,8 +1,12 @@
+use std::hash::Hash;
 use std::marker::PhantomData;
 
 use keyboard_types::Code;
 
-use crate::{Binding, Context, Data, Handle, Lens, Model, MouseButton, TreeExt, View, WindowEvent};
+use crate::{
+    Binding, Context, Data, Handle, Lens, LensExt, Model, MouseButton, Reconciler, TreeExt, View,
+    WindowEvent,
+};
 
 /// An `ItemPtr` is used to access an item from context in a list item template.
 ///
@@ -59,39 +63,48 @@ where
         self.col
     }
 
-    pub fn get<'a>(&self, cx: &'a Context) -> &'a T
+    /// Returns a copy of the item the ItemPtr refers to.
+    pub fn get(&self, cx: &Context) -> T
     where
-        <L as Lens>::Source: 'static,
+        T: Clone + 'static,
     {
         self.lens
-            .view(cx.data().expect("Failed to get data"))
-            .get(self.index)
+            .index(self.index)
+            .view(cx.data().expect("Failed to get data"), |item| item.cloned())
             .expect(&format!("Failed to get item: {}", self.index))
     }
 }
 
 pub trait DataHandle: Clone + Copy {
     type Data;
-    fn get<'a>(&self, cx: &'a Context) -> &'a Self::Data;
+    fn get(&self, cx: &Context) -> Self::Data;
 }
 
 impl<L, T> DataHandle for ItemPtr<L, T>
 where
     L: Lens<Target = Vec<T>>,
+    T: Clone + 'static,
 {
     type Data = T;
-    fn get<'a>(&self, cx: &'a Context) -> &'a Self::Data {
+    fn get(&self, cx: &Context) -> Self::Data {
         self.get(cx)
     }
 }
 
 /// A view for creating a list of items from a binding to a Vec<T>
-pub struct List<L, T: 'static>
+///
+/// When the list changes, the views built for the items are reused rather than rebuilt. By default the items are matched
+/// to the views by position, so only the items which have changed are rebuilt. A list created with `with_key()` matches
+/// the items by a key instead, so the views of items which are moved, inserted or removed keep their local state.
+///
+/// The item template should build a single view for each item.
+pub struct List<L, T: 'static, K: 'static = usize>
 where
     L: Lens<Target = Vec<T>>,
     T: Data,
 {
     p: PhantomData<L>,
+    reconciler: Reconciler<K, T>,
     increment_callback: Option<Box<dyn Fn(&mut Context)>>,
     decrement_callback: Option<Box<dyn Fn(&mut Context)>>,
     clear_callback: Option<Box<dyn Fn(&mut Context)>>,
@@ -104,9 +117,37 @@ impl<L: 'static + Lens<Target = Vec<T>>, T: Data> List<L, T> {
         F: 'static + Fn(&mut Context, ItemPtr<L, T>),
         <L as Lens>::Source: Model,
     {
-        //let item_template = Rc::new(item);
+        Self::build_list(cx, lens, |index, _| index, item)
+    }
+}
+
+impl<L: 'static + Lens<Target = Vec<T>>, T: Data, K: 'static + Hash + Eq> List<L, T, K> {
+    /// Creates a new ListView which matches the list items to their views using the key returned by the `key` closure.
+    ///
+    /// # Example
+    /// ```compile_fail
+    /// List::with_key(cx, AppData::todos, |todo| todo.id, |cx, item|{
+    ///     Label::new(cx, &item.get(cx).text);
+    /// });
+    /// ```
+    pub fn with_key<G, F>(cx: &mut Context, lens: L, key: G, item: F) -> Handle<Self>
+    where
+        G: 'static + Fn(&T) -> K,
+        F: 'static + Fn(&mut Context, ItemPtr<L, T>),
+        <L as Lens>::Source: Model,
+    {
+        Self::build_list(cx, lens, move |_, data| (key)(data), item)
+    }
+
+    fn build_list<G, F>(cx: &mut Context, lens: L, key: G, item: F) -> Handle<Self>
+    where
+        G: 'static + Fn(usize, &T) -> K,
+        F: 'static + Fn(&mut Context, ItemPtr<L, T>),
+        <L as Lens>::Source: Model,
+    {
         List {
             p: PhantomData::default(),
+            reconciler: Reconciler::new(),
             increment_callback: None,
             decrement_callback: None,
             clear_callback: None,
@@ -114,32 +155,31 @@ impl<L: 'static + Lens<Target = Vec<T>>, T: Data> List<L, T> {
         .build2(cx, move |cx| {
             // Bind to the list data
             Binding::new(cx, lens.clone(), move |cx, list| {
-                // If the number of list items is different to the number of children of the ListView
-                // then remove and rebuild all the children
-                let list_len = list.get(cx).len();
-                let children = cx
-                    .current
-                    .child_iter(&cx.tree)
+                let items = list
+                    .get(cx)
+                    .into_iter()
                     .enumerate()
-                    .filter(|(child, _)| *child != 0)
-                    .collect::<Vec<_>>();
-                if children.len() != list_len {
-                    //cx.remove_children(cx.current);
-                    for (_, child) in children {
-                        cx.remove(child);
+                    .map(|(index, data)| ((key)(index, &data), data));
+
+                // The reconciler is stored in the list view, which is the parent of the binding
+                let entity = cx.current;
+                if let Some(mut view) = cx.views.remove(&entity) {
+                    if let Some(list_view) = view.downcast_mut::<List<L, T, K>>() {
+                        list_view.reconciler.reconcile(cx, items, |cx, index| {
+                            let ptr = ItemPtr::new(lens.clone(), index, index, 0);
+                            (item)(cx, ptr);
+                        });
                     }
-                }
 
-                for index in 0..list_len {
-                    let ptr = ItemPtr::new(lens.clone(), index, index, 0);
-                    (item)(cx, ptr);
+                    cx.views.insert(entity, view);
                 }
             });
         })
+        .focusable(true)
     }
 }
 
-impl<L: 'static + Lens<Target = Vec<T>>, T: Data> View for List<L, T> {
+impl<L: 'static + Lens<Target = Vec<T>>, T: Data, K: 'static> View for List<L, T, K> {
     fn element(&self) -> Option<String> {
         Some("list".to_string())
     }
@@ -178,19 +218,19 @@ impl<L: 'static + Lens<Target = Vec<T>>, T: Data> View for List<L, T> {
 
         if let Some(WindowEvent::MouseDown(MouseButton::Left)) = event.message.downcast() {
             if !cx.focused.is_child_of(&cx.tree, cx.current) {
-                cx.focused = cx.current;
+                cx.focus(cx.current);
             }
         }
     }
 }
 
-impl<L: Lens<Target = Vec<T>>, T: Data> Handle<'_, List<L, T>> {
+impl<L: Lens<Target = Vec<T>>, T: Data, K: 'static> Handle<'_, List<L, T, K>> {
     pub fn on_increment<F>(self, callback: F) -> Self
     where
         F: 'static + Fn(&mut Context),
     {
         if let Some(list) =
-            self.cx.views.get_mut(&self.entity).and_then(|f| f.downcast_mut::<List<L, T>>())
+            self.cx.views.get_mut(&self.entity).and_then(|f| f.downcast_mut::<List<L, T, K>>())
         {
             list.increment_callback = Some(Box::new(callback));
         }
@@ -203,7 +243,7 @@ impl<L: Lens<Target = Vec<T>>, T: Data> Handle<'_, List<L, T>> {
         F: 'static + Fn(&mut Context),
     {
         if let Some(list) =
-            self.cx.views.get_mut(&self.entity).and_then(|f| f.downcast_mut::<List<L, T>>())
+            self.cx.views.get_mut(&self.entity).and_then(|f| f.downcast_mut::<List<L, T, K>>())
         {
             list.decrement_callback = Some(Box::new(callback));
         }
@@ -216,7 +256,7 @@ impl<L: Lens<Target = Vec<T>>, T: Data> Handle<'_, List<L, T>> {
         F: 'static + Fn(&mut Context),
     {
         if let Some(list) =
-            self.cx.views.get_mut(&self.entity).and_then(|f| f.downcast_mut::<List<L, T>>())
+            self.cx.views.get_mut(&self.entity).and_then(|f| f.downcast_mut::<List<L, T, K>>())
         {
             list.clear_callback = Some(Box::new(callback));
         }
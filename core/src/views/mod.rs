@@ -1,6 +1,9 @@
 mod label;
 pub use label::Label;
 
+mod rich_text;
+pub use rich_text::RichText;
+
 mod stack;
 pub use stack::{HStack, VStack, ZStack};
 
@@ -10,6 +13,9 @@ pub use button::Button;
 mod list;
 pub use list::{DataHandle, ItemPtr, List};
 
+mod virtual_list;
+pub use virtual_list::{VirtualList, VirtualListEvent};
+
 mod table;
 pub use table::Table;
 
@@ -22,6 +28,12 @@ pub use checkbox::Checkbox;
 mod element;
 pub use element::Element;
 
+mod image;
+pub use image::Image;
+
+mod scroll_view;
+pub use scroll_view::{ScrollEvent, ScrollView};
+
 mod for_each;
 pub use for_each::ForEach;
 
@@ -50,7 +50,7 @@ impl Popup {
         Self {}
             .build2(cx, |cx| {
                 Binding::new(cx, PopupData::is_open, move |cx, flag| {
-                    let is_open = *flag.get(cx);
+                    let is_open = flag.get(cx);
 
                     cx.current.set_visibility(
                         cx,
@@ -8,7 +8,7 @@ pub struct RadioButton {
 
 impl RadioButton {
     pub fn new(cx: &mut Context, checked: bool) -> Handle<Self> {
-        Self { on_select: None }.build2(cx, |_| {}).checked(checked)
+        Self { on_select: None }.build2(cx, |_| {}).focusable(true).checked(checked)
     }
 }
 
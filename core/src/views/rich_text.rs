@@ -0,0 +1,41 @@
+use crate::{parse_markup, Context, Handle, TextSpan, View};
+
+/// A view which displays text made of spans with their own fonts, font sizes, colors and underlines.
+///
+/// Spans take any properties which they don't set from the text style of the view, so a `RichText` can be styled
+/// like a `Label`.
+pub struct RichText;
+
+impl RichText {
+    pub fn new(cx: &mut Context, spans: Vec<TextSpan>) -> Handle<Self> {
+        let handle = Self {}.build2(cx, |_| {});
+
+        if handle.cx.style.rich_text.get(handle.entity) != Some(&spans) {
+            handle
+                .cx
+                .style
+                .rich_text
+                .insert(handle.entity, spans)
+                .expect("Failed to set rich text");
+            handle.cx.style.needs_relayout = true;
+            handle.cx.style.needs_redraw = true;
+        }
+
+        handle
+    }
+
+    /// Creates a rich text from markup. See [parse_markup] for the tags which can be used. Problems with the markup
+    /// are reported according to the style error mode.
+    pub fn markup(cx: &mut Context, markup: &str) -> Handle<Self> {
+        let (spans, diagnostics) = parse_markup(markup);
+        cx.report_style_diagnostics("rich text markup", &diagnostics);
+
+        Self::new(cx, spans)
+    }
+}
+
+impl View for RichText {
+    fn element(&self) -> Option<String> {
+        Some("richtext".to_string())
+    }
+}
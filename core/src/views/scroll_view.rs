@@ -0,0 +1,435 @@
+use morphorm::{GeometryChanged, PositionType};
+
+use crate::{
+    Context, Display, Element, Entity, Event, Handle, Modifiers, MouseButton, Orientation, PropSet,
+    Propagation, TreeExt, Units::*, View, WindowEvent,
+};
+
+// Number of pixels scrolled per line of mouse wheel movement.
+const SCROLL_SENSITIVITY: f32 = 20.0;
+
+// Minimum length of a scrollbar thumb in pixels.
+const MIN_THUMB_SIZE: f32 = 20.0;
+
+/// Events which control the scroll position of a [ScrollView].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollEvent {
+    /// Scroll horizontally by the given number of pixels.
+    ScrollX(f32),
+    /// Scroll vertically by the given number of pixels.
+    ScrollY(f32),
+    /// Set the scroll offset, in pixels, from the top-left corner of the content.
+    SetScroll(f32, f32),
+    /// Scroll the minimum amount needed to bring the given descendant entity into view.
+    ScrollTo(Entity),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ScrollDrag {
+    orientation: Orientation,
+    // Mouse position when the drag started
+    start_pos: f32,
+    // Scroll offset when the drag started
+    start_scroll: f32,
+}
+
+/// A container which clips its content to its bounds and allows the content to be scrolled.
+///
+/// The content is offset by the scroll position and clipped through the `clip_widget` of the content.
+/// The view reacts to the mouse wheel (hold shift to scroll horizontally) and has a vertical and horizontal
+/// scrollbar, each with a draggable thumb, which are hidden when the content fits within the view.
+///
+/// The scrollbars can be styled with the `scrollbar`, `scrollbar.vertical`, `scrollbar.horizontal` and
+/// `scrollbar .thumb` selectors.
+///
+/// # Example
+/// ```compile_fail
+/// ScrollView::new(cx, false, true, |cx| {
+///     for i in 0..100 {
+///         Label::new(cx, &i.to_string());
+///     }
+/// })
+/// .height(Pixels(300.0));
+/// ```
+pub struct ScrollView {
+    scroll_x: f32,
+    scroll_y: f32,
+
+    content: Entity,
+    vertical_bar: Entity,
+    vertical_thumb: Entity,
+    horizontal_bar: Entity,
+    horizontal_thumb: Entity,
+
+    drag: Option<ScrollDrag>,
+}
+
+impl ScrollView {
+    /// Creates a new scroll view.
+    ///
+    /// If `scroll_x` is false the content is stretched to the width of the view, and likewise for `scroll_y`
+    /// and the height, otherwise the content is sized to its children.
+    pub fn new<F>(cx: &mut Context, scroll_x: bool, scroll_y: bool, content: F) -> Handle<Self>
+    where
+        F: 'static + FnOnce(&mut Context),
+    {
+        let handle = Self {
+            scroll_x: 0.0,
+            scroll_y: 0.0,
+            content: Entity::null(),
+            vertical_bar: Entity::null(),
+            vertical_thumb: Entity::null(),
+            horizontal_bar: Entity::null(),
+            horizontal_thumb: Entity::null(),
+            drag: None,
+        }
+        .build2(cx, move |cx| {
+            ScrollContent::new(cx, content)
+                .position_type(PositionType::SelfDirected)
+                .width(if scroll_x { Auto } else { Stretch(1.0) })
+                .height(if scroll_y { Auto } else { Stretch(1.0) });
+
+            ScrollBar::new(cx, Orientation::Vertical)
+                .position_type(PositionType::SelfDirected)
+                .left(Stretch(1.0))
+                .right(Pixels(0.0))
+                .top(Pixels(0.0))
+                .bottom(Pixels(0.0));
+
+            ScrollBar::new(cx, Orientation::Horizontal)
+                .position_type(PositionType::SelfDirected)
+                .left(Pixels(0.0))
+                .right(Pixels(0.0))
+                .top(Stretch(1.0))
+                .bottom(Pixels(0.0));
+        });
+
+        let entity = handle.entity;
+        let mut children = entity.child_iter(&handle.cx.tree);
+        let content = children.next().expect("Failed to find scroll content");
+        let vertical_bar = children.next().expect("Failed to find vertical scrollbar");
+        let horizontal_bar = children.next().expect("Failed to find horizontal scrollbar");
+        let vertical_thumb =
+            vertical_bar.child_iter(&handle.cx.tree).next().expect("Failed to find thumb");
+        let horizontal_thumb =
+            horizontal_bar.child_iter(&handle.cx.tree).next().expect("Failed to find thumb");
+
+        // Clip the content to the bounds of the scroll view rather than to its own bounds
+        content.set_clip_widget(handle.cx, entity);
+
+        // Restore the scroll position and scrollbars, which also hides the scrollbars until the content overflows
+        let prev = handle.cx.current;
+        handle.cx.current = entity;
+        if let Some(mut view) = handle.cx.views.remove(&entity) {
+            if let Some(scroll_view) = view.downcast_mut::<ScrollView>() {
+                scroll_view.content = content;
+                scroll_view.vertical_bar = vertical_bar;
+                scroll_view.vertical_thumb = vertical_thumb;
+                scroll_view.horizontal_bar = horizontal_bar;
+                scroll_view.horizontal_thumb = horizontal_thumb;
+
+                scroll_view.set_scroll(handle.cx, scroll_view.scroll_x, scroll_view.scroll_y);
+            }
+
+            handle.cx.views.insert(entity, view);
+        }
+        handle.cx.current = prev;
+
+        handle
+    }
+
+    /// Scrolls the nearest scroll view ancestor of the entity so that the entity is in view.
+    pub fn scroll_to(cx: &mut Context, entity: Entity) {
+        cx.event_queue.push_back(
+            Event::new(ScrollEvent::ScrollTo(entity))
+                .target(entity)
+                .origin(cx.current)
+                .propagate(Propagation::Up),
+        );
+    }
+
+    // Clamps the scroll offset to the size of the content and updates the content position and scrollbars.
+    // Returns true if the scroll offset changed.
+    fn set_scroll(&mut self, cx: &mut Context, scroll_x: f32, scroll_y: f32) -> bool {
+        let entity = cx.current;
+
+        let view_width = cx.cache.get_width(entity);
+        let view_height = cx.cache.get_height(entity);
+        let content_width = cx.cache.get_width(self.content);
+        let content_height = cx.cache.get_height(self.content);
+
+        let max_x = (content_width - view_width).max(0.0);
+        let max_y = (content_height - view_height).max(0.0);
+
+        let scroll_x = scroll_x.clamp(0.0, max_x);
+        let scroll_y = scroll_y.clamp(0.0, max_y);
+
+        let changed = scroll_x != self.scroll_x || scroll_y != self.scroll_y;
+
+        self.scroll_x = scroll_x;
+        self.scroll_y = scroll_y;
+
+        self.content.set_left(cx, Pixels(-scroll_x));
+        self.content.set_top(cx, Pixels(-scroll_y));
+
+        // Vertical scrollbar
+        if max_y > 0.0 {
+            self.vertical_bar.set_display(cx, Display::Flex);
+
+            let track = cx.cache.get_height(self.vertical_bar);
+            let thumb = (track * view_height / content_height).max(MIN_THUMB_SIZE).min(track);
+
+            self.vertical_thumb.set_top(cx, Pixels((track - thumb) * scroll_y / max_y));
+            self.vertical_thumb.set_height(cx, Pixels(thumb));
+        } else {
+            self.vertical_bar.set_display(cx, Display::None);
+        }
+
+        // Horizontal scrollbar
+        if max_x > 0.0 {
+            self.horizontal_bar.set_display(cx, Display::Flex);
+
+            let track = cx.cache.get_width(self.horizontal_bar);
+            let thumb = (track * view_width / content_width).max(MIN_THUMB_SIZE).min(track);
+
+            self.horizontal_thumb.set_left(cx, Pixels((track - thumb) * scroll_x / max_x));
+            self.horizontal_thumb.set_width(cx, Pixels(thumb));
+        } else {
+            self.horizontal_bar.set_display(cx, Display::None);
+        }
+
+        changed
+    }
+
+    // Returns the number of pixels of content scrolled per pixel of thumb movement.
+    fn drag_ratio(&self, cx: &Context, orientation: Orientation) -> f32 {
+        let (view, content, track, thumb) = match orientation {
+            Orientation::Horizontal => (
+                cx.cache.get_width(cx.current),
+                cx.cache.get_width(self.content),
+                cx.cache.get_width(self.horizontal_bar),
+                cx.cache.get_width(self.horizontal_thumb),
+            ),
+
+            Orientation::Vertical => (
+                cx.cache.get_height(cx.current),
+                cx.cache.get_height(self.content),
+                cx.cache.get_height(self.vertical_bar),
+                cx.cache.get_height(self.vertical_thumb),
+            ),
+        };
+
+        if track - thumb > 0.0 {
+            (content - view).max(0.0) / (track - thumb)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl View for ScrollView {
+    fn element(&self) -> Option<String> {
+        Some("scrollview".to_string())
+    }
+
+    fn event(&mut self, cx: &mut Context, event: &mut Event) {
+        if let Some(scroll_event) = event.message.downcast() {
+            match scroll_event {
+                ScrollEvent::ScrollX(delta) => {
+                    self.set_scroll(cx, self.scroll_x + *delta, self.scroll_y);
+                    event.consume();
+                }
+
+                ScrollEvent::ScrollY(delta) => {
+                    self.set_scroll(cx, self.scroll_x, self.scroll_y + *delta);
+                    event.consume();
+                }
+
+                ScrollEvent::SetScroll(x, y) => {
+                    self.set_scroll(cx, *x, *y);
+                    event.consume();
+                }
+
+                ScrollEvent::ScrollTo(entity) => {
+                    // Let an ancestor scroll view handle a request to show this scroll view
+                    if *entity == cx.current {
+                        return;
+                    }
+
+                    let view = cx.cache.get_bounds(cx.current);
+                    let bounds = cx.cache.get_bounds(*entity);
+
+                    let mut scroll_x = self.scroll_x;
+                    let mut scroll_y = self.scroll_y;
+
+                    if bounds.x < view.x {
+                        scroll_x -= view.x - bounds.x;
+                    } else if bounds.x + bounds.w > view.x + view.w {
+                        scroll_x += (bounds.x + bounds.w - view.x - view.w).min(bounds.x - view.x);
+                    }
+
+                    if bounds.y < view.y {
+                        scroll_y -= view.y - bounds.y;
+                    } else if bounds.y + bounds.h > view.y + view.h {
+                        scroll_y += (bounds.y + bounds.h - view.y - view.h).min(bounds.y - view.y);
+                    }
+
+                    self.set_scroll(cx, scroll_x, scroll_y);
+                    event.consume();
+                }
+            }
+        }
+
+        if let Some(window_event) = event.message.downcast() {
+            match window_event {
+                WindowEvent::GeometryChanged(geo) => {
+                    let target = event.target;
+                    if (target == cx.current
+                        || target == self.content
+                        || target == self.vertical_bar
+                        || target == self.horizontal_bar)
+                        && geo.intersects(
+                            GeometryChanged::WIDTH_CHANGED | GeometryChanged::HEIGHT_CHANGED,
+                        )
+                    {
+                        self.set_scroll(cx, self.scroll_x, self.scroll_y);
+                    }
+                }
+
+                WindowEvent::MouseScroll(x, y) => {
+                    let (x, y) = if cx.modifiers.contains(Modifiers::SHIFT) && *x == 0.0 {
+                        (*y, 0.0)
+                    } else {
+                        (*x, *y)
+                    };
+
+                    // Only consume the event if this view scrolled so that an outer scroll view can scroll instead
+                    if self.set_scroll(
+                        cx,
+                        self.scroll_x - x * SCROLL_SENSITIVITY,
+                        self.scroll_y - y * SCROLL_SENSITIVITY,
+                    ) {
+                        event.consume();
+                    }
+                }
+
+                WindowEvent::MouseDown(button) if *button == MouseButton::Left => {
+                    let (cursor_x, cursor_y) = (cx.mouse.cursorx, cx.mouse.cursory);
+
+                    if event.target == self.vertical_thumb {
+                        self.drag = Some(ScrollDrag {
+                            orientation: Orientation::Vertical,
+                            start_pos: cursor_y,
+                            start_scroll: self.scroll_y,
+                        });
+                    } else if event.target == self.horizontal_thumb {
+                        self.drag = Some(ScrollDrag {
+                            orientation: Orientation::Horizontal,
+                            start_pos: cursor_x,
+                            start_scroll: self.scroll_x,
+                        });
+                    } else if event.target == self.vertical_bar {
+                        // Move the thumb so that it is centered on the cursor
+                        let thumb = cx.cache.get_bounds(self.vertical_thumb);
+                        let ratio = self.drag_ratio(cx, Orientation::Vertical);
+                        let delta = (cursor_y - (thumb.y + thumb.h / 2.0)) * ratio;
+                        self.set_scroll(cx, self.scroll_x, self.scroll_y + delta);
+                    } else if event.target == self.horizontal_bar {
+                        let thumb = cx.cache.get_bounds(self.horizontal_thumb);
+                        let ratio = self.drag_ratio(cx, Orientation::Horizontal);
+                        let delta = (cursor_x - (thumb.x + thumb.w / 2.0)) * ratio;
+                        self.set_scroll(cx, self.scroll_x + delta, self.scroll_y);
+                    } else {
+                        return;
+                    }
+
+                    if self.drag.is_some() {
+                        cx.captured = cx.current;
+                    }
+
+                    event.consume();
+                }
+
+                WindowEvent::MouseUp(button) if *button == MouseButton::Left => {
+                    if self.drag.take().is_some() {
+                        cx.captured = Entity::null();
+                        event.consume();
+                    }
+                }
+
+                WindowEvent::MouseMove(x, y) => {
+                    if let Some(drag) = self.drag {
+                        let ratio = self.drag_ratio(cx, drag.orientation);
+
+                        match drag.orientation {
+                            Orientation::Horizontal => {
+                                let scroll_x = drag.start_scroll + (*x - drag.start_pos) * ratio;
+                                self.set_scroll(cx, scroll_x, self.scroll_y);
+                            }
+
+                            Orientation::Vertical => {
+                                let scroll_y = drag.start_scroll + (*y - drag.start_pos) * ratio;
+                                self.set_scroll(cx, self.scroll_x, scroll_y);
+                            }
+                        }
+                    }
+                }
+
+                _ => {}
+            }
+        }
+    }
+}
+
+// The container of the scrolled content.
+struct ScrollContent {}
+
+impl ScrollContent {
+    pub fn new<F>(cx: &mut Context, content: F) -> Handle<Self>
+    where
+        F: 'static + FnOnce(&mut Context),
+    {
+        Self {}.build2(cx, |cx| {
+            (content)(cx);
+        })
+    }
+}
+
+impl View for ScrollContent {
+    fn element(&self) -> Option<String> {
+        Some("scroll_content".to_string())
+    }
+}
+
+// A scrollbar track containing a thumb. Dragging is handled by the parent scroll view.
+struct ScrollBar {}
+
+impl ScrollBar {
+    pub fn new(cx: &mut Context, orientation: Orientation) -> Handle<Self> {
+        Self {}
+            .build2(cx, move |cx| {
+                let thumb =
+                    Element::new(cx).class("thumb").position_type(PositionType::SelfDirected);
+
+                match orientation {
+                    Orientation::Horizontal => {
+                        thumb.top(Pixels(0.0)).bottom(Pixels(0.0));
+                    }
+
+                    Orientation::Vertical => {
+                        thumb.left(Pixels(0.0)).right(Pixels(0.0));
+                    }
+                }
+            })
+            .class(match orientation {
+                Orientation::Horizontal => "horizontal",
+                Orientation::Vertical => "vertical",
+            })
+    }
+}
+
+impl View for ScrollBar {
+    fn element(&self) -> Option<String> {
+        Some("scrollbar".to_string())
+    }
+}
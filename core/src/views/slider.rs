@@ -1,8 +1,8 @@
 use morphorm::GeometryChanged;
 
 use crate::{
-    Actions, Binding, Context, Data, Element, Entity, Handle, Lens, Model, MouseButton, Overflow,
-    Units::*, View, WindowEvent, ZStack,
+    Actions, Binding, Context, Data, Element, Entity, Handle, HistoryEvent, Lens, Model,
+    MouseButton, Overflow, Units::*, View, WindowEvent, ZStack,
 };
 
 #[derive(Debug, Default, Lens)]
@@ -195,6 +195,7 @@ impl Slider {
                     });
                 });
             })
+            .focusable(true)
     }
 }
 
@@ -207,6 +208,11 @@ impl View for Slider {
         if let Some(window_event) = event.message.downcast() {
             match window_event {
                 WindowEvent::MouseDown(button) if *button == MouseButton::Left => {
+                    // Changes made while dragging are undone in a single step
+                    if !self.is_dragging {
+                        cx.emit(HistoryEvent::BeginTransaction);
+                    }
+
                     self.is_dragging = true;
                     cx.captured = cx.current;
 
@@ -235,6 +241,10 @@ impl View for Slider {
                 }
 
                 WindowEvent::MouseUp(button) if *button == MouseButton::Left => {
+                    if self.is_dragging {
+                        cx.emit(HistoryEvent::EndTransaction);
+                    }
+
                     self.is_dragging = false;
                     cx.captured = Entity::null();
                 }
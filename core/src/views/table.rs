@@ -1,29 +1,64 @@
+use std::marker::PhantomData;
 use std::rc::Rc;
 
 use morphorm::LayoutType;
 
 use crate::Units::*;
-use crate::{Context, HStack, Handle, ItemPtr, Lens, Model, TreeExt, View};
+use crate::{Binding, Context, Data, HStack, Handle, ItemPtr, Lens, Model, Reconciler, View};
 
 // TODO
 
 pub struct Table<L, T: 'static>
 where
     L: Lens<Target = Vec<T>>,
+    T: Data,
 {
-    width: usize,
-    lens: L,
-    builder: Option<Rc<dyn Fn(&mut Context, usize, ItemPtr<L, T>)>>,
+    p: PhantomData<L>,
+    reconciler: Reconciler<usize, T>,
 }
 
-impl<L: 'static + Lens<Target = Vec<T>>, T> Table<L, T> {
+impl<L: 'static + Lens<Target = Vec<T>>, T: Data> Table<L, T> {
     pub fn new<F>(cx: &mut Context, width: usize, lens: L, builder: F) -> Handle<Self>
     where
         F: 'static + Fn(&mut Context, usize, ItemPtr<L, T>),
         <L as Lens>::Source: Model,
     {
-        Self { lens, width, builder: Some(Rc::new(builder)) }
-            .build(cx)
+        let builder = Rc::new(builder);
+
+        Self { p: PhantomData::default(), reconciler: Reconciler::new() }
+            .build2(cx, move |cx| {
+                cx.style.grid_rows.insert(cx.current, vec![Stretch(1.0); width]);
+                cx.style.grid_cols.insert(cx.current, vec![Stretch(1.0); width]);
+
+                Binding::new(cx, lens.clone(), move |cx, table| {
+                    let items = table.get(cx);
+
+                    assert!(
+                        items.len() / width == width,
+                        "Only square tables supported at the moment"
+                    );
+
+                    // The cells are matched to their views by position, so only the changed cells are rebuilt
+                    let entity = cx.current;
+                    if let Some(mut view) = cx.views.remove(&entity) {
+                        if let Some(table_view) = view.downcast_mut::<Table<L, T>>() {
+                            let items = items.into_iter().enumerate();
+                            table_view.reconciler.reconcile(cx, items, |cx, index| {
+                                let (row, col) = (index / width, index % width);
+                                let ptr = ItemPtr::new(lens.clone(), index, row, col);
+                                let builder = builder.clone();
+                                HStack::new(cx, move |cx| {
+                                    (builder)(cx, width, ptr);
+                                })
+                                .row_index(row)
+                                .col_index(col);
+                            });
+                        }
+
+                        cx.views.insert(entity, view);
+                    }
+                });
+            })
             .layout_type(LayoutType::Grid)
             .row_between(Pixels(1.0))
             .col_between(Pixels(1.0))
@@ -33,48 +68,6 @@ impl<L: 'static + Lens<Target = Vec<T>>, T> Table<L, T> {
 impl<L, T> View for Table<L, T>
 where
     L: 'static + Lens<Target = Vec<T>>,
+    T: Data,
 {
-    fn body(&mut self, cx: &mut Context) {
-        for child in cx.current.child_iter(&cx.tree.clone()) {
-            cx.remove(child);
-        }
-
-        let builder = self.builder.take().unwrap();
-
-        let mut found_data = None;
-
-        'tree: for entity in cx.current.parent_iter(&cx.tree.clone()) {
-            if let Some(model_list) = cx.data.get(entity) {
-                for (_, model) in model_list.data.iter() {
-                    if let Some(data) = model.downcast_ref::<L::Source>() {
-                        found_data = Some(data);
-                        break 'tree;
-                    }
-                }
-            }
-        }
-
-        if let Some(data) = found_data {
-            let len = self.lens.view(data).len();
-
-            assert!(len / self.width == self.width, "Only square tables supported at the moment");
-
-            cx.style.grid_rows.insert(cx.current, vec![Stretch(1.0); self.width]);
-            cx.style.grid_cols.insert(cx.current, vec![Stretch(1.0); self.width]);
-
-            for row in 0..len / self.width {
-                for col in 0..self.width {
-                    let ptr = ItemPtr::new(self.lens.clone(), row * self.width + col, row, col);
-                    let width = self.width;
-                    let builder = builder.clone();
-                    HStack::new(cx, move |cx| {
-                        (builder)(cx, width, ptr.clone());
-                    })
-                    .row_index(row)
-                    .col_index(col);
-                    cx.count += 1;
-                }
-            }
-        }
-    }
 }
@@ -1,17 +1,19 @@
 use std::ops::Range;
+use std::time::Duration;
 
 #[cfg(feature = "clipboard")]
 use copypasta::ClipboardProvider;
 
 use femtovg::{Align, Baseline, Paint};
 use keyboard_types::Code;
-use morphorm::{PositionType, Units};
+use morphorm::{GeometryChanged, PositionType, Units};
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::style::PropGet;
 use crate::{
-    Binding, Context, CursorIcon, Data, EditableText, Element, Entity, Event, FontOrId, Handle,
-    Lens, Model, Modifiers, MouseButton, Movement, PropSet, Selection, Units::*, View, WindowEvent,
+    measure_glyphs, Binding, BoundingBox, Context, CursorIcon, Data, EditableText, Element, Entity,
+    Event, FontOrId, Handle, Lens, Model, Modifiers, MouseButton, Movement, PropSet, Selection,
+    TextLayout, Timer, TimerEvent, Units::*, VerticalMovement, View, WindowEvent, ZStack,
 };
 
 use crate::text::Direction;
@@ -20,6 +22,11 @@ use crate::text::Direction;
 pub struct TextboxData {
     editing: bool,
     selection: Selection,
+    // The width of the textbox, which multi-line text is wrapped to
+    width: f32,
+    // Text which is being composed with an input method, and the byte range of the cursor within it
+    preedit: String,
+    preedit_cursor: Option<(usize, usize)>,
 }
 
 #[derive(Debug)]
@@ -29,14 +36,31 @@ pub enum TextEvent {
     SetAnchor(usize),
     SetActive(usize),
     SetSelection(usize, usize),
+    SetPreedit(String, Option<(usize, usize)>),
 }
 
 impl Model for TextboxData {
-    fn event(&mut self, _: &mut Context, event: &mut crate::Event) {
+    fn event(&mut self, cx: &mut Context, event: &mut crate::Event) {
+        if let Some(WindowEvent::GeometryChanged(geo)) = event.message.downcast() {
+            if geo.contains(GeometryChanged::WIDTH_CHANGED) {
+                self.width = cx.cache.get_width(cx.current);
+            }
+        }
+
         if let Some(text_event) = event.message.downcast() {
             match text_event {
                 TextEvent::SetEditing(flag) => {
+                    // Input methods are only used while the text is being edited
+                    if self.editing != *flag {
+                        cx.emit(WindowEvent::SetImeAllowed(*flag));
+                    }
+
                     self.editing = *flag;
+
+                    if !self.editing {
+                        self.preedit.clear();
+                        self.preedit_cursor = None;
+                    }
                 }
 
                 TextEvent::SetCaret(index) => {
@@ -55,6 +79,11 @@ impl Model for TextboxData {
                     self.selection.anchor = *anchor;
                     self.selection.active = *active;
                 }
+
+                TextEvent::SetPreedit(text, cursor) => {
+                    self.preedit = text.clone();
+                    self.preedit_cursor = *cursor;
+                }
             }
         }
     }
@@ -145,6 +174,8 @@ fn update_caret(cx: &Context, text: &str, selection: &Selection) -> (f32, f32, f
         _ => Align::Left,
     };
 
+    x -= cx.style.text_scroll.get(cx.current).cloned().unwrap_or_default();
+
     let baseline = match child_top {
         Units::Pixels(val) => match child_bottom {
             Units::Stretch(_) => {
@@ -231,6 +262,252 @@ fn update_caret(cx: &Context, text: &str, selection: &Selection) -> (f32, f32, f
     (select_left, select_width, caret_left)
 }
 
+// Returns the text shown by a textbox, which has the text being composed with an input method in place of the
+// selection, along with the selection to show and the byte range of the composed text.
+fn compose_preedit(text: &str, text_data: &TextboxData) -> (String, Selection, Range<usize>) {
+    if text_data.preedit.is_empty() {
+        return (text.to_owned(), text_data.selection, 0..0);
+    }
+
+    let range = text_data.selection.range();
+    let start = range.start.min(text.len());
+
+    let mut composed = text.to_owned();
+    composed.replace_range(start..range.end.min(text.len()), &text_data.preedit);
+
+    let preedit = start..start + text_data.preedit.len();
+    let caret = match text_data.preedit_cursor {
+        Some((_, end)) => start + end.min(text_data.preedit.len()),
+        None => preedit.end,
+    };
+
+    (composed, Selection::caret(caret), preedit)
+}
+
+// Tells the window where the caret of the current textbox is, relative to the textbox, so that input methods can
+// place their candidate window next to it.
+fn set_ime_caret(cx: &mut Context, x: f32, y: f32, height: f32) {
+    let mut bounds = BoundingBox::default();
+    bounds.x = cx.cache.get_posx(cx.current) + x;
+    bounds.y = cx.cache.get_posy(cx.current) + y;
+    bounds.w = 1.0;
+    bounds.h = height;
+
+    cx.emit(WindowEvent::SetImeCaret(bounds));
+}
+
+// Returns true if the caret of a textbox is shown, which input methods can hide while text is being composed.
+fn caret_visible(text_data: &TextboxData) -> bool {
+    text_data.editing && (text_data.preedit.is_empty() || text_data.preedit_cursor.is_some())
+}
+
+// Scrolls the text of a single-line textbox so that the caret at the given byte index is in view, or back towards the
+// start of the text if it has become shorter.
+fn scroll_to_caret(cx: &mut Context, text: &str, index: usize) {
+    let entity = cx.current;
+    let (_, _, visible_width) = text_bounds(cx, entity, cx.cache.get_width(entity));
+    let layout = text_layout(cx, entity, text, 0.0);
+    let caret_x = layout.caret_position(index).0;
+    let text_width = layout.lines.first().map(|line| line.width).unwrap_or_default();
+
+    let scroll = cx.style.text_scroll.get(entity).cloned().unwrap_or_default();
+    let scroll = if visible_width <= 0.0 {
+        0.0
+    } else if caret_x < scroll {
+        caret_x
+    } else if caret_x + 1.0 > scroll + visible_width {
+        caret_x + 1.0 - visible_width
+    } else {
+        scroll
+    };
+    let scroll = scroll.min(text_width + 1.0 - visible_width).max(0.0);
+
+    if cx.style.text_scroll.get(entity) != Some(&scroll) {
+        cx.style.text_scroll.insert(entity, scroll).expect("Failed to set text scroll");
+        cx.style.needs_redraw = true;
+    }
+}
+
+// Returns the paint used to measure the text of an entity.
+fn text_paint(cx: &Context, entity: Entity) -> Paint {
+    let font = cx.style.font.get(entity).cloned().unwrap_or_default();
+
+    let default_font = cx
+        .resource_manager
+        .fonts
+        .get(&cx.style.default_font)
+        .and_then(|font| match font {
+            FontOrId::Id(id) => Some(id),
+            _ => None,
+        })
+        .expect("Failed to find default font");
+
+    let font_id = cx
+        .resource_manager
+        .fonts
+        .get(&font)
+        .and_then(|font| match font {
+            FontOrId::Id(id) => Some(id),
+            _ => None,
+        })
+        .unwrap_or(default_font);
+
+    let font_size = cx.style.font_size.get(entity).cloned().unwrap_or(16.0);
+
+    let mut paint = Paint::default();
+    paint.set_font_size(font_size);
+    paint.set_font(&[font_id.clone()]);
+    paint.set_text_align(Align::Left);
+    paint.set_text_baseline(Baseline::Top);
+    paint
+}
+
+// Returns the offset of multi-line text from the top left of a textbox of the given width, and the width to wrap it to.
+fn text_bounds(cx: &Context, entity: Entity, width: f32) -> (f32, f32, f32) {
+    let parent_width =
+        cx.tree.get_parent(entity).map(|parent| cx.cache.get_width(parent)).unwrap_or_default();
+
+    let border_width = match cx.style.border_width.get(entity).cloned().unwrap_or_default() {
+        Units::Pixels(val) => val,
+        Units::Percentage(val) => parent_width * val,
+        _ => 0.0,
+    };
+
+    // Only fixed child spacing is used to inset the text
+    let pixels = |units: Option<&Units>| match units {
+        Some(Units::Pixels(val)) => *val,
+        _ => 0.0,
+    };
+
+    let child_left = pixels(cx.style.child_left.get(entity));
+    let child_right = pixels(cx.style.child_right.get(entity));
+    let child_top = pixels(cx.style.child_top.get(entity));
+
+    (
+        border_width + child_left,
+        border_width + child_top,
+        width - 2.0 * border_width - child_left - child_right,
+    )
+}
+
+// Lays out the text of an entity, wrapping it to the given width if it is positive.
+fn text_layout(cx: &Context, entity: Entity, text: &str, max_width: f32) -> TextLayout {
+    let paint = text_paint(cx, entity);
+
+    let line_height = cx
+        .text_context
+        .measure_font(paint)
+        .map(|font_metrics| font_metrics.height())
+        .expect("Failed to read font metrics");
+
+    TextLayout::new(text, (max_width > 0.0).then(|| max_width), line_height, |line| {
+        measure_glyphs(&cx.text_context, line, paint)
+    })
+}
+
+// Builds the selection, lines, composed text underline and caret of a multi-line textbox from its text and selection.
+fn build_lines(cx: &mut Context, text: &str, text_data: &TextboxData) {
+    let entity = cx.current;
+    let (text, selection, preedit) = compose_preedit(text, text_data);
+    let text = text.as_str();
+    let (left, top, max_width) = text_bounds(cx, entity, text_data.width);
+    let layout = text_layout(cx, entity, text, max_width);
+    let line_height = layout.line_height;
+
+    // Selection
+    let selection_rects = layout.selection_rects(selection.range());
+    ZStack::new(cx, move |cx| {
+        for (x, y, width) in selection_rects {
+            Element::new(cx)
+                .left(Pixels(left + x))
+                .top(Pixels(top + y))
+                .width(Pixels(width))
+                .height(Pixels(line_height))
+                .class("selection")
+                .position_type(PositionType::SelfDirected);
+        }
+
+        remove_unused_children(cx);
+    })
+    .position_type(PositionType::SelfDirected)
+    .width(Stretch(1.0))
+    .height(Stretch(1.0))
+    .hoverable(false)
+    .visibility(text_data.editing);
+
+    // Lines
+    let lines = layout
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            (text[line.range.clone()].to_owned(), index as f32 * line_height, line.width)
+        })
+        .collect::<Vec<_>>();
+    ZStack::new(cx, move |cx| {
+        for (line, y, width) in lines {
+            Element::new(cx)
+                .text(&line)
+                .left(Pixels(left))
+                .top(Pixels(top + y))
+                .width(Pixels(width + 1.0))
+                .height(Pixels(line_height))
+                .position_type(PositionType::SelfDirected)
+                .hoverable(false);
+        }
+
+        remove_unused_children(cx);
+    })
+    .position_type(PositionType::SelfDirected)
+    .width(Stretch(1.0))
+    .height(Stretch(1.0))
+    .hoverable(false);
+
+    // Composed text underline
+    let underline_rects = layout.selection_rects(preedit);
+    ZStack::new(cx, move |cx| {
+        for (x, y, width) in underline_rects {
+            Element::new(cx)
+                .left(Pixels(left + x))
+                .top(Pixels(top + y + line_height - 1.0))
+                .width(Pixels(width))
+                .height(Pixels(1.0))
+                .class("preedit")
+                .position_type(PositionType::SelfDirected);
+        }
+
+        remove_unused_children(cx);
+    })
+    .position_type(PositionType::SelfDirected)
+    .width(Stretch(1.0))
+    .height(Stretch(1.0))
+    .hoverable(false)
+    .visibility(text_data.editing);
+
+    // Caret
+    let (x, y) = layout.caret_position(selection.active);
+    Element::new(cx)
+        .left(Pixels(left + x))
+        .top(Pixels(top + y))
+        .width(Pixels(1.0))
+        .height(Pixels(line_height))
+        .class("caret")
+        .position_type(PositionType::SelfDirected)
+        .visibility(caret_visible(text_data));
+
+    if text_data.editing {
+        set_ime_caret(cx, left + x, top + y, line_height);
+    }
+}
+
+// Removes the children of the current entity after the ones which have been built, such as the lines of a text which
+// has fewer lines than it did.
+fn remove_unused_children(cx: &mut Context) {
+    while let Some(child) = cx.tree.get_child(cx.current, cx.count) {
+        cx.remove(child);
+    }
+}
+
 pub struct Textbox<L, T>
 where
     L: Lens<Target = T>,
@@ -240,8 +517,15 @@ where
     //text_data: TextData,
     //selection: Selection,
     edit: bool,
+    multiline: bool,
     hitx: f32,
     dragx: f32,
+    // The horizontal position the caret returns to when it is moved between lines of different lengths
+    desired_x: Option<f32>,
+    // The number of clicks in a row, which is set by double and triple click events before the press they precede
+    click_count: u32,
+    // Scrolls single-line text while the mouse is dragged outside of the textbox
+    scroll_timer: Option<Timer>,
     on_edit: Option<Box<dyn Fn(&mut Context, Range<usize>, String)>>,
     //on_submit: Option<Box<dyn Fn(&mut Context, &Self)>>,
 }
@@ -255,74 +539,124 @@ where
     where
         <L as Lens>::Source: Model,
     {
-        // let selection = if let Some(source) = cx.data::<L::Source>() {
-        //     let text = lens.view(source);
-        //     Selection::new(0, text.len())
-        // } else {
-        //     Selection::caret(0)
-        // };
+        Self::build_textbox(cx, lens, false)
+    }
 
-        //let text_length = cx.data::<L::Source>().and_then(|source| Some(lens.view(source))).unwrap().len();
+    /// Creates a textbox which edits multiple lines of text.
+    ///
+    /// The text is wrapped to the width of the textbox, Enter inserts a newline, and the caret can be moved between
+    /// lines with the arrow, Home, End, PageUp and PageDown keys.
+    pub fn new_multiline<'a>(cx: &'a mut Context, lens: L) -> Handle<'a, Self>
+    where
+        <L as Lens>::Source: Model,
+    {
+        Self::build_textbox(cx, lens, true).class("multiline")
+    }
 
+    fn build_textbox<'a>(cx: &'a mut Context, lens: L, multiline: bool) -> Handle<'a, Self>
+    where
+        <L as Lens>::Source: Model,
+    {
         Self {
-            // text_data: TextData {
-            //     //text: placeholder.to_string(),
-            //     // selection: Selection::new(0, placeholder.len()),
-            //     selection: Selection::new(0, placeholder.len()),
-            // },
             lens,
-            //selection: Selection::new(0, text_length),
             edit: false,
+            multiline,
             hitx: -1.0,
             dragx: -1.0,
+            desired_x: None,
+            click_count: 1,
+            scroll_timer: None,
             on_edit: None,
-            //on_submit: None,
         }
         .build2(cx, move |cx| {
-            TextboxData { editing: false, selection: Selection::caret(0) }.build(cx);
-
-            Binding::new(cx, lens.clone(), |cx, text| {
-                let text_string = text.get(cx).as_str().to_owned();
-                cx.current.set_text(cx, &text_string);
-
-                Binding::new(cx, TextboxData::root, move |cx, text_data| {
-                    let editing = text_data.get(cx).editing;
-
-                    let (select_left, select_width, caret_left) =
-                        update_caret(cx, text.get(cx).as_str(), &text_data.get(cx).selection);
-
-                    // Selection
-                    Element::new(cx)
-                        .left(Pixels(select_left))
-                        .width(Pixels(select_width))
-                        .class("selection")
-                        //.background_color(Color::rgba(100, 100, 200, 120))
-                        .position_type(PositionType::SelfDirected)
-                        .visibility(editing);
-
-                    // Caret
-                    Element::new(cx)
-                        .left(Pixels(caret_left))
-                        .class("caret")
-                        //.background_color(Color::rgba(255, 0, 0, 255))
-                        .position_type(PositionType::SelfDirected)
-                        .width(Pixels(1.0))
-                        .visibility(editing);
+            TextboxData {
+                editing: false,
+                selection: Selection::caret(0),
+                width: 0.0,
+                preedit: String::new(),
+                preedit_cursor: None,
+            }
+            .build(cx);
+
+            if multiline {
+                Binding::new(cx, lens.clone(), |cx, text| {
+                    Binding::new(cx, TextboxData::root, move |cx, text_data| {
+                        let text = text.get(cx).as_str().to_owned();
+                        let text_data = text_data.get(cx);
+                        build_lines(cx, &text, &text_data);
+                    });
+                });
+            } else {
+                Binding::new(cx, lens.clone(), |cx, text| {
+                    Binding::new(cx, TextboxData::root, move |cx, text_data| {
+                        let text_data = text_data.get(cx);
+                        let editing = text_data.editing;
+
+                        // Text being composed with an input method is shown in place of the selection
+                        let (text_string, selection, preedit) =
+                            compose_preedit(text.get(cx).as_str(), &text_data);
+                        cx.current.set_text(cx, &text_string);
+                        scroll_to_caret(cx, &text_string, selection.active);
+
+                        let (select_left, select_width, caret_left) =
+                            update_caret(cx, &text_string, &selection);
+
+                        // Selection
+                        Element::new(cx)
+                            .left(Pixels(select_left))
+                            .width(Pixels(select_width))
+                            .class("selection")
+                            //.background_color(Color::rgba(100, 100, 200, 120))
+                            .position_type(PositionType::SelfDirected)
+                            .visibility(editing);
+
+                        // Caret
+                        Element::new(cx)
+                            .left(Pixels(caret_left))
+                            .class("caret")
+                            //.background_color(Color::rgba(255, 0, 0, 255))
+                            .position_type(PositionType::SelfDirected)
+                            .width(Pixels(1.0))
+                            .visibility(caret_visible(&text_data));
+
+                        // Composed text underline, which is placed under the vertically centered text
+                        let (preedit_left, preedit_width, _) = update_caret(
+                            cx,
+                            &text_string,
+                            &Selection::new(preedit.start, preedit.end),
+                        );
+                        let font_height = cx
+                            .text_context
+                            .measure_font(text_paint(cx, cx.current))
+                            .map(|font_metrics| font_metrics.height())
+                            .unwrap_or_default();
+                        let text_top = 0.5 * (cx.cache.get_height(cx.current) - font_height);
+
+                        Element::new(cx)
+                            .left(Pixels(preedit_left))
+                            .top(Pixels(text_top + font_height - 1.0))
+                            .width(Pixels(preedit_width))
+                            .height(Pixels(1.0))
+                            .class("preedit")
+                            .position_type(PositionType::SelfDirected)
+                            .visibility(editing && !preedit.is_empty());
+
+                        if editing {
+                            set_ime_caret(cx, caret_left, text_top, font_height);
+                        }
+                    });
                 });
-            });
+            }
         })
-        //.text(text.as_str())
+        .focusable(true)
     }
 
-    pub fn get_text<'a>(&self, cx: &'a Context) -> Option<&'a T> {
-        if let Some(source) = cx.data::<L::Source>() {
-            return Some(self.lens.view(source));
-        }
-
-        None
+    pub fn get_text(&self, cx: &Context) -> Option<T> {
+        cx.data::<L::Source>().and_then(|source| self.lens.view(source, |text| text.cloned()))
     }
 
     pub fn insert_text(&mut self, cx: &mut Context, text: String) {
+        self.desired_x = None;
         if let Some(text_data) = cx.data::<TextboxData>().cloned() {
             let text_length = text.len();
             // Send event to edit text
@@ -337,6 +671,7 @@ where
     }
 
     pub fn delete_text(&mut self, cx: &mut Context, movement: Movement) {
+        self.desired_x = None;
         if let Some(text_data) = cx.data::<TextboxData>().cloned() {
             if let Some(text) = self.get_text(cx) {
                 // If selection is a range - delete the selection
@@ -429,6 +764,11 @@ where
     }
 
     pub fn move_cursor(&mut self, cx: &mut Context, movement: Movement, selection: bool) {
+        // Moving the caret between lines keeps it at the same horizontal position where possible
+        if !matches!(movement, Movement::Vertical(_)) {
+            self.desired_x = None;
+        }
+
         if let Some(text_data) = cx.data::<TextboxData>().cloned() {
             if let Some(text) = self.get_text(cx) {
                 match movement {
@@ -500,18 +840,166 @@ where
                         }
                     }
 
+                    Movement::Line(direction) => {
+                        let layout = self.layout(cx, text.as_str());
+                        let line = &layout.lines[layout.line_of(text_data.selection.active)];
+                        let active = match direction {
+                            Direction::Left | Direction::Upstream => line.range.start,
+                            Direction::Right | Direction::Downstream => line.end(),
+                        };
+
+                        cx.emit(TextEvent::SetActive(active));
+                        if !selection {
+                            cx.emit(TextEvent::SetAnchor(active));
+                        }
+                    }
+
+                    Movement::Vertical(vertical) => {
+                        let layout = self.layout(cx, text.as_str());
+                        let active = text_data.selection.active;
+                        let line = layout.line_of(active);
+                        let last_line = layout.lines.len() - 1;
+                        let x =
+                            *self.desired_x.get_or_insert_with(|| layout.caret_position(active).0);
+
+                        // The number of lines which fit in the textbox
+                        let page = ((cx.cache.get_height(cx.current) / layout.line_height)
+                            as usize)
+                            .max(1);
+
+                        let active = match vertical {
+                            VerticalMovement::LineUp if line == 0 => 0,
+                            VerticalMovement::LineUp => layout.lines[line - 1].hit(x),
+                            VerticalMovement::LineDown if line == last_line => text.len(),
+                            VerticalMovement::LineDown => layout.lines[line + 1].hit(x),
+                            VerticalMovement::PageUp => {
+                                layout.lines[line.saturating_sub(page)].hit(x)
+                            }
+                            VerticalMovement::PageDown => {
+                                layout.lines[(line + page).min(last_line)].hit(x)
+                            }
+                            VerticalMovement::DocumentStart => 0,
+                            VerticalMovement::DocumentEnd => text.len(),
+                        };
+
+                        cx.emit(TextEvent::SetActive(active));
+                        if !selection {
+                            cx.emit(TextEvent::SetAnchor(active));
+                        }
+                    }
+
                     _ => {}
                 }
             }
         }
     }
 
+    // Lays out the text of the textbox, which is wrapped to the width of the textbox if it is multi-line.
+    fn layout(&self, cx: &Context, text: &str) -> TextLayout {
+        let entity = cx.current;
+        let max_width = if self.multiline {
+            text_bounds(cx, entity, cx.cache.get_width(entity)).2
+        } else {
+            0.0
+        };
+
+        text_layout(cx, entity, text, max_width)
+    }
+
+    // Returns the byte index of the caret position closest to the mouse cursor, taking into account how far the text
+    // of a single-line textbox is scrolled.
+    fn hit_caret(&self, cx: &Context) -> Option<usize> {
+        let entity = cx.current;
+        let (left, top, _) = text_bounds(cx, entity, cx.cache.get_width(entity));
+        let scroll = cx.style.text_scroll.get(entity).cloned().unwrap_or_default();
+        let x = cx.mouse.cursorx - cx.cache.get_posx(entity) - left + scroll;
+        let y = cx.mouse.cursory - cx.cache.get_posy(entity) - top;
+
+        self.get_text(cx).map(|text| self.layout(cx, text.as_str()).hit(x, y))
+    }
+
+    // Selects the text under the mouse cursor for a press of the left mouse button, which is a word when double
+    // clicked and a line when triple clicked. Holding shift extends the selection to the cursor instead.
+    fn select_at_cursor(&mut self, cx: &mut Context) {
+        self.desired_x = None;
+
+        let index = match self.hit_caret(cx) {
+            Some(index) => index,
+            None => return,
+        };
+
+        let range = match self.click_count {
+            2 => self.get_text(cx).map(|text| text.word_range(index)),
+            3 => self.get_text(cx).map(|text| {
+                let layout = self.layout(cx, text.as_str());
+                let line = &layout.lines[layout.line_of(index)];
+                line.range.start..line.end()
+            }),
+            _ => None,
+        };
+
+        if let Some(range) = range {
+            cx.emit(TextEvent::SetSelection(range.start, range.end));
+            // Dragging doesn't change a word or line selection
+            self.hitx = -1.0;
+        } else if cx.modifiers.contains(Modifiers::SHIFT) {
+            cx.emit(TextEvent::SetActive(index));
+        } else {
+            cx.emit(TextEvent::SetCaret(index));
+        }
+    }
+
+    // Starts scrolling single-line text when the mouse is dragged past either side of the textbox, and stops when it
+    // is dragged back.
+    fn update_scroll_timer(&mut self, cx: &mut Context) {
+        let posx = cx.cache.get_posx(cx.current);
+        let outside = self.dragx < posx || self.dragx > posx + cx.cache.get_width(cx.current);
+
+        match self.scroll_timer {
+            None if outside => {
+                self.scroll_timer = Some(cx.add_timer(Duration::from_millis(50), true));
+            }
+
+            Some(timer) if !outside => {
+                cx.remove_timer(timer);
+                self.scroll_timer = None;
+            }
+
+            _ => {}
+        }
+    }
+
+    fn stop_scroll_timer(&mut self, cx: &mut Context) {
+        if let Some(timer) = self.scroll_timer.take() {
+            cx.remove_timer(timer);
+        }
+    }
+
+    // Stops editing when the mouse is pressed outside of the textbox or it loses focus.
+    fn stop_editing(&mut self, cx: &mut Context) {
+        self.edit = false;
+        self.hitx = -1.0;
+        self.stop_scroll_timer(cx);
+        cx.emit(TextEvent::SetEditing(false));
+
+        if cx.captured == cx.current {
+            cx.captured = Entity::null();
+        }
+
+        cx.current.set_checked(cx, false);
+    }
+
     pub fn select_all(&mut self, cx: &mut Context) {
         let text_length = self.get_text(cx).unwrap().len();
         cx.emit(TextEvent::SetSelection(0, text_length));
     }
 
     fn set_caret(&mut self, cx: &mut Context, entity: Entity) {
+        // The caret and selection of a multi-line textbox are positioned when its lines are built
+        if self.multiline {
+            return;
+        }
+
         if let Some(text_data) = cx.data::<TextboxData>().cloned() {
             // TODO - replace this with something better
             let selection = cx.tree.get_child(entity, 2).unwrap();
@@ -604,6 +1092,8 @@ where
                     _ => Align::Left,
                 };
 
+                x -= cx.style.text_scroll.get(entity).cloned().unwrap_or_default();
+
                 let baseline = match child_top {
                     Units::Pixels(val) => match child_bottom {
                         Units::Stretch(_) => {
@@ -818,6 +1308,15 @@ where
     }
 
     fn event(&mut self, cx: &mut Context, event: &mut crate::Event) {
+        if let Some(TimerEvent::Tick(timer)) = event.message.downcast() {
+            // Keep selecting towards the mouse as the text scrolls
+            if self.scroll_timer == Some(*timer) && self.hitx != -1.0 {
+                if let Some(index) = self.hit_caret(cx) {
+                    cx.emit(TextEvent::SetActive(index));
+                }
+            }
+        }
+
         if let Some(window_event) = event.message.downcast() {
             match window_event {
                 WindowEvent::MouseDown(button) if *button == MouseButton::Left => {
@@ -825,32 +1324,43 @@ where
                         if !self.edit {
                             self.edit = true;
                             cx.emit(TextEvent::SetEditing(true));
-                            cx.focused = cx.current;
-                            cx.captured = cx.current;
+                            cx.focus(cx.current);
                             cx.current.set_checked(cx, true);
                         }
 
+                        // Capture the mouse so that a press outside of the textbox stops editing
+                        cx.captured = cx.current;
+
                         // Hit test
                         if self.edit {
                             self.hitx = cx.mouse.cursorx;
                             self.dragx = cx.mouse.cursorx;
                         }
-                        self.set_caret(cx, cx.current);
+
+                        self.select_at_cursor(cx);
                     } else {
-                        cx.captured = Entity::null();
-                        cx.current.set_checked(cx, false);
-                        self.edit = false;
-                        cx.emit(TextEvent::SetEditing(false));
+                        self.stop_editing(cx);
                         // Forward event to hovered
                         cx.event_queue.push_back(
                             Event::new(WindowEvent::MouseDown(MouseButton::Left))
                                 .target(cx.hovered),
                         );
                     }
+
+                    self.click_count = 1;
+                }
+
+                WindowEvent::MouseDoubleClick(button) if *button == MouseButton::Left => {
+                    self.click_count = 2;
+                }
+
+                WindowEvent::MouseTripleClick(button) if *button == MouseButton::Left => {
+                    self.click_count = 3;
                 }
 
                 WindowEvent::MouseUp(button) if *button == MouseButton::Left => {
                     self.hitx = -1.0;
+                    self.stop_scroll_timer(cx);
                     self.set_caret(cx, cx.current);
                 }
 
@@ -858,7 +1368,14 @@ where
                     if self.hitx != -1.0 {
                         self.dragx = *x;
 
-                        self.set_caret(cx, cx.current);
+                        // Select from where the mouse was pressed, which may be on another line
+                        if let Some(index) = self.hit_caret(cx) {
+                            cx.emit(TextEvent::SetActive(index));
+                        }
+
+                        if !self.multiline {
+                            self.update_scroll_timer(cx);
+                        }
                     }
                 }
 
@@ -875,6 +1392,8 @@ where
                         if *c != '\u{1b}' && // Escape
                             *c != '\u{8}' && // Backspace
                             *c != '\u{7f}' && // Delete
+                            *c != '\t' && // Tab
+                            *c != '\r' && *c != '\n' && // Enter, which is handled as a key press
                             !cx.modifiers.contains(Modifiers::CTRL)
                         {
                             self.insert_text(cx, String::from(*c));
@@ -885,12 +1404,59 @@ where
                     }
                 }
 
+                WindowEvent::ImePreedit { text, cursor } => {
+                    if self.edit {
+                        cx.emit(TextEvent::SetPreedit(text.clone(), *cursor));
+                    }
+                }
+
+                WindowEvent::ImeCommit(text) => {
+                    if self.edit {
+                        cx.emit(TextEvent::SetPreedit(String::new(), None));
+
+                        // A single line textbox can't contain newlines
+                        let text = if self.multiline {
+                            text.clone()
+                        } else {
+                            text.replace(|c| c == '\r' || c == '\n', "")
+                        };
+
+                        if !text.is_empty() {
+                            self.insert_text(cx, text);
+                        }
+
+                        self.set_caret(cx, cx.current);
+                    }
+                }
+
+                WindowEvent::FocusIn => {
+                    // Start editing when focused with the keyboard, without capturing the mouse
+                    if !self.edit {
+                        self.edit = true;
+                        cx.emit(TextEvent::SetEditing(true));
+                        cx.current.set_checked(cx, true);
+                        self.set_caret(cx, cx.current);
+                    }
+                }
+
+                WindowEvent::FocusOut => {
+                    if self.edit {
+                        self.stop_editing(cx);
+                    }
+                }
+
                 WindowEvent::KeyDown(code, _) => match code {
                     Code::Enter => {
-                        // Finish editing
-                        self.edit = false;
-                        cx.emit(TextEvent::SetEditing(false));
-                        cx.current.set_checked(cx, false);
+                        if self.multiline {
+                            if self.edit {
+                                self.insert_text(cx, String::from("\n"));
+                            }
+                        } else {
+                            // Finish editing
+                            self.edit = false;
+                            cx.emit(TextEvent::SetEditing(false));
+                            cx.current.set_checked(cx, false);
+                        }
                     }
 
                     Code::ArrowLeft => {
@@ -921,11 +1487,21 @@ where
                         }
                     }
 
-                    // TODO
-                    Code::ArrowUp => {}
+                    Code::ArrowUp => {
+                        if self.edit {
+                            let movement = Movement::Vertical(VerticalMovement::LineUp);
+                            self.move_cursor(cx, movement, cx.modifiers.contains(Modifiers::SHIFT));
+                            self.set_caret(cx, cx.current);
+                        }
+                    }
 
-                    // TODO
-                    Code::ArrowDown => {}
+                    Code::ArrowDown => {
+                        if self.edit {
+                            let movement = Movement::Vertical(VerticalMovement::LineDown);
+                            self.move_cursor(cx, movement, cx.modifiers.contains(Modifiers::SHIFT));
+                            self.set_caret(cx, cx.current);
+                        }
+                    }
 
                     Code::Backspace => {
                         if self.edit {
@@ -956,17 +1532,47 @@ where
                         cx.current.set_checked(cx, false);
                     }
 
-                    // TODO
-                    Code::Home => {}
+                    Code::Home => {
+                        if self.edit {
+                            let movement = if cx.modifiers.contains(Modifiers::CTRL) {
+                                Movement::Vertical(VerticalMovement::DocumentStart)
+                            } else {
+                                Movement::Line(Direction::Upstream)
+                            };
 
-                    // TODO
-                    Code::End => {}
+                            self.move_cursor(cx, movement, cx.modifiers.contains(Modifiers::SHIFT));
+                            self.set_caret(cx, cx.current);
+                        }
+                    }
 
-                    // TODO
-                    Code::PageUp => {}
+                    Code::End => {
+                        if self.edit {
+                            let movement = if cx.modifiers.contains(Modifiers::CTRL) {
+                                Movement::Vertical(VerticalMovement::DocumentEnd)
+                            } else {
+                                Movement::Line(Direction::Downstream)
+                            };
 
-                    // TODO
-                    Code::PageDown => {}
+                            self.move_cursor(cx, movement, cx.modifiers.contains(Modifiers::SHIFT));
+                            self.set_caret(cx, cx.current);
+                        }
+                    }
+
+                    Code::PageUp => {
+                        if self.edit {
+                            let movement = Movement::Vertical(VerticalMovement::PageUp);
+                            self.move_cursor(cx, movement, cx.modifiers.contains(Modifiers::SHIFT));
+                            self.set_caret(cx, cx.current);
+                        }
+                    }
+
+                    Code::PageDown => {
+                        if self.edit {
+                            let movement = Movement::Vertical(VerticalMovement::PageDown);
+                            self.move_cursor(cx, movement, cx.modifiers.contains(Modifiers::SHIFT));
+                            self.set_caret(cx, cx.current);
+                        }
+                    }
 
                     Code::KeyA => {
                         if self.edit {
@@ -976,12 +1582,19 @@ where
                         }
                     }
 
+                    // Undo and redo while editing apply to the text rather than the application
+                    Code::KeyZ => {
+                        if self.edit && cx.modifiers.contains(Modifiers::CTRL) {
+                            event.consume();
+                        }
+                    }
+
                     Code::KeyC =>
                     {
                         #[cfg(feature = "clipboard")]
                         if self.edit {
                             if cx.modifiers.contains(Modifiers::CTRL) {
-                                if let Some(text) = self.get_text(cx).cloned() {
+                                if let Some(text) = self.get_text(cx) {
                                     cx.clipboard
                                         .set_contents(text.as_str().to_owned())
                                         .expect("Failed to add text to clipboard");
@@ -0,0 +1,533 @@
+use std::marker::PhantomData;
+
+use keyboard_types::Code;
+use morphorm::{GeometryChanged, PositionType};
+
+use crate::{
+    Binding, Context, Data, Entity, Event, Handle, ItemPtr, Lens, Model, MouseButton, PropSet,
+    ScrollEvent, TreeExt, Units::*, View, WindowEvent,
+};
+
+// Height used for rows which have not been measured when no row height is set.
+const DEFAULT_ROW_HEIGHT: f32 = 30.0;
+
+// Number of rows built above and below the visible rows by default.
+const DEFAULT_OVERSCAN: usize = 5;
+
+// Index of the first row among the children of the list. The first child is the binding to the list data.
+const FIRST_ROW: usize = 1;
+
+// Sums of the measured heights, and numbers of measured rows, before each row, stored as a Fenwick tree
+// so that a height can be changed and the sums read in logarithmic time.
+#[derive(Debug, Default)]
+struct RowOffsets {
+    heights: Vec<f32>,
+    counts: Vec<usize>,
+}
+
+impl RowOffsets {
+    fn new(heights: &[Option<f32>]) -> Self {
+        let len = heights.len() + 1;
+        let mut offsets = Self { heights: vec![0.0; len], counts: vec![0; len] };
+
+        for (index, height) in heights.iter().enumerate() {
+            let node = index + 1;
+            if let Some(height) = height {
+                offsets.heights[node] += *height;
+                offsets.counts[node] += 1;
+            }
+
+            let parent = node + (node & node.wrapping_neg());
+            if parent < len {
+                offsets.heights[parent] += offsets.heights[node];
+                offsets.counts[parent] += offsets.counts[node];
+            }
+        }
+
+        offsets
+    }
+
+    // Adds to the measured height and the number of measured rows of the row with the given index.
+    fn add(&mut self, index: usize, height: f32, count: isize) {
+        let mut node = index + 1;
+        while node < self.heights.len() {
+            self.heights[node] += height;
+            self.counts[node] = (self.counts[node] as isize + count) as usize;
+            node += node & node.wrapping_neg();
+        }
+    }
+
+    // Returns the total measured height and the number of measured rows before the row with the given index.
+    fn prefix(&self, index: usize) -> (f32, usize) {
+        let mut node = index.min(self.heights.len().saturating_sub(1));
+        let mut height = 0.0;
+        let mut count = 0;
+        while node > 0 {
+            height += self.heights[node];
+            count += self.counts[node];
+            node -= node & node.wrapping_neg();
+        }
+
+        (height, count)
+    }
+}
+
+/// Events which can be sent to a [VirtualList].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VirtualListEvent {
+    /// Scroll the parent scroll view so that the row with the given index is in view.
+    ScrollToIndex(usize),
+}
+
+/// A list view which only builds the rows which are visible.
+///
+/// The list is sized to the total height of its rows and should be placed inside a [ScrollView](crate::ScrollView).
+/// Only the rows which intersect the clip region of the list, plus a number of overscan rows above and below,
+/// are built. While scrolling, the entities of rows which leave the view are reused for the rows which enter it.
+/// The `ItemPtr` passed to the item template always refers to the index of the item in the list.
+///
+/// Rows can have a fixed height, set with `row_height()`, or otherwise are measured after they are laid out,
+/// with rows that have not been built yet using the average measured height. The item template must build
+/// a single view for each item, which is positioned by the list.
+///
+/// # Example
+/// ```compile_fail
+/// ScrollView::new(cx, false, true, |cx| {
+///     VirtualList::new(cx, AppData::list, |cx, item| {
+///         Label::new(cx, &item.get(cx).to_string());
+///     })
+///     .row_height(30.0);
+/// });
+/// ```
+pub struct VirtualList<L, T: 'static>
+where
+    L: Lens<Target = Vec<T>>,
+    T: Data,
+{
+    p: PhantomData<T>,
+    lens: L,
+    item: Box<dyn Fn(&mut Context, ItemPtr<L, T>)>,
+
+    row_height: Option<f32>,
+    overscan: usize,
+    // Measured height of each row, if it has been built
+    heights: Vec<Option<f32>>,
+    offsets: RowOffsets,
+
+    // Range of the built rows
+    start: usize,
+    end: usize,
+
+    // Index of the row used for keyboard navigation
+    cursor: usize,
+
+    increment_callback: Option<Box<dyn Fn(&mut Context)>>,
+    decrement_callback: Option<Box<dyn Fn(&mut Context)>>,
+    clear_callback: Option<Box<dyn Fn(&mut Context)>>,
+}
+
+impl<L: 'static + Lens<Target = Vec<T>>, T: Data> VirtualList<L, T> {
+    /// Creates a new virtual list with a binding to the given lens and a template for constructing the list items.
+    pub fn new<F>(cx: &mut Context, lens: L, item: F) -> Handle<Self>
+    where
+        F: 'static + Fn(&mut Context, ItemPtr<L, T>),
+        <L as Lens>::Source: Model,
+    {
+        Self {
+            p: PhantomData::default(),
+            lens: lens.clone(),
+            item: Box::new(item),
+            row_height: None,
+            overscan: DEFAULT_OVERSCAN,
+            heights: Vec::new(),
+            offsets: RowOffsets::default(),
+            start: 0,
+            end: 0,
+            cursor: 0,
+            increment_callback: None,
+            decrement_callback: None,
+            clear_callback: None,
+        }
+        .build2(cx, move |cx| {
+            // Bind to the list data and rebuild the visible rows when it changes
+            Binding::new(cx, lens, |cx, _| {
+                let entity = cx.current;
+                if let Some(mut view) = cx.views.remove(&entity) {
+                    if let Some(virtual_list) = view.downcast_mut::<VirtualList<L, T>>() {
+                        virtual_list.update_range(cx);
+                        virtual_list.build_rows(cx);
+                    }
+
+                    cx.views.insert(entity, view);
+                }
+            });
+        })
+        .focusable(true)
+    }
+
+    fn len(&self, cx: &Context) -> usize {
+        cx.data()
+            .and_then(|data| self.lens.view(data, |list| list.map(|list| list.len())))
+            .unwrap_or_default()
+    }
+
+    fn row_height_at(&self, index: usize) -> f32 {
+        if let Some(row_height) = self.row_height {
+            return row_height;
+        }
+
+        if let Some(Some(height)) = self.heights.get(index) {
+            return *height;
+        }
+
+        self.estimated_height()
+    }
+
+    // Returns the height used for rows which have not been measured.
+    fn estimated_height(&self) -> f32 {
+        let (total, count) = self.offsets.prefix(self.heights.len());
+        if count > 0 {
+            total / count as f32
+        } else {
+            DEFAULT_ROW_HEIGHT
+        }
+    }
+
+    // Returns the offset of the top of the row from the top of the list.
+    fn row_offset(&self, index: usize) -> f32 {
+        if let Some(row_height) = self.row_height {
+            return index as f32 * row_height;
+        }
+
+        let index = index.min(self.heights.len());
+        let (measured, count) = self.offsets.prefix(index);
+        measured + (index - count) as f32 * self.estimated_height()
+    }
+
+    // Returns the index of the row at the given offset from the top of the list.
+    fn row_at(&self, offset: f32) -> usize {
+        let len = self.heights.len();
+
+        if let Some(row_height) = self.row_height {
+            if row_height <= 0.0 || offset < 0.0 {
+                return 0;
+            }
+
+            return ((offset / row_height) as usize).min(len);
+        }
+
+        // Find the first row whose bottom is below the offset
+        let (mut low, mut high) = (0, len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.row_offset(mid + 1) > offset {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        low
+    }
+
+    // Recomputes the range of rows which intersect the clip region of the list.
+    // Returns true if the range changed.
+    fn update_range(&mut self, cx: &mut Context) -> bool {
+        let len = self.len(cx);
+        if len != self.heights.len() {
+            self.heights.resize(len, None);
+            self.offsets = RowOffsets::new(&self.heights);
+        }
+
+        let bounds = cx.cache.get_bounds(cx.current);
+        let clip_region = cx.cache.get_clip_region(cx.current);
+
+        let (start, end) = if clip_region.w <= 0.0 || clip_region.h <= 0.0 {
+            (0, 0)
+        } else {
+            let top = clip_region.y - bounds.y;
+            let bottom = clip_region.y + clip_region.h - bounds.y;
+
+            let first = self.row_at(top);
+            let last = self.row_at(bottom);
+
+            let start = first.saturating_sub(self.overscan);
+            (start, (last + 1 + self.overscan).min(len).max(start))
+        };
+
+        let changed = start != self.start || end != self.end;
+
+        self.start = start;
+        self.end = end;
+
+        changed
+    }
+
+    // Builds the rows in the current range, reusing the entities of previously built rows.
+    fn build_rows(&mut self, cx: &mut Context) {
+        let entity = cx.current;
+        let prev_count = cx.count;
+
+        for (slot, index) in (self.start..self.end).enumerate() {
+            cx.current = entity;
+            cx.count = FIRST_ROW + slot;
+
+            (self.item)(cx, ItemPtr::new(self.lens.clone(), index, index, 0));
+        }
+
+        cx.current = entity;
+        cx.count = prev_count;
+
+        // Remove rows which are no longer in range
+        let unused =
+            entity.child_iter(&cx.tree).skip(FIRST_ROW + self.end - self.start).collect::<Vec<_>>();
+        for row in unused {
+            cx.remove(row);
+        }
+
+        self.position_rows(cx);
+
+        cx.style.needs_restyle = true;
+    }
+
+    // Positions the built rows and sizes the list to the total height of the rows.
+    fn position_rows(&mut self, cx: &mut Context) {
+        let entity = cx.current;
+
+        let rows = entity.child_iter(&cx.tree).skip(FIRST_ROW).collect::<Vec<_>>();
+
+        let mut offset = self.row_offset(self.start);
+
+        for (row, index) in rows.into_iter().zip(self.start..self.end) {
+            row.set_position_type(cx, PositionType::SelfDirected);
+            row.set_top(cx, Pixels(offset));
+
+            if let Some(row_height) = self.row_height {
+                row.set_height(cx, Pixels(row_height));
+            }
+
+            offset += self.row_height_at(index);
+        }
+
+        let total = self.row_offset(self.heights.len());
+        entity.set_height(cx, Pixels(total));
+    }
+
+    // Records the measured height of a built row. Returns true if the height changed.
+    fn measure_row(&mut self, cx: &Context, row: Entity) -> bool {
+        if self.row_height.is_some() {
+            return false;
+        }
+
+        let slot = match cx.current.child_iter(&cx.tree).position(|child| child == row) {
+            Some(position) if position >= FIRST_ROW => position - FIRST_ROW,
+            _ => return false,
+        };
+
+        let index = self.start + slot;
+        let height = cx.cache.get_height(row);
+
+        if let Some(measured) = self.heights.get_mut(index) {
+            if *measured == Some(height) {
+                return false;
+            }
+
+            match measured.replace(height) {
+                Some(old) => self.offsets.add(index, height - old, 0),
+                None => self.offsets.add(index, height, 1),
+            }
+
+            return true;
+        }
+
+        false
+    }
+
+    // Scrolls the parent scroll view by the amount needed to bring the row into the clip region of the list.
+    fn scroll_to_index(&self, cx: &mut Context, index: usize) {
+        let bounds = cx.cache.get_bounds(cx.current);
+        let clip_region = cx.cache.get_clip_region(cx.current);
+
+        let top = bounds.y + self.row_offset(index);
+        let bottom = top + self.row_height_at(index);
+
+        if top < clip_region.y {
+            cx.emit(ScrollEvent::ScrollY(top - clip_region.y));
+        } else if bottom > clip_region.y + clip_region.h {
+            cx.emit(ScrollEvent::ScrollY(bottom - clip_region.y - clip_region.h));
+        }
+    }
+}
+
+impl<L: 'static + Lens<Target = Vec<T>>, T: Data> View for VirtualList<L, T> {
+    fn element(&self) -> Option<String> {
+        Some("list".to_string())
+    }
+
+    fn event(&mut self, cx: &mut Context, event: &mut Event) {
+        if let Some(virtual_list_event) = event.message.downcast() {
+            match virtual_list_event {
+                VirtualListEvent::ScrollToIndex(index) => {
+                    self.scroll_to_index(cx, *index);
+                    event.consume();
+                }
+            }
+        }
+
+        if let Some(window_event) = event.message.downcast() {
+            match window_event {
+                WindowEvent::GeometryChanged(geo) => {
+                    let target = event.target;
+
+                    let mut measured = false;
+
+                    if target != cx.current
+                        && cx.tree.get_parent(target) == Some(cx.current)
+                        && geo.contains(GeometryChanged::HEIGHT_CHANGED)
+                    {
+                        measured = self.measure_row(cx, target);
+                    }
+
+                    if target == cx.current || measured {
+                        if self.update_range(cx) {
+                            self.build_rows(cx);
+                        } else if measured {
+                            self.position_rows(cx);
+                        }
+                    }
+                }
+
+                WindowEvent::KeyDown(code, _) => match code {
+                    Code::ArrowDown => {
+                        let len = self.len(cx);
+                        if len > 0 {
+                            self.cursor = (self.cursor + 1).min(len - 1);
+                            self.scroll_to_index(cx, self.cursor);
+                        }
+
+                        if let Some(callback) = self.increment_callback.take() {
+                            (callback)(cx);
+                            self.increment_callback = Some(callback);
+                        }
+                    }
+
+                    Code::ArrowUp => {
+                        self.cursor = self.cursor.saturating_sub(1);
+                        self.scroll_to_index(cx, self.cursor);
+
+                        if let Some(callback) = self.decrement_callback.take() {
+                            (callback)(cx);
+                            self.decrement_callback = Some(callback);
+                        }
+                    }
+
+                    Code::Escape => {
+                        if let Some(callback) = self.clear_callback.take() {
+                            (callback)(cx);
+                            self.clear_callback = Some(callback);
+                        }
+                    }
+
+                    _ => {}
+                },
+
+                WindowEvent::MouseDown(MouseButton::Left) => {
+                    // Keep the keyboard navigation in sync with the pressed row
+                    let offset = cx.mouse.cursory - cx.cache.get_posy(cx.current);
+                    self.cursor = self.row_at(offset).min(self.heights.len().saturating_sub(1));
+
+                    if !cx.focused.is_child_of(&cx.tree, cx.current) {
+                        cx.focus(cx.current);
+                    }
+                }
+
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<L: Lens<Target = Vec<T>>, T: Data> Handle<'_, VirtualList<L, T>> {
+    /// Sets a fixed height, in pixels, for every row of the list. By default rows are measured.
+    pub fn row_height(self, height: f32) -> Self {
+        if let Some(list) =
+            self.cx.views.get_mut(&self.entity).and_then(|f| f.downcast_mut::<VirtualList<L, T>>())
+        {
+            list.row_height = Some(height);
+        }
+
+        self
+    }
+
+    /// Sets the number of rows built above and below the visible rows.
+    pub fn overscan(self, rows: usize) -> Self {
+        if let Some(list) =
+            self.cx.views.get_mut(&self.entity).and_then(|f| f.downcast_mut::<VirtualList<L, T>>())
+        {
+            list.overscan = rows;
+        }
+
+        self
+    }
+
+    pub fn on_increment<F>(self, callback: F) -> Self
+    where
+        F: 'static + Fn(&mut Context),
+    {
+        if let Some(list) =
+            self.cx.views.get_mut(&self.entity).and_then(|f| f.downcast_mut::<VirtualList<L, T>>())
+        {
+            list.increment_callback = Some(Box::new(callback));
+        }
+
+        self
+    }
+
+    pub fn on_decrement<F>(self, callback: F) -> Self
+    where
+        F: 'static + Fn(&mut Context),
+    {
+        if let Some(list) =
+            self.cx.views.get_mut(&self.entity).and_then(|f| f.downcast_mut::<VirtualList<L, T>>())
+        {
+            list.decrement_callback = Some(Box::new(callback));
+        }
+
+        self
+    }
+
+    pub fn on_clear<F>(self, callback: F) -> Self
+    where
+        F: 'static + Fn(&mut Context),
+    {
+        if let Some(list) =
+            self.cx.views.get_mut(&self.entity).and_then(|f| f.downcast_mut::<VirtualList<L, T>>())
+        {
+            list.clear_callback = Some(Box::new(callback));
+        }
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RowOffsets;
+
+    #[test]
+    fn row_offsets_sum_the_measured_rows_before_each_row() {
+        let heights = [Some(10.0), None, Some(20.0), Some(5.0), None];
+        let mut offsets = RowOffsets::new(&heights);
+
+        assert_eq!(offsets.prefix(0), (0.0, 0));
+        assert_eq!(offsets.prefix(2), (10.0, 1));
+        assert_eq!(offsets.prefix(4), (35.0, 3));
+        assert_eq!(offsets.prefix(5), (35.0, 3));
+
+        offsets.add(1, 15.0, 1);
+        offsets.add(2, -10.0, 0);
+
+        assert_eq!(offsets.prefix(2), (25.0, 2));
+        assert_eq!(offsets.prefix(5), (40.0, 4));
+    }
+}
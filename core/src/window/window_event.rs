@@ -1,4 +1,4 @@
-use crate::{CursorIcon, MouseButton};
+use crate::{BoundingBox, CursorIcon, MouseButton};
 
 use keyboard_types::{Code, Key};
 use morphorm::GeometryChanged;
@@ -11,8 +11,10 @@ pub enum WindowEvent {
     WindowClose,
     /// Emitted when a window is opened
     WindowResize(f32, f32),
-    /// Emitted when a mouse button is double clicked
+    /// Emitted when a mouse button is double clicked, before the second press
     MouseDoubleClick(MouseButton),
+    /// Emitted when a mouse button is clicked three times in a row, before the third press
+    MouseTripleClick(MouseButton),
     /// Emitted when a mouse button is pressed
     MouseDown(MouseButton),
     /// Emitted when a mouse button is released
@@ -36,6 +38,18 @@ pub enum WindowEvent {
 
     /// Emitted when a character is typed
     CharInput(char),
+    /// Emitted while text is being composed with an input method, with the text which hasn't been committed yet.
+    /// The cursor is the byte range within the text which is being edited, or None if it should be hidden.
+    ImePreedit {
+        text: String,
+        cursor: Option<(usize, usize)>,
+    },
+    /// Emitted when an input method commits the text which has been composed
+    ImeCommit(String),
+    /// Enables or disables input methods for the window
+    SetImeAllowed(bool),
+    /// Sets the bounds of the text caret in window coordinates, which input methods place their candidate window next to
+    SetImeCaret(BoundingBox),
     /// Emitted when a keyboard key is pressed
     KeyDown(Code, Option<Key>),
     /// Emitted when a keyboard key is released
@@ -154,8 +154,12 @@ fn derive_struct(input: &syn::DeriveInput) -> Result<proc_macro2::TokenStream, s
                 type Source = #struct_type#ty_generics;
                 type Target = #field_ty;
 
-                fn view<'a>(&self, data: &'a#struct_type#ty_generics) -> &'a#field_ty {
-                    &data.#field_name
+                fn view<LensOutput, LensMap: FnOnce(Option<&#field_ty>) -> LensOutput>(&self, source: &#struct_type#ty_generics, map: LensMap) -> LensOutput {
+                    map(Some(&source.#field_name))
+                }
+
+                fn cache_key(&self) -> Option<u64> {
+                    Some(0)
                 }
             }
         }
@@ -193,8 +197,12 @@ fn derive_struct(input: &syn::DeriveInput) -> Result<proc_macro2::TokenStream, s
             type Source = #struct_type#ty_generics;
             type Target = #struct_type#ty_generics;
 
-            fn view<'a>(&self, source: &'a Self::Source) -> &'a Self::Target {
-                source
+            fn view<O, F: FnOnce(Option<&Self::Target>) -> O>(&self, source: &Self::Source, map: F) -> O {
+                map(Some(source))
+            }
+
+            fn cache_key(&self) -> Option<u64> {
+                Some(0)
             }
         }
 
@@ -19,6 +19,7 @@
 mod attr;
 mod data;
 mod lens;
+mod persistent;
 
 use proc_macro::TokenStream;
 use syn::parse_macro_input;
@@ -34,3 +35,9 @@ pub fn derive_lens(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as syn::DeriveInput);
     lens::derive_lens_impl(input).unwrap_or_else(|err| err.to_compile_error()).into()
 }
+
+#[proc_macro_derive(Persistent, attributes(persistent))]
+pub fn derive_persistent(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    persistent::derive_persistent_impl(input).unwrap_or_else(|err| err.to_compile_error()).into()
+}
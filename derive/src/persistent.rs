@@ -0,0 +1,65 @@
+use quote::quote;
+use syn::{spanned::Spanned, Error, Lit, Meta, NestedMeta};
+
+const PERSISTENT_ATTR_PATH: &str = "persistent";
+const KEY_ATTR_PATH: &str = "key";
+const VERSION_ATTR_PATH: &str = "version";
+
+pub(crate) fn derive_persistent_impl(
+    input: syn::DeriveInput,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // The key defaults to the name of the type and the version to 0
+    let mut key = ident.to_string();
+    let mut version = 0u32;
+
+    for attr in input.attrs.iter().filter(|attr| attr.path.is_ident(PERSISTENT_ATTR_PATH)) {
+        match attr.parse_meta()? {
+            Meta::List(meta) => {
+                for nested in meta.nested.iter() {
+                    match nested {
+                        NestedMeta::Meta(Meta::NameValue(meta))
+                            if meta.path.is_ident(KEY_ATTR_PATH) =>
+                        {
+                            if let Lit::Str(lit) = &meta.lit {
+                                key = lit.value();
+                            } else {
+                                return Err(Error::new(meta.lit.span(), "Expected a string key"));
+                            }
+                        }
+                        NestedMeta::Meta(Meta::NameValue(meta))
+                            if meta.path.is_ident(VERSION_ATTR_PATH) =>
+                        {
+                            if let Lit::Int(lit) = &meta.lit {
+                                version = lit.base10_parse()?;
+                            } else {
+                                return Err(Error::new(
+                                    meta.lit.span(),
+                                    "Expected an integer version",
+                                ));
+                            }
+                        }
+                        other => return Err(Error::new(other.span(), "Unknown attribute")),
+                    }
+                }
+            }
+            other => {
+                return Err(Error::new(
+                    other.span(),
+                    "Expected attribute list (the form #[persistent(key = \"name\", version = 1)])",
+                ));
+            }
+        }
+    }
+
+    let res = quote! {
+        impl #impl_generics Persistent for #ident #ty_generics #where_clause {
+            const KEY: &'static str = #key;
+            const VERSION: u32 = #version;
+        }
+    };
+
+    Ok(res)
+}
@@ -78,7 +78,7 @@ fn main() {
 
                 HStack::new(cx, |cx| {
                     Binding::new(cx, AppData::options.then(Options::option1), |cx, option1| {
-                        Checkbox::new(cx, *option1.get(cx))
+                        Checkbox::new(cx, option1.get(cx))
                             .on_toggle(|cx| cx.emit(AppEvent::ToggleOption(0)));
                     });
                     Label::new(cx, "Option 1");
@@ -87,7 +87,7 @@ fn main() {
 
                 HStack::new(cx, |cx| {
                     Binding::new(cx, AppData::options.then(Options::option2), |cx, option2| {
-                        Checkbox::new(cx, *option2.get(cx))
+                        Checkbox::new(cx, option2.get(cx))
                             .on_toggle(|cx| cx.emit(AppEvent::ToggleOption(1)));
                     });
                     Label::new(cx, "Option 2");
@@ -96,7 +96,7 @@ fn main() {
 
                 HStack::new(cx, |cx| {
                     Binding::new(cx, AppData::options.then(Options::option3), |cx, option3| {
-                        Checkbox::new(cx, *option3.get(cx))
+                        Checkbox::new(cx, option3.get(cx))
                             .on_toggle(|cx| cx.emit(AppEvent::ToggleOption(2)));
                     });
                     Label::new(cx, "Option 3");
@@ -115,7 +115,7 @@ fn main() {
                         cx,
                         AppData::exclusive_options.then(Options::option1),
                         |cx, option1| {
-                            Checkbox::new(cx, *option1.get(cx))
+                            Checkbox::new(cx, option1.get(cx))
                                 .on_toggle(|cx| cx.emit(AppEvent::ToggleExclusiveOption(0)));
                         },
                     );
@@ -128,7 +128,7 @@ fn main() {
                         cx,
                         AppData::exclusive_options.then(Options::option2),
                         |cx, option2| {
-                            Checkbox::new(cx, *option2.get(cx))
+                            Checkbox::new(cx, option2.get(cx))
                                 .on_toggle(|cx| cx.emit(AppEvent::ToggleExclusiveOption(1)));
                         },
                     );
@@ -141,7 +141,7 @@ fn main() {
                         cx,
                         AppData::exclusive_options.then(Options::option3),
                         |cx, option3| {
-                            Checkbox::new(cx, *option3.get(cx))
+                            Checkbox::new(cx, option3.get(cx))
                                 .on_toggle(|cx| cx.emit(AppEvent::ToggleExclusiveOption(2)));
                         },
                     );
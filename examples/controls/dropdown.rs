@@ -62,13 +62,13 @@ fn main() {
 
 
         Binding::new(cx, AppData::choice, |cx, choice|{
-            let option = choice.get(cx).clone();
+            let option = choice.get(cx);
             HStack::new(cx, move |cx|{
                 // Dropdown List
                 Dropdown::new(cx, move |cx|
                     // A Label and an Icon
                     HStack::new(cx, move |cx|{
-                        // /let choice = choice.get(cx).clone();
+                        // /let choice = choice.get(cx);
                         Binding::new(cx, AppData::choice, |cx, choice|{
                             Label::new(cx, &choice.get(cx).to_string());
                         });
@@ -78,12 +78,12 @@ fn main() {
                     List::new(cx, AppData::list, |cx, item|{
                         VStack::new(cx, move |cx|{
                             Binding::new(cx, AppData::choice, move |cx, choice|{
-                                let selected = *item.get(cx) == *choice.get(cx);
+                                let selected = item.get(cx) == choice.get(cx);
                                 Label::new(cx, &item.get(cx).to_string())
                                     .width(Stretch(1.0))
                                     .background_color(if selected {Color::from("#f8ac14")} else {Color::white()})
                                     .on_press(move |cx| {
-                                        cx.emit(AppEvent::SetChoice(item.get(cx).clone()));
+                                        cx.emit(AppEvent::SetChoice(item.get(cx)));
                                         cx.emit(PopupEvent::Close);
                                     });
                             });
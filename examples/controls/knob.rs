@@ -48,7 +48,7 @@ fn main() {
         }
 
         Binding::new(cx, AppData::value, |cx, value| {
-            let val = *value.get(cx);
+            let val = value.get(cx);
             Knob::new(cx, 0.5, val, false).on_changing(|knob, cx| {
                 cx.emit(AppEvent::SetValue(knob.normalized_value));
             });
@@ -0,0 +1,40 @@
+use vizia::*;
+
+const TEXT: &str =
+    "The quick brown fox jumps over the lazy dog. The quick brown fox jumps over the lazy dog.";
+
+fn main() {
+    Application::new(WindowDescription::new().with_title("Label"), |cx| {
+        VStack::new(cx, |cx| {
+            // Wrapped between words, with the height of the label taken from its lines
+            Label::new(cx, TEXT)
+                .width(Stretch(1.0))
+                .text_wrap(TextWrap::Word)
+                .line_height(Percentage(150.0));
+
+            // Wrapped between characters
+            Label::new(cx, TEXT).width(Stretch(1.0)).text_wrap(TextWrap::Char);
+
+            // Cut short with an ellipsis
+            Label::new(cx, TEXT).width(Stretch(1.0)).text_overflow(TextOverflow::Ellipsis);
+
+            // Spans with their own fonts, sizes and colors
+            RichText::markup(
+                cx,
+                "Volume: <b>-6.0</b> <font color=\"#cc3333\" size=\"12\">dB</font><br><u>Enabled</u> <font face=\"icons\">&#x2713;</font>",
+            );
+
+            RichText::new(
+                cx,
+                vec![
+                    TextSpan::new("Gain "),
+                    TextSpan::new("+3 dB").font("roboto-bold").color(Color::rgb(50, 150, 50)),
+                ],
+            );
+        })
+        .width(Pixels(200.0))
+        .row_between(Pixels(10.0))
+        .space(Pixels(10.0));
+    })
+    .run();
+}
@@ -44,7 +44,7 @@ fn main() {
 
         // Picker::new(cx, AppData::option, |cx, option|{
 
-        //     let opt = *option.get(cx);
+        //     let opt = option.get(cx);
 
         //     Button::new(cx, |cx| cx.emit(AppEvent::SetOption(Options::First)), |cx|{
         //         Label::new(cx, "First")
@@ -60,14 +60,14 @@ fn main() {
         // });
 
         // Picker::new(cx, AppData::option, |cx, option|{
-        //     let opt = *option.get(cx);
+        //     let opt = option.get(cx);
         //     PickerItem::new(cx, "First", Options::First, opt);
         //     PickerItem::new(cx, "Second",Options::Second, opt);
         //     PickerItem::new(cx, "Third",Options::Third, opt);
         // });
 
         // Picker::new(cx, AppData::option, |cx, option|{
-        //     let opt = *option.get(cx);
+        //     let opt = option.get(cx);
         //     picker_item(cx, "First", Options::First, opt);
         //     picker_item(cx, "Second",Options::Second, opt);
         //     picker_item(cx, "Third",Options::Third, opt);
@@ -78,7 +78,7 @@ fn main() {
             |cx| Label::new(cx, "Options"),
             |cx| {
                 Picker::new(cx, AppData::option, |cx, option| {
-                    let opt = *option.get(cx);
+                    let opt = option.get(cx);
                     picker_item(cx, "First", Options::First, opt);
                     picker_item(cx, "Second", Options::Second, opt);
                     picker_item(cx, "Third", Options::Third, opt);
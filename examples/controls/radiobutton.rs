@@ -65,7 +65,7 @@ fn main() {
 
             HStack::new(cx, |cx| {
                 Binding::new(cx, AppData::options.then(Options::option1), |cx, option1| {
-                    RadioButton::new(cx, *option1.get(cx))
+                    RadioButton::new(cx, option1.get(cx))
                         .on_select(|cx| cx.emit(AppEvent::ToggleOption(0)));
                 });
                 Label::new(cx, "Option 1");
@@ -77,7 +77,7 @@ fn main() {
 
             HStack::new(cx, |cx| {
                 Binding::new(cx, AppData::options.then(Options::option2), |cx, option2| {
-                    RadioButton::new(cx, *option2.get(cx))
+                    RadioButton::new(cx, option2.get(cx))
                         .on_select(|cx| cx.emit(AppEvent::ToggleOption(1)));
                 });
                 Label::new(cx, "Option 2");
@@ -89,7 +89,7 @@ fn main() {
 
             HStack::new(cx, |cx| {
                 Binding::new(cx, AppData::options.then(Options::option3), |cx, option3| {
-                    RadioButton::new(cx, *option3.get(cx))
+                    RadioButton::new(cx, option3.get(cx))
                         .on_select(|cx| cx.emit(AppEvent::ToggleOption(2)));
                 });
                 Label::new(cx, "Option 3");
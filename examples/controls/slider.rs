@@ -55,8 +55,8 @@ fn main() {
         for _ in 0..5 {
             HStack::new(cx, |cx| {
                 Binding::new(cx, SliderData::value, |cx, value| {
-                    Slider::new(cx, *value.get(cx), Orientation::Horizontal);
-                    let value = *value.get(cx);
+                    Slider::new(cx, value.get(cx), Orientation::Horizontal);
+                    let value = value.get(cx);
                     Label::new(cx, &format!("{:.*}", 2, value));
                 });
             })
@@ -67,10 +67,10 @@ fn main() {
 
         // HStack::new(cx, |cx| {
         //     Binding::new(cx, SliderData::value, |cx, value| {
-        //         Slider::new(cx, *value.get(cx), Orientation::Vertical)
+        //         Slider::new(cx, value.get(cx), Orientation::Vertical)
         //             .class("vertical")
         //             .on_press(cx, |_| println!("Press"));
-        //         let value = *value.get(cx);
+        //         let value = value.get(cx);
         //         Label::new(cx, &format!("{:.*}", 2, value));
         //     });
         // })
@@ -85,12 +85,12 @@ pub fn style_dropdown(cx: &mut Context) -> Handle<ZStack> {
                     List::new(cx, ThemeData::list, |cx, item|{
                         VStack::new(cx, move |cx|{
                             Binding::new(cx, ThemeData::choice, move |cx, choice|{
-                                let selected = *item.get(cx) == *choice.get(cx);
+                                let selected = item.get(cx) == choice.get(cx);
                                 Label::new(cx, &item.get(cx).to_string())
                                     .width(Stretch(1.0))
                                     .background_color(if selected {Color::from("#f8ac14")} else {Color::white()})
                                     .on_press(move |cx| {
-                                        cx.emit(ThemeEvent::SetTheme(item.get(cx).clone()));
+                                        cx.emit(ThemeEvent::SetTheme(item.get(cx)));
                                         cx.emit(PopupEvent::Close);
                                     });
                             });
@@ -33,8 +33,14 @@ fn main() {
                 .width(Pixels(200.0))
                 .child_left(Pixels(5.0));
 
+            // Edits the same text over several lines, wrapping it to the width of the textbox
+            Textbox::new_multiline(cx, AppData::text)
+                .on_edit(|cx, range, text| cx.emit(AppEvent::EditRange(range, text)))
+                .width(Pixels(200.0))
+                .height(Pixels(100.0));
+
             Binding::new(cx, AppData::text, |cx, text| {
-                Label::new(cx, &text.get(cx).clone())
+                Label::new(cx, &text.get(cx))
                     .width(Pixels(200.0))
                     .height(Pixels(30.0))
                     .child_left(Pixels(5.0));
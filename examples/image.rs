@@ -0,0 +1,32 @@
+use vizia::*;
+
+const STYLE: &str = r#"
+    .tiled {
+        background-image: url("checker");
+        background-fit: tile;
+        border-radius: 10px;
+    }
+"#;
+
+fn main() {
+    Application::new(WindowDescription::new().with_title("Image"), |cx| {
+        cx.load_image("checker", include_bytes!("resources/checker.png"));
+        cx.add_theme(STYLE);
+
+        HStack::new(cx, |cx| {
+            for fit in [ImageFit::Fill, ImageFit::Contain, ImageFit::Cover] {
+                Image::new(cx, "checker")
+                    .background_fit(fit)
+                    .width(Pixels(150.0))
+                    .height(Pixels(100.0))
+                    .border_width(Pixels(1.0))
+                    .border_color(Color::black());
+            }
+
+            Element::new(cx).class("tiled").width(Pixels(150.0)).height(Pixels(100.0));
+        })
+        .child_space(Pixels(10.0))
+        .col_between(Pixels(10.0));
+    })
+    .run();
+}
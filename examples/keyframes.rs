@@ -0,0 +1,28 @@
+use vizia::*;
+
+// Example showing animations defined with @keyframes rules in a theme
+
+const STYLE: &str = r#"
+    @keyframes pulse {
+        from { background-color: #4c7bd9; width: 100px; }
+        50% { background-color: #d94c7b; }
+        to { background-color: #4c7bd9; width: 200px; }
+    }
+
+    .pulse {
+        height: 50px;
+        animation: pulse 800ms ease-in-out infinite alternate;
+    }
+"#;
+
+fn main() {
+    Application::new(WindowDescription::new().with_title("Keyframes"), |cx| {
+        cx.add_theme(STYLE);
+
+        VStack::new(cx, |cx| {
+            Element::new(cx).class("pulse");
+        })
+        .child_space(Pixels(50.0));
+    })
+    .run();
+}
@@ -87,7 +87,7 @@ fn main() {
                 let item_text = item.get(cx).to_string();
                 let item_index = item.index();
                 Binding::new(cx, AppData::selected, move |cx, selected| {
-                    let selected = *selected.get(cx);
+                    let selected = selected.get(cx);
 
                     Label::new(cx, &item_text)
                         .width(Pixels(100.0))
@@ -45,7 +45,7 @@ fn main() {
             // This vstack shouldn't be necessary but because of how bindings work it's required
             VStack::new(cx, move |cx| {
                 Binding::new(cx, AppData::selected, move |cx, selected| {
-                    let selected = selected.get(cx).clone();
+                    let selected = selected.get(cx);
 
                     Label::new(cx, &item_text)
                         // Set the checked state based on whether this item is selected
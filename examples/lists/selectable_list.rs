@@ -47,7 +47,7 @@ fn main() {
                 let item_index = item.index();
                 VStack::new(cx, move |cx| {
                     Binding::new(cx, AppData::selected, move |cx, selected| {
-                        let selected = *selected.get(cx);
+                        let selected = selected.get(cx);
                         Label::new(cx, &item_text)
                             // Set the checked state based on whether this item is selected
                             .checked(if selected == item_index { true } else { false })
@@ -51,7 +51,7 @@ fn main() {
                 let item_index = item.index();
                 VStack::new(cx, move |cx| {
                     Binding::new(cx, AppData::selected, move |cx, selected| {
-                        let selected = *selected.get(cx);
+                        let selected = selected.get(cx);
                         Label::new(cx, &item_text)
                             .class("list_item")
                             // Set the checked state based on whether this item is selected
@@ -2,8 +2,12 @@ use vizia::*;
 
 fn main() {
     Application::new(WindowDescription::new().with_title("Localization"), |cx| {
+        cx.add_translation("en-US", include_str!("resources/en-US/hello.ftl").to_owned());
+        cx.add_translation("fr", include_str!("resources/fr/hello.ftl").to_owned());
+
         HStack::new(cx, |cx| {
             Label::new(cx, "hello-world");
+            Label::new(cx, Localized::new("greeting").arg("name", "Ferris"));
             Button::new(cx, |cx| cx.enviroment.set_locale("fr"), |cx| Label::new(cx, "fr"));
             Button::new(cx, |cx| cx.enviroment.set_locale("en-US"), |cx| Label::new(cx, "en-US"));
         });
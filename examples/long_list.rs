@@ -50,27 +50,30 @@ fn main() {
 
         VStack::new(cx, move |cx| {
             Binding::new(cx, AppData::visible, |cx, visible| {
-                Checkbox::new(cx, *visible.get(cx))
-                    .on_toggle(|cx| cx.emit(AppEvent::ToggleVisible));
+                Checkbox::new(cx, visible.get(cx)).on_toggle(|cx| cx.emit(AppEvent::ToggleVisible));
 
-                List::new(cx, AppData::list, move |cx, item| {
-                    //println!("Do This");
-                    let item_text = item.get(cx).to_string();
-                    let item_index = item.index();
-                    VStack::new(cx, move |cx| {
-                        Binding::new(cx, AppData::selected, move |cx, selected| {
-                            //println!("Select");
-                            let selected = *selected.get(cx);
-                            Label::new(cx, &item_text)
-                                // Set the checked state based on whether this item is selected
-                                .checked(if selected == item_index { true } else { false })
-                                // Set the selected item to this one if pressed
-                                .on_press(move |cx| cx.emit(AppEvent::Select(item_index)));
+                ScrollView::new(cx, false, true, move |cx| {
+                    VirtualList::new(cx, AppData::list, move |cx, item| {
+                        //println!("Do This");
+                        let item_text = item.get(cx).to_string();
+                        let item_index = item.index();
+                        VStack::new(cx, move |cx| {
+                            Binding::new(cx, AppData::selected, move |cx, selected| {
+                                //println!("Select");
+                                let selected = selected.get(cx);
+                                Label::new(cx, &item_text)
+                                    // Set the checked state based on whether this item is selected
+                                    .checked(if selected == item_index { true } else { false })
+                                    // Set the selected item to this one if pressed
+                                    .on_press(move |cx| cx.emit(AppEvent::Select(item_index)));
+                            });
                         });
-                    });
+                    })
+                    .row_height(30.0)
+                    .on_increment(move |cx| cx.emit(AppEvent::IncrementSelection))
+                    .on_decrement(move |cx| cx.emit(AppEvent::DecrementSelection));
                 })
-                .on_increment(move |cx| cx.emit(AppEvent::IncrementSelection))
-                .on_decrement(move |cx| cx.emit(AppEvent::DecrementSelection))
+                .height(Pixels(400.0))
                 .display(visible);
             });
 
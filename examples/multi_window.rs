@@ -0,0 +1,87 @@
+use vizia::*;
+
+const STYLE: &str = r#"
+    button {
+        width: 120px;
+        height: 30px;
+    }
+
+    label {
+        width: 100px;
+        height: 30px;
+    }
+"#;
+
+// Data shared by the main window and the windows opened from it
+#[derive(Lens)]
+pub struct CounterData {
+    count: i32,
+}
+
+#[derive(Debug)]
+pub enum CounterEvent {
+    Increment,
+}
+
+impl Model for CounterData {
+    fn event(&mut self, _: &mut Context, event: &mut Event) {
+        if let Some(counter_event) = event.message.downcast() {
+            match counter_event {
+                CounterEvent::Increment => self.count += 1,
+            }
+        }
+    }
+}
+
+fn main() {
+    let window_description =
+        WindowDescription::new().with_title("Multiple Windows").with_inner_size(500, 100);
+
+    Application::new(window_description, |cx| {
+        cx.add_theme(STYLE);
+
+        CounterData { count: 0 }.build(cx);
+
+        HStack::new(cx, |cx| {
+            Button::new(
+                cx,
+                |cx| cx.emit(CounterEvent::Increment),
+                |cx| Label::new(cx, "Increment"),
+            );
+
+            // Each press opens another window which shares the counter data with this one
+            Button::new(
+                cx,
+                |cx| {
+                    cx.open_window(
+                        WindowDescription::new().with_title("Counter").with_inner_size(300, 100),
+                        |cx| {
+                            HStack::new(cx, |cx| {
+                                Binding::new(cx, CounterData::count, |cx, count| {
+                                    Label::new(cx, &count.get(cx).to_string());
+                                });
+
+                                // Closes the window containing the button
+                                Button::new(
+                                    cx,
+                                    |cx| cx.emit(WindowEvent::WindowClose),
+                                    |cx| Label::new(cx, "Close"),
+                                );
+                            })
+                            .child_space(Stretch(1.0))
+                            .col_between(Pixels(10.0));
+                        },
+                    );
+                },
+                |cx| Label::new(cx, "Open Window"),
+            );
+
+            Binding::new(cx, CounterData::count, |cx, count| {
+                Label::new(cx, &count.get(cx).to_string());
+            });
+        })
+        .child_space(Stretch(1.0))
+        .col_between(Pixels(10.0));
+    })
+    .run();
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use vizia::*;
+
+// Example showing a counter which keeps its value when the application is restarted
+
+// The count is saved under the "counter" key. Increasing the version drops any count saved by an older version.
+#[derive(Lens, Serialize, Deserialize, Persistent)]
+#[persistent(key = "counter", version = 1)]
+pub struct CounterData {
+    count: i32,
+}
+
+#[derive(Debug)]
+pub enum CounterEvent {
+    Increment,
+    Decrement,
+}
+
+impl Model for CounterData {
+    fn event(&mut self, _: &mut Context, event: &mut Event) {
+        if let Some(counter_event) = event.message.downcast() {
+            match counter_event {
+                CounterEvent::Increment => self.count += 1,
+                CounterEvent::Decrement => self.count -= 1,
+            }
+        }
+    }
+}
+
+fn main() {
+    let window_description =
+        WindowDescription::new().with_title("Persistence").with_inner_size(400, 100);
+
+    Application::new(window_description, |cx| {
+        // Restore the count saved by the last run of the application
+        CounterData { count: 0 }.build_persistent(cx);
+
+        HStack::new(cx, |cx| {
+            Button::new(
+                cx,
+                |cx| cx.emit(CounterEvent::Increment),
+                |cx| Label::new(cx, "Increment").width(Pixels(100.0)),
+            );
+
+            Button::new(
+                cx,
+                |cx| cx.emit(CounterEvent::Decrement),
+                |cx| Label::new(cx, "Decrement").width(Pixels(100.0)),
+            );
+
+            Binding::new(cx, CounterData::count, |cx, count| {
+                Label::new(cx, &count.get(cx).to_string()).width(Pixels(100.0));
+            });
+        })
+        .child_space(Stretch(1.0))
+        .col_between(Pixels(10.0));
+    })
+    .persist("vizia-persistence-example")
+    .run();
+}
@@ -1 +1,2 @@
-hello-world = Hello World
\ No newline at end of file
+hello-world = Hello World
+greeting = Hello, { $name }!
@@ -1 +1,2 @@
-hello-world = Bonjour monde!
\ No newline at end of file
+hello-world = Bonjour monde!
+greeting = Bonjour, { $name }!
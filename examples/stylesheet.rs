@@ -3,6 +3,8 @@ use vizia::*;
 fn main() {
     Application::new(WindowDescription::new().with_title("External CSS"), |cx| {
         cx.add_stylesheet("examples/resources/test.css").expect("Failed to find file");
+        // Reload the stylesheet whenever the file is saved
+        cx.watch_stylesheets(true);
 
         Button::new(cx, |_| {}, |cx| Label::new(cx, "Button"));
     })
@@ -1,6 +1,10 @@
+use std::collections::HashMap;
+
+use femtovg::{FontId, ImageFlags, ImageId};
 use glutin::{
-    event::{ElementState, VirtualKeyCode},
-    event_loop::{ControlFlow, EventLoop, EventLoopProxy},
+    event::{ElementState, Ime, VirtualKeyCode},
+    event_loop::{ControlFlow, EventLoop, EventLoopProxy, EventLoopWindowTarget},
+    window::WindowId,
 };
 
 use vizia_core::*;
@@ -10,6 +14,13 @@ use crate::window::Window;
 
 static DEFAULT_THEME: &str = include_str!("../../core/src/default_theme.css");
 
+// How often to check for modified stylesheets when they are being watched.
+const STYLESHEET_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+// Sent to the event loop by the task pool when a background task finishes.
+#[derive(Debug)]
+struct TasksFinished;
+
 pub struct Application {
     context: Context,
     event_loop: EventLoop<Event>,
@@ -87,6 +98,24 @@ impl Application {
         self
     }
 
+    /// Enables persistence for the application with the given name.
+    ///
+    /// The state of models built with `build_persistent()` is restored from a file in the configuration
+    /// directory of the user before the application is built, and is saved when the models change, at most
+    /// once per `PERSISTENT_SAVE_INTERVAL`, and when the application exits.
+    pub fn persist(mut self, app_name: &str) -> Self {
+        if let Some(path) = persistence_path(app_name) {
+            self.context.load_persistent_state(path);
+        } else {
+            self.context.report(
+                "persistence",
+                format!("Failed to find a directory to save the state of {} to", app_name),
+            );
+        }
+
+        self
+    }
+
     pub fn run(mut self) {
         let mut context = self.context;
 
@@ -125,29 +154,15 @@ impl Application {
 
         context.style.default_font = "roboto".to_string();
 
-        // Load resources
-        for (name, font) in context.resource_manager.fonts.iter_mut() {
-            match font {
-                FontOrId::Font(data) => {
-                    let id1 = window
-                        .canvas
-                        .add_font_mem(&data.clone())
-                        .expect(&format!("Failed to load font file for: {}", name));
-                    let id2 = context.text_context.add_font_mem(&data.clone()).expect("failed");
-                    if id1 != id2 {
-                        panic!(
-                            "Fonts in canvas must have the same id as fonts in the text context"
-                        );
-                    }
-                    *font = FontOrId::Id(id1);
-                }
+        // Fonts and images shared by the canvases of all windows, in the order they were loaded
+        let mut fonts: Vec<(Vec<u8>, FontId)> = Vec::new();
+        let mut images: Vec<(Vec<u8>, ImageId)> = Vec::new();
 
-                _ => {}
-            }
-        }
+        // Load resources
+        load_resources(&mut context, &mut window, &mut fonts, &mut images);
 
-        let dpi_factor = window.handle.window().scale_factor();
-        let size = window.handle.window().inner_size();
+        let dpi_factor = window.handle().window().scale_factor();
+        let size = window.handle().window().inner_size();
 
         let clear_color =
             context.style.background_color.get(Entity::root()).cloned().unwrap_or_default();
@@ -155,6 +170,10 @@ impl Application {
         window.canvas.set_size(size.width as u32, size.height as u32, dpi_factor as f32);
         window.canvas.clear_rect(0, 0, size.width as u32, size.height as u32, clear_color.into());
 
+        // Maps OS windows to the entity at the root of their subtree
+        let mut window_entities: HashMap<WindowId, Entity> = HashMap::new();
+        window_entities.insert(window.id, Entity::root());
+
         context.views.insert(Entity::root(), Box::new(window));
 
         context.cache.set_width(Entity::root(), self.window_description.inner_size.width as f32);
@@ -192,25 +211,50 @@ impl Application {
 
         let event_loop_proxy = event_loop.create_proxy();
 
+        // Wake the event loop when a background task finishes so that its result is handled
+        let task_proxy = std::sync::Mutex::new(event_loop.create_proxy());
+        context.tasks.set_waker(move || {
+            let _ = task_proxy
+                .lock()
+                .expect("Failed to lock event loop proxy")
+                .send_event(Event::new(TasksFinished));
+        });
+
         let should_poll = self.should_poll;
 
+        // Whether any animations were playing during the last frame
+        let mut animating = false;
+
         let mut click_time = std::time::Instant::now();
         let double_click_interval = std::time::Duration::from_millis(500);
-        let mut double_click = false;
+        let mut click_count = 0;
         let mut click_pos = (0.0, 0.0);
 
-        event_loop.run(move |event, _, control_flow|{
+        event_loop.run(move |event, event_loop_target, control_flow|{
 
-            if should_poll {
+            if should_poll || animating {
                 *control_flow = ControlFlow::Poll;
             } else {
-                *control_flow = ControlFlow::Wait;
+                // Wake up for the next timer, and periodically to check for modified stylesheets
+                let mut deadline = context.timers.next_deadline();
+                if context.resource_manager.watch_stylesheets {
+                    let poll_time = std::time::Instant::now() + STYLESHEET_POLL_INTERVAL;
+                    deadline = Some(deadline.map_or(poll_time, |deadline| deadline.min(poll_time)));
+                }
+
+                *control_flow = match deadline {
+                    Some(deadline) => ControlFlow::WaitUntil(deadline),
+                    None => ControlFlow::Wait,
+                };
             }
 
             match event {
 
                 glutin::event::Event::UserEvent(event) => {
-                    context.event_queue.push_back(event);
+                    // Only wakes the loop, which polls the finished tasks before handling events
+                    if !event.message.is::<TasksFinished>() {
+                        context.event_queue.push_back(event);
+                    }
                 }
 
                 glutin::event::Event::MainEventsCleared => {
@@ -225,35 +269,56 @@ impl Application {
                         context.enviroment.needs_rebuild = false;
                     }
 
+                    // Reload any stylesheets which have been modified
+                    if context.resource_manager.watch_stylesheets {
+                        context.poll_stylesheets();
+                    }
+
+                    // Load resources
                     if let Some(mut window_view) = context.views.remove(&Entity::root()) {
                         if let Some(window) = window_view.downcast_mut::<Window>() {
-
-                            // Load resources
-                            for (name, font) in context.resource_manager.fonts.iter_mut() {
-                                match font {
-                                    FontOrId::Font(data) => {
-                                        let id1 = window.canvas.add_font_mem(&data.clone()).expect(&format!("Failed to load font file for: {}", name));
-                                        let id2 = context.text_context.add_font_mem(&data.clone()).expect("failed");
-                                        if id1 != id2 {
-                                            panic!("Fonts in canvas must have the same id as fonts in the text context");
-                                        }
-                                        *font = FontOrId::Id(id1);
-                                    }
-
-                                    _=> {}
-                                }
-                            }
-
+                            window.make_current();
+                            load_resources(&mut context, window, &mut fonts, &mut images);
                         }
 
                         context.views.insert(Entity::root(), window_view);
                     }
 
+                    for window_entity in window_entities.values() {
+                        if let Some(window) = context.views.get_mut(window_entity).and_then(|view| view.downcast_mut::<Window>()) {
+                            window.make_current();
+                            window.load_resources(&fonts, &images);
+                        }
+                    }
+
+                    // Timers which have reached their deadline
+                    apply_timers(&mut context, std::time::Instant::now());
+
+                    // Results of finished background tasks
+                    context.poll_tasks();
+
                     // Events
-                    while !context.event_queue.is_empty() {
-                        event_manager.flush_events(&mut context);
+                    if !context.event_queue.is_empty() {
+                        while !context.event_queue.is_empty() {
+                            event_manager.flush_events(&mut context);
+                        }
+
+                        // Persistent models may have been changed by the events
+                        context.persistent_state.mark_dirty();
                     }
 
+                    // Save persistent models which have changed, at most once per save interval
+                    context.autosave_persistent_state(std::time::Instant::now());
+
+                    // Report problems found while handling the events, such as translations which failed to load
+                    context.flush_diagnostics();
+
+                    // Create windows opened during the events and remove the subtrees of closed windows
+                    update_windows(&mut context, event_loop_target, &mut window_entities, &fonts, &images);
+
+                    // Computed values are updated before the bindings which depend on them
+                    update_computed_values(&mut context);
+
                     // Data Updates
                     let mut observers: Vec<Entity> = Vec::new();
 
@@ -290,6 +355,8 @@ impl Application {
                         context.style.needs_restyle = false;
                     }
 
+                    animating = apply_animations(&mut context, std::time::Instant::now());
+
                     apply_shared_inheritance(&mut context, &tree);
 
                     apply_z_ordering(&mut context, &tree);
@@ -317,13 +384,14 @@ impl Application {
 
                     apply_clipping(&mut context, &tree);
 
-                    if let Some(window_view) = context.views.get(&Entity::root()) {
-                        if let Some(window) = window_view.downcast_ref::<Window>() {
-                            if context.style.needs_redraw {
-                                window.handle.window().request_redraw();
-                                context.style.needs_redraw = false;
+                    if context.style.needs_redraw {
+                        for window_entity in window_entities.values() {
+                            if let Some(window) = context.views.get(window_entity).and_then(|view| view.downcast_ref::<Window>()) {
+                                window.handle().window().request_redraw();
                             }
                         }
+
+                        context.style.needs_redraw = false;
                     }
 
                     if let Some(idle_callback) = &on_idle {
@@ -337,99 +405,42 @@ impl Application {
                     }
                 }
 
-                glutin::event::Event::RedrawRequested(_) => {
+                glutin::event::Event::RedrawRequested(window_id) => {
                     // Redraw here
                     //println!("Redraw");
 
-                    if let Some(mut window_view) = context.views.remove(&Entity::root()) {
-                        if let Some(window) = window_view.downcast_mut::<Window>() {
-
-                            let window_width = context.cache.get_width(Entity::root());
-                            let window_height = context.cache.get_height(Entity::root());
-
-                            window.canvas.set_size(window_width as u32, window_height as u32, dpi_factor as f32);
-                            let clear_color = context.style.background_color.get(Entity::root()).cloned().unwrap_or(Color::white());
-                            window.canvas.clear_rect(
-                                0,
-                                0,
-                                window_width as u32,
-                                window_height as u32,
-                                clear_color.into(),
-                            );
-
-                            // Sort the tree by z order
-                            let mut draw_tree: Vec<Entity> = context.tree.into_iter().collect();
-                            draw_tree.sort_by_cached_key(|entity| context.cache.get_z_index(*entity));
-
-                            for entity in draw_tree.into_iter() {
-
-
-                                // Skip window
-                                if entity == Entity::root() {
-                                    continue;
-                                }
-
-                                // Skip invisible widgets
-                                if context.cache.get_visibility(entity) == Visibility::Invisible {
-                                    continue;
-                                }
-
-                                // Skip non-displayed widgets
-                                if context.cache.get_display(entity) == Display::None {
-                                    continue;
-                                }
-
-                                // Skip widgets that have 0 opacity
-                                if context.cache.get_opacity(entity) == 0.0 {
-                                    continue;
-                                }
-
-                                let bounds = context.cache.get_bounds(entity);
-
-                                if bounds.x > window_width || bounds.y > window_height {
-                                    continue;
-                                }
-
-                                // Apply clipping
-                                let clip_region = context.cache.get_clip_region(entity);
-                                window.canvas.scissor(
-                                    clip_region.x,
-                                    clip_region.y,
-                                    clip_region.w,
-                                    clip_region.h,
-                                );
-
-                                // Apply transform
-                                let transform = context.cache.get_transform(entity);
-                                window.canvas.save();
-                                window.canvas.set_transform(transform[0], transform[1], transform[2], transform[3], transform[4], transform[5]);
-
-                                if let Some(view) = context.views.remove(&entity) {
-
-                                    context.current = entity;
-                                    view.draw(&mut context, &mut window.canvas);
-
-                                    context.views.insert(entity, view);
-                                }
-
-                                window.canvas.restore();
+                    if let Some(window_entity) = window_entities.get(&window_id).cloned() {
+                        if let Some(mut window_view) = context.views.remove(&window_entity) {
+                            if let Some(window) = window_view.downcast_mut::<Window>() {
+                                window.make_current();
+                                draw_window(&mut context, window_entity, window);
                             }
 
-                            window.canvas.flush();
-                            window.handle.swap_buffers().expect("Failed to swap buffers");
+                            context.views.insert(window_entity, window_view);
                         }
-
-                        context.views.insert(Entity::root(), window_view);
                     }
                 }
 
                 glutin::event::Event::WindowEvent {
-                    window_id: _,
+                    window_id,
                     event,
                 } => {
+                    let window_entity = window_entities.get(&window_id).cloned().unwrap_or(Entity::root());
+
                     match event {
                         glutin::event::WindowEvent::CloseRequested => {
-                            *control_flow = ControlFlow::Exit;
+                            if window_entity == Entity::root() {
+                                *control_flow = ControlFlow::Exit;
+                            } else {
+                                context.close_window(window_entity);
+                            }
+                        }
+
+                        glutin::event::WindowEvent::Focused(true) => {
+                            // Move keyboard focus into the window if it is focused elsewhere
+                            if context.window_of(context.focused) != window_entity {
+                                context.focus(window_entity);
+                            }
                         }
 
                         #[allow(deprecated)]
@@ -439,6 +450,7 @@ impl Application {
                             modifiers: _
                         } => {
 
+                            context.active_window = window_entity;
                             context.mouse.cursorx = position.x as f32;
                             context.mouse.cursory = position.y as f32;
 
@@ -499,25 +511,30 @@ impl Application {
                                     let new_click_pos = (context.mouse.cursorx, context.mouse.cursory);
 
                                     if click_duration <= double_click_interval && new_click_pos == click_pos{
-                                        if !double_click {
-                                            let _target = if context.captured != Entity::null() {
-                                                context.event_queue.push_back(
-                                                    Event::new(WindowEvent::MouseDoubleClick(button))
-                                                        .target(context.captured)
-                                                        .propagate(Propagation::Direct),
-                                                );
-                                                context.captured
-                                            } else {
-                                                context.event_queue.push_back(
-                                                    Event::new(WindowEvent::MouseDoubleClick(button))
-                                                        .target(context.hovered),
-                                                );
-                                                context.hovered
-                                            };
-                                            double_click = true;
-                                        }
+                                        click_count += 1;
                                     } else {
-                                        double_click = false;
+                                        click_count = 1;
+                                    }
+
+                                    let click_event = match click_count {
+                                        2 => Some(WindowEvent::MouseDoubleClick(button)),
+                                        3 => Some(WindowEvent::MouseTripleClick(button)),
+                                        _ => None,
+                                    };
+
+                                    if let Some(click_event) = click_event {
+                                        if context.captured != Entity::null() {
+                                            context.event_queue.push_back(
+                                                Event::new(click_event)
+                                                    .target(context.captured)
+                                                    .propagate(Propagation::Direct),
+                                            );
+                                        } else {
+                                            context.event_queue.push_back(
+                                                Event::new(click_event)
+                                                    .target(context.hovered),
+                                            );
+                                        }
                                     }
 
                                     click_time = new_click_time;
@@ -628,6 +645,15 @@ impl Application {
                                 input.virtual_keycode.unwrap_or(VirtualKeyCode::NoConvert),
                             );
 
+                            // Move keyboard focus with Tab and Shift+Tab
+                            if code == Code::Tab && s == MouseButtonState::Pressed {
+                                if context.modifiers.contains(Modifiers::SHIFT) {
+                                    focus_prev(&mut context);
+                                } else {
+                                    focus_next(&mut context);
+                                }
+                            }
+
                             match s {
                                 MouseButtonState::Pressed => {
                                     if context.focused != Entity::null() {
@@ -671,49 +697,61 @@ impl Application {
                             );
                         }
 
+                        glutin::event::WindowEvent::Ime(ime) => {
+                            let window_event = match ime {
+                                Ime::Preedit(text, cursor) => {
+                                    Some(WindowEvent::ImePreedit { text, cursor })
+                                }
+                                Ime::Commit(text) => Some(WindowEvent::ImeCommit(text)),
+                                _ => None,
+                            };
+
+                            if let Some(window_event) = window_event {
+                                context.event_queue.push_back(
+                                    Event::new(window_event)
+                                        .target(context.focused)
+                                        .propagate(Propagation::Up),
+                                );
+                            }
+                        }
+
                         glutin::event::WindowEvent::Resized(size) => {
                             //println!("Resized: {:?}", size);
 
-                            if let Some(mut window_view) = context.views.remove(&Entity::root()) {
+                            if let Some(mut window_view) = context.views.remove(&window_entity) {
                                 if let Some(window) = window_view.downcast_mut::<Window>() {
-                                    window.handle.resize(size);
+                                    window.handle().resize(size);
                                 }
 
-                                context.views.insert(Entity::root(), window_view);
+                                context.views.insert(window_entity, window_view);
                             }
 
                             context
                                 .style
                                 .width
-                                .insert(Entity::root(), Units::Pixels(size.width as f32));
+                                .insert(window_entity, Units::Pixels(size.width as f32));
 
                             context
                                 .style
                                 .height
-                                .insert(Entity::root(), Units::Pixels(size.height as f32));
+                                .insert(window_entity, Units::Pixels(size.height as f32));
 
                             context
                                 .cache
-                                .set_width(Entity::root(), size.width as f32);
+                                .set_width(window_entity, size.width as f32);
                             context
                                 .cache
-                                .set_height(Entity::root(), size.height as f32);
+                                .set_height(window_entity, size.height as f32);
 
                             let mut bounding_box = BoundingBox::default();
                             bounding_box.w = size.width as f32;
                             bounding_box.h = size.height as f32;
 
-                            context.cache.set_clip_region(Entity::root(), bounding_box);
+                            context.cache.set_clip_region(window_entity, bounding_box);
 
                             context.style.needs_restyle = true;
                             context.style.needs_relayout = true;
                             context.style.needs_redraw = true;
-
-                            // let mut bounding_box = BoundingBox::default();
-                            // bounding_box.w = size.width as f32;
-                            // bounding_box.h = size.height as f32;
-
-                            // context.cache.set_clip_region(Entity::root(), bounding_box);
                         }
 
                         glutin::event::WindowEvent::ModifiersChanged(modifiers_state) => {
@@ -727,6 +765,11 @@ impl Application {
                     }
                 }
 
+                glutin::event::Event::LoopDestroyed => {
+                    // Save the state of persistent models when the application exits
+                    context.save_persistent_state();
+                }
+
                 _=> {}
             }
         });
@@ -745,6 +788,185 @@ impl Env for Application {
     }
 }
 
+// Loads new fonts and images into the canvas of the main window and records them so that they can be
+// loaded into the canvases of other windows with the same ids.
+fn load_resources(
+    context: &mut Context,
+    window: &mut Window,
+    fonts: &mut Vec<(Vec<u8>, FontId)>,
+    images: &mut Vec<(Vec<u8>, ImageId)>,
+) {
+    for (name, font) in context.resource_manager.fonts.iter_mut() {
+        match font {
+            FontOrId::Font(data) => {
+                let id1 = window
+                    .canvas
+                    .add_font_mem(&data.clone())
+                    .expect(&format!("Failed to load font file for: {}", name));
+                let id2 = context.text_context.add_font_mem(&data.clone()).expect("failed");
+                if id1 != id2 {
+                    panic!("Fonts in canvas must have the same id as fonts in the text context");
+                }
+                fonts.push((data.clone(), id1));
+                *font = FontOrId::Id(id1);
+            }
+
+            _ => {}
+        }
+    }
+
+    let mut failed = Vec::new();
+    for (name, image) in context.resource_manager.images.iter_mut() {
+        match image {
+            ImageOrId::Image(data) => {
+                match window
+                    .canvas
+                    .load_image_mem(data, ImageFlags::REPEAT_X | ImageFlags::REPEAT_Y)
+                {
+                    Ok(id) => {
+                        images.push((data.clone(), id));
+                        *image = ImageOrId::Id(id);
+                    }
+
+                    Err(error) => failed.push((name.clone(), error)),
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    // Images which fail to load are removed so that they aren't retried every frame
+    for (name, error) in failed {
+        context.resource_manager.images.remove(&name);
+        context.report("images", format!("Failed to load image {}: {:?}", name, error));
+    }
+
+    // The resources were loaded directly into the canvas of the main window
+    window.set_resources_loaded(fonts.len(), images.len());
+}
+
+// Creates the OS windows for windows opened with `open_window` and removes the subtrees of closed windows.
+fn update_windows(
+    context: &mut Context,
+    event_loop_target: &EventLoopWindowTarget<Event>,
+    window_entities: &mut HashMap<WindowId, Entity>,
+    fonts: &[(Vec<u8>, FontId)],
+    images: &[(Vec<u8>, ImageId)],
+) {
+    window_entities
+        .retain(|_, entity| *entity == Entity::root() || context.windows.contains_key(entity));
+
+    // Removing the subtree drops the window view, which closes the OS window
+    for entity in context.closed_windows() {
+        context.remove(entity);
+    }
+
+    let opened = context
+        .windows
+        .keys()
+        .filter(|entity| !window_entities.values().any(|window_entity| window_entity == *entity))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    for entity in opened {
+        if let Some(window_description) = context.windows.get(&entity) {
+            let mut window = Window::new(event_loop_target, window_description);
+            window.load_resources(fonts, images);
+
+            window_entities.insert(window.id, entity);
+            context.views.insert(entity, Box::new(window));
+
+            context.style.needs_restyle = true;
+            context.style.needs_relayout = true;
+            context.style.needs_redraw = true;
+        }
+    }
+}
+
+// Draws the entities in the subtree of a window to the canvas of the window.
+fn draw_window(context: &mut Context, window_entity: Entity, window: &mut Window) {
+    let dpi_factor = window.handle().window().scale_factor();
+
+    let window_width = context.cache.get_width(window_entity);
+    let window_height = context.cache.get_height(window_entity);
+
+    window.canvas.set_size(window_width as u32, window_height as u32, dpi_factor as f32);
+    let clear_color = context
+        .style
+        .background_color
+        .get(window_entity)
+        .or(context.style.background_color.get(Entity::root()))
+        .cloned()
+        .unwrap_or(Color::white());
+    window.canvas.clear_rect(0, 0, window_width as u32, window_height as u32, clear_color.into());
+
+    // Sort the tree by z order
+    let mut draw_tree: Vec<Entity> = context.tree.into_iter().collect();
+    draw_tree.sort_by_cached_key(|entity| context.cache.get_z_index(*entity));
+
+    for entity in draw_tree.into_iter() {
+        // Skip window
+        if entity == window_entity {
+            continue;
+        }
+
+        // Skip widgets in other windows
+        if context.window_of(entity) != window_entity {
+            continue;
+        }
+
+        // Skip invisible widgets
+        if context.cache.get_visibility(entity) == Visibility::Invisible {
+            continue;
+        }
+
+        // Skip non-displayed widgets
+        if context.cache.get_display(entity) == Display::None {
+            continue;
+        }
+
+        // Skip widgets that have 0 opacity
+        if context.cache.get_opacity(entity) == 0.0 {
+            continue;
+        }
+
+        let bounds = context.cache.get_bounds(entity);
+
+        if bounds.x > window_width || bounds.y > window_height {
+            continue;
+        }
+
+        // Apply clipping
+        let clip_region = context.cache.get_clip_region(entity);
+        window.canvas.scissor(clip_region.x, clip_region.y, clip_region.w, clip_region.h);
+
+        // Apply transform
+        let transform = context.cache.get_transform(entity);
+        window.canvas.save();
+        window.canvas.set_transform(
+            transform[0],
+            transform[1],
+            transform[2],
+            transform[3],
+            transform[4],
+            transform[5],
+        );
+
+        if let Some(view) = context.views.remove(&entity) {
+            context.current = entity;
+            view.draw(context, &mut window.canvas);
+
+            context.views.insert(entity, view);
+        }
+
+        window.canvas.restore();
+    }
+
+    window.canvas.flush();
+    window.handle().swap_buffers().expect("Failed to swap buffers");
+}
+
 // fn debug(cx: &mut Context, entity: Entity) -> String {
 //     if let Some(view) = cx.views.get(&entity) {
 //         view.debug(entity)
@@ -1,21 +1,28 @@
-use glutin::event_loop::EventLoop;
-use glutin::window::WindowBuilder;
+use glutin::event_loop::EventLoopWindowTarget;
+use glutin::window::{CursorGrabMode, WindowBuilder};
 use glutin::ContextBuilder;
 use glutin::{dpi::*, window::WindowId};
 
-use femtovg::{renderer::OpenGl, Canvas, Color};
+use femtovg::{renderer::OpenGl, Canvas, Color, FontId, ImageFlags, ImageId};
 
 use vizia_core::{Context, CursorIcon, Event, View, WindowDescription, WindowEvent};
 
 pub struct Window {
     pub id: WindowId,
-    pub handle: glutin::WindowedContext<glutin::PossiblyCurrent>,
+    // Only None while the context is being made current
+    handle: Option<glutin::WindowedContext<glutin::PossiblyCurrent>>,
     pub canvas: Canvas<OpenGl>,
     //pub window_widget: WindowWidget,
+    // Number of shared fonts and images which have been loaded into the canvas
+    fonts_loaded: usize,
+    images_loaded: usize,
 }
 
 impl Window {
-    pub fn new(events_loop: &EventLoop<Event>, window_description: &WindowDescription) -> Self {
+    pub fn new(
+        events_loop: &EventLoopWindowTarget<Event>,
+        window_description: &WindowDescription,
+    ) -> Self {
         //Windows COM doesn't play nicely with winit's drag and drop right now
         #[cfg(target_os = "windows")]
         let mut window_builder = {
@@ -74,263 +81,374 @@ impl Window {
 
         Window {
             id: handle.window().id(),
-            handle,
+            handle: Some(handle),
             canvas,
             //window_widget: WindowWidget::new(),
+            fonts_loaded: 0,
+            images_loaded: 0,
         }
     }
+
+    /// Returns the OpenGL context of this window, along with the window itself.
+    pub fn handle(&self) -> &glutin::WindowedContext<glutin::PossiblyCurrent> {
+        self.handle.as_ref().expect("Failed to find window context")
+    }
+
+    /// Makes the OpenGL context of this window current. Must be called before drawing when there is more than one window.
+    pub fn make_current(&mut self) {
+        if self.handle().is_current() {
+            return;
+        }
+
+        let handle = self.handle.take().expect("Failed to find window context");
+
+        // The contexts of the other windows are only used again after they have been made current themselves
+        match unsafe { handle.make_current() } {
+            Ok(handle) => self.handle = Some(handle),
+            Err((handle, err)) => {
+                self.handle = Some(handle);
+                panic!("Failed to make window context current: {:?}", err);
+            }
+        }
+    }
+
+    /// Loads any of the shared fonts and images which have not yet been loaded into the canvas of this window.
+    ///
+    /// Resources are loaded in the same order for every window so that their ids match across canvases.
+    pub fn load_resources(&mut self, fonts: &[(Vec<u8>, FontId)], images: &[(Vec<u8>, ImageId)]) {
+        for (data, font_id) in fonts.iter().skip(self.fonts_loaded) {
+            let id = self.canvas.add_font_mem(data).expect("Failed to load font");
+            if id != *font_id {
+                panic!("Fonts in canvas must have the same id as fonts in the text context");
+            }
+        }
+
+        for (data, image_id) in images.iter().skip(self.images_loaded) {
+            let id = self
+                .canvas
+                .load_image_mem(data, ImageFlags::REPEAT_X | ImageFlags::REPEAT_Y)
+                .expect("Failed to load image");
+            if id != *image_id {
+                panic!("Images must have the same id in every window");
+            }
+        }
+
+        self.set_resources_loaded(fonts.len(), images.len());
+    }
+
+    // Records the number of shared fonts and images which have been loaded into the canvas of this window.
+    pub(crate) fn set_resources_loaded(&mut self, fonts: usize, images: usize) {
+        self.fonts_loaded = fonts;
+        self.images_loaded = images;
+    }
 }
 
 impl View for Window {
-    fn event(&mut self, _: &mut Context, event: &mut Event) {
+    fn event(&mut self, cx: &mut Context, event: &mut Event) {
         //self.window_widget.on_event(state, entity, event);
         if let Some(window_event) = event.message.downcast() {
             match window_event {
+                // Windows opened with `open_window` are removed by the application at the start of the next frame
+                WindowEvent::WindowClose => {
+                    if cx.windows.contains_key(&cx.current) {
+                        cx.close_window(cx.current);
+                        event.consume();
+                    }
+                }
+
                 WindowEvent::GrabCursor(flag) => {
-                    self.handle.window().set_cursor_grab(*flag).expect("Failed to set cursor grab");
+                    let window = self.handle().window();
+                    if *flag {
+                        // Not every platform can confine the cursor, so fall back to locking it
+                        window
+                            .set_cursor_grab(CursorGrabMode::Confined)
+                            .or_else(|_| window.set_cursor_grab(CursorGrabMode::Locked))
+                            .expect("Failed to set cursor grab");
+                    } else {
+                        window
+                            .set_cursor_grab(CursorGrabMode::None)
+                            .expect("Failed to set cursor grab");
+                    }
+                    event.consume();
+                }
+
+                WindowEvent::SetImeAllowed(flag) => {
+                    self.handle().window().set_ime_allowed(*flag);
+                    event.consume();
+                }
+
+                WindowEvent::SetImeCaret(bounds) => {
+                    // The candidate window is placed below the caret
+                    self.handle().window().set_ime_position(glutin::dpi::Position::Physical(
+                        PhysicalPosition::new(bounds.x as i32, (bounds.y + bounds.h) as i32),
+                    ));
+                    event.consume();
                 }
 
                 WindowEvent::SetCursorPosition(x, y) => {
-                    self.handle
+                    self.handle()
                         .window()
                         .set_cursor_position(glutin::dpi::Position::Physical(
                             PhysicalPosition::new(*x as i32, *y as i32),
                         ))
                         .expect("Failed to set cursor position");
+                    event.consume();
                 }
 
                 WindowEvent::SetCursor(cursor) => {
                     //println!("Set The Cursor: {:?}", cursor);
                     match *cursor {
                         CursorIcon::Default => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::Default);
                         }
 
                         CursorIcon::Crosshair => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::Crosshair);
                         }
 
                         CursorIcon::Hand => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle.window().set_cursor_icon(glutin::window::CursorIcon::Hand);
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
+                                .window()
+                                .set_cursor_icon(glutin::window::CursorIcon::Hand);
                         }
 
                         CursorIcon::Arrow => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle.window().set_cursor_icon(glutin::window::CursorIcon::Arrow);
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
+                                .window()
+                                .set_cursor_icon(glutin::window::CursorIcon::Arrow);
                         }
 
                         CursorIcon::Move => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle.window().set_cursor_icon(glutin::window::CursorIcon::Move);
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
+                                .window()
+                                .set_cursor_icon(glutin::window::CursorIcon::Move);
                         }
 
                         CursorIcon::Text => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle.window().set_cursor_icon(glutin::window::CursorIcon::Text);
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
+                                .window()
+                                .set_cursor_icon(glutin::window::CursorIcon::Text);
                         }
 
                         CursorIcon::Wait => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle.window().set_cursor_icon(glutin::window::CursorIcon::Wait);
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
+                                .window()
+                                .set_cursor_icon(glutin::window::CursorIcon::Wait);
                         }
 
                         CursorIcon::Help => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle.window().set_cursor_icon(glutin::window::CursorIcon::Help);
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
+                                .window()
+                                .set_cursor_icon(glutin::window::CursorIcon::Help);
                         }
 
                         CursorIcon::Progress => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::Progress);
                         }
 
                         CursorIcon::NotAllowed => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::NotAllowed);
                         }
 
                         CursorIcon::ContextMenu => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::ContextMenu);
                         }
 
                         CursorIcon::Cell => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle.window().set_cursor_icon(glutin::window::CursorIcon::Cell);
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
+                                .window()
+                                .set_cursor_icon(glutin::window::CursorIcon::Cell);
                         }
 
                         CursorIcon::VerticalText => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::VerticalText);
                         }
 
                         CursorIcon::Alias => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle.window().set_cursor_icon(glutin::window::CursorIcon::Alias);
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
+                                .window()
+                                .set_cursor_icon(glutin::window::CursorIcon::Alias);
                         }
 
                         CursorIcon::Copy => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle.window().set_cursor_icon(glutin::window::CursorIcon::Copy);
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
+                                .window()
+                                .set_cursor_icon(glutin::window::CursorIcon::Copy);
                         }
 
                         CursorIcon::NoDrop => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::NoDrop);
                         }
 
                         CursorIcon::Grab => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle.window().set_cursor_icon(glutin::window::CursorIcon::Grab);
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
+                                .window()
+                                .set_cursor_icon(glutin::window::CursorIcon::Grab);
                         }
 
                         CursorIcon::Grabbing => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::Grabbing);
                         }
 
                         CursorIcon::AllScroll => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::AllScroll);
                         }
 
                         CursorIcon::ZoomIn => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::ZoomIn);
                         }
 
                         CursorIcon::ZoomOut => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::ZoomOut);
                         }
 
                         CursorIcon::EResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::EResize);
                         }
 
                         CursorIcon::NResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::NResize);
                         }
 
                         CursorIcon::NeResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::NeResize);
                         }
 
                         CursorIcon::NwResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::NwResize);
                         }
 
                         CursorIcon::SResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::SResize);
                         }
 
                         CursorIcon::SeResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::SeResize);
                         }
 
                         CursorIcon::SwResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::SwResize);
                         }
 
                         CursorIcon::WResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::WResize);
                         }
 
                         CursorIcon::EwResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::EwResize);
                         }
 
                         CursorIcon::NsResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::NsResize);
                         }
 
                         CursorIcon::NeswResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::NeswResize);
                         }
 
                         CursorIcon::NwseResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::NwseResize);
                         }
 
                         CursorIcon::ColResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::ColResize);
                         }
 
                         CursorIcon::RowResize => {
-                            self.handle.window().set_cursor_visible(true);
-                            self.handle
+                            self.handle().window().set_cursor_visible(true);
+                            self.handle()
                                 .window()
                                 .set_cursor_icon(glutin::window::CursorIcon::RowResize);
                         }
 
                         CursorIcon::None => {
-                            self.handle.window().set_cursor_visible(false);
+                            self.handle().window().set_cursor_visible(false);
                         }
                     }
+
+                    event.consume();
                 }
 
                 _ => {}
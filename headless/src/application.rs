@@ -0,0 +1,589 @@
+use std::time::{Duration, Instant};
+
+use vizia_core::*;
+
+static DEFAULT_THEME: &str = include_str!("../../core/src/default_theme.css");
+
+// The maximum number of frames `step()` will run while waiting for the event queue to empty.
+const MAX_FRAMES_PER_STEP: usize = 100;
+
+/// An application which runs without a window or a renderer.
+///
+/// Nothing is drawn, but events, data updates, styling and layout are processed exactly as they
+/// would be by a windowed backend, so the resulting state of the [Context] can be inspected.
+///
+/// # Example
+/// ```no_run
+/// # use vizia_core::*;
+/// # use vizia_headless::Application;
+/// let mut app = Application::new(WindowDescription::new(), |cx| {
+///     Label::new(cx, "Hello").width(Pixels(100.0)).height(Pixels(30.0));
+/// });
+///
+/// app.step();
+///
+/// let label = Entity::root().child_iter(&app.context().tree).nth(0).unwrap();
+/// assert_eq!(app.context().cache.get_width(label), 100.0);
+/// ```
+pub struct Application {
+    context: Context,
+    event_manager: EventManager,
+    builder: Option<Box<dyn Fn(&mut Context)>>,
+    on_idle: Option<Box<dyn Fn(&mut Context)>>,
+    // The time used to update animations and timers, or None to use the current time
+    time: Option<Instant>,
+}
+
+impl Application {
+    pub fn new<F>(window_description: WindowDescription, builder: F) -> Self
+    where
+        F: 'static + Fn(&mut Context),
+    {
+        let mut context = Context::new();
+
+        context.entity_manager.create();
+
+        context.add_theme(DEFAULT_THEME);
+
+        let regular_font = include_bytes!("../../fonts/Roboto-Regular.ttf");
+        let bold_font = include_bytes!("../../fonts/Roboto-Bold.ttf");
+        let icon_font = include_bytes!("../../fonts/entypo.ttf");
+        let emoji_font = include_bytes!("../../fonts/OpenSansEmoji.ttf");
+        let arabic_font = include_bytes!("../../fonts/amiri-regular.ttf");
+        let material_font = include_bytes!("../../fonts/MaterialIcons-Regular.ttf");
+
+        context.add_font_mem("roboto", regular_font);
+        context.add_font_mem("roboto-bold", bold_font);
+        context.add_font_mem("icons", icon_font);
+        context.add_font_mem("emoji", emoji_font);
+        context.add_font_mem("arabic", arabic_font);
+        context.add_font_mem("material", material_font);
+
+        context.style.default_font = "roboto".to_string();
+
+        let width = window_description.inner_size.width as f32;
+        let height = window_description.inner_size.height as f32;
+
+        context.cache.set_width(Entity::root(), width);
+        context.cache.set_height(Entity::root(), height);
+
+        context.style.width.insert(Entity::root(), Units::Pixels(width));
+        context.style.height.insert(Entity::root(), Units::Pixels(height));
+
+        context.style.pseudo_classes.insert(Entity::root(), PseudoClass::default()).unwrap();
+        context.style.disabled.insert(Entity::root(), false);
+
+        let mut bounding_box = BoundingBox::default();
+        bounding_box.w = width;
+        bounding_box.h = height;
+
+        context.cache.set_clip_region(Entity::root(), bounding_box);
+
+        let mut app = Self {
+            context,
+            event_manager: EventManager::new(),
+            builder: Some(Box::new(builder)),
+            on_idle: None,
+            time: None,
+        };
+
+        app.load_fonts();
+
+        app
+    }
+
+    /// Takes a closure which will be called at the end of every frame of the application.
+    pub fn on_idle<F: 'static + Fn(&mut Context)>(mut self, callback: F) -> Self {
+        self.on_idle = Some(Box::new(callback));
+
+        self
+    }
+
+    pub fn locale(mut self, id: &str) -> Self {
+        self.context.enviroment.set_locale(id);
+
+        self
+    }
+
+    /// Enables persistence for the application with the given name.
+    ///
+    /// The state of models built with `build_persistent()` is restored from a file in the configuration
+    /// directory of the user before the application is built, and is saved when the models change, at most
+    /// once per `PERSISTENT_SAVE_INTERVAL`. Use `context_mut().save_persistent_state()` to save immediately
+    /// and `context_mut().load_persistent_state()` to save to a different file.
+    pub fn persist(mut self, app_name: &str) -> Self {
+        if let Some(path) = persistence_path(app_name) {
+            self.context.load_persistent_state(path);
+        } else {
+            self.context.report(
+                "persistence",
+                format!("Failed to find a directory to save the state of {} to", app_name),
+            );
+        }
+
+        self
+    }
+
+    /// Returns a reference to the context of the application.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Returns a mutable reference to the context of the application.
+    pub fn context_mut(&mut self) -> &mut Context {
+        &mut self.context
+    }
+
+    /// Runs frames until there are no more events left to process.
+    ///
+    /// A frame rebuilds the application if required, flushes the event queue, updates any bound views
+    /// and then applies styling, layout, transforms, hover and clipping. If a frame produces new events,
+    /// such as geometry changed events, another frame is run, which is the same behaviour as the
+    /// windowed backends.
+    pub fn step(&mut self) {
+        for _ in 0..MAX_FRAMES_PER_STEP {
+            self.frame();
+
+            if self.context.event_queue.is_empty() {
+                return;
+            }
+        }
+    }
+
+    /// Moves the clock used for animations forward by the given duration and runs a step.
+    ///
+    /// Once called, animations and timers are updated using this clock rather than the current time, so
+    /// that animated values can be checked at known points in an animation.
+    pub fn advance_time(&mut self, duration: Duration) {
+        let time = self.time.unwrap_or_else(Instant::now) + duration;
+        self.time = Some(time);
+        self.context.timers.set_clock(time);
+
+        self.step();
+    }
+
+    /// Waits for all spawned background tasks to finish and runs a step to handle their results.
+    pub fn wait_for_tasks(&mut self) {
+        self.context.wait_for_tasks();
+
+        self.step();
+    }
+
+    /// Adds an event to the event queue. The event is processed on the next call to `step()`.
+    pub fn send_event(&mut self, event: Event) {
+        self.context.event_queue.push_back(event);
+    }
+
+    /// Resizes the (virtual) window and runs a step.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let width = width as f32;
+        let height = height as f32;
+
+        self.context.style.width.insert(Entity::root(), Units::Pixels(width));
+        self.context.style.height.insert(Entity::root(), Units::Pixels(height));
+
+        self.context.cache.set_width(Entity::root(), width);
+        self.context.cache.set_height(Entity::root(), height);
+
+        let mut bounding_box = BoundingBox::default();
+        bounding_box.w = width;
+        bounding_box.h = height;
+
+        self.context.cache.set_clip_region(Entity::root(), bounding_box);
+
+        self.context.style.needs_restyle = true;
+        self.context.style.needs_relayout = true;
+        self.context.style.needs_redraw = true;
+
+        self.step();
+    }
+
+    /// Moves the mouse cursor to the given position in window coordinates and runs a step.
+    pub fn mouse_move(&mut self, x: f32, y: f32) {
+        self.context.mouse.cursorx = x;
+        self.context.mouse.cursory = y;
+
+        apply_hover(&mut self.context);
+
+        if self.context.captured != Entity::null() {
+            self.context.event_queue.push_back(
+                Event::new(WindowEvent::MouseMove(x, y))
+                    .target(self.context.captured)
+                    .propagate(Propagation::Direct),
+            );
+        } else if self.context.hovered != Entity::root() {
+            self.context
+                .event_queue
+                .push_back(Event::new(WindowEvent::MouseMove(x, y)).target(self.context.hovered));
+        }
+
+        self.step();
+    }
+
+    /// Presses a mouse button at the current cursor position and runs a step.
+    pub fn mouse_down(&mut self, button: MouseButton) {
+        self.press(button, None);
+    }
+
+    // Presses a mouse button, sending a double or triple click event before the press like the windowed backends.
+    fn press(&mut self, button: MouseButton, click_event: Option<WindowEvent>) {
+        if let Some(click_event) = click_event {
+            self.send_mouse_event(click_event);
+        }
+
+        self.set_button_state(button, MouseButtonState::Pressed);
+
+        let cursor = (self.context.mouse.cursorx, self.context.mouse.cursory);
+        let hovered = self.context.hovered;
+
+        match button {
+            MouseButton::Left => {
+                self.context.mouse.left.pos_down = cursor;
+                self.context.mouse.left.pressed = hovered;
+            }
+
+            MouseButton::Right => {
+                self.context.mouse.right.pos_down = cursor;
+                self.context.mouse.right.pressed = hovered;
+            }
+
+            MouseButton::Middle => {
+                self.context.mouse.middle.pos_down = cursor;
+                self.context.mouse.middle.pressed = hovered;
+            }
+
+            _ => {}
+        }
+
+        self.send_mouse_event(WindowEvent::MouseDown(button));
+
+        self.step();
+    }
+
+    /// Releases a mouse button at the current cursor position and runs a step.
+    pub fn mouse_up(&mut self, button: MouseButton) {
+        self.set_button_state(button, MouseButtonState::Released);
+
+        let cursor = (self.context.mouse.cursorx, self.context.mouse.cursory);
+        let hovered = self.context.hovered;
+
+        match button {
+            MouseButton::Left => {
+                self.context.mouse.left.pos_up = cursor;
+                self.context.mouse.left.released = hovered;
+            }
+
+            MouseButton::Right => {
+                self.context.mouse.right.pos_up = cursor;
+                self.context.mouse.right.released = hovered;
+            }
+
+            MouseButton::Middle => {
+                self.context.mouse.middle.pos_up = cursor;
+                self.context.mouse.middle.released = hovered;
+            }
+
+            _ => {}
+        }
+
+        self.send_mouse_event(WindowEvent::MouseUp(button));
+
+        self.step();
+    }
+
+    /// Moves the cursor to the given position and then presses and releases the left mouse button.
+    pub fn click(&mut self, x: f32, y: f32) {
+        self.mouse_move(x, y);
+        self.mouse_down(MouseButton::Left);
+        self.mouse_up(MouseButton::Left);
+    }
+
+    /// Clicks the left mouse button twice at the given position.
+    pub fn double_click(&mut self, x: f32, y: f32) {
+        self.click(x, y);
+        self.press(MouseButton::Left, Some(WindowEvent::MouseDoubleClick(MouseButton::Left)));
+        self.mouse_up(MouseButton::Left);
+    }
+
+    /// Clicks the left mouse button three times at the given position.
+    pub fn triple_click(&mut self, x: f32, y: f32) {
+        self.double_click(x, y);
+        self.press(MouseButton::Left, Some(WindowEvent::MouseTripleClick(MouseButton::Left)));
+        self.mouse_up(MouseButton::Left);
+    }
+
+    /// Sends a mouse scroll event to the hovered entity and runs a step.
+    pub fn mouse_scroll(&mut self, x: f32, y: f32) {
+        self.send_mouse_event(WindowEvent::MouseScroll(x, y));
+
+        self.step();
+    }
+
+    /// Presses a key and runs a step. The event is sent to the focused entity.
+    ///
+    /// As with the windowed backends, pressing Tab moves keyboard focus forwards, or backwards if shift is held.
+    pub fn key_down(&mut self, code: Code, key: Option<Key>) {
+        if code == Code::Tab {
+            if self.context.modifiers.contains(Modifiers::SHIFT) {
+                focus_prev(&mut self.context);
+            } else {
+                focus_next(&mut self.context);
+            }
+        }
+
+        self.send_key_event(WindowEvent::KeyDown(code, key));
+
+        self.step();
+    }
+
+    /// Releases a key and runs a step. The event is sent to the focused entity.
+    pub fn key_up(&mut self, code: Code, key: Option<Key>) {
+        self.send_key_event(WindowEvent::KeyUp(code, key));
+
+        self.step();
+    }
+
+    /// Presses and releases a key.
+    pub fn key_press(&mut self, code: Code, key: Option<Key>) {
+        self.key_down(code, key.clone());
+        self.key_up(code, key);
+    }
+
+    /// Sends a character input event to the focused entity for each character of the text and runs a step.
+    pub fn type_text(&mut self, text: &str) {
+        for character in text.chars() {
+            self.context.event_queue.push_back(
+                Event::new(WindowEvent::CharInput(character))
+                    .target(self.context.focused)
+                    .propagate(Propagation::Up),
+            );
+        }
+
+        self.step();
+    }
+
+    /// Sends the text being composed with an input method to the focused entity and runs a step.
+    ///
+    /// The cursor is the byte range of the cursor within the composed text, if it should be shown.
+    pub fn ime_preedit(&mut self, text: &str, cursor: Option<(usize, usize)>) {
+        self.context.event_queue.push_back(
+            Event::new(WindowEvent::ImePreedit { text: text.to_owned(), cursor })
+                .target(self.context.focused)
+                .propagate(Propagation::Up),
+        );
+
+        self.step();
+    }
+
+    /// Sends the text committed by an input method to the focused entity and runs a step.
+    pub fn ime_commit(&mut self, text: &str) {
+        self.context.event_queue.push_back(
+            Event::new(WindowEvent::ImeCommit(text.to_owned()))
+                .target(self.context.focused)
+                .propagate(Propagation::Up),
+        );
+
+        self.step();
+    }
+
+    /// Sets the state of the modifier keys.
+    pub fn set_modifiers(&mut self, modifiers: Modifiers) {
+        self.context.modifiers = modifiers;
+    }
+
+    fn set_button_state(&mut self, button: MouseButton, state: MouseButtonState) {
+        match button {
+            MouseButton::Left => {
+                self.context.mouse.left.state = state;
+            }
+            MouseButton::Right => {
+                self.context.mouse.right.state = state;
+            }
+            MouseButton::Middle => {
+                self.context.mouse.middle.state = state;
+            }
+            _ => {}
+        }
+    }
+
+    fn send_mouse_event(&mut self, window_event: WindowEvent) {
+        if self.context.captured != Entity::null() {
+            self.context.event_queue.push_back(
+                Event::new(window_event)
+                    .target(self.context.captured)
+                    .propagate(Propagation::Direct),
+            );
+        } else {
+            self.context.event_queue.push_back(
+                Event::new(window_event).target(self.context.hovered).propagate(Propagation::Up),
+            );
+        }
+    }
+
+    fn send_key_event(&mut self, window_event: WindowEvent) {
+        let target = if self.context.focused != Entity::null() {
+            self.context.focused
+        } else {
+            self.context.hovered
+        };
+
+        self.context
+            .event_queue
+            .push_back(Event::new(window_event).target(target).propagate(Propagation::Up));
+    }
+
+    // Fonts are only registered with the text context because there is no canvas to draw to.
+    fn load_fonts(&mut self) {
+        for (name, font) in self.context.resource_manager.fonts.iter_mut() {
+            match font {
+                FontOrId::Font(data) => {
+                    let id = self
+                        .context
+                        .text_context
+                        .add_font_mem(&data.clone())
+                        .expect(&format!("Failed to load font file for: {}", name));
+                    *font = FontOrId::Id(id);
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    fn frame(&mut self) {
+        let time = self.time.unwrap_or_else(Instant::now);
+
+        let context = &mut self.context;
+
+        // Rebuild application if required
+        if context.enviroment.needs_rebuild {
+            context.current = Entity::root();
+            context.count = 0;
+            if let Some(builder) = &self.builder {
+                (builder)(context);
+            }
+            context.enviroment.needs_rebuild = false;
+        }
+
+        // Remove the subtrees of closed windows, which the windowed backends do when closing the OS windows
+        for window in context.closed_windows() {
+            context.remove(window);
+        }
+
+        // Reload any stylesheets which have been modified
+        if context.resource_manager.watch_stylesheets {
+            context.poll_stylesheets();
+        }
+
+        self.load_fonts();
+
+        let context = &mut self.context;
+
+        // Timers which have reached their deadline
+        apply_timers(context, time);
+
+        // Results of finished background tasks
+        context.poll_tasks();
+
+        // Events
+        if !context.event_queue.is_empty() {
+            while !context.event_queue.is_empty() {
+                self.event_manager.flush_events(context);
+            }
+
+            // Persistent models may have been changed by the events
+            context.persistent_state.mark_dirty();
+        }
+
+        // Save persistent models which have changed, at most once per save interval
+        context.autosave_persistent_state(time);
+
+        // Report problems found while handling the events, such as translations which failed to load
+        context.flush_diagnostics();
+
+        // Computed values are updated before the bindings which depend on them
+        update_computed_values(context);
+
+        // Data Updates
+        let mut observers: Vec<Entity> = Vec::new();
+
+        for model_store in context.data.dense.iter_mut().map(|entry| &mut entry.value) {
+            for (_, lens) in model_store.lenses.iter_mut() {
+                for (_, model) in model_store.data.iter() {
+                    if lens.update(model) {
+                        observers.extend(lens.observers().iter());
+                    }
+                }
+            }
+        }
+
+        for observer in observers.iter() {
+            if let Some(mut view) = context.views.remove(observer) {
+                let prev = context.current;
+                context.current = *observer;
+                let prev_count = context.count;
+                context.count = 0;
+                view.body(context);
+                context.current = prev;
+                context.count = prev_count;
+                context.views.insert(*observer, view);
+            }
+        }
+
+        // Not ideal
+        let tree = context.tree.clone();
+
+        apply_inline_inheritance(context, &tree);
+
+        if context.style.needs_restyle {
+            apply_styles(context, &tree);
+            context.style.needs_restyle = false;
+        }
+
+        apply_animations(context, time);
+
+        apply_shared_inheritance(context, &tree);
+
+        apply_z_ordering(context, &tree);
+
+        apply_visibility(context, &tree);
+
+        apply_text_constraints(context, &tree);
+
+        // Layout
+        if context.style.needs_relayout {
+            vizia_core::apply_layout(&mut context.cache, &context.tree, &context.style);
+            context.style.needs_relayout = false;
+        }
+
+        // Emit any geometry changed events
+        geometry_changed(context, &tree);
+
+        apply_transform(context, &tree);
+
+        apply_hover(context);
+
+        apply_clipping(context, &tree);
+
+        // Nothing is drawn so the redraw flag is simply cleared
+        context.style.needs_redraw = false;
+
+        if let Some(idle_callback) = &self.on_idle {
+            context.current = Entity::root();
+            context.count = 0;
+            (idle_callback)(context);
+        }
+    }
+}
+
+impl Env for Application {
+    fn ignore_default_styles(mut self) -> Self {
+        if self.context.enviroment.include_default_theme {
+            self.context.enviroment.include_default_theme = false;
+            self.context.enviroment.needs_rebuild = true;
+            self.context.reload_styles().expect("Failed to reload styles");
+        }
+
+        self
+    }
+}
@@ -0,0 +1,7 @@
+//! A headless backend for vizia.
+//!
+//! The headless [Application] owns a [Context](vizia_core::Context) and runs the same
+//! style, layout and event pipeline as the windowed backends, but without creating a window
+//! or a renderer. This makes it possible to drive a vizia UI from integration tests.
+mod application;
+pub use application::Application;
@@ -0,0 +1,1595 @@
+use vizia_core::*;
+use vizia_headless::Application;
+
+// Returns the model of the given type which was built on the root.
+fn model<T: Model>(app: &Application) -> &T {
+    app.context()
+        .data
+        .get(Entity::root())
+        .and_then(|store| store.data.get(&std::any::TypeId::of::<T>()))
+        .and_then(|model| model.downcast_ref::<T>())
+        .expect("Failed to find model")
+}
+
+#[derive(Lens)]
+pub struct CounterData {
+    count: i32,
+}
+
+#[derive(Debug)]
+pub enum CounterEvent {
+    Increment,
+}
+
+impl Model for CounterData {
+    fn event(&mut self, _: &mut Context, event: &mut Event) {
+        if let Some(counter_event) = event.message.downcast() {
+            match counter_event {
+                CounterEvent::Increment => self.count += 1,
+            }
+        }
+    }
+}
+
+#[test]
+fn layout_is_applied() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        Element::new(cx)
+            .left(Pixels(10.0))
+            .top(Pixels(20.0))
+            .width(Pixels(100.0))
+            .height(Pixels(50.0));
+    });
+
+    app.step();
+
+    let cx = app.context();
+    let element = Entity::root().child_iter(&cx.tree).next().expect("Failed to find element");
+    let bounds = cx.cache.get_bounds(element);
+
+    assert_eq!(bounds.x, 10.0);
+    assert_eq!(bounds.y, 20.0);
+    assert_eq!(bounds.w, 100.0);
+    assert_eq!(bounds.h, 50.0);
+}
+
+#[test]
+fn resize_updates_layout() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        Element::new(cx).width(Stretch(1.0)).height(Stretch(1.0));
+    });
+
+    app.step();
+    app.resize(200, 100);
+
+    let cx = app.context();
+    let element = Entity::root().child_iter(&cx.tree).next().expect("Failed to find element");
+
+    assert_eq!(cx.cache.get_width(element), 200.0);
+    assert_eq!(cx.cache.get_height(element), 100.0);
+}
+
+#[test]
+fn click_updates_model() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        CounterData { count: 0 }.build(cx);
+
+        Button::new(cx, |cx| cx.emit(CounterEvent::Increment), |cx| Label::new(cx, "Increment"))
+            .width(Pixels(100.0))
+            .height(Pixels(30.0));
+    });
+
+    app.step();
+    assert_eq!(model::<CounterData>(&app).count, 0);
+
+    app.click(50.0, 15.0);
+    assert_eq!(model::<CounterData>(&app).count, 1);
+
+    // Clicking outside of the button does nothing
+    app.click(200.0, 200.0);
+    assert_eq!(model::<CounterData>(&app).count, 1);
+}
+
+#[test]
+fn mouse_move_sets_hovered() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        Element::new(cx).left(Pixels(100.0)).top(Pixels(100.0)).size(Pixels(50.0));
+    });
+
+    app.step();
+
+    let element =
+        Entity::root().child_iter(&app.context().tree).next().expect("Failed to find element");
+
+    app.mouse_move(120.0, 120.0);
+    assert_eq!(app.context().hovered, element);
+
+    app.mouse_move(10.0, 10.0);
+    assert_eq!(app.context().hovered, Entity::root());
+}
+
+#[test]
+fn scroll_view_scrolls_content() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        ScrollView::new(cx, false, true, |cx| {
+            for _ in 0..10 {
+                Element::new(cx).height(Pixels(50.0));
+            }
+        })
+        .height(Pixels(100.0));
+    });
+
+    app.step();
+
+    let (content, last_row) = {
+        let cx = app.context();
+        let scroll_view =
+            Entity::root().child_iter(&cx.tree).next().expect("Failed to find scroll view");
+        let content = scroll_view.child_iter(&cx.tree).next().expect("Failed to find content");
+        let last_row = content.child_iter(&cx.tree).last().expect("Failed to find row");
+        (content, last_row)
+    };
+
+    assert_eq!(app.context().cache.get_height(content), 500.0);
+
+    app.mouse_move(50.0, 50.0);
+    app.mouse_scroll(0.0, -1.0);
+    assert_eq!(app.context().cache.get_posy(content), -20.0);
+
+    // Scrolling up past the start is clamped
+    app.mouse_scroll(0.0, 5.0);
+    assert_eq!(app.context().cache.get_posy(content), 0.0);
+
+    let cx = app.context_mut();
+    ScrollView::scroll_to(cx, last_row);
+    app.step();
+    assert_eq!(app.context().cache.get_posy(content), -400.0);
+}
+
+#[derive(Lens)]
+pub struct ListData {
+    list: Vec<u32>,
+}
+
+impl Model for ListData {}
+
+#[test]
+fn virtual_list_builds_visible_rows() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        ListData { list: (0..10000).collect() }.build(cx);
+
+        ScrollView::new(cx, false, true, |cx| {
+            VirtualList::new(cx, ListData::list, |cx, item| {
+                Label::new(cx, &item.get(cx).to_string());
+            })
+            .row_height(10.0);
+        })
+        .height(Pixels(100.0));
+    });
+
+    app.step();
+    app.step();
+
+    let list = {
+        let cx = app.context();
+        let scroll_view =
+            Entity::root().child_iter(&cx.tree).last().expect("Failed to find scroll view");
+        let content = scroll_view.child_iter(&cx.tree).next().expect("Failed to find content");
+        content.child_iter(&cx.tree).next().expect("Failed to find list")
+    };
+
+    let rows = |app: &Application| list.child_iter(&app.context().tree).skip(1).collect::<Vec<_>>();
+
+    assert_eq!(app.context().cache.get_height(list), 100000.0);
+    assert!(!rows(&app).is_empty());
+    assert!(rows(&app).len() < 100);
+
+    app.mouse_move(50.0, 50.0);
+    app.mouse_scroll(0.0, -10.0);
+    app.step();
+    app.step();
+
+    // The first built row is no longer the first item in the list
+    let first_row = rows(&app)[0];
+    let cx = app.context();
+    assert!(cx.cache.get_posy(first_row) > cx.cache.get_posy(list));
+    assert!(rows(&app).len() < 100);
+}
+
+#[test]
+fn tab_moves_focus_within_scope() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        Button::new(cx, |_| {}, |cx| Label::new(cx, "A"));
+        Button::new(cx, |_| {}, |cx| Label::new(cx, "B"));
+        VStack::new(cx, |cx| {
+            Button::new(cx, |_| {}, |cx| Label::new(cx, "C"));
+            Button::new(cx, |_| {}, |cx| Label::new(cx, "D"));
+        })
+        .focus_scope(true);
+    });
+
+    app.step();
+
+    let (a, b, c, d) = {
+        let cx = app.context();
+        let children = Entity::root().child_iter(&cx.tree).collect::<Vec<_>>();
+        let scoped = children[2].child_iter(&cx.tree).collect::<Vec<_>>();
+        (children[0], children[1], scoped[0], scoped[1])
+    };
+
+    app.key_press(Code::Tab, None);
+    assert_eq!(app.context().focused, a);
+    assert!(a.is_focused(app.context()));
+
+    app.key_press(Code::Tab, None);
+    assert_eq!(app.context().focused, b);
+    assert!(!a.is_focused(app.context()));
+
+    // Shift+Tab from the first entity wraps around to the last
+    app.set_modifiers(Modifiers::SHIFT);
+    app.key_press(Code::Tab, None);
+    app.key_press(Code::Tab, None);
+    assert_eq!(app.context().focused, d);
+
+    // Focus is trapped inside the scope
+    app.set_modifiers(Modifiers::empty());
+    app.key_press(Code::Tab, None);
+    assert_eq!(app.context().focused, c);
+    app.key_press(Code::Tab, None);
+    assert_eq!(app.context().focused, d);
+}
+
+#[test]
+fn hover_respects_transform() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        Element::new(cx)
+            .left(Pixels(100.0))
+            .top(Pixels(100.0))
+            .width(Pixels(100.0))
+            .height(Pixels(20.0))
+            .rotate(90.0)
+            // Otherwise the element is clipped to its untransformed bounds
+            .overflow(Overflow::Visible);
+    });
+
+    app.step();
+
+    let element =
+        Entity::root().child_iter(&app.context().tree).next().expect("Failed to find element");
+
+    // Inside the rotated element but outside of its layout bounds
+    app.mouse_move(150.0, 70.0);
+    assert_eq!(app.context().hovered, element);
+
+    // Inside the layout bounds but outside of the rotated element
+    app.mouse_move(110.0, 110.0);
+    assert_eq!(app.context().hovered, Entity::root());
+}
+
+#[test]
+fn hover_respects_rounded_corners() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        Element::new(cx)
+            .left(Pixels(100.0))
+            .top(Pixels(100.0))
+            .size(Pixels(100.0))
+            .border_radius(Percentage(50.0));
+    });
+
+    app.step();
+
+    let element =
+        Entity::root().child_iter(&app.context().tree).next().expect("Failed to find element");
+
+    app.mouse_move(150.0, 150.0);
+    assert_eq!(app.context().hovered, element);
+
+    // Inside the bounds but outside of the rounded corner
+    app.mouse_move(105.0, 105.0);
+    assert_eq!(app.context().hovered, Entity::root());
+}
+
+#[test]
+fn window_subtree_is_laid_out_and_hovered_separately() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        Element::new(cx).size(Pixels(50.0));
+    });
+
+    app.step();
+
+    let window =
+        app.context_mut().open_window(WindowDescription::new().with_inner_size(600, 500), |cx| {
+            Element::new(cx).left(Pixels(500.0)).top(Pixels(400.0)).size(Pixels(50.0));
+        });
+
+    app.step();
+
+    let element = window.child_iter(&app.context().tree).next().expect("Failed to find element");
+
+    let cx = app.context();
+    assert_eq!(cx.window_of(element), window);
+    assert_eq!(cx.cache.get_width(window), 600.0);
+
+    // The element is outside of the main window but is only clipped to its own window
+    assert_eq!(cx.cache.get_clip_region(element).w, 50.0);
+
+    // The cursor is relative to the main window so the element is not hovered
+    app.mouse_move(520.0, 420.0);
+    assert_eq!(app.context().hovered, Entity::root());
+
+    app.context_mut().active_window = window;
+    app.mouse_move(520.0, 420.0);
+    assert_eq!(app.context().hovered, element);
+}
+
+#[test]
+fn windows_opened_by_builders_are_kept_when_rebuilt() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        MixerData { volume: 5, balance: 0 }.build(cx);
+
+        Binding::new(cx, MixerData::volume, |cx, volume| {
+            cx.open_window(WindowDescription::new(), |cx| {
+                Element::new(cx);
+            });
+            Label::new(cx, &volume.get(cx).to_string());
+        });
+    });
+
+    app.step();
+
+    let windows = app.context().windows.keys().cloned().collect::<Vec<_>>();
+    assert_eq!(windows.len(), 1);
+
+    // The label keeps its place after the window which was opened before it
+    let label = app.context().built_child(Entity::root(), 2).expect("Failed to find label");
+    assert_eq!(app.context().style.text.get(label).map(String::as_str), Some("5"));
+
+    app.send_event(Event::new(MixerEvent::SetVolume(6)));
+    app.step();
+
+    assert_eq!(app.context().windows.keys().cloned().collect::<Vec<_>>(), windows);
+    assert_eq!(app.context().built_child(Entity::root(), 2), Some(label));
+    assert_eq!(app.context().style.text.get(label).map(String::as_str), Some("6"));
+
+    // Closed windows are removed
+    app.context_mut().close_window(windows[0]);
+    app.step();
+    assert!(!app.context().entity_manager.is_alive(windows[0]));
+}
+
+// Writes the stylesheet and waits until its modification time has changed.
+fn write_stylesheet(path: &std::path::Path, contents: &str) {
+    let modified = std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+
+    for _ in 0..200 {
+        std::fs::write(path, contents).expect("Failed to write stylesheet");
+
+        if std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok() != modified {
+            return;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn modified_stylesheet_is_reloaded() {
+    let path = std::env::temp_dir().join(format!("vizia_hot_reload_{}.css", std::process::id()));
+    write_stylesheet(&path, ".a { width: 100px; }");
+
+    let stylesheet = path.to_str().expect("Invalid path").to_owned();
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), move |cx| {
+        cx.add_stylesheet(&stylesheet).expect("Failed to add stylesheet");
+        cx.watch_stylesheets(true);
+        Element::new(cx).class("a").height(Pixels(50.0));
+    });
+
+    app.step();
+
+    let element =
+        Entity::root().child_iter(&app.context().tree).next().expect("Failed to find element");
+    assert_eq!(app.context().cache.get_width(element), 100.0);
+
+    write_stylesheet(&path, ".a { width: 200px; }");
+    app.step();
+    assert_eq!(app.context().cache.get_width(element), 200.0);
+
+    // A stylesheet with errors keeps the rules from the last version without errors
+    write_stylesheet(&path, ".a { width: 300px; }\n.b! { width: 10px; }");
+    app.step();
+    assert_eq!(app.context().cache.get_width(element), 200.0);
+
+    std::fs::remove_file(&path).expect("Failed to remove stylesheet");
+}
+
+#[test]
+#[should_panic(expected = "Unrecognised color name: reddish")]
+fn style_error_mode_panics_on_theme_errors() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        cx.set_style_error_mode(StyleErrorMode::Panic);
+        cx.add_theme(".a { color: reddish; }");
+    });
+
+    app.step();
+}
+
+#[test]
+fn style_error_mode_collects_problems() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        cx.set_style_error_mode(StyleErrorMode::Collect);
+        cx.add_theme(".a { color: reddish; }");
+    });
+
+    app.step();
+
+    let diagnostics = app.context_mut().take_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].source, "theme");
+    assert!(diagnostics[0].message.ends_with("Unrecognised color name: reddish"));
+
+    assert!(app.context_mut().take_diagnostics().is_empty());
+}
+
+#[test]
+fn images_with_existing_names_are_reported() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        cx.set_style_error_mode(StyleErrorMode::Collect);
+        cx.load_image("logo", &[1, 2, 3]);
+        cx.load_image("logo", &[4, 5, 6]);
+    });
+
+    app.step();
+
+    let diagnostics = app.context_mut().take_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].source, "images");
+    assert_eq!(diagnostics[0].message, "Image already exists: logo");
+}
+
+#[test]
+fn keyframes_animate_properties() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        cx.add_theme(
+            r#"
+            @keyframes grow {
+                from { width: 100px; }
+                to { width: 200px; }
+            }
+
+            .a { width: 50px; animation: grow 1s linear; }
+            "#,
+        );
+        Element::new(cx).class("a").height(Pixels(50.0));
+    });
+
+    app.step();
+
+    let element =
+        Entity::root().child_iter(&app.context().tree).next().expect("Failed to find element");
+
+    app.advance_time(std::time::Duration::from_millis(500));
+    let width = app.context().cache.get_width(element);
+    assert!((width - 150.0).abs() < 5.0, "Unexpected width halfway through animation: {}", width);
+
+    // Once finished the property returns to the value set by the rule
+    app.advance_time(std::time::Duration::from_millis(600));
+    app.advance_time(std::time::Duration::from_millis(100));
+    assert_eq!(app.context().cache.get_width(element), 50.0);
+}
+
+#[test]
+fn stylesheets_wrap_text() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        cx.add_theme(".wrapped { text-wrap: word; line-height: 20px; }");
+        Label::new(cx, "the quick brown fox jumps over the lazy dog").width(Pixels(100.0));
+        Label::new(cx, "the quick brown fox jumps over the lazy dog")
+            .class("wrapped")
+            .width(Pixels(100.0));
+    });
+
+    app.step();
+
+    let mut labels = Entity::root().child_iter(&app.context().tree);
+    let unwrapped = labels.next().expect("Failed to find label");
+    let wrapped = labels.next().expect("Failed to find label");
+
+    // The wrapped label is a whole number of 20px lines high, plus antialiasing
+    let height = app.context().cache.get_height(wrapped);
+    assert!(height > app.context().cache.get_height(unwrapped));
+    assert!(height > 41.0 && (height - 1.0) % 20.0 == 0.0, "Unexpected height: {}", height);
+}
+
+#[test]
+fn text_in_auto_sized_parents_is_not_wrapped() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        cx.add_theme(".wrapped { text-wrap: word; line-height: 20px; }");
+        HStack::new(cx, |cx| {
+            Label::new(cx, "the quick brown fox").class("wrapped").width(Auto).height(Auto);
+        })
+        .width(Auto)
+        .height(Auto);
+    });
+
+    app.step();
+
+    let stack =
+        Entity::root().child_iter(&app.context().tree).next().expect("Failed to find stack");
+    let label = stack.child_iter(&app.context().tree).next().expect("Failed to find label");
+
+    // The parent has no width of its own to wrap the text against, so the text is on a single line
+    assert_eq!(app.context().cache.get_height(label), 21.0);
+    assert!(app.context().cache.get_width(label) > 100.0);
+}
+
+#[test]
+fn rich_text_is_measured_by_its_lines() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        cx.set_style_error_mode(StyleErrorMode::Collect);
+        RichText::markup(cx, "abc").width(Auto).height(Auto);
+        RichText::markup(cx, "abc<br>a").width(Auto).height(Auto);
+        RichText::markup(cx, "abc<font size=32>abc</font>").width(Auto).height(Auto);
+        RichText::markup(cx, "abc<i>").width(Auto).height(Auto);
+    });
+
+    app.step();
+
+    let texts = Entity::root().child_iter(&app.context().tree).collect::<Vec<_>>();
+    let size = |entity: Entity| {
+        (app.context().cache.get_width(entity), app.context().cache.get_height(entity))
+    };
+
+    let (width, height) = size(texts[0]);
+    assert!(width > 1.0);
+
+    // A second, shorter line makes the text taller but no wider
+    let (two_lines_width, two_lines_height) = size(texts[1]);
+    assert_eq!(two_lines_width, width);
+    assert!((two_lines_height - 1.0 - 2.0 * (height - 1.0)).abs() <= 1.0);
+
+    // A line is as high as its largest font
+    let (mixed_width, mixed_height) = size(texts[2]);
+    assert!(mixed_width > 2.0 * width - 1.0);
+    assert!(mixed_height > height);
+
+    // Text with an unknown tag is still laid out
+    assert_eq!(size(texts[3]), (width, height));
+
+    let diagnostics = app.context_mut().take_diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].source, "rich text markup");
+    assert!(diagnostics[0].message.ends_with("1:4: Unknown tag: <i>"));
+}
+
+#[test]
+fn animation_builder_applies_timing_and_fill_mode() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        let animation = cx
+            .create_animation(std::time::Duration::from_secs(1))
+            .with_timing_function(TimingFunction::Steps(2, StepPosition::End))
+            .persistent()
+            .add_keyframe(0.0, |keyframe| keyframe.set_width(Pixels(100.0)))
+            .add_keyframe(1.0, |keyframe| keyframe.set_width(Pixels(200.0)))
+            .build();
+
+        let element = Element::new(cx).width(Pixels(50.0)).height(Pixels(50.0)).entity();
+        element.play_animation(cx, animation);
+    });
+
+    app.step();
+
+    let element =
+        Entity::root().child_iter(&app.context().tree).next().expect("Failed to find element");
+
+    app.advance_time(std::time::Duration::from_millis(250));
+    assert_eq!(app.context().cache.get_width(element), 100.0);
+
+    app.advance_time(std::time::Duration::from_millis(500));
+    assert_eq!(app.context().cache.get_width(element), 150.0);
+
+    // A persistent animation keeps its last value once finished
+    app.advance_time(std::time::Duration::from_millis(500));
+    app.advance_time(std::time::Duration::from_millis(100));
+    assert_eq!(app.context().cache.get_width(element), 200.0);
+}
+
+#[derive(Default)]
+pub struct AnimationLog {
+    events: Vec<AnimationEvent>,
+}
+
+impl Model for AnimationLog {
+    fn event(&mut self, _: &mut Context, event: &mut Event) {
+        if let Some(animation_event) = event.message.downcast() {
+            self.events.push(*animation_event);
+        }
+    }
+}
+
+fn play_fade(app: &mut Application, iterations: f32) -> Animation {
+    let cx = app.context_mut();
+    cx.current = Entity::root();
+    let animation = cx
+        .create_animation(std::time::Duration::from_secs(1))
+        .with_iterations(iterations)
+        .add_keyframe(0.0, |keyframe| keyframe.set_opacity(0.0))
+        .add_keyframe(1.0, |keyframe| keyframe.set_opacity(1.0))
+        .build();
+
+    Entity::root().play_animation(cx, animation);
+
+    animation
+}
+
+#[test]
+fn animation_events_are_sent_to_the_animated_entity() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        AnimationLog::default().build(cx);
+    });
+
+    app.step();
+
+    let animation = play_fade(&mut app, 2.0);
+
+    app.step();
+    assert_eq!(
+        model::<AnimationLog>(&app).events,
+        vec![AnimationEvent::AnimationStarted(animation)]
+    );
+
+    app.advance_time(std::time::Duration::from_millis(1200));
+    assert_eq!(
+        model::<AnimationLog>(&app).events,
+        vec![
+            AnimationEvent::AnimationStarted(animation),
+            AnimationEvent::AnimationIteration(animation)
+        ]
+    );
+
+    app.advance_time(std::time::Duration::from_secs(1));
+    assert_eq!(
+        model::<AnimationLog>(&app).events,
+        vec![
+            AnimationEvent::AnimationStarted(animation),
+            AnimationEvent::AnimationIteration(animation),
+            AnimationEvent::AnimationEnded(animation)
+        ]
+    );
+}
+
+#[test]
+fn animations_can_be_paused_seeked_and_cancelled() {
+    let mut app = Application::new(WindowDescription::new().with_inner_size(400, 300), |cx| {
+        AnimationLog::default().build(cx);
+    });
+
+    app.step();
+
+    let animation = play_fade(&mut app, 1.0);
+    app.step();
+    app.advance_time(std::time::Duration::from_millis(250));
+
+    // A paused animation holds its value
+    Entity::root().pause_animation(app.context_mut(), animation);
+    app.advance_time(std::time::Duration::from_secs(5));
+    let opacity = app.context().style.opacity.get(Entity::root()).expect("Failed to get opacity");
+    assert!((opacity.0 - 0.25).abs() < 0.05, "Unexpected opacity while paused: {:?}", opacity);
+    assert!(Entity::root().is_animating(app.context_mut(), animation));
+
+    // Seeking moves the paused animation
+    Entity::root().seek_animation(
+        app.context_mut(),
+        animation,
+        std::time::Duration::from_millis(750),
+    );
+    app.step();
+    let opacity = app.context().style.opacity.get(Entity::root()).expect("Failed to get opacity");
+    assert!((opacity.0 - 0.75).abs() < 0.05, "Unexpected opacity after seeking: {:?}", opacity);
+
+    // A cancelled animation doesn't send an ended event
+    Entity::root().resume_animation(app.context_mut(), animation);
+    Entity::root().cancel_animation(app.context_mut(), animation);
+    app.advance_time(std::time::Duration::from_secs(1));
+    assert!(!Entity::root().is_animating(app.context_mut(), animation));
+    assert_eq!(
+        model::<AnimationLog>(&app).events,
+        vec![AnimationEvent::AnimationStarted(animation)]
+    );
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Persistent)]
+#[persistent(key = "counter", version = 1)]
+pub struct PersistentCounter {
+    count: i32,
+}
+
+impl Model for PersistentCounter {
+    fn event(&mut self, _: &mut Context, event: &mut Event) {
+        if let Some(CounterEvent::Increment) = event.message.downcast() {
+            self.count += 1;
+        }
+    }
+}
+
+fn persistent_app(path: &std::path::Path) -> Application {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        PersistentCounter { count: 0 }.build_persistent(cx);
+    });
+
+    app.context_mut().load_persistent_state(path);
+    app.step();
+
+    app
+}
+
+#[test]
+fn persistent_models_are_restored() {
+    let path = std::env::temp_dir().join(format!("vizia_persist_{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut app = persistent_app(&path);
+    assert_eq!(model::<PersistentCounter>(&app).count, 0);
+
+    // Changes are saved after the events which cause them, at most once per save interval
+    app.send_event(Event::new(CounterEvent::Increment).target(Entity::root()));
+    app.send_event(Event::new(CounterEvent::Increment).target(Entity::root()));
+    app.step();
+    assert_eq!(model::<PersistentCounter>(&app).count, 2);
+    let contents = std::fs::read_to_string(&path).expect("Failed to read saved state");
+    assert!(contents.contains("\"count\": 0"));
+
+    app.advance_time(PERSISTENT_SAVE_INTERVAL);
+    let contents = std::fs::read_to_string(&path).expect("Failed to read saved state");
+    assert!(contents.contains("\"count\": 2"));
+
+    // The state is written to a temporary file which replaces the saved state
+    let mut temp_name = path.file_name().expect("Failed to get file name").to_owned();
+    temp_name.push(".tmp");
+    assert!(!path.with_file_name(temp_name).exists());
+
+    // Saving immediately writes changes made within the save interval
+    app.send_event(Event::new(CounterEvent::Increment).target(Entity::root()));
+    app.step();
+    assert!(app.context_mut().save_persistent_state());
+
+    let app = persistent_app(&path);
+    assert_eq!(model::<PersistentCounter>(&app).count, 3);
+
+    // Saved state with a different schema version is dropped
+    let contents = std::fs::read_to_string(&path).expect("Failed to read saved state");
+    std::fs::write(&path, contents.replace("\"version\": 1", "\"version\": 0"))
+        .expect("Failed to write saved state");
+    let app = persistent_app(&path);
+    assert_eq!(model::<PersistentCounter>(&app).count, 0);
+
+    // A corrupted file is ignored
+    std::fs::write(&path, "{ not json").expect("Failed to write saved state");
+    let app = persistent_app(&path);
+    assert_eq!(model::<PersistentCounter>(&app).count, 0);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[derive(Debug, Clone)]
+pub enum VolumeEvent {
+    Set(i32),
+}
+
+pub struct VolumeData {
+    volume: i32,
+}
+
+impl Model for VolumeData {
+    fn event(&mut self, cx: &mut Context, event: &mut Event) {
+        if let Some(VolumeEvent::Set(volume)) = event.message.downcast() {
+            cx.record_undo(VolumeEvent::Set(self.volume), VolumeEvent::Set(*volume));
+            self.volume = *volume;
+        }
+    }
+}
+
+#[test]
+fn changes_are_undone_and_redone_with_shortcuts() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        VolumeData { volume: 0 }.build(cx);
+    });
+
+    app.step();
+
+    app.send_event(Event::new(VolumeEvent::Set(1)));
+    // Changes in a transaction are undone in a single step
+    app.send_event(Event::new(HistoryEvent::BeginTransaction));
+    app.send_event(Event::new(VolumeEvent::Set(2)));
+    app.send_event(Event::new(VolumeEvent::Set(3)));
+    app.send_event(Event::new(HistoryEvent::EndTransaction));
+    app.step();
+    assert_eq!(model::<VolumeData>(&app).volume, 3);
+
+    app.context_mut().modifiers = Modifiers::CTRL;
+    app.key_press(Code::KeyZ, None);
+    assert_eq!(model::<VolumeData>(&app).volume, 1);
+    app.key_press(Code::KeyZ, None);
+    assert_eq!(model::<VolumeData>(&app).volume, 0);
+    app.key_press(Code::KeyZ, None);
+    assert_eq!(model::<VolumeData>(&app).volume, 0);
+
+    app.context_mut().modifiers = Modifiers::CTRL | Modifiers::SHIFT;
+    app.key_press(Code::KeyZ, None);
+    assert_eq!(model::<VolumeData>(&app).volume, 1);
+    app.key_press(Code::KeyZ, None);
+    assert_eq!(model::<VolumeData>(&app).volume, 3);
+    assert!(!app.context().history.can_redo());
+}
+
+#[test]
+fn undo_shortcuts_are_not_applied_while_editing_text() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        VolumeData { volume: 0 }.build(cx);
+        NotesData { text: String::from("hello") }.build(cx);
+
+        Textbox::new(cx, NotesData::text).width(Pixels(300.0)).height(Pixels(30.0));
+    });
+
+    app.step();
+
+    app.send_event(Event::new(VolumeEvent::Set(1)));
+    app.step();
+
+    // Start editing the textbox
+    app.click(5.0, 15.0);
+
+    app.set_modifiers(Modifiers::CTRL);
+    app.key_press(Code::KeyZ, None);
+    assert_eq!(model::<VolumeData>(&app).volume, 1);
+
+    app.set_modifiers(Modifiers::CTRL | Modifiers::SHIFT);
+    app.key_press(Code::KeyZ, None);
+    assert_eq!(model::<VolumeData>(&app).volume, 1);
+    assert!(app.context().history.can_undo());
+}
+
+#[derive(Lens)]
+pub struct ItemsData {
+    items: Vec<i32>,
+    selected: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum ItemsEvent {
+    Set(usize, i32),
+    Select(Option<usize>),
+}
+
+impl Model for ItemsData {
+    fn event(&mut self, _: &mut Context, event: &mut Event) {
+        if let Some(items_event) = event.message.downcast() {
+            match items_event {
+                ItemsEvent::Set(index, value) => self.items[*index] = *value,
+                ItemsEvent::Select(selected) => self.selected = *selected,
+            }
+        }
+    }
+}
+
+fn take_builds(builds: &std::rc::Rc<std::cell::RefCell<Vec<String>>>) -> Vec<String> {
+    let mut builds = std::mem::take(&mut *builds.borrow_mut());
+    builds.sort();
+    builds
+}
+
+#[test]
+fn removed_bindings_drop_their_stores() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        CounterData { count: 0 }.build(cx);
+
+        Binding::new(cx, CounterData::count, |cx, _| {
+            Element::new(cx);
+        });
+
+        VStack::new(cx, |cx| {
+            // Lenses without a cache key get a store for each binding
+            Binding::new(cx, CounterData::count.map(|count| count * 2), |cx, count| {
+                Label::new(cx, &count.get(cx).to_string());
+            });
+
+            // Shares the store of the binding above the stack
+            Binding::new(cx, CounterData::count, |cx, count| {
+                Label::new(cx, &count.get(cx).to_string());
+            });
+        });
+    });
+
+    app.step();
+
+    let num_stores =
+        |app: &Application| app.context().data.get(Entity::root()).unwrap().lenses.len();
+    assert_eq!(num_stores(&app), 2);
+
+    let stack = Entity::root().child_iter(&app.context().tree).nth(1).unwrap();
+    app.context_mut().remove(stack);
+    assert_eq!(num_stores(&app), 1);
+
+    app.send_event(Event::new(CounterEvent::Increment));
+    app.step();
+    assert_eq!(model::<CounterData>(&app).count, 1);
+
+    let binding = Entity::root().child_iter(&app.context().tree).next().unwrap();
+    app.context_mut().remove(binding);
+    assert_eq!(num_stores(&app), 0);
+}
+
+#[test]
+fn bindings_through_composed_lenses_only_rebuild_on_changes() {
+    let builds = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+
+    let log = builds.clone();
+    let mut app = Application::new(WindowDescription::new(), move |cx| {
+        ItemsData { items: vec![1, 2, 3], selected: None }.build(cx);
+
+        let log1 = log.clone();
+        Binding::new(cx, ItemsData::items.index(1), move |cx, item| {
+            log1.borrow_mut().push(format!("second {}", item.get(cx)));
+        });
+
+        let log2 = log.clone();
+        Binding::new(cx, ItemsData::items.index(2), move |cx, item| {
+            log2.borrow_mut().push(format!("third {}", item.get(cx)));
+        });
+
+        let log3 = log.clone();
+        Binding::new(
+            cx,
+            ItemsData::items.map(|items| items.iter().sum::<i32>()),
+            move |cx, sum| {
+                log3.borrow_mut().push(format!("sum {}", sum.get(cx)));
+            },
+        );
+
+        let log4 = log.clone();
+        Binding::new(cx, ItemsData::items.index(0).and(ItemsData::selected), move |cx, pair| {
+            log4.borrow_mut().push(format!("pair {:?}", pair.get(cx)));
+        });
+
+        let log5 = log.clone();
+        Binding::new(cx, ItemsData::selected.some(), move |cx, selected| {
+            log5.borrow_mut().push(format!("selected {:?}", selected.try_get(cx)));
+        });
+    });
+
+    app.step();
+    assert_eq!(
+        take_builds(&builds),
+        vec!["pair (1, None)", "second 2", "selected None", "sum 6", "third 3"]
+    );
+
+    app.send_event(Event::new(ItemsEvent::Set(0, 5)));
+    app.step();
+    assert_eq!(take_builds(&builds), vec!["pair (5, None)", "sum 10"]);
+
+    // Swapping two items leaves the sum unchanged
+    app.send_event(Event::new(ItemsEvent::Set(1, 3)));
+    app.send_event(Event::new(ItemsEvent::Set(2, 2)));
+    app.step();
+    assert_eq!(take_builds(&builds), vec!["second 3", "third 2"]);
+
+    app.send_event(Event::new(ItemsEvent::Select(Some(1))));
+    app.step();
+    assert_eq!(take_builds(&builds), vec!["pair (5, Some(1))", "selected Some(1)"]);
+}
+
+#[derive(Lens)]
+pub struct TodoData {
+    todos: Vec<(u32, String)>,
+}
+
+#[derive(Debug)]
+pub enum TodoEvent {
+    Set(Vec<(u32, String)>),
+}
+
+impl Model for TodoData {
+    fn event(&mut self, _: &mut Context, event: &mut Event) {
+        if let Some(TodoEvent::Set(todos)) = event.message.downcast() {
+            self.todos = todos.clone();
+        }
+    }
+}
+
+fn todos(items: &[(u32, &str)]) -> Vec<(u32, String)> {
+    items.iter().map(|(id, text)| (*id, text.to_string())).collect()
+}
+
+// Returns the entities of the list items, skipping the binding which is the first child of the list.
+fn list_items(app: &Application) -> Vec<Entity> {
+    let cx = app.context();
+    let list = Entity::root().child_iter(&cx.tree).next().expect("Failed to find list");
+    list.child_iter(&cx.tree).skip(1).collect()
+}
+
+#[test]
+fn keyed_list_reuses_the_entities_of_existing_items() {
+    let builds = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+
+    let log = builds.clone();
+    let mut app = Application::new(WindowDescription::new(), move |cx| {
+        TodoData { todos: todos(&[(1, "a"), (2, "b"), (3, "c")]) }.build(cx);
+
+        let log = log.clone();
+        List::with_key(
+            cx,
+            TodoData::todos,
+            |todo| todo.0,
+            move |cx, item| {
+                let text = item.get(cx).1;
+                log.borrow_mut().push(text.clone());
+                Label::new(cx, &text);
+            },
+        );
+    });
+
+    app.step();
+    assert_eq!(take_builds(&builds), vec!["a", "b", "c"]);
+    let items = list_items(&app);
+    assert_eq!(items.len(), 3);
+
+    // Moved items are rebuilt with their new index but keep their entities
+    app.send_event(Event::new(TodoEvent::Set(todos(&[(3, "c"), (1, "a"), (4, "d")]))));
+    app.step();
+    assert_eq!(take_builds(&builds), vec!["a", "c", "d"]);
+    let moved = list_items(&app);
+    assert_eq!(moved.len(), 3);
+    assert_eq!(moved[0], items[2]);
+    assert_eq!(moved[1], items[0]);
+    assert!(!items.contains(&moved[2]));
+
+    // Only changed items are rebuilt
+    app.send_event(Event::new(TodoEvent::Set(todos(&[(3, "c"), (1, "a"), (4, "e")]))));
+    app.step();
+    assert_eq!(take_builds(&builds), vec!["e"]);
+    assert_eq!(list_items(&app), moved);
+}
+
+#[derive(Lens)]
+pub struct MixerData {
+    volume: i32,
+    balance: i32,
+}
+
+#[derive(Lens)]
+pub struct SettingsData {
+    units: String,
+}
+
+#[derive(Debug)]
+pub enum MixerEvent {
+    SetVolume(i32),
+    SetBalance(i32),
+    SetUnits(String),
+}
+
+impl Model for MixerData {
+    fn event(&mut self, _: &mut Context, event: &mut Event) {
+        if let Some(mixer_event) = event.message.downcast() {
+            match mixer_event {
+                MixerEvent::SetVolume(volume) => self.volume = *volume,
+                MixerEvent::SetBalance(balance) => self.balance = *balance,
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Model for SettingsData {
+    fn event(&mut self, _: &mut Context, event: &mut Event) {
+        if let Some(MixerEvent::SetUnits(units)) = event.message.downcast() {
+            self.units = units.clone();
+        }
+    }
+}
+
+#[test]
+fn computed_values_depend_on_several_models() {
+    let builds = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let computes = std::rc::Rc::new(std::cell::Cell::new(0));
+
+    let log = builds.clone();
+    let counter = computes.clone();
+    let mut app = Application::new(WindowDescription::new(), move |cx| {
+        MixerData { volume: 5, balance: 0 }.build(cx);
+        SettingsData { units: "dB".to_string() }.build(cx);
+
+        let counter = counter.clone();
+        let text =
+            Computed::new(cx, (MixerData::volume, SettingsData::units), move |(volume, units)| {
+                counter.set(counter.get() + 1);
+                format!("{} {}", volume, units)
+            });
+
+        let audible = Computed::new(cx, MixerData::volume, |volume| *volume > 0);
+
+        let log1 = log.clone();
+        Binding::new(cx, text, move |cx, text| {
+            log1.borrow_mut().push(text.get(cx));
+        });
+
+        let log2 = log.clone();
+        Binding::new(cx, audible, move |cx, audible| {
+            log2.borrow_mut().push(format!("audible {}", audible.get(cx)));
+        });
+    });
+
+    app.step();
+    assert_eq!(take_builds(&builds), vec!["5 dB", "audible true"]);
+    assert_eq!(computes.get(), 1);
+
+    // Changes to data which isn't an input don't recompute the value
+    app.send_event(Event::new(MixerEvent::SetBalance(3)));
+    app.step();
+    assert!(take_builds(&builds).is_empty());
+    assert_eq!(computes.get(), 1);
+
+    // Bindings are only rebuilt when the computed value changes
+    app.send_event(Event::new(MixerEvent::SetVolume(6)));
+    app.step();
+    assert_eq!(take_builds(&builds), vec!["6 dB"]);
+    assert_eq!(computes.get(), 2);
+
+    app.send_event(Event::new(MixerEvent::SetUnits("%".to_string())));
+    app.step();
+    assert_eq!(take_builds(&builds), vec!["6 %"]);
+
+    app.send_event(Event::new(MixerEvent::SetVolume(0)));
+    app.step();
+    assert_eq!(take_builds(&builds), vec!["0 %", "audible false"]);
+}
+
+#[test]
+fn computed_values_declared_from_one_place_are_separate() {
+    let builds = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+
+    let log = builds.clone();
+    let mut app = Application::new(WindowDescription::new(), move |cx| {
+        MixerData { volume: 5, balance: 0 }.build(cx);
+
+        for offset in 0..2 {
+            let value = Computed::new(cx, MixerData::volume, move |volume| volume + offset);
+
+            let log = log.clone();
+            Binding::new(cx, value, move |cx, value| {
+                log.borrow_mut().push(value.get(cx).to_string());
+            });
+        }
+    });
+
+    app.step();
+    assert_eq!(take_builds(&builds), vec!["5", "6"]);
+
+    app.send_event(Event::new(MixerEvent::SetVolume(1)));
+    app.step();
+    assert_eq!(take_builds(&builds), vec!["1", "2"]);
+}
+
+#[derive(Lens)]
+pub struct LoaderData {
+    loaded: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum LoaderEvent {
+    LoadBlocking(String),
+    LoadAsync(String),
+    Loaded(String),
+}
+
+impl Model for LoaderData {
+    fn event(&mut self, cx: &mut Context, event: &mut Event) {
+        if let Some(loader_event) = event.message.downcast() {
+            match loader_event {
+                LoaderEvent::LoadBlocking(name) => {
+                    let name = name.clone();
+                    cx.spawn_blocking(move || LoaderEvent::Loaded(format!("{} (blocking)", name)));
+                }
+
+                LoaderEvent::LoadAsync(name) => {
+                    let name = name.clone();
+                    cx.spawn(async move { LoaderEvent::Loaded(format!("{} (async)", name)) });
+                }
+
+                LoaderEvent::Loaded(name) => self.loaded.push(name.clone()),
+            }
+        }
+    }
+}
+
+fn loaded(app: &Application) -> Vec<String> {
+    let mut loaded = model::<LoaderData>(app).loaded.clone();
+    loaded.sort();
+    loaded
+}
+
+#[test]
+fn task_results_are_sent_to_the_spawning_entity() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        LoaderData { loaded: Vec::new() }.build(cx);
+        Element::new(cx);
+    });
+
+    app.step();
+
+    app.send_event(Event::new(LoaderEvent::LoadBlocking("presets".to_string())));
+    app.send_event(Event::new(LoaderEvent::LoadAsync("samples".to_string())));
+    app.step();
+    app.wait_for_tasks();
+    assert_eq!(loaded(&app), vec!["presets (blocking)", "samples (async)"]);
+    assert_eq!(app.context().tasks.num_pending(), 0);
+
+    // Tasks spawned by a view are cancelled when the view is removed
+    let element = Entity::root().child_iter(&app.context().tree).next().unwrap();
+    let (sender, receiver) = std::sync::mpsc::channel::<()>();
+    let cx = app.context_mut();
+    cx.current = element;
+    cx.spawn_blocking(move || {
+        receiver.recv().unwrap();
+        LoaderEvent::Loaded("blocked".to_string())
+    });
+    cx.spawn(std::future::pending::<LoaderEvent>());
+    cx.current = Entity::root();
+    assert_eq!(cx.tasks.num_pending(), 2);
+
+    cx.remove(element);
+    sender.send(()).unwrap();
+    app.wait_for_tasks();
+    assert_eq!(loaded(&app), vec!["presets (blocking)", "samples (async)"]);
+    assert_eq!(app.context().tasks.num_pending(), 0);
+}
+
+#[test]
+fn pending_futures_do_not_block_other_tasks() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        LoaderData { loaded: Vec::new() }.build(cx);
+        Element::new(cx);
+    });
+
+    app.step();
+
+    // More pending futures than there are worker threads
+    let element = Entity::root().child_iter(&app.context().tree).next().unwrap();
+    let cx = app.context_mut();
+    cx.current = element;
+    for _ in 0..8 {
+        cx.spawn(std::future::pending::<LoaderEvent>());
+    }
+    cx.current = Entity::root();
+
+    app.send_event(Event::new(LoaderEvent::LoadBlocking("presets".to_string())));
+    app.send_event(Event::new(LoaderEvent::LoadAsync("samples".to_string())));
+    app.step();
+
+    let start = std::time::Instant::now();
+    while loaded(&app).len() < 2 {
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(5),
+            "Tasks were blocked by pending futures"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        app.step();
+    }
+    assert_eq!(loaded(&app), vec!["presets (blocking)", "samples (async)"]);
+    assert_eq!(app.context().tasks.num_pending(), 8);
+
+    app.context_mut().remove(element);
+    app.wait_for_tasks();
+    assert_eq!(app.context().tasks.num_pending(), 0);
+}
+
+#[derive(Lens)]
+pub struct TickData {
+    ticks: Vec<Timer>,
+}
+
+impl Model for TickData {
+    fn event(&mut self, _: &mut Context, event: &mut Event) {
+        if let Some(TimerEvent::Tick(timer)) = event.message.downcast() {
+            self.ticks.push(*timer);
+        }
+    }
+}
+
+#[test]
+fn timers_tick_the_owning_entity_until_removed() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        TickData { ticks: Vec::new() }.build(cx);
+        Element::new(cx);
+    });
+
+    // Use a fixed clock so that the timers start from a known time
+    app.advance_time(std::time::Duration::ZERO);
+
+    let element = Entity::root().child_iter(&app.context().tree).next().unwrap();
+    let cx = app.context_mut();
+    cx.current = Entity::root();
+    let repeating = cx.add_timer(std::time::Duration::from_millis(100), true);
+    let once = cx.add_timer(std::time::Duration::from_millis(150), false);
+    cx.current = element;
+    let removed = cx.add_timer(std::time::Duration::from_millis(50), false);
+    cx.current = Entity::root();
+    let deadline = app.context().timers.next_deadline().unwrap();
+
+    app.advance_time(std::time::Duration::from_millis(40));
+    assert!(model::<TickData>(&app).ticks.is_empty());
+
+    // Timers are removed along with their entity
+    app.context_mut().remove(element);
+    assert!(!app.context().timers.contains(removed));
+    assert!(app.context().timers.next_deadline().unwrap() > deadline);
+
+    app.advance_time(std::time::Duration::from_millis(70));
+    assert_eq!(model::<TickData>(&app).ticks, vec![repeating]);
+
+    app.advance_time(std::time::Duration::from_millis(50));
+    assert_eq!(model::<TickData>(&app).ticks, vec![repeating, once]);
+    assert!(!app.context().timers.contains(once));
+
+    // Missed intervals of a repeating timer are skipped
+    app.advance_time(std::time::Duration::from_millis(350));
+    assert_eq!(model::<TickData>(&app).ticks, vec![repeating, once, repeating]);
+
+    app.context_mut().remove_timer(repeating);
+    app.advance_time(std::time::Duration::from_millis(200));
+    assert_eq!(model::<TickData>(&app).ticks, vec![repeating, once, repeating]);
+    assert_eq!(app.context().timers.next_deadline(), None);
+}
+
+#[test]
+fn repeating_timers_with_a_zero_interval_wait_between_ticks() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        TickData { ticks: Vec::new() }.build(cx);
+    });
+
+    app.advance_time(std::time::Duration::ZERO);
+
+    let cx = app.context_mut();
+    cx.current = Entity::root();
+    let timer = cx.add_timer(std::time::Duration::ZERO, true);
+
+    app.advance_time(std::time::Duration::ZERO);
+    assert!(model::<TickData>(&app).ticks.is_empty());
+
+    app.advance_time(std::time::Duration::from_millis(1));
+    assert_eq!(model::<TickData>(&app).ticks, vec![timer]);
+
+    app.advance_time(std::time::Duration::ZERO);
+    assert_eq!(model::<TickData>(&app).ticks, vec![timer]);
+}
+
+#[derive(Lens)]
+pub struct NotesData {
+    text: String,
+}
+
+#[derive(Debug)]
+pub enum NotesEvent {
+    Edit(std::ops::Range<usize>, String),
+}
+
+impl Model for NotesData {
+    fn event(&mut self, _: &mut Context, event: &mut Event) {
+        if let Some(NotesEvent::Edit(range, text)) = event.message.downcast() {
+            self.text.replace_range(range.clone(), text);
+        }
+    }
+}
+
+// Types one character per step, as the textbox edits the text it was given in the previous step.
+fn type_chars(app: &mut Application, text: &str) {
+    for character in text.chars() {
+        app.type_text(&character.to_string());
+    }
+}
+
+#[test]
+fn multiline_textbox_edits_and_moves_between_lines() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        NotesData { text: String::new() }.build(cx);
+
+        Textbox::new_multiline(cx, NotesData::text)
+            .on_edit(|cx, range, text| cx.emit(NotesEvent::Edit(range, text)))
+            .width(Pixels(100.0))
+            .height(Pixels(200.0));
+    });
+
+    app.step();
+    app.click(10.0, 10.0);
+
+    type_chars(&mut app, "one");
+    app.key_press(Code::Enter, None);
+    type_chars(&mut app, "two");
+    assert_eq!(model::<NotesData>(&app).text, "one\ntwo");
+
+    // The caret moves up to the previous line and to the start of it
+    app.key_press(Code::ArrowUp, None);
+    app.key_press(Code::Home, None);
+    type_chars(&mut app, "x");
+    assert_eq!(model::<NotesData>(&app).text, "xone\ntwo");
+
+    app.set_modifiers(Modifiers::CTRL);
+    app.key_press(Code::End, None);
+    app.set_modifiers(Modifiers::empty());
+    type_chars(&mut app, "!");
+    assert_eq!(model::<NotesData>(&app).text, "xone\ntwo!");
+
+    // Long text is wrapped to the width of the textbox, so End moves to the end of the first wrapped line
+    app.set_modifiers(Modifiers::CTRL);
+    app.key_press(Code::KeyA, None);
+    app.set_modifiers(Modifiers::empty());
+    type_chars(&mut app, "aaaa bbbb cccc dddd eeee");
+    app.set_modifiers(Modifiers::CTRL);
+    app.key_press(Code::Home, None);
+    app.set_modifiers(Modifiers::empty());
+    app.key_press(Code::End, None);
+    type_chars(&mut app, "|");
+
+    let text = model::<NotesData>(&app).text.clone();
+    let index = text.find('|').unwrap();
+    assert!(index > 0 && index < text.len() - 1, "{}", text);
+    assert_eq!(text.replace('|', ""), "aaaa bbbb cccc dddd eeee");
+}
+
+#[derive(Default)]
+pub struct ImeLog {
+    requests: Vec<WindowEvent>,
+}
+
+impl Model for ImeLog {
+    fn event(&mut self, _: &mut Context, event: &mut Event) {
+        if let Some(window_event) = event.message.downcast::<WindowEvent>() {
+            match window_event {
+                WindowEvent::SetImeAllowed(_) | WindowEvent::SetImeCaret(_) => {
+                    self.requests.push(window_event.clone());
+                }
+
+                _ => {}
+            }
+        }
+    }
+}
+
+#[test]
+fn textbox_shows_preedit_text_until_it_is_committed() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        NotesData { text: String::from("ab") }.build(cx);
+        ImeLog::default().build(cx);
+
+        Textbox::new(cx, NotesData::text)
+            .on_edit(|cx, range, text| cx.emit(NotesEvent::Edit(range, text)))
+            .width(Pixels(100.0))
+            .height(Pixels(30.0));
+    });
+
+    app.step();
+    app.click(90.0, 10.0);
+
+    let requests = model::<ImeLog>(&app).requests.clone();
+    assert!(matches!(requests.first(), Some(WindowEvent::SetImeAllowed(true))), "{:?}", requests);
+    assert!(requests.iter().any(|request| matches!(request, WindowEvent::SetImeCaret(_))));
+
+    // Composed text is only shown by the textbox
+    app.ime_preedit("にほ", Some((6, 6)));
+    assert_eq!(model::<NotesData>(&app).text, "ab");
+
+    app.ime_preedit("日本", None);
+    app.ime_commit("日本");
+    assert_eq!(model::<NotesData>(&app).text, "ab日本");
+
+    // The caret is placed after the committed text
+    type_chars(&mut app, "!");
+    assert_eq!(model::<NotesData>(&app).text, "ab日本!");
+}
+
+#[test]
+fn textbox_selects_words_and_lines() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        NotesData { text: String::from("hello wide world") }.build(cx);
+
+        Textbox::new(cx, NotesData::text)
+            .on_edit(|cx, range, text| cx.emit(NotesEvent::Edit(range, text)))
+            .width(Pixels(300.0))
+            .height(Pixels(30.0));
+    });
+
+    app.step();
+
+    // Double clicking selects the word under the cursor
+    app.double_click(5.0, 15.0);
+    type_chars(&mut app, "bye");
+    assert_eq!(model::<NotesData>(&app).text, "bye wide world");
+
+    // Ctrl+Arrow moves to the ends of words, and shift extends the selection
+    app.key_press(Code::Home, None);
+    app.set_modifiers(Modifiers::CTRL);
+    app.key_press(Code::ArrowRight, None);
+    app.key_press(Code::ArrowRight, None);
+    app.set_modifiers(Modifiers::empty());
+    type_chars(&mut app, "r");
+    assert_eq!(model::<NotesData>(&app).text, "bye wider world");
+
+    app.set_modifiers(Modifiers::CTRL | Modifiers::SHIFT);
+    app.key_press(Code::ArrowLeft, None);
+    app.set_modifiers(Modifiers::empty());
+    type_chars(&mut app, "narrow");
+    assert_eq!(model::<NotesData>(&app).text, "bye narrow world");
+
+    app.set_modifiers(Modifiers::CTRL);
+    app.key_press(Code::Backspace, None);
+    app.set_modifiers(Modifiers::empty());
+    assert_eq!(model::<NotesData>(&app).text, "bye  world");
+
+    // Triple clicking selects the whole line
+    app.triple_click(5.0, 15.0);
+    type_chars(&mut app, "x");
+    assert_eq!(model::<NotesData>(&app).text, "x");
+}
+
+#[test]
+fn tabbing_into_a_textbox_does_not_capture_the_mouse() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        NotesData { text: String::from("ab") }.build(cx);
+        CounterData { count: 0 }.build(cx);
+
+        Textbox::new(cx, NotesData::text)
+            .on_edit(|cx, range, text| cx.emit(NotesEvent::Edit(range, text)))
+            .width(Pixels(100.0))
+            .height(Pixels(30.0));
+
+        Button::new(cx, |cx| cx.emit(CounterEvent::Increment), |cx| Label::new(cx, "Increment"))
+            .width(Pixels(100.0))
+            .height(Pixels(30.0));
+    });
+
+    app.step();
+
+    let textbox = Entity::root().child_iter(&app.context().tree).next().unwrap();
+
+    app.key_press(Code::Tab, None);
+    assert_eq!(app.context().focused, textbox);
+    assert!(textbox.is_checked(app.context()));
+    assert_eq!(app.context().captured, Entity::null());
+
+    type_chars(&mut app, "c");
+    assert_eq!(model::<NotesData>(&app).text, "abc");
+
+    // Clicks outside of the textbox go to the views under the mouse
+    app.click(50.0, 45.0);
+    assert_eq!(model::<CounterData>(&app).count, 1);
+
+    // Moving the focus away stops editing
+    app.key_press(Code::Tab, None);
+    assert!(!textbox.is_checked(app.context()));
+
+    type_chars(&mut app, "d");
+    assert_eq!(model::<NotesData>(&app).text, "abc");
+}
+
+#[test]
+fn textbox_scrolls_to_keep_the_caret_in_view() {
+    let mut app = Application::new(WindowDescription::new(), |cx| {
+        NotesData { text: String::new() }.build(cx);
+
+        Textbox::new(cx, NotesData::text)
+            .on_edit(|cx, range, text| cx.emit(NotesEvent::Edit(range, text)))
+            .width(Pixels(50.0))
+            .height(Pixels(30.0));
+    });
+
+    app.step();
+    app.click(10.0, 15.0);
+    type_chars(&mut app, "the quick brown fox");
+
+    let textbox = Entity::root().child_iter(&app.context().tree).next().unwrap();
+    let scroll = |app: &Application| {
+        app.context().style.text_scroll.get(textbox).cloned().unwrap_or_default()
+    };
+    assert!(scroll(&app) > 0.0);
+
+    app.key_press(Code::Home, None);
+    assert_eq!(scroll(&app), 0.0);
+}
@@ -5,3 +5,6 @@ pub use vizia_glutin::application::Application;
 pub use vizia_baseview::{Application, ParentWindow};
 
 pub use vizia_core::*;
+
+#[cfg(feature = "headless")]
+pub use vizia_headless as headless;